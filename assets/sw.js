@@ -0,0 +1,20 @@
+// Service worker which shows push notifications sent by the server.
+
+self.addEventListener("push", (event) => {
+  if (!event.data) {
+    return;
+  }
+  const data = event.data.json();
+  event.waitUntil(
+    self.registration.showNotification(data.title, {
+      body: data.body,
+      icon: "/logo.png",
+      data: { url: data.url },
+    }),
+  );
+});
+
+self.addEventListener("notificationclick", (event) => {
+  event.notification.close();
+  event.waitUntil(clients.openWindow(event.notification.data.url));
+});
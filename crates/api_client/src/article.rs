@@ -5,7 +5,7 @@ use ibis_database::common::{
     ResolveObjectParams,
     SuccessResponse,
     article::{ApiConflict, Article, ArticleView, EditVersion, EditView},
-    newtypes::{ArticleId, ConflictId, InstanceId, PersonId},
+    newtypes::{ArticleId, ConflictId, EditId, InstanceId, PersonId},
 };
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -43,6 +43,8 @@ pub struct EditArticleParams {
     pub new_text: String,
     /// What was changed
     pub summary: String,
+    /// Small change like a typo fix, followers are not notified about it by default
+    pub minor: bool,
     /// The version that this edit is based on, ie [DbArticle.latest_version] or
     /// [ApiConflict.previous_version]
     pub previous_version_id: EditVersion,
@@ -74,6 +76,32 @@ pub struct GetEditList {
     pub person_id: Option<PersonId>,
 }
 
+/// Filters for the edits of all articles, newest first.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+pub struct RecentChangesParams {
+    /// Only edits of local articles if true, only edits of remote articles if false
+    pub local: Option<bool>,
+    pub instance_id: Option<InstanceId>,
+    /// Leave out edits by bot accounts, defaults to true
+    pub hide_bots: Option<bool>,
+    /// Only edits which were not patrolled yet
+    pub unpatrolled_only: Option<bool>,
+    pub hide_minor: Option<bool>,
+    /// Time window in days, 7 by default
+    pub days: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct PatrolEditParams {
+    pub edit_id: EditId,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RevertEditParams {
+    pub edit_id: EditId,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct DeleteConflictParams {
     pub conflict_id: ConflictId,
@@ -133,6 +161,25 @@ impl ApiClient {
             .await
     }
 
+    pub async fn recent_changes(
+        &self,
+        params: &RecentChangesParams,
+    ) -> FrontendResult<Vec<EditView>> {
+        self.get("/api/v1/edit/recent", Some(params)).await
+    }
+
+    /// Mark the edit as checked, only for admin.
+    pub async fn patrol_edit(&self, edit_id: EditId) -> FrontendResult<SuccessResponse> {
+        let params = PatrolEditParams { edit_id };
+        self.post("/api/v1/edit/patrol", Some(params)).await
+    }
+
+    /// Undo the changes of the edit with a new edit.
+    pub async fn revert_edit(&self, edit_id: EditId) -> FrontendResult<SuccessResponse> {
+        let params = RevertEditParams { edit_id };
+        self.post("/api/v1/edit/revert", Some(params)).await
+    }
+
     pub async fn remove_article(&self, article_id: ArticleId, remove: bool) -> FrontendResult<()> {
         let params = RemoveArticleParams { article_id, remove };
         self.post("/api/v1/article/remove", Some(&params)).await
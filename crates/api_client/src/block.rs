@@ -0,0 +1,73 @@
+use super::ApiClient;
+use crate::errors::FrontendResult;
+use http::Method;
+use ibis_database::common::{
+    SuccessResponse,
+    block::{BlockKind, BlockList, InstanceBlock, PersonBlock},
+    newtypes::{InstanceId, PersonId},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BlockPersonParams {
+    pub person_id: PersonId,
+    pub kind: BlockKind,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct UnblockPersonParams {
+    pub person_id: PersonId,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BlockInstanceParams {
+    pub instance_id: InstanceId,
+    pub kind: BlockKind,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct UnblockInstanceParams {
+    pub instance_id: InstanceId,
+}
+
+impl ApiClient {
+    /// Muted persons don't trigger any notifications, blocked persons additionally have their
+    /// comments hidden.
+    pub async fn block_person(
+        &self,
+        person_id: PersonId,
+        kind: BlockKind,
+    ) -> FrontendResult<PersonBlock> {
+        let params = BlockPersonParams { person_id, kind };
+        self.post("/api/v1/user/block/person", Some(params)).await
+    }
+
+    pub async fn unblock_person(&self, person_id: PersonId) -> FrontendResult<SuccessResponse> {
+        let params = UnblockPersonParams { person_id };
+        self.send(Method::DELETE, "/api/v1/user/block/person", Some(params))
+            .await
+    }
+
+    /// Same as [ApiClient::block_person] for all users of the instance.
+    pub async fn block_instance(
+        &self,
+        instance_id: InstanceId,
+        kind: BlockKind,
+    ) -> FrontendResult<InstanceBlock> {
+        let params = BlockInstanceParams { instance_id, kind };
+        self.post("/api/v1/user/block/instance", Some(params)).await
+    }
+
+    pub async fn unblock_instance(
+        &self,
+        instance_id: InstanceId,
+    ) -> FrontendResult<SuccessResponse> {
+        let params = UnblockInstanceParams { instance_id };
+        self.send(Method::DELETE, "/api/v1/user/block/instance", Some(params))
+            .await
+    }
+
+    pub async fn list_blocks(&self) -> FrontendResult<BlockList> {
+        self.get("/api/v1/user/block/list", None::<()>).await
+    }
+}
@@ -0,0 +1,28 @@
+use super::ApiClient;
+use crate::errors::FrontendResult;
+use ibis_database::common::utils::solve_proof_of_work;
+use serde::{Deserialize, Serialize};
+
+/// Error message for actions which need a solved proof-of-work challenge.
+pub const CHALLENGE_REQUIRED: &str = "Proof-of-work challenge required";
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ProofOfWorkChallenge {
+    pub challenge: String,
+    /// Number of leading zero bits which the hash needs to have
+    pub difficulty: u32,
+}
+
+impl ApiClient {
+    pub async fn get_challenge(&self) -> FrontendResult<ProofOfWorkChallenge> {
+        self.get("/api/v1/challenge", None::<()>).await
+    }
+
+    /// Get a new challenge and solve it. Returns the challenge and the nonce which solves it.
+    /// This takes a moment, depending on the difficulty.
+    pub async fn solve_challenge(&self) -> FrontendResult<(String, u64)> {
+        let res = self.get_challenge().await?;
+        let nonce = solve_proof_of_work(&res.challenge, res.difficulty);
+        Ok((res.challenge, nonce))
+    }
+}
@@ -6,11 +6,14 @@ use ibis_database::common::{
 };
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Default)]
 pub struct CreateCommentParams {
     pub content: String,
     pub article_id: ArticleId,
     pub parent_id: Option<CommentId>,
+    /// Solved proof-of-work challenge, if required for the first comment
+    pub challenge: Option<String>,
+    pub challenge_nonce: Option<u64>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
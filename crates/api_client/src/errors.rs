@@ -105,6 +105,12 @@ impl From<serde_urlencoded::ser::Error> for FrontendError {
     }
 }
 
+impl From<serde_json::Error> for FrontendError {
+    fn from(value: serde_json::Error) -> Self {
+        Self(value.to_string())
+    }
+}
+
 impl From<ParseIntError> for FrontendError {
     fn from(value: ParseIntError) -> Self {
         Self(value.to_string())
@@ -5,8 +5,9 @@ use ibis_database::common::{
     ResolveObjectParams,
     SuccessResponse,
     article::Article,
+    email::{EmailPreview, EmailTemplate},
     instance::{Instance, InstanceView, SiteView},
-    newtypes::InstanceId,
+    newtypes::{InstanceId, PersonId},
 };
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -34,6 +35,18 @@ pub struct UpdateInstanceParams {
     pub topic: Option<String>,
 }
 
+/// Rotate the key of the given local user, or of the local instance if no user is given.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RotateKeyParams {
+    pub person_id: Option<PersonId>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct EmailPreviewParams {
+    pub template: EmailTemplate,
+    pub locale: Option<String>,
+}
+
 impl ApiClient {
     pub async fn get_instance(&self, params: &GetInstanceParams) -> FrontendResult<InstanceView> {
         self.get("/api/v1/instance", Some(&params)).await
@@ -73,6 +86,22 @@ impl ApiClient {
         self.post("/api/v1/instance/follow", Some(params)).await
     }
 
+    pub async fn rotate_key(&self, person_id: Option<PersonId>) -> FrontendResult<SuccessResponse> {
+        let params = RotateKeyParams { person_id };
+        self.post("/api/v1/instance/rotate_key", Some(params)).await
+    }
+
+    /// Render an email template with example data, only for admin.
+    pub async fn email_preview(
+        &self,
+        template: EmailTemplate,
+        locale: Option<String>,
+    ) -> FrontendResult<EmailPreview> {
+        let params = EmailPreviewParams { template, locale };
+        self.get("/api/v1/instance/email_preview", Some(params))
+            .await
+    }
+
     pub async fn site(&self) -> FrontendResult<SiteView> {
         self.get("/api/v1/site", None::<()>).await
     }
@@ -94,4 +123,25 @@ impl ApiClient {
         self.follow_instance(instance_resolved.id, true).await?;
         Ok(instance_resolved)
     }
+
+    /// Download a backup archive of all local articles, only for admin.
+    #[cfg(feature = "ssr")]
+    pub async fn export_wiki(&self) -> FrontendResult<Vec<u8>> {
+        use crate::errors::FrontendError;
+        let url = self.request_endpoint("/api/v1/instance/export", true)?;
+        let res = self.client.get(url).send().await?;
+        if !res.status().is_success() {
+            return Err(FrontendError::new(res.text().await?));
+        }
+        Ok(res.bytes().await?.to_vec())
+    }
+
+    /// Restore articles from a backup archive, only for admin.
+    #[cfg(feature = "ssr")]
+    pub async fn import_wiki(&self, archive: Vec<u8>) -> FrontendResult<SuccessResponse> {
+        let url = self.request_endpoint("/api/v1/instance/import", true)?;
+        let res = self.client.post(&url).body(archive).send().await?;
+        let status = res.status();
+        Self::response(status.into(), res.text().await?, &url)
+    }
 }
@@ -5,11 +5,15 @@ use serde::{Deserialize, Serialize};
 use std::{fmt::Debug, sync::LazyLock};
 
 pub mod article;
+pub mod block;
+pub mod challenge;
 pub mod comment;
 pub mod errors;
 pub mod instance;
 pub mod notifications;
+pub mod registration;
 pub mod user;
+pub mod webhook;
 
 pub static CLIENT: LazyLock<ApiClient> = LazyLock::new(|| ApiClient::new(None));
 
@@ -19,6 +23,8 @@ pub struct ApiClient {
     client: reqwest::Client,
     #[cfg(feature = "ssr")]
     test_hostname: Option<String>,
+    #[cfg(feature = "ssr")]
+    api_token: Option<String>,
 }
 
 impl ApiClient {
@@ -33,6 +39,7 @@ impl ApiClient {
             Self {
                 client,
                 test_hostname,
+                api_token: None,
             }
         }
         #[cfg(not(feature = "ssr"))]
@@ -41,6 +48,13 @@ impl ApiClient {
         }
     }
 
+    /// Authenticate all requests with a personal api token, instead of logging in with password.
+    #[cfg(feature = "ssr")]
+    pub fn with_api_token(mut self, api_token: String) -> Self {
+        self.api_token = Some(api_token);
+        self
+    }
+
     async fn get<T, R>(&self, endpoint: &str, query: Option<R>) -> FrontendResult<T>
     where
         T: for<'de> Deserialize<'de>,
@@ -87,6 +101,9 @@ impl ApiClient {
         if let Some(Auth(Some(auth))) = auth {
             req = req.header(HeaderName::from_static(AUTH_COOKIE), auth);
         }
+        if let Some(api_token) = &self.api_token {
+            req = req.bearer_auth(api_token);
+        }
         let res = req.send().await?;
         let status = res.status();
         let url = res.url().to_string();
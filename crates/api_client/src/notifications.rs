@@ -2,8 +2,8 @@ use super::ApiClient;
 use crate::errors::FrontendResult;
 use ibis_database::common::{
     SuccessResponse,
-    newtypes::NotificationId,
-    notifications::ApiNotification,
+    newtypes::{ArticleId, NotificationId},
+    notifications::{ApiNotification, WebPushKey},
 };
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +12,32 @@ pub struct MarkAsReadParams {
     pub id: NotificationId,
 }
 
+/// Push subscription of a browser, with keys as returned by `PushSubscription.toJSON()`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct WebPushSubscribeParams {
+    pub endpoint: String,
+    pub p256dh: String,
+    pub auth: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct WebPushUnsubscribeParams {
+    pub endpoint: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct LiveEventsParams {
+    /// Also receive changes to this article
+    pub article_id: Option<ArticleId>,
+}
+
+/// Path for the stream of [LiveEvent](ibis_database::common::events::LiveEvent), which the
+/// browser opens with an `EventSource`.
+pub fn live_events_path(params: &LiveEventsParams) -> String {
+    let query = serde_urlencoded::to_string(params).unwrap_or_default();
+    format!("/api/v1/events?{query}")
+}
+
 impl ApiClient {
     pub async fn notifications_list(&self) -> FrontendResult<Vec<ApiNotification>> {
         self.get("/api/v1/user/notifications/list", None::<()>)
@@ -23,6 +49,16 @@ impl ApiClient {
             .await
     }
 
+    /// Open the stream of live events. Only used in tests, as the browser uses `EventSource`.
+    #[cfg(feature = "ssr")]
+    pub async fn live_events(
+        &self,
+        params: &LiveEventsParams,
+    ) -> FrontendResult<reqwest::Response> {
+        let url = self.request_endpoint(&live_events_path(params), true)?;
+        Ok(self.client.get(url).send().await?.error_for_status()?)
+    }
+
     pub async fn article_notif_mark_as_read(
         &self,
         id: NotificationId,
@@ -31,4 +67,22 @@ impl ApiClient {
         self.post("/api/v1/user/notifications/mark_as_read", Some(params))
             .await
     }
+
+    pub async fn web_push_key(&self) -> FrontendResult<WebPushKey> {
+        self.get("/api/v1/user/web_push/key", None::<()>).await
+    }
+
+    pub async fn web_push_subscribe(
+        &self,
+        params: WebPushSubscribeParams,
+    ) -> FrontendResult<SuccessResponse> {
+        self.post("/api/v1/user/web_push/subscribe", Some(params))
+            .await
+    }
+
+    pub async fn web_push_unsubscribe(&self, endpoint: String) -> FrontendResult<SuccessResponse> {
+        let params = WebPushUnsubscribeParams { endpoint };
+        self.post("/api/v1/user/web_push/unsubscribe", Some(params))
+            .await
+    }
 }
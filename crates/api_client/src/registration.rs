@@ -0,0 +1,67 @@
+use super::ApiClient;
+use crate::errors::FrontendResult;
+use http::Method;
+use ibis_database::common::{
+    SuccessResponse,
+    newtypes::{InviteCodeId, RegistrationApplicationId},
+    registration::{InviteCode, RegistrationApplicationView},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CreateInviteCodeParams {
+    /// How many accounts can be registered with the code
+    pub max_uses: i32,
+    /// Number of days until the code expires, or never if not set
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct DeleteInviteCodeParams {
+    pub id: InviteCodeId,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ApproveRegistrationParams {
+    pub id: RegistrationApplicationId,
+    pub approve: bool,
+    /// Sent to the user if the application is denied
+    pub deny_reason: Option<String>,
+}
+
+impl ApiClient {
+    /// Invite codes can be created by admins, and by users with enough edits if enabled in the
+    /// config.
+    pub async fn create_invite_code(
+        &self,
+        params: CreateInviteCodeParams,
+    ) -> FrontendResult<InviteCode> {
+        self.post("/api/v1/invite_code", Some(params)).await
+    }
+
+    pub async fn list_invite_codes(&self) -> FrontendResult<Vec<InviteCode>> {
+        self.get("/api/v1/invite_code/list", None::<()>).await
+    }
+
+    pub async fn delete_invite_code(&self, id: InviteCodeId) -> FrontendResult<SuccessResponse> {
+        let params = DeleteInviteCodeParams { id };
+        self.send(Method::DELETE, "/api/v1/invite_code", Some(params))
+            .await
+    }
+
+    /// Pending registration applications, only for admins.
+    pub async fn list_registration_applications(
+        &self,
+    ) -> FrontendResult<Vec<RegistrationApplicationView>> {
+        self.get("/api/v1/registration_application/list", None::<()>)
+            .await
+    }
+
+    pub async fn approve_registration_application(
+        &self,
+        params: ApproveRegistrationParams,
+    ) -> FrontendResult<SuccessResponse> {
+        self.post("/api/v1/registration_application/approve", Some(params))
+            .await
+    }
+}
@@ -2,40 +2,92 @@ use super::ApiClient;
 use crate::{article::GetEditList, errors::FrontendResult};
 use ibis_database::common::{
     SuccessResponse,
-    article::EditView,
-    instance::InstanceFollow,
-    newtypes::PersonId,
-    user::{LocalUserView, Person},
+    article::{Article, Conflict, Edit, EditView},
+    block::BlockList,
+    comment::Comment,
+    contributions::{PersonContributions, PersonStatsView},
+    instance::{Instance, InstanceFollow},
+    newtypes::{ApiTokenId, PersonId, SessionId},
+    notifications::ApiNotification,
+    user::{
+        ApiSession,
+        ApiToken,
+        ApiTokenScope,
+        EmailDigest,
+        LocalUser,
+        LocalUserView,
+        NotificationPreference,
+        OAuthAccount,
+        Person,
+    },
 };
 use serde::{Deserialize, Serialize};
 use url::Url;
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
 pub struct RegisterUserParams {
     pub username: String,
     pub email: Option<String>,
     pub password: String,
     pub confirm_password: String,
+    /// Required if registration is invite-only, optional for registration applications
+    pub invite_code: Option<String>,
+    /// Answer to the registration question, if registration requires an application
+    pub application_answer: Option<String>,
+    /// Solved proof-of-work challenge, if required by the instance
+    pub challenge: Option<String>,
+    pub challenge_nonce: Option<u64>,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct LoginUserParams {
     pub username_or_email: String,
     pub password: String,
+    /// Code from the authenticator app or a recovery code, if two-factor authentication is enabled
+    pub totp_token: Option<String>,
 }
 
+/// Error message for a login with correct password, which is missing the second factor.
+pub const TOTP_REQUIRED: &str = "Two-factor authentication code required";
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct GetUserParams {
     pub name: String,
     pub domain: Option<String>,
 }
 
+/// Number of edits and of comments on each page of contributions.
+pub const CONTRIBUTIONS_PAGE_SIZE: i64 = 20;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct GetContributionsParams {
+    pub person_id: PersonId,
+    /// Starts at 1
+    pub page: Option<i64>,
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct GetPersonStatsParams {
+    pub person_id: PersonId,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
 pub struct UpdateUserParams {
     pub display_name: Option<String>,
     pub bio: Option<String>,
     pub email: Option<String>,
     pub email_notifications: Option<bool>,
+    pub email_digest: Option<EmailDigest>,
+    pub locale: Option<String>,
+    pub notify_comment_reply: Option<NotificationPreference>,
+    pub notify_article_comment: Option<NotificationPreference>,
+    pub notify_article_edit: Option<NotificationPreference>,
+    pub notify_new_article: Option<NotificationPreference>,
+    pub notify_mention: Option<NotificationPreference>,
+    pub notify_minor_edit: Option<NotificationPreference>,
+    pub notify_bot_edit: Option<NotificationPreference>,
+    /// Mark the account as automated
+    pub bot: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -46,6 +98,15 @@ pub struct AuthenticateWithOauth {
     pub redirect_uri: Url,
     /// Username is mandatory at registration time
     pub username: Option<String>,
+    /// PKCE code verifier, whose hash was sent to the provider as code challenge
+    pub pkce_code_verifier: Option<String>,
+    /// Required to login if the user enabled two-factor authentication
+    pub totp_token: Option<String>,
+    /// Same as in [RegisterUserParams], only needed when registering a new account
+    pub invite_code: Option<String>,
+    pub application_answer: Option<String>,
+    pub challenge: Option<String>,
+    pub challenge_nonce: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -56,12 +117,16 @@ pub struct OAuthTokenResponse {
     pub expires_in: Option<i64>,
     pub refresh_token: Option<String>,
     pub scope: Option<String>,
+    /// Only returned by OpenID Connect providers
+    pub id_token: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RegistrationResponse {
     pub user: LocalUserView,
     pub email_verification_required: bool,
+    /// Login is only possible after an admin approves the registration application
+    pub application_pending: bool,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -76,6 +141,62 @@ pub struct ChangePasswordParams {
     pub old_password: String,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TrustBotParams {
+    pub person_id: PersonId,
+    pub trusted: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RevokeSessionParams {
+    pub session_id: SessionId,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CreateApiTokenParams {
+    /// Shown in the list of tokens, eg the name of the script which uses it
+    pub name: String,
+    pub scope: ApiTokenScope,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CreateApiTokenResponse {
+    pub api_token: ApiToken,
+    /// The token itself, which can't be retrieved again later
+    pub token: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DeleteApiTokenParams {
+    pub id: ApiTokenId,
+}
+
+/// Secret for a new authenticator app, which needs to be confirmed with a code before two-factor
+/// authentication is enabled.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TotpSetup {
+    pub secret: String,
+    /// `otpauth://` link with the secret, which can be opened by authenticator apps
+    pub uri: Url,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct EnableTotpParams {
+    pub totp_token: String,
+}
+
+/// One-time codes to login if the authenticator app is lost. These are only shown once.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TotpRecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DisableTotpParams {
+    pub password: String,
+    pub totp_token: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
 pub struct PasswordReset {
     pub email: String,
@@ -88,6 +209,67 @@ pub struct ChangePasswordAfterReset {
     pub confirm_password: String,
 }
 
+/// Data which is exported from an account, to import it into a new account on another instance.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AccountExport {
+    pub ap_id: Url,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub email_notifications: bool,
+    #[serde(default)]
+    pub email_digest: EmailDigest,
+    #[serde(default)]
+    pub locale: Option<String>,
+    pub following_persons: Vec<Url>,
+    pub following_instances: Vec<Url>,
+    pub following_articles: Vec<Url>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportAccountParams {
+    /// Serialized [AccountExport]
+    pub data: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MoveAccountParams {
+    /// New account which the user moves to. It needs to list the current account in
+    /// `alsoKnownAs`, which happens when importing the account data there.
+    pub target: Url,
+}
+
+/// All data which is stored about a user, for download by the user. Passwords and other secrets
+/// are not included.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PersonalDataExport {
+    pub person: Person,
+    pub local_user: LocalUser,
+    /// Edits including the diff
+    pub edits: Vec<Edit>,
+    pub comments: Vec<Comment>,
+    /// Edits which couldn't be applied yet because of a merge conflict
+    pub conflicts: Vec<Conflict>,
+    pub following_persons: Vec<Person>,
+    pub following_instances: Vec<Instance>,
+    pub following_articles: Vec<Article>,
+    pub followers: Vec<Person>,
+    pub notifications: Vec<ApiNotification>,
+    pub sessions: Vec<ApiSession>,
+    pub api_tokens: Vec<ApiToken>,
+    pub blocks: BlockList,
+    /// Accounts of external providers which can be used for login
+    pub oauth_accounts: Vec<OAuthAccount>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DeleteAccountParams {
+    /// Only needed if the account has a password, which is not the case for accounts registered
+    /// via OAuth
+    pub password: Option<String>,
+    /// Needed if two-factor authentication is enabled
+    pub totp_token: Option<String>,
+}
+
 impl ApiClient {
     pub async fn register(
         &self,
@@ -126,6 +308,59 @@ impl ApiClient {
             .await
     }
 
+    /// Devices where the current user is logged in.
+    pub async fn list_sessions(&self) -> FrontendResult<Vec<ApiSession>> {
+        self.get("/api/v1/account/sessions", None::<()>).await
+    }
+
+    pub async fn revoke_session(&self, session_id: SessionId) -> FrontendResult<SuccessResponse> {
+        let params = RevokeSessionParams { session_id };
+        self.post("/api/v1/account/sessions/revoke", Some(params))
+            .await
+    }
+
+    /// Mark edits of a bot account as patrolled automatically, only for admins.
+    pub async fn trust_bot(
+        &self,
+        person_id: PersonId,
+        trusted: bool,
+    ) -> FrontendResult<SuccessResponse> {
+        let params = TrustBotParams { person_id, trusted };
+        self.post("/api/v1/user/trust_bot", Some(params)).await
+    }
+
+    pub async fn list_api_tokens(&self) -> FrontendResult<Vec<ApiToken>> {
+        self.get("/api/v1/account/api_token/list", None::<()>).await
+    }
+
+    pub async fn create_api_token(
+        &self,
+        params: CreateApiTokenParams,
+    ) -> FrontendResult<CreateApiTokenResponse> {
+        self.post("/api/v1/account/api_token", Some(params)).await
+    }
+
+    pub async fn delete_api_token(&self, id: ApiTokenId) -> FrontendResult<SuccessResponse> {
+        let params = DeleteApiTokenParams { id };
+        self.post("/api/v1/account/api_token/delete", Some(params))
+            .await
+    }
+
+    /// Generate a new secret for two-factor authentication, which is enabled with [Self::enable_totp].
+    pub async fn generate_totp(&self) -> FrontendResult<TotpSetup> {
+        self.post("/api/v1/account/totp/generate", None::<()>).await
+    }
+
+    pub async fn enable_totp(&self, totp_token: String) -> FrontendResult<TotpRecoveryCodes> {
+        let params = EnableTotpParams { totp_token };
+        self.post("/api/v1/account/totp/enable", Some(params)).await
+    }
+
+    pub async fn disable_totp(&self, params: DisableTotpParams) -> FrontendResult<SuccessResponse> {
+        self.post("/api/v1/account/totp/disable", Some(params))
+            .await
+    }
+
     pub async fn get_person_edits(&self, person_id: PersonId) -> FrontendResult<Vec<EditView>> {
         let data = GetEditList {
             person_id: Some(person_id),
@@ -134,6 +369,20 @@ impl ApiClient {
         self.get("/api/v1/edit/list", Some(data)).await
     }
 
+    pub async fn get_person_contributions(
+        &self,
+        params: GetContributionsParams,
+    ) -> FrontendResult<PersonContributions> {
+        self.get("/api/v1/user/contributions", Some(params)).await
+    }
+
+    /// Aggregated stats and activity heatmap. The stats are recalculated periodically, so they may
+    /// not include the latest contributions.
+    pub async fn get_person_stats(&self, person_id: PersonId) -> FrontendResult<PersonStatsView> {
+        let params = GetPersonStatsParams { person_id };
+        self.get("/api/v1/user/stats", Some(params)).await
+    }
+
     pub async fn verify_email(&self, token: String) -> FrontendResult<SuccessResponse> {
         let params = VerifyEmailParams { token };
         self.post("/api/v1/account/verify_email", Some(params))
@@ -163,4 +412,32 @@ impl ApiClient {
         self.post("/api/v1/account/change_password_after_reset", Some(params))
             .await
     }
+
+    pub async fn export_account(&self) -> FrontendResult<AccountExport> {
+        self.get("/api/v1/account/export", None::<()>).await
+    }
+
+    pub async fn import_account(&self, data: &AccountExport) -> FrontendResult<SuccessResponse> {
+        let params = ImportAccountParams {
+            data: serde_json::to_string(data)?,
+        };
+        self.post("/api/v1/account/import", Some(params)).await
+    }
+
+    pub async fn move_account(&self, target: Url) -> FrontendResult<SuccessResponse> {
+        let params = MoveAccountParams { target };
+        self.post("/api/v1/account/move", Some(params)).await
+    }
+
+    pub async fn export_personal_data(&self) -> FrontendResult<PersonalDataExport> {
+        self.get("/api/v1/account/personal_data", None::<()>).await
+    }
+
+    /// Delete the account permanently. Edits remain, but are attributed to a placeholder user.
+    pub async fn delete_account(
+        &self,
+        params: DeleteAccountParams,
+    ) -> FrontendResult<SuccessResponse> {
+        self.post("/api/v1/account/delete", Some(params)).await
+    }
 }
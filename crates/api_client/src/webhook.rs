@@ -0,0 +1,57 @@
+use super::ApiClient;
+use crate::errors::FrontendResult;
+use http::Method;
+use ibis_database::common::{
+    SuccessResponse,
+    newtypes::WebhookId,
+    webhook::{CreatedWebhook, Webhook, WebhookDelivery},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CreateWebhookParams {
+    pub url: String,
+    /// Key for the HMAC signature of payloads
+    pub secret: String,
+    /// Comma separated list of events, eg `article_created,comment_created`. Empty for all events.
+    #[serde(default)]
+    pub events: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct DeleteWebhookParams {
+    pub id: WebhookId,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ListWebhookDeliveriesParams {
+    pub webhook_id: WebhookId,
+}
+
+/// Webhooks can only be managed by admin.
+impl ApiClient {
+    pub async fn list_webhooks(&self) -> FrontendResult<Vec<Webhook>> {
+        self.get("/api/v1/webhook/list", None::<()>).await
+    }
+
+    pub async fn create_webhook(
+        &self,
+        params: CreateWebhookParams,
+    ) -> FrontendResult<CreatedWebhook> {
+        self.post("/api/v1/webhook", Some(params)).await
+    }
+
+    pub async fn delete_webhook(&self, id: WebhookId) -> FrontendResult<SuccessResponse> {
+        let params = DeleteWebhookParams { id };
+        self.send(Method::DELETE, "/api/v1/webhook", Some(params))
+            .await
+    }
+
+    pub async fn list_webhook_deliveries(
+        &self,
+        webhook_id: WebhookId,
+    ) -> FrontendResult<Vec<WebhookDelivery>> {
+        let params = ListWebhookDeliveriesParams { webhook_id };
+        self.get("/api/v1/webhook/deliveries", Some(params)).await
+    }
+}
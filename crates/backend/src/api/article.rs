@@ -4,7 +4,7 @@ use activitypub_federation::{config::Data, fetch::object_id::ObjectId};
 use anyhow::anyhow;
 use axum::{Form, Json, extract::Query};
 use axum_macros::debug_handler;
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use diffy::{Patch, apply, create_patch, merge};
 use ibis_api_client::{
     article::{
@@ -16,8 +16,11 @@ use ibis_api_client::{
         GetArticleParams,
         GetConflictParams,
         ListArticlesParams,
+        PatrolEditParams,
         ProtectArticleParams,
+        RecentChangesParams,
         RemoveArticleParams,
+        RevertEditParams,
     },
     instance::SearchArticleParams,
 };
@@ -31,14 +34,21 @@ use ibis_database::{
             ArticleView,
             Conflict,
             Edit,
+            EditTag,
             EditVersion,
+            EditView,
             can_edit_article,
         },
         instance::Instance,
         user::Person,
     },
     error::BackendResult,
-    impls::{IbisContext, article::DbArticleForm, conflict::DbConflictForm, edit::DbEditForm},
+    impls::{
+        IbisContext,
+        article::DbArticleForm,
+        conflict::DbConflictForm,
+        edit::{DbEditForm, EditDescription, RecentChangesFilter},
+    },
 };
 use ibis_federate::{
     activities::{
@@ -52,7 +62,7 @@ use ibis_federate::{
     objects::article::ArticleWrapper,
     validate::{validate_article_title, validate_not_empty},
 };
-use ibis_markdown::format_markdown;
+use ibis_markdown::{format_markdown, is_formatting_change};
 
 /// Create a new article with empty text, and federate it to followers.
 #[debug_handler]
@@ -68,7 +78,7 @@ pub(crate) async fn create_article(
         Some(id) => Instance::read(id, &context)?,
         None => Instance::read_local(&context)?,
     };
-    let ap_id = generate_article_ap_id(&params.title, &instance)?;
+    let ap_id = generate_article_ap_id(&params.title, &instance.ap_id)?;
     let form = DbArticleForm {
         title: params.title,
         text: String::new(),
@@ -85,7 +95,7 @@ pub(crate) async fn create_article(
     let text = format_markdown(&params.text)?;
     submit_article_update(
         text,
-        params.summary,
+        params.summary.into(),
         article.latest_edit_version(&context)?,
         &article,
         user.person.clone().into(),
@@ -137,12 +147,28 @@ pub(crate) async fn edit_article(
 
     // Markdown formatting
     let new_text = format_markdown(&params.new_text)?;
+    if new_text == original_article.article.text {
+        return Err(anyhow!("Edit contains no changes").into());
+    }
 
     if params.previous_version_id == original_article.latest_version {
         // No intermediate changes, simply submit new version
+        let mut description = EditDescription {
+            summary: params.summary.clone(),
+            minor: params.minor,
+            tags: vec![],
+        };
+        // changes which only affect formatting are always minor
+        if is_formatting_change(&original_article.article.text, &new_text)? {
+            description.minor = true;
+            description.tags.push(EditTag::Formatting);
+        }
+        if params.resolve_conflict_id.is_some() {
+            description.tags.push(EditTag::ConflictResolution);
+        }
         submit_article_update(
             new_text.clone(),
-            params.summary.clone(),
+            description,
             params.previous_version_id,
             &original_article.article,
             user.person.clone().into(),
@@ -166,6 +192,7 @@ pub(crate) async fn edit_article(
             creator_id: user.person.id,
             article_id: original_article.article.id,
             previous_version_id: previous_version.hash,
+            minor: params.minor || is_formatting_change(&ancestor, &new_text)?,
         };
         let conflict = Conflict::create(&form, &context)?;
         Ok(Json(
@@ -226,7 +253,7 @@ pub(crate) async fn fork_article(
     validate_article_title(&params.new_title)?;
 
     let local_instance = Instance::read_local(&context)?;
-    let ap_id = generate_article_ap_id(&params.new_title, &local_instance)?;
+    let ap_id = generate_article_ap_id(&params.new_title, &local_instance.ap_id)?;
     let form = DbArticleForm {
         title: params.new_title,
         text: original_article.article.text.clone(),
@@ -255,6 +282,8 @@ pub(crate) async fn fork_article(
             previous_version_id: e.previous_version_id,
             published: e.published,
             pending: false,
+            minor: e.minor,
+            tags: e.tags,
         };
         Edit::create(&form, &context).await?;
     }
@@ -297,7 +326,7 @@ pub(crate) async fn protect_article(
     context: Data<IbisContext>,
     Form(params): Form<ProtectArticleParams>,
 ) -> BackendResult<Json<Article>> {
-    check_is_admin(&user)?;
+    check_is_admin(&user, &context)?;
     let article = Article::update_protected(params.article_id, params.protected, &context)?;
     Ok(Json(article))
 }
@@ -308,7 +337,7 @@ pub async fn remove_article(
     context: Data<IbisContext>,
     Form(params): Form<RemoveArticleParams>,
 ) -> BackendResult<Json<()>> {
-    check_is_admin(&user)?;
+    check_is_admin(&user, &context)?;
     let article = Article::update_removed(params.article_id, params.remove, &context)?;
     let actor = user.person.ap_id.clone().into();
     if params.remove {
@@ -319,6 +348,79 @@ pub async fn remove_article(
     Ok(Json(()))
 }
 
+/// Edits of all articles, for patrolling new changes.
+#[debug_handler]
+pub(crate) async fn recent_changes(
+    Query(params): Query<RecentChangesParams>,
+    context: Data<IbisContext>,
+) -> BackendResult<Json<Vec<EditView>>> {
+    let days = params.days.unwrap_or(7).clamp(1, 90);
+    let filter = RecentChangesFilter {
+        local: params.local,
+        instance_id: params.instance_id,
+        hide_bots: params.hide_bots.unwrap_or(true),
+        unpatrolled_only: params.unpatrolled_only.unwrap_or_default(),
+        hide_minor: params.hide_minor.unwrap_or_default(),
+        since: Utc::now() - Duration::days(days),
+        limit: params.limit.unwrap_or(50).clamp(1, 500),
+    };
+    Ok(Json(Edit::list_recent_changes(&filter, &context)?))
+}
+
+#[debug_handler]
+pub(crate) async fn patrol_edit(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<PatrolEditParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    check_is_admin(&user, &context)?;
+    Edit::update_patrolled(params.edit_id, true, &context)?;
+    Ok(Json(SuccessResponse::default()))
+}
+
+/// Undo the changes of an edit by applying its diff in reverse to the current article text. This
+/// fails if later edits changed the same lines.
+#[debug_handler]
+pub(crate) async fn revert_edit(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<RevertEditParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    let edit = Edit::read_by_id(params.edit_id, &context)?;
+    if edit.pending {
+        return Err(anyhow!("Pending edits can't be reverted").into());
+    }
+    let article = Article::read_view(edit.article_id, Some(&user), &context)?;
+    can_edit_article(&article.article, user.local_user.admin)?;
+    let patch = Patch::from_str(&edit.diff)?;
+    let new_text = apply(&article.article.text, &patch.reverse())
+        .map_err(|_| anyhow!("Edit can't be reverted because of later changes"))?;
+    if new_text == article.article.text {
+        return Err(anyhow!("Edit contains no changes").into());
+    }
+    let creator = Person::read(edit.creator_id, &context)?;
+    let description = EditDescription {
+        summary: format!("Revert \"{}\" by {}", edit.summary, creator.title()),
+        minor: false,
+        tags: vec![EditTag::Revert],
+    };
+    submit_article_update(
+        new_text,
+        description,
+        article.latest_version,
+        &article.article,
+        user.person.clone().into(),
+        false,
+        &context,
+    )
+    .await?;
+    // reverting means that the edit was checked
+    if user.local_user.admin {
+        Edit::update_patrolled(edit.id, true, &context)?;
+    }
+    Ok(Json(SuccessResponse::default()))
+}
+
 #[debug_handler]
 pub async fn get_conflict(
     user: UserExt,
@@ -381,9 +483,17 @@ pub async fn db_conflict_to_api_conflict(
         Ok(new_text) => {
             let person = Person::read(conflict.creator_id, context)?.into();
             // patch applies cleanly so we are done, federate the change
+            let mut description = EditDescription {
+                summary: conflict.summary.clone(),
+                minor: conflict.minor,
+                tags: vec![EditTag::ConflictResolution],
+            };
+            if is_formatting_change(&ancestor, &ours)? {
+                description.tags.push(EditTag::Formatting);
+            }
             submit_article_update(
                 new_text,
-                conflict.summary.clone(),
+                description,
                 conflict.previous_version_id.clone(),
                 &original_article,
                 person,
@@ -404,6 +514,7 @@ pub async fn db_conflict_to_api_conflict(
                 article: original_article.clone().0,
                 previous_version_id: original_article.latest_edit_version(context)?,
                 published: conflict.published,
+                minor: conflict.minor,
             }))
         }
     }
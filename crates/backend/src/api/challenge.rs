@@ -0,0 +1,58 @@
+use activitypub_federation::config::Data;
+use anyhow::anyhow;
+use axum::Json;
+use axum_macros::debug_handler;
+use chrono::Utc;
+use ibis_api_client::challenge::{CHALLENGE_REQUIRED, ProofOfWorkChallenge};
+use ibis_database::{
+    error::BackendResult,
+    impls::{IbisContext, read_jwt_secret},
+    proof_of_work::{CHALLENGE_VALIDITY, create_challenge, verify_challenge},
+};
+use moka::sync::Cache;
+use std::sync::LazyLock;
+
+#[debug_handler]
+pub(crate) async fn get_challenge(
+    context: Data<IbisContext>,
+) -> BackendResult<Json<ProofOfWorkChallenge>> {
+    let difficulty = context.conf.options.proof_of_work_difficulty;
+    if difficulty == 0 {
+        return Err(anyhow!("Proof-of-work challenge is disabled").into());
+    }
+    let challenge = create_challenge(&read_jwt_secret(&context)?, difficulty, Utc::now())?;
+    Ok(Json(ProofOfWorkChallenge {
+        challenge,
+        difficulty,
+    }))
+}
+
+/// Check the solved challenge if the instance requires it. Each challenge can only be used once.
+pub(crate) fn check_challenge(
+    challenge: Option<&str>,
+    nonce: Option<u64>,
+    context: &IbisContext,
+) -> BackendResult<()> {
+    // challenges which were already used, until they expire anyway
+    static USED: LazyLock<Cache<String, ()>> = LazyLock::new(|| {
+        Cache::builder()
+            .max_capacity(100_000)
+            .time_to_live(CHALLENGE_VALIDITY.to_std().unwrap_or_default())
+            .build()
+    });
+
+    let difficulty = context.conf.options.proof_of_work_difficulty;
+    if difficulty == 0 {
+        return Ok(());
+    }
+    let (Some(challenge), Some(nonce)) = (challenge, nonce) else {
+        return Err(anyhow!(CHALLENGE_REQUIRED).into());
+    };
+    let secret = read_jwt_secret(context)?;
+    verify_challenge(&secret, challenge, nonce, difficulty, Utc::now())?;
+    let entry = USED.entry(challenge.to_string()).or_insert(());
+    if !entry.is_fresh() {
+        return Err(anyhow!("Proof-of-work challenge was already used").into());
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use super::UserExt;
+use super::{UserExt, challenge::check_challenge};
 use activitypub_federation::config::Data;
 use anyhow::anyhow;
 use axum::{Form, Json};
@@ -14,6 +14,7 @@ use ibis_database::{
     impls::{
         IbisContext,
         comment::{DbCommentInsertForm, DbCommentUpdateForm},
+        notifications::Notification,
     },
 };
 use ibis_federate::{
@@ -22,6 +23,7 @@ use ibis_federate::{
         delete_comment::DeleteComment,
         undo_delete_comment::UndoDeleteComment,
     },
+    mentions::resolve_mentions,
     objects::comment::CommentWrapper,
     validate::{validate_comment_max_depth, validate_not_empty},
 };
@@ -34,6 +36,15 @@ pub(crate) async fn create_comment(
     Form(params): Form<CreateCommentParams>,
 ) -> BackendResult<Json<CommentView>> {
     validate_not_empty(&params.content)?;
+    if context.conf.options.proof_of_work_first_comment
+        && Comment::count_for_person(user.person.id, &context)? == 0
+    {
+        check_challenge(
+            params.challenge.as_deref(),
+            params.challenge_nonce,
+            &context,
+        )?;
+    }
     let mut depth = 0;
     if let Some(parent_id) = params.parent_id {
         let parent = Comment::read(parent_id, &context)?;
@@ -75,6 +86,9 @@ pub(crate) async fn create_comment(
     };
     let comment = Comment::update(form, comment.id, &context)?;
 
+    let mentioned = resolve_mentions(&comment.comment.content, &user.person, &context).await;
+    Notification::notify_comment_mentions(&comment.comment, &mentioned, &context).await?;
+
     CreateOrUpdateComment::send(&comment.comment.clone().into(), &context).await?;
 
     Ok(Json(comment))
@@ -107,6 +121,8 @@ pub(crate) async fn edit_comment(
     let apub_comment: CommentWrapper = comment.comment.clone().into();
     // federate
     if orig_comment.content != comment.comment.content {
+        let mentioned = resolve_mentions(&comment.comment.content, &user.person, &context).await;
+        Notification::notify_comment_mentions(&comment.comment, &mentioned, &context).await?;
         CreateOrUpdateComment::send(&apub_comment, &context).await?;
     }
     if !orig_comment.deleted && comment.comment.deleted {
@@ -0,0 +1,42 @@
+use super::UserExt;
+use activitypub_federation::config::Data;
+use axum::{
+    extract::Query,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use axum_macros::debug_handler;
+use futures::{Stream, stream::unfold};
+use ibis_api_client::notifications::LiveEventsParams;
+use ibis_database::{common::events::LiveEvent, impls::IbisContext};
+use tokio::sync::broadcast::error::RecvError;
+
+/// Stream new notifications of the logged-in user, and changes to the article which is being
+/// viewed.
+#[debug_handler]
+pub(crate) async fn live_events(
+    user: Option<UserExt>,
+    Query(params): Query<LiveEventsParams>,
+    context: Data<IbisContext>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let local_user_id = user.map(|u| u.local_user.id);
+    let stream = unfold(context.subscribe(), move |mut receiver| async move {
+        loop {
+            let message = match receiver.recv().await {
+                Ok(m) => m,
+                // client is too slow, skip the missed events
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            };
+            if message.local_user_id.is_some() && message.local_user_id != local_user_id {
+                continue;
+            }
+            if let LiveEvent::ArticleUpdated { article_id, .. } = &message.event {
+                if Some(*article_id) != params.article_id {
+                    continue;
+                }
+            }
+            return Some((Event::default().json_data(&message.event), receiver));
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
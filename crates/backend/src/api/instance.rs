@@ -1,25 +1,44 @@
-use super::{UserExt, empty_to_none};
-use activitypub_federation::{config::Data, fetch::object_id::ObjectId};
+use super::{UserExt, check_is_admin, empty_to_none};
+use crate::backup::{read_archive, write_archive};
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    http_signatures::generate_actor_keypair,
+};
 use anyhow::anyhow;
-use axum::{Form, Json, extract::Query};
+use axum::{Form, Json, body::Bytes, extract::Query, response::IntoResponse};
 use axum_macros::debug_handler;
-use ibis_api_client::instance::{FollowInstanceParams, GetInstanceParams, UpdateInstanceParams};
+use chrono::Utc;
+use http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use ibis_api_client::instance::{
+    EmailPreviewParams,
+    FollowInstanceParams,
+    GetInstanceParams,
+    RotateKeyParams,
+    UpdateInstanceParams,
+};
 use ibis_database::{
     common::{
         ResolveObjectParams,
         SuccessResponse,
+        email::EmailPreview,
         instance::{Instance, InstanceView},
         user::Person,
         utils::http_protocol_str,
     },
+    email::template::preview_email,
     error::BackendResult,
     impls::{
         IbisContext,
+        backup::WikiBackup,
         instance::{DbInstanceUpdateForm, InstanceViewQuery},
     },
 };
 use ibis_federate::{
-    activities::following::{follow::Follow, undo_follow::UndoFollow},
+    activities::{
+        following::{follow::Follow, undo_follow::UndoFollow},
+        update_actor::UpdateActor,
+    },
     objects::instance::InstanceWrapper,
 };
 use moka::sync::Cache;
@@ -83,7 +102,7 @@ pub(crate) async fn follow_instance(
     if params.follow {
         let pending = !instance.local;
         Instance::follow(&person, &instance, pending, &context)?;
-        Follow::send(&actor, &instance.into(), &context).await?;
+        Follow::send(&actor, &InstanceWrapper::from(instance), &context).await?;
     } else {
         Instance::unfollow(&person, &instance, &context)?;
         UndoFollow::send(&actor, &instance.into(), &context).await?;
@@ -91,6 +110,56 @@ pub(crate) async fn follow_instance(
     Ok(Json(SuccessResponse::default()))
 }
 
+/// Generate a new key for the local instance or one of its users, and publish it to other
+/// instances. Signatures made with the old key are still accepted during the grace period.
+#[debug_handler]
+pub(crate) async fn rotate_key(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<RotateKeyParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    check_is_admin(&user, &context)?;
+    let keypair = generate_actor_keypair()?;
+    if let Some(person_id) = params.person_id {
+        let person = Person::read(person_id, &context)?;
+        if !person.local {
+            return Err(anyhow!("Can only rotate key of local user").into());
+        }
+        let person = Person::update_key(
+            person.id,
+            keypair.public_key,
+            Some(keypair.private_key),
+            &context,
+        )?;
+        UpdateActor::send_person(person.into(), &context).await?;
+    } else {
+        let instance = Instance::read_local(&context)?;
+        let instance = Instance::update_key(
+            instance.id,
+            keypair.public_key,
+            Some(keypair.private_key),
+            &context,
+        )?;
+        UpdateActor::send_instance(instance.into(), &context).await?;
+    }
+    Ok(Json(SuccessResponse::default()))
+}
+
+/// Render an email template with example data, so that admins can check customized templates.
+#[debug_handler]
+pub(crate) async fn email_preview(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Query(params): Query<EmailPreviewParams>,
+) -> BackendResult<Json<EmailPreview>> {
+    check_is_admin(&user, &context)?;
+    Ok(Json(preview_email(
+        params.template,
+        params.locale.as_deref(),
+        &context,
+    )?))
+}
+
 /// Fetch a remote instance actor. This automatically synchronizes the remote articles collection to
 /// the local instance, and allows for interactions such as following.
 #[debug_handler]
@@ -136,3 +205,37 @@ pub(crate) async fn list_instance_views(
     }
     Ok(Json(instances))
 }
+
+/// Download all local articles with their history as backup archive.
+#[debug_handler]
+pub(crate) async fn export_wiki(
+    user: UserExt,
+    context: Data<IbisContext>,
+) -> BackendResult<impl IntoResponse> {
+    check_is_admin(&user, &context)?;
+    let archive = write_archive(WikiBackup::read(&context)?)?;
+    let filename = format!(
+        "attachment; filename=\"{}-{}.tar\"",
+        context.conf.federation.domain,
+        Utc::now().format("%Y-%m-%d")
+    );
+    Ok((
+        [
+            (CONTENT_TYPE, "application/x-tar".to_string()),
+            (CONTENT_DISPOSITION, filename),
+        ],
+        archive,
+    ))
+}
+
+/// Restore articles from a backup archive which is sent as request body.
+#[debug_handler]
+pub(crate) async fn import_wiki(
+    user: UserExt,
+    context: Data<IbisContext>,
+    body: Bytes,
+) -> BackendResult<Json<SuccessResponse>> {
+    check_is_admin(&user, &context)?;
+    read_archive(&body)?.restore(&context)?;
+    Ok(Json(SuccessResponse::default()))
+}
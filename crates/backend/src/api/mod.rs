@@ -6,13 +6,22 @@ use crate::api::{
         get_article,
         get_conflict,
         list_articles,
+        patrol_edit,
         protect_article,
+        recent_changes,
         resolve_article,
+        revert_edit,
         search_article,
     },
     comment::{create_comment, edit_comment},
     instance::{follow_instance, get_instance, resolve_instance},
-    user::{get_user, login_user, logout_user, register::register_user},
+    user::{
+        get_user,
+        login_user,
+        logout_user,
+        oauth::public_oauth_providers,
+        register::register_user,
+    },
 };
 use activitypub_federation::config::Data;
 use anyhow::anyhow;
@@ -21,13 +30,22 @@ use axum::{
     Extension,
     Json,
     Router,
-    extract::{Query, rejection::ExtensionRejection},
+    async_trait,
+    extract::{
+        ConnectInfo,
+        DefaultBodyLimit,
+        FromRequestParts,
+        Query,
+        rejection::ExtensionRejection,
+    },
     response::IntoResponse,
     routing::{delete, get, patch, post},
 };
 use axum_macros::{FromRequestParts, debug_handler};
+use challenge::get_challenge;
 use comment::get_comment;
-use http::StatusCode;
+use events::live_events;
+use http::{StatusCode, header::USER_AGENT, request::Parts};
 use ibis_api_client::article::GetEditList;
 use ibis_database::{
     common::{
@@ -38,25 +56,54 @@ use ibis_database::{
     error::BackendResult,
     impls::{IbisContext, edit::ViewEditParams},
 };
-use instance::{list_instance_views, update_instance};
-use std::ops::Deref;
+use instance::{
+    email_preview,
+    export_wiki,
+    import_wiki,
+    list_instance_views,
+    rotate_key,
+    update_instance,
+};
+use registration::{
+    approve_registration_application,
+    create_invite_code,
+    delete_invite_code,
+    list_invite_codes,
+    list_registration_applications,
+};
+use std::{convert::Infallible, net::SocketAddr, ops::Deref};
 use user::{
+    api_token::{create_api_token, delete_api_token, list_api_tokens},
     article_notif_mark_as_read,
+    block::{block_instance, block_person, list_blocks, unblock_instance, unblock_person},
     change_password,
     change_password_after_reset,
+    contributions::{get_person_contributions, get_person_stats},
     count_notifications,
     get_user_follows,
     list_notifications,
+    list_sessions,
+    migration::{export_account, import_account, move_account},
+    personal_data::{delete_account, export_personal_data},
     register::authenticate_with_oauth,
     request_reset_password,
+    revoke_session,
+    totp::{disable_totp, enable_totp, generate_totp},
+    trust_bot,
     update_user_profile,
     verify_email,
+    web_push::{web_push_key, web_push_subscribe, web_push_unsubscribe},
 };
+use webhook::{create_webhook, delete_webhook, list_webhook_deliveries, list_webhooks};
 
 mod article;
+mod challenge;
 mod comment;
+mod events;
 mod instance;
+mod registration;
 pub(super) mod user;
+mod webhook;
 
 pub fn api_routes() -> Router<()> {
     Router::new()
@@ -71,8 +118,12 @@ pub fn api_routes() -> Router<()> {
         .route("/article/remove", post(remove_article))
         .route("/article/follow", post(follow_article))
         .route("/edit/list", get(edit_list))
+        .route("/edit/recent", get(recent_changes))
+        .route("/edit/patrol", post(patrol_edit))
+        .route("/edit/revert", post(revert_edit))
         .route("/conflict", get(get_conflict))
         .route("/conflict", delete(delete_conflict))
+        .route("/challenge", get(get_challenge))
         .route("/comment", post(create_comment))
         .route("/comment", patch(edit_comment))
         .route("/comment", get(get_comment))
@@ -81,15 +132,53 @@ pub fn api_routes() -> Router<()> {
         .route("/instance/follow", post(follow_instance))
         .route("/instance/resolve", get(resolve_instance))
         .route("/instance/list", get(list_instance_views))
+        .route("/instance/rotate_key", post(rotate_key))
+        .route("/instance/export", get(export_wiki))
+        .route("/instance/email_preview", get(email_preview))
+        .route(
+            "/instance/import",
+            post(import_wiki).layer(DefaultBodyLimit::disable()),
+        )
+        .route(
+            "/invite_code",
+            post(create_invite_code).delete(delete_invite_code),
+        )
+        .route("/invite_code/list", get(list_invite_codes))
+        .route(
+            "/registration_application/list",
+            get(list_registration_applications),
+        )
+        .route(
+            "/registration_application/approve",
+            post(approve_registration_application),
+        )
+        .route("/webhook", post(create_webhook).delete(delete_webhook))
+        .route("/webhook/list", get(list_webhooks))
+        .route("/webhook/deliveries", get(list_webhook_deliveries))
         .route("/search", get(search_article))
         .route("/user", get(get_user))
         .route("/user/follows", get(get_user_follows))
+        .route("/user/contributions", get(get_person_contributions))
+        .route("/user/stats", get(get_person_stats))
+        .route("/user/trust_bot", post(trust_bot))
+        .route(
+            "/user/block/person",
+            post(block_person).delete(unblock_person),
+        )
+        .route(
+            "/user/block/instance",
+            post(block_instance).delete(unblock_instance),
+        )
+        .route("/user/block/list", get(list_blocks))
         .route("/user/notifications/list", get(list_notifications))
         .route("/user/notifications/count", get(count_notifications))
         .route(
             "/user/notifications/mark_as_read",
             post(article_notif_mark_as_read),
         )
+        .route("/user/web_push/key", get(web_push_key))
+        .route("/user/web_push/subscribe", post(web_push_subscribe))
+        .route("/user/web_push/unsubscribe", post(web_push_unsubscribe))
         .route("/account/register", post(register_user))
         .route("/account/login", post(login_user))
         .route("/account/logout", post(logout_user))
@@ -97,6 +186,19 @@ pub fn api_routes() -> Router<()> {
         .route("/account/oauth/authenticate", post(authenticate_with_oauth))
         .route("/account/verify_email", post(verify_email))
         .route("/account/change_password", post(change_password))
+        .route("/account/sessions", get(list_sessions))
+        .route("/account/sessions/revoke", post(revoke_session))
+        .route("/account/api_token", post(create_api_token))
+        .route("/account/api_token/list", get(list_api_tokens))
+        .route("/account/api_token/delete", post(delete_api_token))
+        .route("/account/totp/generate", post(generate_totp))
+        .route("/account/totp/enable", post(enable_totp))
+        .route("/account/totp/disable", post(disable_totp))
+        .route("/account/export", get(export_account))
+        .route("/account/import", post(import_account))
+        .route("/account/move", post(move_account))
+        .route("/account/personal_data", get(export_personal_data))
+        .route("/account/delete", post(delete_account))
         .route(
             "/account/request_reset_password",
             post(request_reset_password),
@@ -106,12 +208,16 @@ pub fn api_routes() -> Router<()> {
             post(change_password_after_reset),
         )
         .route("/site", get(site_view))
+        .route("/events", get(live_events))
 }
 
-pub fn check_is_admin(user: &LocalUserView) -> BackendResult<()> {
+pub fn check_is_admin(user: &LocalUserView, context: &IbisContext) -> BackendResult<()> {
     if !user.local_user.admin {
         return Err(anyhow!("Only admin can perform this action").into());
     }
+    if context.conf.options.admin_totp_required && !user.local_user.totp_enabled {
+        return Err(anyhow!("Enable two-factor authentication to perform admin actions").into());
+    }
     Ok(())
 }
 
@@ -120,13 +226,7 @@ pub(crate) async fn site_view(
     context: Data<IbisContext>,
     user: Option<UserExt>,
 ) -> BackendResult<Json<SiteView>> {
-    let oauth_providers = context
-        .conf
-        .oauth_providers
-        .clone()
-        .into_iter()
-        .map(Into::into)
-        .collect();
+    let oauth_providers = public_oauth_providers(&context).await;
 
     Ok(Json(SiteView {
         my_profile: user.map(|u| u.inner()),
@@ -201,3 +301,39 @@ impl IntoResponse for NotLoggedInError {
         (StatusCode::FORBIDDEN, "Login required").into_response()
     }
 }
+
+/// User agent and IP address of the client, which are shown in the list of login sessions.
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let header = |name: &str| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|h| h.to_str().ok())
+                .map(|h| h.trim().to_string())
+        };
+        // Behind a reverse proxy the client address is only available from headers. These can be
+        // set by the client, but the address is only informational.
+        let ip = header("X-Forwarded-For")
+            .and_then(|h| h.split(',').next().map(|ip| ip.trim().to_string()))
+            .or_else(|| header("X-Real-IP"))
+            .or_else(|| {
+                parts
+                    .extensions
+                    .get::<ConnectInfo<SocketAddr>>()
+                    .map(|c| c.0.ip().to_string())
+            });
+        Ok(ClientInfo {
+            user_agent: header(USER_AGENT.as_str()),
+            ip,
+        })
+    }
+}
@@ -0,0 +1,125 @@
+use super::{UserExt, check_is_admin};
+use activitypub_federation::config::Data;
+use anyhow::anyhow;
+use axum::{Form, Json};
+use axum_macros::debug_handler;
+use chrono::{Duration, Utc};
+use ibis_api_client::registration::{
+    ApproveRegistrationParams,
+    CreateInviteCodeParams,
+    DeleteInviteCodeParams,
+};
+use ibis_database::{
+    common::{
+        SuccessResponse,
+        article::Edit,
+        registration::{
+            InviteCode,
+            RegistrationApplication,
+            RegistrationApplicationStatus,
+            RegistrationApplicationView,
+        },
+        user::LocalUserView,
+    },
+    email::registration_application::send_application_result_email,
+    error::BackendResult,
+    impls::{IbisContext, registration::InviteCodeInsertForm, user::LocalUserViewQuery},
+};
+
+/// Maximum number of accounts which can be registered with a single invite code.
+const MAX_INVITE_CODE_USES: i32 = 100;
+
+#[debug_handler]
+pub(crate) async fn create_invite_code(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<CreateInviteCodeParams>,
+) -> BackendResult<Json<InviteCode>> {
+    if !user.local_user.admin {
+        let min_edits = context
+            .conf
+            .options
+            .invite_code_min_edits
+            .ok_or(anyhow!("Only admins can create invite codes"))?;
+        let edits = Edit::count_for_person(user.person.id, &context)?;
+        if edits < i64::from(min_edits) {
+            return Err(anyhow!("You need {min_edits} edits to create invite codes").into());
+        }
+    }
+    if !(1..=MAX_INVITE_CODE_USES).contains(&params.max_uses) {
+        return Err(
+            anyhow!("Invite code uses must be between 1 and {MAX_INVITE_CODE_USES}").into(),
+        );
+    }
+    let expires = match params.expires_in_days {
+        Some(days) if days > 0 => Some(Utc::now() + Duration::days(days)),
+        Some(_) => return Err(anyhow!("Invite code must expire in the future").into()),
+        None => None,
+    };
+    let form = InviteCodeInsertForm {
+        creator_id: user.local_user.id,
+        max_uses: params.max_uses,
+        expires,
+    };
+    Ok(Json(InviteCode::create(&form, &context)?))
+}
+
+#[debug_handler]
+pub(crate) async fn list_invite_codes(
+    user: UserExt,
+    context: Data<IbisContext>,
+) -> BackendResult<Json<Vec<InviteCode>>> {
+    Ok(Json(InviteCode::list(user.local_user.id, &context)?))
+}
+
+#[debug_handler]
+pub(crate) async fn delete_invite_code(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<DeleteInviteCodeParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    InviteCode::delete(params.id, user.local_user.id, &context)?;
+    Ok(Json(SuccessResponse::default()))
+}
+
+#[debug_handler]
+pub(crate) async fn list_registration_applications(
+    user: UserExt,
+    context: Data<IbisContext>,
+) -> BackendResult<Json<Vec<RegistrationApplicationView>>> {
+    check_is_admin(&user, &context)?;
+    Ok(Json(RegistrationApplication::list_pending(&context)?))
+}
+
+/// Approve or deny a pending application, and inform the user by email.
+#[debug_handler]
+pub(crate) async fn approve_registration_application(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<ApproveRegistrationParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    check_is_admin(&user, &context)?;
+    let (status, deny_reason) = if params.approve {
+        (RegistrationApplicationStatus::Approved, None)
+    } else {
+        let deny_reason = params.deny_reason.filter(|r| !r.trim().is_empty());
+        (RegistrationApplicationStatus::Denied, deny_reason)
+    };
+    let application = RegistrationApplication::decide(
+        params.id,
+        status,
+        user.local_user.id,
+        deny_reason,
+        &context,
+    )?;
+    let applicant =
+        LocalUserView::read(LocalUserViewQuery::Id(application.local_user_id), &context)?;
+    send_application_result_email(
+        &applicant,
+        params.approve,
+        application.deny_reason.as_deref(),
+        &context,
+    )
+    .await?;
+    Ok(Json(SuccessResponse::default()))
+}
@@ -0,0 +1,43 @@
+use crate::api::UserExt;
+use activitypub_federation::config::Data;
+use anyhow::anyhow;
+use axum::{Form, Json};
+use axum_macros::debug_handler;
+use ibis_api_client::user::{CreateApiTokenParams, CreateApiTokenResponse, DeleteApiTokenParams};
+use ibis_database::{
+    common::{SuccessResponse, user::ApiToken},
+    error::BackendResult,
+    impls::IbisContext,
+};
+
+#[debug_handler]
+pub(crate) async fn list_api_tokens(
+    user: UserExt,
+    context: Data<IbisContext>,
+) -> BackendResult<Json<Vec<ApiToken>>> {
+    Ok(Json(ApiToken::list(user.local_user.id, &context)?))
+}
+
+#[debug_handler]
+pub(crate) async fn create_api_token(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<CreateApiTokenParams>,
+) -> BackendResult<Json<CreateApiTokenResponse>> {
+    let name = params.name.trim().to_string();
+    if name.is_empty() {
+        return Err(anyhow!("Api token name must not be empty").into());
+    }
+    let (api_token, token) = ApiToken::create(user.local_user.id, name, params.scope, &context)?;
+    Ok(Json(CreateApiTokenResponse { api_token, token }))
+}
+
+#[debug_handler]
+pub(crate) async fn delete_api_token(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<DeleteApiTokenParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    ApiToken::delete(params.id, user.local_user.id, &context)?;
+    Ok(Json(SuccessResponse::default()))
+}
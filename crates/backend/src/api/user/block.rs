@@ -0,0 +1,77 @@
+use crate::api::UserExt;
+use activitypub_federation::config::Data;
+use anyhow::anyhow;
+use axum::{Form, Json};
+use axum_macros::debug_handler;
+use ibis_api_client::block::{
+    BlockInstanceParams,
+    BlockPersonParams,
+    UnblockInstanceParams,
+    UnblockPersonParams,
+};
+use ibis_database::{
+    common::{
+        SuccessResponse,
+        block::{BlockList, InstanceBlock, PersonBlock},
+        instance::Instance,
+        user::Person,
+    },
+    error::BackendResult,
+    impls::IbisContext,
+};
+
+#[debug_handler]
+pub(crate) async fn list_blocks(
+    user: UserExt,
+    context: Data<IbisContext>,
+) -> BackendResult<Json<BlockList>> {
+    Ok(Json(BlockList::read(user.local_user.id, &context)?))
+}
+
+#[debug_handler]
+pub(crate) async fn block_person(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<BlockPersonParams>,
+) -> BackendResult<Json<PersonBlock>> {
+    if params.person_id == user.person.id {
+        return Err(anyhow!("You can't block yourself").into());
+    }
+    let person = Person::read(params.person_id, &context)?;
+    let block = PersonBlock::create(user.local_user.id, person.id, params.kind, &context)?;
+    Ok(Json(block))
+}
+
+#[debug_handler]
+pub(crate) async fn unblock_person(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<UnblockPersonParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    PersonBlock::delete(user.local_user.id, params.person_id, &context)?;
+    Ok(Json(SuccessResponse::default()))
+}
+
+#[debug_handler]
+pub(crate) async fn block_instance(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<BlockInstanceParams>,
+) -> BackendResult<Json<InstanceBlock>> {
+    let instance = Instance::read(params.instance_id, &context)?;
+    if instance.local {
+        return Err(anyhow!("You can't block your own instance").into());
+    }
+    let block = InstanceBlock::create(user.local_user.id, instance.id, params.kind, &context)?;
+    Ok(Json(block))
+}
+
+#[debug_handler]
+pub(crate) async fn unblock_instance(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<UnblockInstanceParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    InstanceBlock::delete(user.local_user.id, params.instance_id, &context)?;
+    Ok(Json(SuccessResponse::default()))
+}
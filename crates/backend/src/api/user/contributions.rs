@@ -0,0 +1,53 @@
+use activitypub_federation::config::Data;
+use anyhow::anyhow;
+use axum::{Json, extract::Query};
+use axum_macros::debug_handler;
+use ibis_api_client::user::{
+    CONTRIBUTIONS_PAGE_SIZE,
+    GetContributionsParams,
+    GetPersonStatsParams,
+};
+use ibis_database::{
+    common::{
+        article::Edit,
+        comment::Comment,
+        contributions::{ActivityDay, PersonContributions, PersonStats, PersonStatsView},
+    },
+    error::BackendResult,
+    impls::IbisContext,
+};
+
+#[debug_handler]
+pub(crate) async fn get_person_contributions(
+    Query(params): Query<GetContributionsParams>,
+    context: Data<IbisContext>,
+) -> BackendResult<Json<PersonContributions>> {
+    let offset = (params.page.unwrap_or(1).max(1) - 1)
+        .checked_mul(CONTRIBUTIONS_PAGE_SIZE)
+        .ok_or(anyhow!("Invalid page"))?;
+    Ok(Json(PersonContributions {
+        edits: Edit::list_views_for_person(
+            params.person_id,
+            CONTRIBUTIONS_PAGE_SIZE,
+            offset,
+            &context,
+        )?,
+        comments: Comment::list_views_for_person(
+            params.person_id,
+            CONTRIBUTIONS_PAGE_SIZE,
+            offset,
+            &context,
+        )?,
+    }))
+}
+
+#[debug_handler]
+pub(crate) async fn get_person_stats(
+    Query(params): Query<GetPersonStatsParams>,
+    context: Data<IbisContext>,
+) -> BackendResult<Json<PersonStatsView>> {
+    Ok(Json(PersonStatsView {
+        stats: PersonStats::read(params.person_id, &context)?,
+        activity: ActivityDay::list_for_person(params.person_id, &context)?,
+    }))
+}
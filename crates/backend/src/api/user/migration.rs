@@ -0,0 +1,143 @@
+use crate::api::UserExt;
+use activitypub_federation::{config::Data, fetch::object_id::ObjectId};
+use axum::{Form, Json};
+use axum_macros::debug_handler;
+use ibis_api_client::user::{AccountExport, ImportAccountParams, MoveAccountParams};
+use ibis_database::{
+    common::{
+        SuccessResponse,
+        article::Article,
+        instance::Instance,
+        user::{LocalUser, Person},
+    },
+    error::BackendResult,
+    impls::{
+        IbisContext,
+        user::{LocalUserUpdateForm, PersonUpdateForm},
+    },
+};
+use ibis_federate::{
+    activities::{
+        following::{follow::Follow, follow_person},
+        move_person::MovePerson,
+    },
+    objects::{article::ArticleWrapper, instance::InstanceWrapper, user::PersonWrapper},
+    validate::validate_locale,
+};
+use log::warn;
+
+/// Export profile, notification settings and follows, so that they can be imported into an
+/// account on another instance.
+#[debug_handler]
+pub(crate) async fn export_account(
+    user: UserExt,
+    context: Data<IbisContext>,
+) -> BackendResult<Json<AccountExport>> {
+    let following_persons = Person::read_followed_persons(user.person.id, &context)?
+        .into_iter()
+        .map(|p| p.ap_id.into())
+        .collect();
+    let following_instances = Person::read_following(user.person.id, &context)?
+        .into_iter()
+        .map(|f| f.instance.ap_id.into())
+        .collect();
+    let following_articles = Article::read_followed(user.local_user.id, &context)?
+        .into_iter()
+        .map(|a| a.ap_id.into())
+        .collect();
+    Ok(Json(AccountExport {
+        ap_id: user.person.ap_id.clone().into(),
+        display_name: user.person.display_name.clone(),
+        bio: user.person.bio.clone(),
+        email_notifications: user.local_user.email_notifications,
+        email_digest: user.local_user.email_digest,
+        locale: user.local_user.locale.clone(),
+        following_persons,
+        following_instances,
+        following_articles,
+    }))
+}
+
+/// Import data exported from another account. The old account is added as alias, which allows
+/// it to move to this account afterwards.
+#[debug_handler]
+pub(crate) async fn import_account(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<ImportAccountParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    let data: AccountExport = serde_json::from_str(&params.data)?;
+    validate_locale(&data.locale)?;
+    let person_form = PersonUpdateForm {
+        display_name: data.display_name,
+        bio: data.bio,
+        ..Default::default()
+    };
+    let local_user_form = LocalUserUpdateForm {
+        email_notifications: Some(data.email_notifications),
+        email_digest: Some(data.email_digest),
+        locale: data.locale,
+        ..Default::default()
+    };
+    // an update without any values would fail
+    if person_form.display_name.is_some() || person_form.bio.is_some() {
+        Person::update(&person_form, user.person.id, &context)?;
+    }
+    LocalUser::update(&local_user_form, user.local_user.id, &context)?;
+    let person = Person::add_alias(user.person.id, data.ap_id.into(), &context)?;
+
+    // Follows which cant be resolved (eg because the remote instance is down) are skipped, so
+    // that the rest of the data can still be imported.
+    let actor: PersonWrapper = person.clone().into();
+    for ap_id in data.following_instances {
+        let res = async {
+            let instance = ObjectId::<InstanceWrapper>::from(ap_id.clone())
+                .dereference(&context)
+                .await?;
+            Instance::follow(&person, &instance, !instance.local, &context)?;
+            Follow::send(&actor, &instance, &context).await
+        };
+        if let Err(e) = res.await {
+            warn!("Failed to import follow for instance {ap_id}: {e}");
+        }
+    }
+    for ap_id in data.following_persons {
+        let res = async {
+            let followed = ObjectId::<PersonWrapper>::from(ap_id.clone())
+                .dereference(&context)
+                .await?;
+            follow_person(&actor, &followed, &context).await
+        };
+        if let Err(e) = res.await {
+            warn!("Failed to import follow for user {ap_id}: {e}");
+        }
+    }
+    for ap_id in data.following_articles {
+        let res = async {
+            let article = ObjectId::<ArticleWrapper>::from(ap_id.clone())
+                .dereference(&context)
+                .await?;
+            Article::follow(article.id, &user, &context)
+        };
+        if let Err(e) = res.await {
+            warn!("Failed to import follow for article {ap_id}: {e}");
+        }
+    }
+    Ok(Json(SuccessResponse::default()))
+}
+
+/// Move the account to a new one, which needs to list the current account as alias. Followers
+/// are notified so that they can follow the new account instead.
+#[debug_handler]
+pub(crate) async fn move_account(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<MoveAccountParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    // Fetch the latest version so that a recently added alias is included
+    let target = ObjectId::<PersonWrapper>::from(params.target)
+        .dereference_forced(&context)
+        .await?;
+    MovePerson::send(&user.person.clone().into(), &target, &context).await?;
+    Ok(Json(SuccessResponse::default()))
+}
@@ -1,7 +1,7 @@
-use super::{UserExt, empty_to_none};
+use super::{ClientInfo, UserExt, check_is_admin, empty_to_none};
 use activitypub_federation::config::Data;
 use anyhow::anyhow;
-use axum::{Form, Json, extract::Query};
+use axum::{Extension, Form, Json, extract::Query};
 use axum_extra::extract::cookie::{Cookie, CookieJar, Expiration, SameSite};
 use axum_macros::debug_handler;
 use bcrypt::verify;
@@ -14,6 +14,9 @@ use ibis_api_client::{
         GetUserParams,
         LoginUserParams,
         PasswordReset,
+        RevokeSessionParams,
+        TOTP_REQUIRED,
+        TrustBotParams,
         UpdateUserParams,
         VerifyEmailParams,
     },
@@ -24,7 +27,8 @@ use ibis_database::{
         SuccessResponse,
         instance::InstanceFollow,
         notifications::ApiNotification,
-        user::{LocalUser, LocalUserView, Person},
+        registration::{RegistrationApplication, RegistrationApplicationStatus},
+        user::{ApiSession, ApiToken, LocalUser, LocalUserView, Person},
     },
     email::{
         reset_password::PasswordResetRequest,
@@ -35,10 +39,11 @@ use ibis_database::{
         IbisContext,
         notifications::Notification,
         read_jwt_secret,
+        session::{Session, SessionInsertForm},
         user::{LocalUserUpdateForm, LocalUserViewQuery, PersonUpdateForm},
     },
 };
-use ibis_federate::validate::{validate_display_name, validate_email};
+use ibis_federate::validate::{validate_display_name, validate_email, validate_locale};
 use jsonwebtoken::{
     DecodingKey,
     EncodingKey,
@@ -52,8 +57,17 @@ use log::warn;
 use register::validate_new_password;
 use serde::{Deserialize, Serialize};
 use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
 
+pub mod api_token;
+pub mod block;
+pub mod contributions;
+pub mod migration;
+pub mod oauth;
+pub mod personal_data;
 pub mod register;
+pub mod totp;
+pub mod web_push;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Claims {
@@ -65,17 +79,25 @@ struct Claims {
     pub iat: i64,
     /// Expiration time
     pub exp: u64,
+    /// Token of the login session, the jwt is only valid as long as the session exists
+    pub jti: Uuid,
 }
 
-pub async fn validate(jwt: &str, context: &IbisContext) -> BackendResult<LocalUserView> {
+pub async fn validate(jwt: &str, context: &IbisContext) -> BackendResult<(LocalUserView, Session)> {
     let validation = Validation::default();
     let secret = read_jwt_secret(context)?;
     let key = DecodingKey::from_secret(secret.as_bytes());
     let claims = decode::<Claims>(jwt, &key, &validation)?;
-    LocalUserView::read(
+    let user = LocalUserView::read(
         LocalUserViewQuery::LocalNameOrEmail(&claims.claims.sub),
         context,
-    )
+    )?;
+    let session = Session::read_from_token(claims.claims.jti, context)?;
+    if session.local_user_id != user.local_user.id {
+        return Err(anyhow!("Invalid session").into());
+    }
+    session.update_last_seen(context)?;
+    Ok((user, session))
 }
 
 fn validate_password(user: &LocalUserView, password: &str) -> BackendResult<()> {
@@ -95,6 +117,7 @@ fn validate_password(user: &LocalUserView, password: &str) -> BackendResult<()>
 pub(crate) async fn login_user(
     context: Data<IbisContext>,
     jar: CookieJar,
+    client: ClientInfo,
     Form(params): Form<LoginUserParams>,
 ) -> BackendResult<(CookieJar, Json<LocalUserView>)> {
     let invalid_login: BackendError = anyhow!("Invalid login").into();
@@ -107,20 +130,49 @@ pub(crate) async fn login_user(
         return Err(anyhow!("Verify your email address to login").into());
     }
     validate_password(&user, &params.password)?;
-    let jar = add_login_cookie(&user.person, jar, &context)?;
+    if user.local_user.totp_enabled {
+        let totp_token = params.totp_token.ok_or(anyhow!(TOTP_REQUIRED))?;
+        totp::check_totp(&user, &totp_token, &context)?;
+    }
+    let jar = add_login_cookie(&user, client, jar, &context)?;
     Ok((jar, Json(user)))
 }
 
+/// Users who registered with an application can only login after it was approved.
+fn check_application_approved(user: &LocalUserView, context: &IbisContext) -> BackendResult<()> {
+    let application = RegistrationApplication::read_for_user(user.local_user.id, context)?;
+    match application.map(|a| a.status) {
+        Some(RegistrationApplicationStatus::Pending) => {
+            Err(anyhow!("Your registration application is still pending").into())
+        }
+        Some(RegistrationApplicationStatus::Denied) => {
+            Err(anyhow!("Your registration application was denied").into())
+        }
+        Some(RegistrationApplicationStatus::Approved) | None => Ok(()),
+    }
+}
+
+/// Start a new session for the user, and set the login token as cookie.
 pub(crate) fn add_login_cookie(
-    person: &Person,
+    user: &LocalUserView,
+    client: ClientInfo,
     jar: CookieJar,
     context: &Data<IbisContext>,
 ) -> BackendResult<CookieJar> {
+    check_application_approved(user, context)?;
+    let form = SessionInsertForm {
+        local_user_id: user.local_user.id,
+        token: Uuid::new_v4(),
+        user_agent: client.user_agent,
+        ip: client.ip,
+    };
+    let session = Session::create(&form, context)?;
     let claims = Claims {
-        sub: person.username.clone(),
+        sub: user.person.username.clone(),
         iss: context.conf.domain.clone(),
         iat: Utc::now().timestamp(),
         exp: get_current_timestamp() + 60 * 60 * 24 * 365,
+        jti: session.token,
     };
 
     let secret = read_jwt_secret(context)?;
@@ -153,12 +205,48 @@ pub fn create_cookie(jwt: String, context: &Data<IbisContext>) -> Cookie<'static
 #[debug_handler]
 pub(crate) async fn logout_user(
     context: Data<IbisContext>,
+    session: Option<Extension<Session>>,
     jar: CookieJar,
 ) -> BackendResult<(CookieJar, Json<SuccessResponse>)> {
+    if let Some(Extension(session)) = session {
+        Session::delete(session.id, session.local_user_id, &context)?;
+    }
     let jar = jar.remove(create_cookie(String::new(), &context));
     Ok((jar, Json(SuccessResponse::default())))
 }
 
+/// Devices where the user is logged in.
+#[debug_handler]
+pub(crate) async fn list_sessions(
+    user: UserExt,
+    Extension(current): Extension<Session>,
+    context: Data<IbisContext>,
+) -> BackendResult<Json<Vec<ApiSession>>> {
+    let sessions = Session::list(user.local_user.id, &context)?
+        .into_iter()
+        .map(|s| ApiSession {
+            id: s.id,
+            user_agent: s.user_agent,
+            ip: s.ip,
+            published: s.published,
+            last_seen: s.last_seen,
+            current: s.id == current.id,
+        })
+        .collect();
+    Ok(Json(sessions))
+}
+
+/// Sign out the user on another device.
+#[debug_handler]
+pub(crate) async fn revoke_session(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<RevokeSessionParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    Session::delete(params.session_id, user.local_user.id, &context)?;
+    Ok(Json(SuccessResponse::default()))
+}
+
 #[debug_handler]
 pub(crate) async fn get_user(
     params: Query<GetUserParams>,
@@ -188,13 +276,28 @@ pub(crate) async fn update_user_profile(
     empty_to_none(&mut params.display_name);
     empty_to_none(&mut params.bio);
     empty_to_none(&mut params.email);
+    empty_to_none(&mut params.locale);
     validate_display_name(&params.display_name)?;
+    validate_locale(&params.locale)?;
     let person_form = PersonUpdateForm {
         display_name: params.display_name,
         bio: params.bio,
+        bot: params.bot,
+        // trust is granted by admins for a specific bot
+        bot_trusted: params.bot.filter(|b| !b),
     };
     let local_user_form = LocalUserUpdateForm {
         email_notifications: params.email_notifications,
+        email_digest: params.email_digest,
+        locale: params.locale,
+        notify_comment_reply: params.notify_comment_reply,
+        notify_article_comment: params.notify_article_comment,
+        notify_article_edit: params.notify_article_edit,
+        notify_new_article: params.notify_new_article,
+        notify_mention: params.notify_mention,
+        notify_minor_edit: params.notify_minor_edit,
+        notify_bot_edit: params.notify_bot_edit,
+        ..Default::default()
     };
     // update, ignoring empty query errors
     Person::update(&person_form, user.person.id, &context).ok();
@@ -208,6 +311,26 @@ pub(crate) async fn update_user_profile(
     Ok(Json(SuccessResponse::default()))
 }
 
+/// Mark edits of a bot account as patrolled automatically.
+#[debug_handler]
+pub(crate) async fn trust_bot(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<TrustBotParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    check_is_admin(&user, &context)?;
+    let person = Person::read(params.person_id, &context)?;
+    if !person.bot {
+        return Err(anyhow!("Only bot accounts can be trusted").into());
+    }
+    let form = PersonUpdateForm {
+        bot_trusted: Some(params.trusted),
+        ..Default::default()
+    };
+    Person::update(&form, person.id, &context)?;
+    Ok(Json(SuccessResponse::default()))
+}
+
 #[debug_handler]
 pub(crate) async fn list_notifications(
     user: UserExt,
@@ -250,12 +373,16 @@ pub(crate) async fn verify_email(
 #[debug_handler]
 pub(crate) async fn change_password(
     user: UserExt,
+    Extension(session): Extension<Session>,
     context: Data<IbisContext>,
     Form(params): Form<ChangePasswordParams>,
 ) -> BackendResult<Json<SuccessResponse>> {
     validate_password(&user, &params.old_password)?;
     validate_new_password(&params.new_password, &params.confirm_new_password)?;
     LocalUser::update_password(params.new_password, user.local_user.id, &context)?;
+    // sign out on other devices and revoke api tokens, in case the old password was compromised
+    Session::delete_all(user.local_user.id, Some(session.id), &context)?;
+    ApiToken::delete_all(user.local_user.id, &context)?;
     Ok(Json(SuccessResponse::default()))
 }
 
@@ -283,6 +410,8 @@ pub async fn change_password_after_reset(
     validate_new_password(&params.password, &params.confirm_password)?;
 
     LocalUser::update_password(params.password, local_user_id, &context)?;
+    Session::delete_all(local_user_id, None, &context)?;
+    ApiToken::delete_all(local_user_id, &context)?;
 
     Ok(Json(SuccessResponse::default()))
 }
@@ -0,0 +1,298 @@
+//! OpenID Connect discovery of provider endpoints, and validation of ID tokens.
+
+use anyhow::anyhow;
+use ibis_database::{
+    common::instance::OAuthProviderPublic,
+    config::OAuthProvider,
+    error::BackendResult,
+    impls::IbisContext,
+};
+use jsonwebtoken::{
+    DecodingKey,
+    Validation,
+    decode,
+    decode_header,
+    jwk::{AlgorithmParameters, JwkSet},
+};
+use log::warn;
+use moka::sync::Cache;
+use serde::Deserialize;
+use std::{sync::LazyLock, time::Duration};
+use url::Url;
+
+/// Endpoints of an OAuth provider, from the config or from discovery.
+#[derive(Clone, Debug, Deserialize)]
+pub(super) struct ProviderMetadata {
+    issuer: String,
+    pub authorization_endpoint: Url,
+    pub token_endpoint: Url,
+    pub userinfo_endpoint: Option<Url>,
+    pub jwks_uri: Option<Url>,
+}
+
+/// Claims of the ID token which are needed for login.
+#[derive(Deserialize, Debug)]
+pub(super) struct IdTokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+}
+
+/// Endpoints which are set in the config take precedence. Plain OAuth 2.0 providers don't support
+/// discovery, so they need to have at least the authorization and token endpoints configured.
+pub(super) async fn provider_metadata(
+    provider: &OAuthProvider,
+    context: &IbisContext,
+) -> BackendResult<ProviderMetadata> {
+    if let (Some(authorization_endpoint), Some(token_endpoint)) =
+        (&provider.authorization_endpoint, &provider.token_endpoint)
+    {
+        return Ok(ProviderMetadata {
+            issuer: provider.issuer.to_string(),
+            authorization_endpoint: authorization_endpoint.clone(),
+            token_endpoint: token_endpoint.clone(),
+            userinfo_endpoint: provider.userinfo_endpoint.clone(),
+            jwks_uri: provider.jwks_uri.clone(),
+        });
+    }
+    let discovered = discover(&provider.issuer, context).await?;
+    Ok(ProviderMetadata {
+        issuer: discovered.issuer,
+        authorization_endpoint: provider
+            .authorization_endpoint
+            .clone()
+            .unwrap_or(discovered.authorization_endpoint),
+        token_endpoint: provider
+            .token_endpoint
+            .clone()
+            .unwrap_or(discovered.token_endpoint),
+        userinfo_endpoint: provider
+            .userinfo_endpoint
+            .clone()
+            .or(discovered.userinfo_endpoint),
+        jwks_uri: provider.jwks_uri.clone().or(discovered.jwks_uri),
+    })
+}
+
+/// Fetch the provider configuration from `issuer/.well-known/openid-configuration`.
+async fn discover(issuer: &Url, context: &IbisContext) -> BackendResult<ProviderMetadata> {
+    // The configuration is needed on every page load for the login buttons, and rarely changes
+    static CACHE: LazyLock<Cache<Url, ProviderMetadata>> = LazyLock::new(|| {
+        Cache::builder()
+            .max_capacity(100)
+            .time_to_live(Duration::from_secs(60 * 60))
+            .build()
+    });
+    // Errors are cached for a shorter time, so that an unreachable provider doesn't slow down
+    // every page load
+    static FAILED: LazyLock<Cache<Url, String>> = LazyLock::new(|| {
+        Cache::builder()
+            .max_capacity(100)
+            .time_to_live(Duration::from_secs(5 * 60))
+            .build()
+    });
+    if let Some(metadata) = CACHE.get(issuer) {
+        return Ok(metadata);
+    }
+    if let Some(error) = FAILED.get(issuer) {
+        return Err(anyhow!(error).into());
+    }
+
+    let metadata = fetch_discovery(issuer, context).await.inspect_err(|e| {
+        FAILED.insert(issuer.clone(), e.to_string());
+    })?;
+    CACHE.insert(issuer.clone(), metadata.clone());
+    Ok(metadata)
+}
+
+async fn fetch_discovery(issuer: &Url, context: &IbisContext) -> BackendResult<ProviderMetadata> {
+    let url = format!(
+        "{}/.well-known/openid-configuration",
+        issuer.as_str().trim_end_matches('/')
+    );
+    let metadata: ProviderMetadata = context
+        .client
+        .get(url)
+        .header("Accept", "application/json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if !same_issuer(&metadata.issuer, issuer) {
+        return Err(anyhow!("Discovered issuer {} doesn't match config", metadata.issuer).into());
+    }
+    Ok(metadata)
+}
+
+/// Providers which are shown on the login page. Those which can't be discovered are skipped.
+pub(crate) async fn public_oauth_providers(context: &IbisContext) -> Vec<OAuthProviderPublic> {
+    let mut providers = vec![];
+    for provider in &context.conf.oauth_providers {
+        match provider_metadata(provider, context).await {
+            Ok(metadata) => providers.push(OAuthProviderPublic {
+                issuer: provider.issuer.clone(),
+                display_name: provider.display_name.clone(),
+                authorization_endpoint: metadata.authorization_endpoint,
+                client_id: provider.client_id.clone(),
+                scopes: provider.scopes.clone(),
+            }),
+            Err(e) => warn!("Failed to discover oauth provider {}: {e}", provider.issuer),
+        }
+    }
+    providers
+}
+
+/// Keys of the provider are cached like the discovery document. They are fetched again if the
+/// ID token is signed with a key which is not in the cached set, as the provider may have
+/// rotated its keys.
+pub(super) async fn fetch_jwks(
+    jwks_uri: &Url,
+    id_token: &str,
+    context: &IbisContext,
+) -> BackendResult<JwkSet> {
+    static CACHE: LazyLock<Cache<Url, JwkSet>> = LazyLock::new(|| {
+        Cache::builder()
+            .max_capacity(100)
+            .time_to_live(Duration::from_secs(60 * 60))
+            .build()
+    });
+    let kid = decode_header(id_token)?.kid;
+    if let Some(jwks) = CACHE.get(jwks_uri) {
+        if kid.as_ref().is_none_or(|kid| jwks.find(kid).is_some()) {
+            return Ok(jwks);
+        }
+    }
+
+    let jwks: JwkSet = context
+        .client
+        .get(jwks_uri.as_str())
+        .header("Accept", "application/json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    CACHE.insert(jwks_uri.clone(), jwks.clone());
+    Ok(jwks)
+}
+
+/// Check the signature, issuer, audience and expiration of the ID token.
+pub(super) fn validate_id_token(
+    id_token: &str,
+    provider: &OAuthProvider,
+    jwks: &JwkSet,
+) -> BackendResult<IdTokenClaims> {
+    let header = decode_header(id_token)?;
+    let jwk = match &header.kid {
+        Some(kid) => jwks.find(kid),
+        None => jwks.keys.first(),
+    }
+    .ok_or(anyhow!("No matching key for ID token"))?;
+    // symmetric keys would allow anyone who knows the key to sign tokens
+    if let AlgorithmParameters::OctetKey(_) = jwk.algorithm {
+        return Err(anyhow!("ID token must be signed with a public key").into());
+    }
+    let key = DecodingKey::from_jwk(jwk)?;
+    let mut validation = Validation::new(header.alg);
+    validation.set_audience(&[&provider.client_id]);
+    let issuer = provider.issuer.as_str();
+    validation.set_issuer(&[issuer, issuer.trim_end_matches('/')]);
+    Ok(decode::<IdTokenClaims>(id_token, &key, &validation)?.claims)
+}
+
+/// Url parsing adds a trailing slash to the issuer, which may not be present in the provider data.
+fn same_issuer(issuer: &str, expected: &Url) -> bool {
+    issuer.trim_end_matches('/') == expected.as_str().trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+    use chrono::Utc;
+    use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+    use ring::{
+        rand::SystemRandom,
+        signature::{Ed25519KeyPair, KeyPair},
+    };
+    use serde_json::json;
+
+    /// Generate a signing key, and the matching key set as published by the provider.
+    fn generate_key(kid: &str) -> BackendResult<(EncodingKey, JwkSet)> {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|_| anyhow!("Failed to generate key"))?;
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+            .map_err(|_| anyhow!("Failed to parse key"))?;
+        let jwks = json!({
+            "keys": [{
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "kid": kid,
+                "x": URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref()),
+            }]
+        });
+        Ok((
+            EncodingKey::from_ed_der(pkcs8.as_ref()),
+            serde_json::from_value(jwks)?,
+        ))
+    }
+
+    fn provider() -> BackendResult<OAuthProvider> {
+        Ok(OAuthProvider {
+            display_name: "Example".to_string(),
+            issuer: Url::parse("https://sso.example.com")?,
+            authorization_endpoint: None,
+            token_endpoint: None,
+            userinfo_endpoint: None,
+            jwks_uri: None,
+            client_id: "ibis".to_string(),
+            client_secret: "secret".to_string(),
+            scopes: "openid email".to_string(),
+        })
+    }
+
+    fn sign(key: &EncodingKey, aud: &str) -> BackendResult<String> {
+        let header = Header {
+            kid: Some("key1".to_string()),
+            ..Header::new(Algorithm::EdDSA)
+        };
+        let claims = json!({
+            "iss": "https://sso.example.com",
+            "aud": aud,
+            "sub": "1234",
+            "email": "alice@example.com",
+            "exp": Utc::now().timestamp() + 60,
+        });
+        Ok(encode(&header, &claims, key)?)
+    }
+
+    #[test]
+    fn test_validate_id_token() -> BackendResult<()> {
+        let provider = provider()?;
+        let (key, jwks) = generate_key("key1")?;
+        let claims = validate_id_token(&sign(&key, "ibis")?, &provider, &jwks)?;
+        assert_eq!("1234", claims.sub);
+        assert_eq!(Some("alice@example.com".to_string()), claims.email);
+
+        // token for another client
+        assert!(validate_id_token(&sign(&key, "other")?, &provider, &jwks).is_err());
+
+        // signed by a different key
+        let (other_key, _) = generate_key("key1")?;
+        assert!(validate_id_token(&sign(&other_key, "ibis")?, &provider, &jwks).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_same_issuer() -> BackendResult<()> {
+        let issuer = Url::parse("https://sso.example.com/realms/ibis")?;
+        assert!(same_issuer("https://sso.example.com/realms/ibis", &issuer));
+        assert!(same_issuer("https://sso.example.com/realms/ibis/", &issuer));
+        assert!(!same_issuer(
+            "https://sso.example.com/realms/other",
+            &issuer
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use super::{create_cookie, totp::check_totp, validate_password};
+use crate::api::UserExt;
+use activitypub_federation::config::Data;
+use anyhow::anyhow;
+use axum::{Form, Json};
+use axum_extra::extract::CookieJar;
+use axum_macros::debug_handler;
+use ibis_api_client::user::{DeleteAccountParams, PersonalDataExport, TOTP_REQUIRED};
+use ibis_database::{
+    common::{
+        SuccessResponse,
+        article::{Article, Conflict, Edit},
+        block::BlockList,
+        comment::Comment,
+        user::{ApiSession, ApiToken, OAuthAccount, Person},
+    },
+    error::BackendResult,
+    impls::{IbisContext, notifications::Notification, session::Session},
+};
+use ibis_federate::activities::delete_person::DeletePerson;
+
+/// Everything which is stored about the user, so that they can download it.
+#[debug_handler]
+pub(crate) async fn export_personal_data(
+    user: UserExt,
+    context: Data<IbisContext>,
+) -> BackendResult<Json<PersonalDataExport>> {
+    let person_id = user.person.id;
+    let sessions = Session::list(user.local_user.id, &context)?
+        .into_iter()
+        .map(|s| ApiSession {
+            id: s.id,
+            user_agent: s.user_agent,
+            ip: s.ip,
+            published: s.published,
+            last_seen: s.last_seen,
+            current: false,
+        })
+        .collect();
+    Ok(Json(PersonalDataExport {
+        edits: Edit::list_all_for_person(person_id, &context)?,
+        comments: Comment::list_all_for_person(person_id, &context)?,
+        conflicts: Conflict::list_for_person(person_id, &context)?,
+        following_persons: Person::read_followed_persons(person_id, &context)?,
+        following_instances: Person::read_following(person_id, &context)?
+            .into_iter()
+            .map(|f| f.instance)
+            .collect(),
+        following_articles: Article::read_followed(user.local_user.id, &context)?,
+        followers: Person::read_followers(person_id, &context)?,
+        notifications: Notification::list(&user, &context).await?,
+        sessions,
+        api_tokens: ApiToken::list(user.local_user.id, &context)?,
+        blocks: BlockList::read(user.local_user.id, &context)?,
+        oauth_accounts: OAuthAccount::list(user.local_user.id, &context)?,
+        person: user.person.clone(),
+        local_user: user.local_user.clone(),
+    }))
+}
+
+/// Delete the account and federate the deletion. Edits are kept for the article history, but
+/// attributed to the ghost user.
+#[debug_handler]
+pub(crate) async fn delete_account(
+    user: UserExt,
+    context: Data<IbisContext>,
+    jar: CookieJar,
+    Form(params): Form<DeleteAccountParams>,
+) -> BackendResult<(CookieJar, Json<SuccessResponse>)> {
+    if user.local_user.admin {
+        return Err(anyhow!("Admin account can't be deleted").into());
+    }
+    if user.local_user.password_encrypted.is_some() {
+        validate_password(&user, params.password.as_deref().unwrap_or_default())?;
+    }
+    if user.local_user.totp_enabled {
+        let totp_token = params.totp_token.ok_or(anyhow!(TOTP_REQUIRED))?;
+        check_totp(&user, &totp_token, &context)?;
+    }
+    DeletePerson::send(&user.person.clone().into(), &context).await?;
+    Person::delete(user.person.id, &context)?;
+    let jar = jar.remove(create_cookie(String::new(), &context));
+    Ok((jar, Json(SuccessResponse::default())))
+}
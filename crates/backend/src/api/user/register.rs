@@ -1,4 +1,11 @@
-use crate::api::{empty_to_none, user::add_login_cookie};
+use super::oauth::{ProviderMetadata, fetch_jwks, provider_metadata, validate_id_token};
+use crate::api::{
+    ClientInfo,
+    UserExt,
+    challenge::check_challenge,
+    empty_to_none,
+    user::{add_login_cookie, totp::check_totp},
+};
 use activitypub_federation::config::Data;
 use anyhow::anyhow;
 use axum::{Form, Json};
@@ -9,15 +16,19 @@ use ibis_api_client::user::{
     OAuthTokenResponse,
     RegisterUserParams,
     RegistrationResponse,
+    TOTP_REQUIRED,
 };
 use ibis_database::{
-    common::user::{LocalUser, LocalUserView},
+    common::{
+        instance::RegistrationMode,
+        user::{LocalUser, LocalUserView, OAuthAccount},
+    },
     config::OAuthProvider,
     email::verification::send_verification_email,
     error::{BackendError, BackendResult},
     impls::{
         IbisContext,
-        user::{LocalUserViewQuery, OAuthAccount, OAuthAccountInsertForm},
+        user::{LocalUserViewQuery, OAuthAccountInsertForm},
     },
 };
 use ibis_federate::validate::{validate_email, validate_user_name};
@@ -26,16 +37,26 @@ use serde::{Deserialize, Serialize};
 
 type RegisterReturnType = BackendResult<(CookieJar, Json<RegistrationResponse>)>;
 
+/// Maximum length of the answer to the registration question.
+const MAX_APPLICATION_ANSWER_LENGTH: usize = 2000;
+
 #[debug_handler]
 pub async fn register_user(
     context: Data<IbisContext>,
     jar: CookieJar,
+    client: ClientInfo,
     Form(mut params): Form<RegisterUserParams>,
 ) -> RegisterReturnType {
     empty_to_none(&mut params.email);
-    if !context.conf.options.registration_open {
-        return Err(anyhow!("Registration is closed").into());
-    }
+    empty_to_none(&mut params.invite_code);
+    empty_to_none(&mut params.application_answer);
+    let admission = check_registration(
+        params.invite_code,
+        params.application_answer,
+        params.challenge.as_deref(),
+        params.challenge_nonce,
+        &context,
+    )?;
 
     validate_new_password(&params.password, &params.confirm_password)?;
 
@@ -46,11 +67,13 @@ pub async fn register_user(
     check_new_user(&params.username, params.email.as_deref(), &context)?;
 
     // dont pass the email here, it needs to be validated first
-    let user = LocalUserView::create(
+    let application_pending = admission.application_answer.is_some();
+    let user = LocalUserView::register(
         params.username,
         Some(params.password),
-        false,
         None,
+        admission.invite_code.as_deref(),
+        admission.application_answer,
         &context,
     )?;
 
@@ -58,20 +81,39 @@ pub async fn register_user(
         send_verification_email(&user.local_user, email, &context).await?;
     }
 
-    register_return(user, jar, context.conf.options.email_required, &context)
+    register_return(
+        user,
+        jar,
+        client,
+        context.conf.options.email_required,
+        application_pending,
+        &context,
+    )
 }
 
 #[debug_handler]
 pub async fn authenticate_with_oauth(
     context: Data<IbisContext>,
+    user: Option<UserExt>,
     jar: CookieJar,
-    Form(params): Form<AuthenticateWithOauth>,
+    client: ClientInfo,
+    Form(mut params): Form<AuthenticateWithOauth>,
 ) -> RegisterReturnType {
+    empty_to_none(&mut params.invite_code);
+    empty_to_none(&mut params.application_answer);
     let oauth_invalid_err: BackendError = anyhow!("Oauth Authorization is invalid").into();
     // validate inputs
     if params.code.is_empty() || params.code.len() > 300 {
         return Err(oauth_invalid_err);
     }
+    // verifier length is defined in RFC 7636
+    if params
+        .pkce_code_verifier
+        .as_ref()
+        .is_some_and(|v| !(43..=128).contains(&v.len()))
+    {
+        return Err(oauth_invalid_err);
+    }
 
     // validate the redirect_uri
     let redirect_uri = &params.redirect_uri;
@@ -92,22 +134,20 @@ pub async fn authenticate_with_oauth(
         .find(|provider| provider.issuer == params.oauth_issuer)
         .ok_or(oauth_invalid_err)?;
 
+    let metadata = provider_metadata(oauth_provider, &context).await?;
     let token_response = oauth_request_access_token(
         oauth_provider,
+        &metadata,
         &params.code,
         redirect_uri.as_str(),
+        params.pkce_code_verifier.as_deref(),
         &context,
     )
     .await?;
 
-    let user_info = oauth_get_user_info(
-        oauth_provider,
-        token_response.access_token.as_str(),
-        &context,
-    )
-    .await?;
+    let user_info =
+        oauth_get_user_info(oauth_provider, &metadata, &token_response, &context).await?;
     let oauth_user_id = user_info.sub;
-    let email = user_info.email;
 
     // Lookup user by oauth_user_id
     let mut local_user_view = LocalUserView::read(
@@ -115,17 +155,52 @@ pub async fn authenticate_with_oauth(
         &context,
     );
 
+    let mut application_pending = false;
     let user = if let Ok(user_view) = local_user_view {
-        // user found by oauth_user_id => Login user
+        if user
+            .as_ref()
+            .is_some_and(|u| u.local_user.id != user_view.local_user.id)
+        {
+            return Err(anyhow!("This account is already linked to another user").into());
+        }
+        // user found by oauth_user_id => Login user, the provider doesn't replace the second
+        // factor
+        if user.is_none() && user_view.local_user.totp_enabled {
+            let totp_token = params.totp_token.ok_or(anyhow!(TOTP_REQUIRED))?;
+            check_totp(&user_view, &totp_token, &context)?;
+        }
         user_view
+    } else if let Some(user) = user {
+        // logged in user => link the account so it can be used for login
+        let oauth_account_form = OAuthAccountInsertForm {
+            local_user_id: user.local_user.id,
+            oauth_issuer_url: oauth_provider.issuer.clone().into(),
+            oauth_user_id,
+        };
+        OAuthAccount::create(&oauth_account_form, &context)?;
+
+        user.inner()
     } else {
         // user has never previously registered using oauth
 
-        // Lookup user by OAUTH email and link accounts
-        local_user_view = LocalUserView::read(LocalUserViewQuery::Email(&email), &context);
+        // Lookup user by OAUTH email and link accounts, only if the provider says that the email
+        // is verified
+        let email = user_info.email;
+        local_user_view = match &email {
+            Some(email) if user_info.email_verified == Some(true) => {
+                LocalUserView::read(LocalUserViewQuery::Email(email), &context)
+            }
+            _ => Err(anyhow!("No email for linking").into()),
+        };
 
         if let Ok(user) = local_user_view {
             // user found by email => link and login
+            if user.local_user.totp_enabled {
+                return Err(anyhow!(
+                    "Login with password and two-factor authentication to link this account"
+                )
+                .into());
+            }
 
             let oauth_account_form = OAuthAccountInsertForm {
                 local_user_id: user.local_user.id,
@@ -141,9 +216,24 @@ pub async fn authenticate_with_oauth(
             let username = params
                 .username
                 .ok_or(anyhow!("Username is required to register new account"))?;
-
-            check_new_user(&username, Some(&email), &context)?;
-            let user = LocalUserView::create(username, None, false, Some(email), &context)?;
+            let admission = check_registration(
+                params.invite_code,
+                params.application_answer,
+                params.challenge.as_deref(),
+                params.challenge_nonce,
+                &context,
+            )?;
+
+            check_new_user(&username, email.as_deref(), &context)?;
+            application_pending = admission.application_answer.is_some();
+            let user = LocalUserView::register(
+                username,
+                None,
+                email,
+                admission.invite_code.as_deref(),
+                admission.application_answer,
+                &context,
+            )?;
 
             // Create the oauth account
             let oauth_account_form = OAuthAccountInsertForm {
@@ -158,27 +248,32 @@ pub async fn authenticate_with_oauth(
     };
 
     // dont require any email validation for oauth
-    register_return(user, jar, false, &context)
+    register_return(user, jar, client, false, application_pending, &context)
 }
 
 /// Request an Access Token from the OAUTH provider
 async fn oauth_request_access_token(
     oauth_provider: &OAuthProvider,
+    metadata: &ProviderMetadata,
     code: &str,
     redirect_uri: &str,
+    pkce_code_verifier: Option<&str>,
     context: &IbisContext,
 ) -> BackendResult<OAuthTokenResponse> {
-    let form = [
+    let mut form = vec![
         ("client_id", &*oauth_provider.client_id),
         ("client_secret", &*oauth_provider.client_secret),
         ("code", code),
         ("grant_type", "authorization_code"),
         ("redirect_uri", redirect_uri),
     ];
+    if let Some(pkce_code_verifier) = pkce_code_verifier {
+        form.push(("code_verifier", pkce_code_verifier));
+    }
 
     let response = context
         .client
-        .post(oauth_provider.token_endpoint.as_str())
+        .post(metadata.token_endpoint.as_str())
         .header("Accept", "application/json")
         .form(&form[..])
         .send()
@@ -190,30 +285,99 @@ async fn oauth_request_access_token(
     Ok(serde_json::from_str(&text)?)
 }
 
-/// Request the user info from the OAUTH provider
+/// Get the user info from the ID token if the provider supports OpenID Connect, and from the
+/// userinfo endpoint if available.
 async fn oauth_get_user_info(
     oauth_provider: &OAuthProvider,
-    access_token: &str,
+    metadata: &ProviderMetadata,
+    token_response: &OAuthTokenResponse,
     context: &IbisContext,
 ) -> BackendResult<OauthUserInfo> {
+    let id_token_claims = match (&token_response.id_token, &metadata.jwks_uri) {
+        (Some(id_token), Some(jwks_uri)) => {
+            let jwks = fetch_jwks(jwks_uri, id_token, context).await?;
+            Some(validate_id_token(id_token, oauth_provider, &jwks)?)
+        }
+        _ => None,
+    };
+
+    let Some(userinfo_endpoint) = &metadata.userinfo_endpoint else {
+        let claims = id_token_claims.ok_or(anyhow!("Oauth provider returned no user info"))?;
+        return Ok(OauthUserInfo {
+            sub: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified,
+        });
+    };
     let response = context
         .client
-        .get(oauth_provider.userinfo_endpoint.as_str())
+        .get(userinfo_endpoint.as_str())
         .header("Accept", "application/json")
-        .bearer_auth(access_token)
+        .bearer_auth(&token_response.access_token)
         .send()
         .await?;
     let status = response.status();
     let text = response.text().await?;
     debug!("Oauth get user info response: status {status}, text {text}");
 
-    Ok(serde_json::from_str(&text)?)
+    let user_info: OauthUserInfo = serde_json::from_str(&text)?;
+    // userinfo must be for the same user as the ID token, see OpenID Connect Core 5.3.2
+    if id_token_claims.is_some_and(|c| c.sub != user_info.sub) {
+        return Err(anyhow!("Oauth user info doesn't match ID token").into());
+    }
+    Ok(user_info)
 }
 
 #[derive(Serialize, Deserialize)]
 struct OauthUserInfo {
     sub: String,
-    email: String,
+    email: Option<String>,
+    email_verified: Option<bool>,
+}
+
+/// Invite code to redeem and registration application to submit for a new account.
+struct Admission {
+    invite_code: Option<String>,
+    application_answer: Option<String>,
+}
+
+/// Checks which apply to every new account, regardless if it registers with password or SSO.
+fn check_registration(
+    invite_code: Option<String>,
+    application_answer: Option<String>,
+    challenge: Option<&str>,
+    challenge_nonce: Option<u64>,
+    context: &IbisContext,
+) -> BackendResult<Admission> {
+    let options = &context.conf.options;
+    if !options.registration_open {
+        return Err(anyhow!("Registration is closed").into());
+    }
+    check_challenge(challenge, challenge_nonce, context)?;
+
+    // a valid invite code allows registering without application
+    match (options.registration_mode, invite_code) {
+        (RegistrationMode::Open, _) => Ok(Admission {
+            invite_code: None,
+            application_answer: None,
+        }),
+        (_, Some(invite_code)) => Ok(Admission {
+            invite_code: Some(invite_code),
+            application_answer: None,
+        }),
+        (RegistrationMode::Invite, None) => Err(anyhow!("Invite code required").into()),
+        (RegistrationMode::Application, None) => {
+            let answer = application_answer
+                .ok_or(anyhow!("Answer the registration question to register"))?;
+            if answer.chars().count() > MAX_APPLICATION_ANSWER_LENGTH {
+                return Err(anyhow!("Registration answer is too long").into());
+            }
+            Ok(Admission {
+                invite_code: None,
+                application_answer: Some(answer),
+            })
+        }
+    }
 }
 
 fn check_new_user(username: &str, email: Option<&str>, context: &IbisContext) -> BackendResult<()> {
@@ -229,11 +393,13 @@ fn check_new_user(username: &str, email: Option<&str>, context: &IbisContext) ->
 fn register_return(
     user: LocalUserView,
     mut jar: CookieJar,
+    client: ClientInfo,
     email_verification_required: bool,
+    application_pending: bool,
     context: &Data<IbisContext>,
 ) -> RegisterReturnType {
-    if !email_verification_required {
-        jar = add_login_cookie(&user.person, jar, context)?;
+    if !email_verification_required && !application_pending {
+        jar = add_login_cookie(&user, client, jar, context)?;
     }
 
     Ok((
@@ -241,6 +407,7 @@ fn register_return(
         Json(RegistrationResponse {
             user,
             email_verification_required,
+            application_pending,
         }),
     ))
 }
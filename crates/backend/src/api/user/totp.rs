@@ -0,0 +1,121 @@
+use super::validate_password;
+use crate::api::UserExt;
+use activitypub_federation::config::Data;
+use anyhow::anyhow;
+use axum::{Form, Json};
+use axum_macros::debug_handler;
+use chrono::Utc;
+use ibis_api_client::user::{DisableTotpParams, EnableTotpParams, TotpRecoveryCodes, TotpSetup};
+use ibis_database::{
+    common::{
+        SuccessResponse,
+        user::{LocalUser, LocalUserView},
+    },
+    error::{BackendError, BackendResult},
+    impls::{IbisContext, user::LocalUserUpdateForm},
+    totp::{
+        generate_recovery_codes,
+        generate_secret,
+        hash_recovery_code,
+        is_locked,
+        otpauth_uri,
+        verify_code,
+    },
+};
+
+/// Generate a new secret, which is stored but not used until it is confirmed with a valid code.
+#[debug_handler]
+pub(crate) async fn generate_totp(
+    user: UserExt,
+    context: Data<IbisContext>,
+) -> BackendResult<Json<TotpSetup>> {
+    if user.local_user.totp_enabled {
+        return Err(anyhow!("Two-factor authentication is already enabled").into());
+    }
+    let secret = generate_secret()?;
+    let form = LocalUserUpdateForm {
+        totp_secret: Some(Some(secret.clone())),
+        ..Default::default()
+    };
+    LocalUser::update(&form, user.local_user.id, &context)?;
+    let uri = otpauth_uri(
+        &secret,
+        &user.person.username,
+        &context.conf.federation.domain,
+    )?;
+    Ok(Json(TotpSetup { secret, uri }))
+}
+
+#[debug_handler]
+pub(crate) async fn enable_totp(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<EnableTotpParams>,
+) -> BackendResult<Json<TotpRecoveryCodes>> {
+    if user.local_user.totp_enabled {
+        return Err(anyhow!("Two-factor authentication is already enabled").into());
+    }
+    let secret = user
+        .local_user
+        .totp_secret
+        .as_ref()
+        .ok_or(anyhow!("Generate a two-factor authentication secret first"))?;
+    let Some(step) = verify_code(secret, &params.totp_token, Utc::now())? else {
+        return Err(anyhow!("Invalid two-factor authentication code").into());
+    };
+    let (recovery_codes, hashes) = generate_recovery_codes()?;
+    let form = LocalUserUpdateForm {
+        totp_enabled: Some(true),
+        totp_recovery_codes: Some(hashes),
+        totp_last_step: Some(Some(step)),
+        ..Default::default()
+    };
+    LocalUser::update(&form, user.local_user.id, &context)?;
+    Ok(Json(TotpRecoveryCodes { recovery_codes }))
+}
+
+#[debug_handler]
+pub(crate) async fn disable_totp(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<DisableTotpParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    validate_password(&user, &params.password)?;
+    check_totp(&user, &params.totp_token, &context)?;
+    let form = LocalUserUpdateForm {
+        totp_secret: Some(None),
+        totp_enabled: Some(false),
+        totp_recovery_codes: Some(vec![]),
+        totp_last_step: Some(None),
+        ..Default::default()
+    };
+    LocalUser::update(&form, user.local_user.id, &context)?;
+    Ok(Json(SuccessResponse::default()))
+}
+
+/// Check the code from the authenticator app, or a recovery code which is then removed. Each code
+/// can only be used once, and after too many invalid codes no more are accepted for some time.
+pub(super) fn check_totp(
+    user: &LocalUserView,
+    token: &str,
+    context: &IbisContext,
+) -> BackendResult<()> {
+    let invalid = || -> BackendError { anyhow!("Invalid two-factor authentication code").into() };
+    let secret = user.local_user.totp_secret.as_ref().ok_or_else(invalid)?;
+    let now = Utc::now();
+    if is_locked(&user.local_user, now) {
+        return Err(
+            anyhow!("Too many invalid two-factor authentication codes, try again later").into(),
+        );
+    }
+    let id = user.local_user.id;
+    let valid = match verify_code(secret, token, now)? {
+        Some(step) => LocalUser::use_totp_step(id, step, context)?,
+        None => LocalUser::use_recovery_code(id, &hash_recovery_code(token), context)?,
+    };
+    if !valid {
+        LocalUser::totp_failed(id, context)?;
+        return Err(invalid());
+    }
+    Ok(())
+}
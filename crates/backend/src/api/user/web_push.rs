@@ -0,0 +1,46 @@
+use crate::api::UserExt;
+use activitypub_federation::config::Data;
+use axum::{Form, Json};
+use axum_macros::debug_handler;
+use ibis_api_client::notifications::{WebPushSubscribeParams, WebPushUnsubscribeParams};
+use ibis_database::{
+    common::{SuccessResponse, notifications::WebPushKey},
+    error::BackendResult,
+    impls::{
+        IbisContext,
+        web_push::{WebPushSubscription, WebPushSubscriptionInsertForm},
+    },
+    web_push::{check_push_endpoint, vapid_public_key},
+};
+
+#[debug_handler]
+pub(crate) async fn web_push_key(context: Data<IbisContext>) -> BackendResult<Json<WebPushKey>> {
+    Ok(Json(vapid_public_key(&context)?))
+}
+
+#[debug_handler]
+pub(crate) async fn web_push_subscribe(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<WebPushSubscribeParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    check_push_endpoint(&params.endpoint).await?;
+    let form = WebPushSubscriptionInsertForm {
+        local_user_id: user.local_user.id,
+        endpoint: params.endpoint,
+        p256dh: params.p256dh,
+        auth: params.auth,
+    };
+    WebPushSubscription::create(&form, &context)?;
+    Ok(Json(SuccessResponse::default()))
+}
+
+#[debug_handler]
+pub(crate) async fn web_push_unsubscribe(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<WebPushUnsubscribeParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    WebPushSubscription::delete(&params.endpoint, user.local_user.id, &context)?;
+    Ok(Json(SuccessResponse::default()))
+}
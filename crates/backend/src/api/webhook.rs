@@ -0,0 +1,83 @@
+use super::{UserExt, check_is_admin};
+use activitypub_federation::config::Data;
+use anyhow::anyhow;
+use axum::{Form, Json, extract::Query};
+use axum_macros::debug_handler;
+use ibis_api_client::webhook::{
+    CreateWebhookParams,
+    DeleteWebhookParams,
+    ListWebhookDeliveriesParams,
+};
+use ibis_database::{
+    common::{
+        SuccessResponse,
+        webhook::{CreatedWebhook, Webhook, WebhookDelivery, WebhookEvent},
+    },
+    error::BackendResult,
+    impls::{IbisContext, webhook::WebhookInsertForm},
+};
+use url::Url;
+
+#[debug_handler]
+pub(crate) async fn list_webhooks(
+    user: UserExt,
+    context: Data<IbisContext>,
+) -> BackendResult<Json<Vec<Webhook>>> {
+    check_is_admin(&user, &context)?;
+    Ok(Json(Webhook::list(&context)?))
+}
+
+#[debug_handler]
+pub(crate) async fn create_webhook(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<CreateWebhookParams>,
+) -> BackendResult<Json<CreatedWebhook>> {
+    check_is_admin(&user, &context)?;
+    let url = Url::parse(&params.url)?;
+    if !["http", "https"].contains(&url.scheme()) {
+        return Err(anyhow!("Webhook url must use http or https").into());
+    }
+    if params.secret.trim().is_empty() {
+        return Err(anyhow!("Webhook secret must not be empty").into());
+    }
+    let events = params
+        .events
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<WebhookEvent>, _>>()?;
+    let form = WebhookInsertForm {
+        url: url.to_string(),
+        secret: params.secret,
+        events,
+    };
+    let webhook = Webhook::create(&form, &context)?;
+    Ok(Json(CreatedWebhook {
+        secret: webhook.secret.clone(),
+        webhook,
+    }))
+}
+
+#[debug_handler]
+pub(crate) async fn delete_webhook(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Form(params): Form<DeleteWebhookParams>,
+) -> BackendResult<Json<SuccessResponse>> {
+    check_is_admin(&user, &context)?;
+    Webhook::delete(params.id, &context)?;
+    Ok(Json(SuccessResponse::default()))
+}
+
+/// Latest requests to the webhook with their result, newest first.
+#[debug_handler]
+pub(crate) async fn list_webhook_deliveries(
+    user: UserExt,
+    context: Data<IbisContext>,
+    Query(params): Query<ListWebhookDeliveriesParams>,
+) -> BackendResult<Json<Vec<WebhookDelivery>>> {
+    check_is_admin(&user, &context)?;
+    Ok(Json(WebhookDelivery::list(params.webhook_id, &context)?))
+}
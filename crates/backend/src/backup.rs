@@ -0,0 +1,103 @@
+//! Archive format for wiki backups. It is a tar file with the following content:
+//!
+//! - `manifest.json`: format version and export time
+//! - `instance.json`: metadata of the exported instance
+//! - `persons.json`: public profiles of all authors
+//! - `articles/<n>.json`: article metadata with edit history and comments
+//! - `articles/<n>.md`: current article text
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use ibis_database::{
+    error::BackendResult,
+    impls::backup::{BackupArticle, WIKI_BACKUP_VERSION, WikiBackup},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, io::Read};
+use tar::{Archive, Builder, Header};
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    exported_at: DateTime<Utc>,
+}
+
+pub fn write_archive(backup: WikiBackup) -> BackendResult<Vec<u8>> {
+    let mut builder = Builder::new(vec![]);
+    let manifest = Manifest {
+        version: WIKI_BACKUP_VERSION,
+        exported_at: Utc::now(),
+    };
+    append(
+        &mut builder,
+        "manifest.json",
+        &serde_json::to_vec(&manifest)?,
+    )?;
+    append(
+        &mut builder,
+        "instance.json",
+        &serde_json::to_vec_pretty(&backup.instance)?,
+    )?;
+    append(
+        &mut builder,
+        "persons.json",
+        &serde_json::to_vec_pretty(&backup.persons)?,
+    )?;
+    for (i, article) in backup.articles.iter().enumerate() {
+        append(
+            &mut builder,
+            &format!("articles/{i}.json"),
+            &serde_json::to_vec_pretty(article)?,
+        )?;
+        append(
+            &mut builder,
+            &format!("articles/{i}.md"),
+            article.text.as_bytes(),
+        )?;
+    }
+    Ok(builder.into_inner()?)
+}
+
+fn append(builder: &mut Builder<Vec<u8>>, path: &str, data: &[u8]) -> BackendResult<()> {
+    let mut header = Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().try_into()?);
+    builder.append_data(&mut header, path, data)?;
+    Ok(())
+}
+
+pub fn read_archive(data: &[u8]) -> BackendResult<WikiBackup> {
+    let mut files = BTreeMap::new();
+    for entry in Archive::new(data).entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_string_lossy().to_string();
+        let mut content = vec![];
+        entry.read_to_end(&mut content)?;
+        files.insert(path, content);
+    }
+    let file = |path: &str| {
+        files
+            .get(path)
+            .ok_or(anyhow!("Missing file {path} in archive"))
+    };
+
+    let manifest: Manifest = serde_json::from_slice(file("manifest.json")?)?;
+    if manifest.version != WIKI_BACKUP_VERSION {
+        return Err(anyhow!("Unsupported backup version {}", manifest.version).into());
+    }
+    let mut articles = vec![];
+    for i in 0.. {
+        let Ok(json) = file(&format!("articles/{i}.json")) else {
+            break;
+        };
+        let mut article: BackupArticle = serde_json::from_slice(json)?;
+        article.text = String::from_utf8(file(&format!("articles/{i}.md"))?.clone())?;
+        articles.push(article);
+    }
+    Ok(WikiBackup {
+        instance: serde_json::from_slice(file("instance.json")?)?,
+        persons: serde_json::from_slice(file("persons.json")?)?,
+        articles,
+    })
+}
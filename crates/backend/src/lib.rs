@@ -1,18 +1,31 @@
 use activitypub_federation::config::FederationConfig;
+use backup::{read_archive, write_archive};
 use ibis_database::{
     common::instance::Instance,
     config::IbisConfig,
     error::BackendResult,
-    impls::IbisContext,
+    impls::{
+        IbisContext,
+        backup::{BackupInstance, WikiBackup},
+    },
     scheduled_tasks,
 };
 use ibis_federate::VerifyUrlData;
 use log::info;
+use mediawiki::{dump_to_backup, parse_dump};
 use server::{setup::setup, start_server};
-use std::{net::SocketAddr, thread};
-use tokio::sync::oneshot;
+use std::{
+    fs::{File, read, write},
+    io::BufReader,
+    net::SocketAddr,
+    ops::Deref,
+    thread,
+};
+use tokio::{runtime::Handle, sync::oneshot};
 
 pub mod api;
+pub mod backup;
+pub mod mediawiki;
 mod server;
 pub mod utils;
 
@@ -21,13 +34,68 @@ pub async fn start(
     override_hostname: Option<SocketAddr>,
     notify_start: Option<oneshot::Sender<()>>,
 ) -> BackendResult<()> {
-    let context = IbisContext::init(config, override_hostname.is_some())?;
+    let data = init(config, override_hostname.is_some()).await?;
+
+    let context = data.deref().clone();
+    let runtime = Handle::current();
+    thread::spawn(move || {
+        scheduled_tasks::start(context, runtime);
+    });
+
+    start_server(data, override_hostname, notify_start).await?;
+
+    Ok(())
+}
+
+/// Write all local articles with their history into a backup archive.
+pub async fn export_wiki(config: IbisConfig, path: &str) -> BackendResult<()> {
+    let data = init(config, false).await?;
+    let archive = write_archive(WikiBackup::read(&data)?)?;
+    write(path, archive)?;
+    info!("Exported wiki to {path}");
+    Ok(())
+}
+
+/// Restore articles from a backup archive, usually into a new instance.
+pub async fn import_wiki(config: IbisConfig, path: &str) -> BackendResult<()> {
+    let data = init(config, false).await?;
+    read_archive(&read(path)?)?.restore(&data)?;
+    info!("Imported wiki from {path}");
+    Ok(())
+}
+
+/// Import articles with their revision history from a MediaWiki XML dump.
+pub async fn import_mediawiki(config: IbisConfig, path: &str) -> BackendResult<()> {
+    let data = init(config, false).await?;
+    let pages = parse_dump(BufReader::new(File::open(path)?))?;
+    let instance = Instance::read_local(&data)?;
+    let instance = BackupInstance {
+        ap_id: instance.ap_id,
+        domain: instance.domain,
+        name: None,
+        topic: None,
+    };
+    let backup = dump_to_backup(pages, instance)?;
+    let count = backup.articles.len();
+    backup.restore(&data)?;
+    info!("Imported {count} articles from {path}");
+    Ok(())
+}
+
+async fn init(
+    config: IbisConfig,
+    ignore_env: bool,
+) -> BackendResult<FederationConfig<IbisContext>> {
+    let context = IbisContext::init(config, ignore_env)?;
     let data = FederationConfig::builder()
         .domain(context.conf.federation.domain.clone())
         .url_verifier(Box::new(VerifyUrlData(context.conf.clone())))
         .app_data(context.clone())
         .client(context.client.clone().into())
         .http_fetch_limit(1000)
+        // Private keys are cached by actor id without expiration, which would keep using the old
+        // key after a key rotation
+        .actor_pkey_cache(0)
         .debug(cfg!(debug_assertions))
         .build()
         .await?;
@@ -36,13 +104,5 @@ pub async fn start(
         info!("Running setup for new instance");
         setup(&data.to_request_data()).await?;
     }
-
-    let db_pool = data.db_pool.clone();
-    thread::spawn(move || {
-        scheduled_tasks::start(db_pool);
-    });
-
-    start_server(data, override_hostname, notify_start).await?;
-
-    Ok(())
+    Ok(data)
 }
@@ -1,10 +1,11 @@
 use env_logger::Env;
-use ibis::start;
+use ibis::{export_wiki, import_mediawiki, import_wiki, start};
 use ibis_database::config::IbisConfig;
 
 #[tokio::main]
 pub async fn main() -> ibis_database::error::BackendResult<()> {
-    if std::env::args().collect::<Vec<_>>().get(1) == Some(&"--print-config".to_string()) {
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.get(1) == Some(&"--print-config".to_string()) {
         println!("{}", doku::to_toml::<IbisConfig>());
         std::process::exit(0);
     }
@@ -15,6 +16,19 @@ pub async fn main() -> ibis_database::error::BackendResult<()> {
     .init();
 
     let ibis_config = IbisConfig::read()?;
-    start(ibis_config, None, None).await?;
+    match (args.get(1).map(String::as_str), args.get(2)) {
+        (Some("--export-wiki"), Some(path)) => export_wiki(ibis_config, path).await?,
+        (Some("--import-wiki"), Some(path)) => import_wiki(ibis_config, path).await?,
+        (Some("--import-mediawiki"), Some(path)) => import_mediawiki(ibis_config, path).await?,
+        (None, _) => start(ibis_config, None, None).await?,
+        (Some(_), _) => {
+            eprintln!("Invalid arguments: {}", args[1..].join(" "));
+            eprintln!(
+                "Usage: ibis [--print-config | --export-wiki <path> | --import-wiki <path> | \
+                 --import-mediawiki <path>]"
+            );
+            std::process::exit(1);
+        }
+    }
     Ok(())
 }
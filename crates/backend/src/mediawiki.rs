@@ -0,0 +1,436 @@
+//! Import of MediaWiki XML dumps (`pages-articles.xml`). Pages are converted into a
+//! [WikiBackup] and restored like a regular backup, so no activities or notifications are sent.
+
+use crate::utils::generate_article_ap_id;
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use diffy::create_patch;
+use ibis_database::{
+    common::{article::EditVersion, utils::http_protocol_str},
+    error::BackendResult,
+    impls::backup::{BackupArticle, BackupEdit, BackupInstance, BackupPerson, WikiBackup},
+};
+use ibis_federate::validate::validate_article_title;
+use ibis_markdown::wikitext::{normalize_title, wikitext_to_markdown};
+use log::warn;
+use quick_xml::{Reader, events::Event};
+use std::{
+    collections::{BTreeMap, HashSet, btree_map::Entry},
+    io::BufRead,
+};
+use url::Url;
+
+/// Used as edit author if the revision has no username, eg for edits by IP address.
+const ANONYMOUS_USER: &str = "anonymous";
+/// Prefix for placeholder users, to avoid conflicts with existing accounts.
+const USER_PREFIX: &str = "mw_";
+/// Usernames longer than this are rejected by [ibis_federate::validate::validate_user_name].
+const MAX_USERNAME_LENGTH: usize = 20;
+/// MediaWiki namespace for articles, pages from other namespaces are not imported.
+const ARTICLE_NAMESPACE: &str = "0";
+
+#[derive(Debug, Default)]
+pub struct MediaWikiPage {
+    pub title: String,
+    pub revisions: Vec<MediaWikiRevision>,
+}
+
+#[derive(Debug, Default)]
+pub struct MediaWikiRevision {
+    pub timestamp: String,
+    pub username: Option<String>,
+    pub comment: String,
+    pub text: String,
+    pub minor: bool,
+}
+
+/// Read all article pages from the dump. Redirects and pages in other namespaces are skipped.
+pub fn parse_dump(input: impl BufRead) -> BackendResult<Vec<MediaWikiPage>> {
+    let mut reader = Reader::from_reader(input);
+    let mut buf = vec![];
+    let mut path: Vec<String> = vec![];
+    let mut pages = vec![];
+    let mut page = MediaWikiPage::default();
+    let mut revision = MediaWikiRevision::default();
+    let mut namespace = String::new();
+    let mut redirect = false;
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) => {
+                path.push(String::from_utf8_lossy(e.local_name().as_ref()).to_string());
+            }
+            Event::Empty(e) => match e.local_name().as_ref() {
+                b"redirect" => redirect = true,
+                b"minor" => revision.minor = true,
+                _ => {}
+            },
+            Event::Text(e) => {
+                let text = e.unescape()?;
+                append_text(&path, &text, &mut page, &mut revision, &mut namespace);
+            }
+            Event::CData(e) => {
+                let text = String::from_utf8_lossy(&e).to_string();
+                append_text(&path, &text, &mut page, &mut revision, &mut namespace);
+            }
+            Event::End(e) => {
+                path.pop();
+                match e.local_name().as_ref() {
+                    b"redirect" => redirect = true,
+                    b"revision" => page.revisions.push(std::mem::take(&mut revision)),
+                    b"page" => {
+                        let page = std::mem::take(&mut page);
+                        let is_redirect = redirect
+                            || page.revisions.last().is_some_and(|r| {
+                                r.text.trim_start().to_uppercase().starts_with("#REDIRECT")
+                            });
+                        if namespace.trim() == ARTICLE_NAMESPACE && !is_redirect {
+                            pages.push(page);
+                        }
+                        namespace.clear();
+                        redirect = false;
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(pages)
+}
+
+fn append_text(
+    path: &[String],
+    text: &str,
+    page: &mut MediaWikiPage,
+    revision: &mut MediaWikiRevision,
+    namespace: &mut String,
+) {
+    let parent = path.len().checked_sub(2).and_then(|i| path.get(i));
+    let field = match (parent.map(String::as_str), path.last().map(String::as_str)) {
+        (Some("page"), Some("title")) => &mut page.title,
+        (Some("page"), Some("ns")) => namespace,
+        (Some("revision"), Some("timestamp")) => &mut revision.timestamp,
+        (Some("revision"), Some("comment")) => &mut revision.comment,
+        (Some("revision"), Some("text")) => &mut revision.text,
+        (Some("contributor"), Some("username")) => {
+            revision.username.get_or_insert_default().push_str(text);
+            return;
+        }
+        _ => return,
+    };
+    field.push_str(text);
+}
+
+/// Convert the pages to articles on the given instance. Each revision becomes an edit, which is
+/// attributed to a placeholder user for the MediaWiki account.
+pub fn dump_to_backup(
+    pages: Vec<MediaWikiPage>,
+    instance: BackupInstance,
+) -> BackendResult<WikiBackup> {
+    // placeholder users by MediaWiki username
+    let mut persons: BTreeMap<String, BackupPerson> = BTreeMap::new();
+    let mut usernames = HashSet::new();
+    let mut titles = HashSet::new();
+    let mut articles = vec![];
+    for mut page in pages {
+        page.revisions.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        let title = normalize_title(&page.title)
+            .filter(|t| validate_article_title(t).is_ok() && titles.insert(t.clone()));
+        let Some(title) = title else {
+            warn!(
+                "Skipping page with invalid or duplicate title {}",
+                page.title
+            );
+            continue;
+        };
+        let ap_id = generate_article_ap_id(&title, &instance.ap_id)?;
+
+        let mut text = String::new();
+        let mut previous_version = EditVersion::default();
+        let mut hashes = HashSet::new();
+        let mut edits = vec![];
+        for revision in page.revisions {
+            let new_text = wikitext_to_markdown(&revision.text, &instance.domain);
+            let diff = create_patch(&text, &new_text).to_string();
+            let hash = EditVersion::new(&diff);
+            // Skip revisions without changes, and those which would repeat an earlier edit (eg
+            // reverts). The next edit is then based on the last imported text, so that the
+            // edit history still produces the current text.
+            if new_text == text || !hashes.insert(hash.clone()) {
+                continue;
+            }
+            let name = revision.username.unwrap_or(ANONYMOUS_USER.to_string());
+            let creator = match persons.entry(name) {
+                Entry::Occupied(e) => e.get().ap_id.clone(),
+                Entry::Vacant(e) => {
+                    let person = placeholder_person(e.key(), &usernames, &instance)?;
+                    usernames.insert(person.username.clone());
+                    e.insert(person).ap_id.clone()
+                }
+            };
+            let summary = if revision.comment.trim().is_empty() {
+                "Imported from MediaWiki".to_string()
+            } else {
+                revision.comment.trim().to_string()
+            };
+            edits.push(BackupEdit {
+                ap_id: Url::parse(&format!("{ap_id}/{}", hash.hash()))?.into(),
+                hash: hash.clone(),
+                creator,
+                diff,
+                summary,
+                previous_version_id: previous_version,
+                published: parse_timestamp(&revision.timestamp)?,
+                minor: revision.minor,
+                tags: vec![],
+            });
+            previous_version = hash;
+            text = new_text;
+        }
+        let (Some(first), Some(last)) = (edits.first(), edits.last()) else {
+            continue;
+        };
+        articles.push(BackupArticle {
+            title,
+            text,
+            ap_id,
+            protected: false,
+            removed: false,
+            published: first.published,
+            updated: last.published,
+            edits,
+            comments: vec![],
+        });
+    }
+    Ok(WikiBackup {
+        instance,
+        persons: persons.into_values().collect(),
+        articles,
+    })
+}
+
+/// Usernames are limited to 20 alphanumeric characters, so different MediaWiki names may result
+/// in the same username. In that case a number is appended to make it unique.
+fn placeholder_person(
+    display_name: &str,
+    taken: &HashSet<String>,
+    instance: &BackupInstance,
+) -> BackendResult<BackupPerson> {
+    let mut base: String = display_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    base.insert_str(0, USER_PREFIX);
+    base.truncate(MAX_USERNAME_LENGTH);
+    let mut username = base.clone();
+    let mut count = 2;
+    while taken.contains(&username) {
+        let suffix = format!("_{count}");
+        let len = base.len().min(MAX_USERNAME_LENGTH - suffix.len());
+        username = format!("{}{suffix}", &base[..len]);
+        count += 1;
+    }
+    Ok(BackupPerson {
+        ap_id: Url::parse(&format!(
+            "{}://{}/user/{username}",
+            http_protocol_str(),
+            instance.domain
+        ))?
+        .into(),
+        username,
+        inbox_url: String::new(),
+        public_key: String::new(),
+        local: true,
+        display_name: Some(display_name.chars().take(MAX_USERNAME_LENGTH).collect()),
+        bio: None,
+        bot: false,
+        last_refreshed_at: Utc::now(),
+    })
+}
+
+fn parse_timestamp(timestamp: &str) -> BackendResult<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(timestamp.trim())
+        .map_err(|e| anyhow!("Invalid timestamp {timestamp}: {e}"))?
+        .to_utc())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::generate_article_version;
+    use ibis_database::common::{
+        article::Edit,
+        newtypes::{ArticleId, EditId, PersonId},
+    };
+
+    const DUMP: &str = r#"<mediawiki xmlns="http://www.mediawiki.org/xml/export-0.11/">
+  <siteinfo><sitename>Test</sitename></siteinfo>
+  <page>
+    <title>Rust language</title>
+    <ns>0</ns>
+    <revision>
+      <timestamp>2020-01-01T00:00:00Z</timestamp>
+      <contributor><username>Jane Doe</username></contributor>
+      <comment>create</comment>
+      <text xml:space="preserve">'''Rust''' is a [[programming language]].</text>
+    </revision>
+    <revision>
+      <timestamp>2020-01-02T00:00:00Z</timestamp>
+      <contributor><ip>127.0.0.1</ip></contributor>
+      <text xml:space="preserve">Vandalism &amp; more</text>
+    </revision>
+    <revision>
+      <timestamp>2020-01-03T00:00:00Z</timestamp>
+      <contributor><username>Jane Doe</username></contributor>
+      <comment>revert</comment>
+      <text xml:space="preserve">'''Rust''' is a [[programming language]].</text>
+    </revision>
+    <revision>
+      <timestamp>2020-01-04T00:00:00Z</timestamp>
+      <contributor><ip>127.0.0.1</ip></contributor>
+      <text xml:space="preserve">Vandalism &amp; more</text>
+    </revision>
+    <revision>
+      <timestamp>2020-01-05T00:00:00Z</timestamp>
+      <contributor><username>Jane Doe</username></contributor>
+      <text xml:space="preserve">'''Rust''' is a [[programming language]].
+== History ==</text>
+    </revision>
+  </page>
+  <page>
+    <title>Rust</title>
+    <ns>0</ns>
+    <redirect title="Rust language" />
+    <revision>
+      <timestamp>2020-01-01T00:00:00Z</timestamp>
+      <text xml:space="preserve">#REDIRECT [[Rust language]]</text>
+    </revision>
+  </page>
+  <page>
+    <title>Talk:Rust language</title>
+    <ns>1</ns>
+    <revision>
+      <timestamp>2020-01-01T00:00:00Z</timestamp>
+      <text xml:space="preserve">Discussion</text>
+    </revision>
+  </page>
+</mediawiki>"#;
+
+    #[test]
+    fn test_import_mediawiki_dump() -> BackendResult<()> {
+        let pages = parse_dump(DUMP.as_bytes())?;
+        assert_eq!(1, pages.len());
+        assert_eq!("Rust language", pages[0].title);
+        assert_eq!(5, pages[0].revisions.len());
+        assert_eq!("Vandalism & more", pages[0].revisions[1].text);
+
+        let instance = BackupInstance {
+            ap_id: Url::parse("http://example.com/")?.into(),
+            domain: "example.com".to_string(),
+            name: None,
+            topic: None,
+        };
+        let backup = dump_to_backup(pages, instance)?;
+        let article = &backup.articles[0];
+        assert_eq!(
+            "**Rust** is a [[Programming language@example.com|programming language]].\n\n\
+            # History\n",
+            article.text
+        );
+        assert_eq!(
+            "http://example.com/article/Rust_language",
+            article.ap_id.to_string()
+        );
+
+        let usernames: Vec<_> = backup.persons.iter().map(|p| &p.username).collect();
+        assert_eq!(vec!["mw_Jane_Doe", "mw_anonymous"], usernames);
+        assert_eq!(Some("Jane Doe".to_string()), backup.persons[0].display_name);
+
+        // the second vandalism repeats an earlier edit, so it is skipped
+        assert_eq!(4, article.edits.len());
+        assert_eq!(article.edits[0].hash, article.edits[1].previous_version_id);
+        let edits: Vec<_> = article
+            .edits
+            .iter()
+            .map(|e| Edit {
+                id: EditId(0),
+                creator_id: PersonId(0),
+                hash: e.hash.clone(),
+                ap_id: e.ap_id.clone(),
+                diff: e.diff.clone(),
+                summary: e.summary.clone(),
+                article_id: ArticleId(0),
+                previous_version_id: e.previous_version_id.clone(),
+                published: e.published,
+                pending: false,
+                patrolled: false,
+                minor: e.minor,
+                tags: e.tags.clone(),
+            })
+            .collect();
+        let generated = generate_article_version(&edits, &edits[3].hash)?;
+        assert_eq!(article.text, generated);
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_invalid_titles() -> BackendResult<()> {
+        let page = |title: &str| MediaWikiPage {
+            title: title.to_string(),
+            revisions: vec![MediaWikiRevision {
+                timestamp: "2020-01-01T00:00:00Z".to_string(),
+                text: "text".to_string(),
+                ..Default::default()
+            }],
+        };
+        let pages = vec![
+            page("Rust_(language)"),
+            page("Rust language"),
+            page("C++"),
+            page("What?#"),
+        ];
+        let instance = BackupInstance {
+            ap_id: Url::parse("http://example.com/")?.into(),
+            domain: "example.com".to_string(),
+            name: None,
+            topic: None,
+        };
+        let backup = dump_to_backup(pages, instance)?;
+        assert_eq!(2, backup.articles.len());
+        assert_eq!("Rust language", backup.articles[0].title);
+        assert_eq!(
+            "http://example.com/article/Rust_language",
+            backup.articles[0].ap_id.to_string()
+        );
+        assert_eq!("What", backup.articles[1].title);
+        assert_eq!(
+            "http://example.com/article/What",
+            backup.articles[1].ap_id.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_placeholder_person_collision() -> BackendResult<()> {
+        let instance = BackupInstance {
+            ap_id: Url::parse("http://example.com/")?.into(),
+            domain: "example.com".to_string(),
+            name: None,
+            topic: None,
+        };
+        let mut taken = HashSet::new();
+        let mut username = |name: &str| -> BackendResult<String> {
+            let person = placeholder_person(name, &taken, &instance)?;
+            taken.insert(person.username.clone());
+            Ok(person.username)
+        };
+        assert_eq!("mw_Jane_Doe", username("Jane Doe")?);
+        assert_eq!("mw_Jane_Doe_2", username("Jane.Doe")?);
+        assert_eq!("mw_Alexander_Hamilto", username("Alexander Hamilton")?);
+        assert_eq!("mw_Alexander_Hamil_2", username("Alexander Hamilton Jr")?);
+        assert_eq!("mw_Alexander_Hamil_3", username("Alexander Hamilton III")?);
+        Ok(())
+    }
+}
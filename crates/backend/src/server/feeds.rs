@@ -0,0 +1,273 @@
+//! Atom feeds, so that changes on the wiki can be followed with a feed reader.
+
+use activitypub_federation::config::Data;
+use axum::{
+    Router,
+    extract::Path,
+    http::header::CONTENT_TYPE,
+    response::IntoResponse,
+    routing::get,
+};
+use axum_macros::debug_handler;
+use chrono::{DateTime, SecondsFormat, Utc};
+use ibis_database::{
+    common::{
+        article::{Article, Edit, EditView},
+        comment::Comment,
+        instance::Instance,
+        newtypes::ArticleId,
+        user::Person,
+        utils::http_protocol_str,
+    },
+    error::BackendResult,
+    impls::{IbisContext, edit::ViewEditParams},
+};
+use ibis_frontend_components::utils::formatting::{
+    article_path,
+    comment_path,
+    edit_path,
+    instance_title,
+    user_path,
+};
+use quick_xml::{
+    Writer,
+    escape::escape,
+    events::{BytesDecl, BytesText, Event},
+};
+
+/// Number of entries in each feed.
+const FEED_LIMIT: i64 = 50;
+
+const ATOM_CONTENT_TYPE: &str = "application/atom+xml; charset=utf-8";
+
+pub(super) fn feed_routes() -> Router<()> {
+    Router::new()
+        .route("/changes", get(recent_changes_feed))
+        .route("/article/:id", get(article_feed))
+        .route("/article/:id/comments", get(article_comments_feed))
+        .route("/user/:name", get(user_feed))
+}
+
+/// Recent edits of all articles.
+#[debug_handler]
+async fn recent_changes_feed(context: Data<IbisContext>) -> BackendResult<impl IntoResponse> {
+    let instance = Instance::read_local(&context)?;
+    let edits = Edit::list_recent_views(ViewEditParams::All, FEED_LIMIT, &context)?;
+    let feed = Feed::new(
+        format!("{} - Recent changes", instance_title(&instance)),
+        "/feeds/changes",
+        "/".to_string(),
+        edit_entries(edits, &context),
+        &context,
+    );
+    feed.response()
+}
+
+/// Edit history of a single article.
+#[debug_handler]
+async fn article_feed(
+    Path(id): Path<ArticleId>,
+    context: Data<IbisContext>,
+) -> BackendResult<impl IntoResponse> {
+    let article = Article::read(id, &context)?;
+    let edits = Edit::list_recent_views(ViewEditParams::ArticleId(id), FEED_LIMIT, &context)?;
+    let feed = Feed::new(
+        format!("{} - History", article.title()),
+        &format!("/feeds/article/{}", id.0),
+        format!("{}/history", article_path(&article)),
+        edit_entries(edits, &context),
+        &context,
+    );
+    feed.response()
+}
+
+/// Comments on a single article.
+#[debug_handler]
+async fn article_comments_feed(
+    Path(id): Path<ArticleId>,
+    context: Data<IbisContext>,
+) -> BackendResult<impl IntoResponse> {
+    let article = Article::read(id, &context)?;
+    let entries = Comment::read_for_article(id, None, &context)?
+        .into_iter()
+        .filter(|c| !c.comment.deleted)
+        .take(FEED_LIMIT as usize)
+        .map(|c| {
+            let link = absolute_url(&comment_path(&c.comment, &article), &context);
+            Entry {
+                id: c.comment.ap_id.to_string(),
+                title: format!("Comment by {} on {}", c.creator.title(), article.title()),
+                link,
+                author: c.creator.title(),
+                updated: c.comment.updated.unwrap_or(c.comment.published),
+                content: escape(&c.comment.content).to_string(),
+            }
+        })
+        .collect();
+    let feed = Feed::new(
+        format!("{} - Discussion", article.title()),
+        &format!("/feeds/article/{}/comments", id.0),
+        format!("{}/discussion", article_path(&article)),
+        entries,
+        &context,
+    );
+    feed.response()
+}
+
+/// Edits by a single user. The name has the format `username` for local users, and
+/// `username@domain` for remote users.
+#[debug_handler]
+async fn user_feed(
+    Path(name): Path<String>,
+    context: Data<IbisContext>,
+) -> BackendResult<impl IntoResponse> {
+    let (username, domain) = match name.split_once('@') {
+        Some((username, domain)) => (username, Some(domain.to_string())),
+        None => (name.as_str(), None),
+    };
+    let person = Person::read_from_name(username, &domain, &context)?;
+    let edits = Edit::list_recent_views(ViewEditParams::PersonId(person.id), FEED_LIMIT, &context)?;
+    let feed = Feed::new(
+        format!("Contributions of {}", person.title()),
+        &format!("/feeds/user/{name}"),
+        user_path(&person),
+        edit_entries(edits, &context),
+        &context,
+    );
+    feed.response()
+}
+
+fn edit_entries(edits: Vec<EditView>, context: &IbisContext) -> Vec<Entry> {
+    edits
+        .into_iter()
+        .map(|e| {
+            let link = absolute_url(&edit_path(&e.edit, &e.article), context);
+            Entry {
+                id: e.edit.ap_id.to_string(),
+                title: format!("{}: {}", e.article.title(), e.edit.summary),
+                link,
+                author: e.creator.title(),
+                updated: e.edit.published,
+                content: format!(
+                    "<p>{}</p><pre>{}</pre>",
+                    escape(&e.edit.summary),
+                    escape(&e.edit.diff)
+                ),
+            }
+        })
+        .collect()
+}
+
+fn absolute_url(path: &str, context: &IbisContext) -> String {
+    if path.starts_with("http") {
+        return path.to_string();
+    }
+    format!(
+        "{}://{}{path}",
+        http_protocol_str(),
+        context.conf.federation.domain
+    )
+}
+
+struct Feed {
+    title: String,
+    /// Url of the feed itself, also used as id
+    self_link: String,
+    /// Url of the corresponding html page
+    link: String,
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    id: String,
+    title: String,
+    link: String,
+    author: String,
+    updated: DateTime<Utc>,
+    /// Html content
+    content: String,
+}
+
+impl Feed {
+    fn new(
+        title: String,
+        self_path: &str,
+        path: String,
+        entries: Vec<Entry>,
+        context: &IbisContext,
+    ) -> Self {
+        Feed {
+            title,
+            self_link: absolute_url(self_path, context),
+            link: absolute_url(&path, context),
+            entries,
+        }
+    }
+
+    fn response(&self) -> BackendResult<impl IntoResponse> {
+        Ok(([(CONTENT_TYPE, ATOM_CONTENT_TYPE)], self.to_xml()?))
+    }
+
+    fn to_xml(&self) -> BackendResult<String> {
+        let updated = self
+            .entries
+            .iter()
+            .map(|e| e.updated)
+            .max()
+            .unwrap_or_else(Utc::now);
+        let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))?;
+        writer
+            .create_element("feed")
+            .with_attribute(("xmlns", "http://www.w3.org/2005/Atom"))
+            .write_inner_content::<_, quick_xml::Error>(|w| {
+                w.create_element("id")
+                    .write_text_content(BytesText::new(&self.self_link))?;
+                w.create_element("title")
+                    .write_text_content(BytesText::new(&self.title))?;
+                w.create_element("updated")
+                    .write_text_content(BytesText::new(&format_time(updated)))?;
+                w.create_element("link")
+                    .with_attributes([("rel", "self"), ("href", self.self_link.as_str())])
+                    .write_empty()?;
+                w.create_element("link")
+                    .with_attributes([("rel", "alternate"), ("href", self.link.as_str())])
+                    .write_empty()?;
+                for entry in &self.entries {
+                    entry.write(w)?;
+                }
+                Ok(())
+            })?;
+        Ok(String::from_utf8(writer.into_inner())?)
+    }
+}
+
+impl Entry {
+    fn write(&self, w: &mut Writer<Vec<u8>>) -> quick_xml::Result<()> {
+        w.create_element("entry").write_inner_content(|w| {
+            w.create_element("id")
+                .write_text_content(BytesText::new(&self.id))?;
+            w.create_element("title")
+                .write_text_content(BytesText::new(&self.title))?;
+            w.create_element("updated")
+                .write_text_content(BytesText::new(&format_time(self.updated)))?;
+            w.create_element("link")
+                .with_attributes([("rel", "alternate"), ("href", self.link.as_str())])
+                .write_empty()?;
+            w.create_element("author").write_inner_content(|w| {
+                w.create_element("name")
+                    .write_text_content(BytesText::new(&self.author))?;
+                Ok::<_, quick_xml::Error>(())
+            })?;
+            w.create_element("content")
+                .with_attribute(("type", "html"))
+                .write_text_content(BytesText::new(&self.content))?;
+            Ok::<_, quick_xml::Error>(())
+        })?;
+        Ok(())
+    }
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
@@ -1,10 +1,26 @@
 use crate::api::user::validate;
-use axum::{body::Body, extract::State, http::Request, middleware::Next, response::Response};
+use axum::{
+    body::Body,
+    extract::State,
+    http::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
 use axum_macros::debug_middleware;
-use http::{HeaderValue, header::COOKIE};
+use http::{
+    HeaderValue,
+    Method,
+    StatusCode,
+    header::{AUTHORIZATION, COOKIE},
+};
 use ibis_database::{
-    common::{AUTH_COOKIE, Auth},
-    impls::IbisContext,
+    common::{
+        AUTH_COOKIE,
+        Auth,
+        user::{ApiToken, ApiTokenScope, LocalUserView},
+    },
+    error::BackendResult,
+    impls::{IbisContext, api_token::API_TOKEN_PREFIX, user::LocalUserViewQuery},
 };
 use std::{collections::HashSet, sync::Arc};
 
@@ -12,7 +28,8 @@ pub(super) const FEDERATION_ROUTES_PREFIX: &str = "/federation_routes";
 
 /// Checks all headers and cookies (including duplicates) for first valid auth token.
 /// We need to extract cookies manually because CookieJar ignores duplicates.
-/// If user is authenticated sets extensions `Auth` and `LocalUserView`.
+/// If user is authenticated sets extensions `Auth`, `LocalUserView` and `Session`, or `ApiToken`
+/// instead of `Session` for requests with an api token.
 #[debug_middleware]
 pub(super) async fn auth_middleware(
     State(context): State<Arc<IbisContext>>,
@@ -28,21 +45,77 @@ pub(super) async fn auth_middleware(
         .flat_map(|s| s.split_once('='))
         .filter(|s| s.0.trim() == AUTH_COOKIE)
         .map(|s| s.1);
-    let headers = headers
+    let bearer = headers
+        .get(AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+    let auth: HashSet<_> = headers
         .get_all(AUTH_COOKIE)
         .into_iter()
-        .filter_map(|h| h.to_str().ok());
-    let auth: HashSet<_> = headers.chain(cookies).map(|s| s.to_string()).collect();
+        .filter_map(|h| h.to_str().ok())
+        .chain(cookies)
+        .chain(bearer)
+        .map(|s| s.trim().to_string())
+        .collect();
 
     for auth in auth {
-        if let Ok(local_user) = validate(&auth, &context).await {
+        if auth.starts_with(API_TOKEN_PREFIX) {
+            let Ok((local_user, api_token)) = validate_api_token(&auth, &context) else {
+                continue;
+            };
+            if !api_token_allows(api_token.scope, request.method(), request.uri().path()) {
+                return (
+                    StatusCode::FORBIDDEN,
+                    "Api token scope does not allow this action",
+                )
+                    .into_response();
+            }
             request.extensions_mut().insert(Auth(Some(auth)));
             request.extensions_mut().insert(local_user);
+            request.extensions_mut().insert(api_token);
+        } else if let Ok((local_user, session)) = validate(&auth, &context).await {
+            request.extensions_mut().insert(Auth(Some(auth)));
+            request.extensions_mut().insert(local_user);
+            request.extensions_mut().insert(session);
         }
     }
     next.run(request).await
 }
 
+fn validate_api_token(
+    token: &str,
+    context: &IbisContext,
+) -> BackendResult<(LocalUserView, ApiToken)> {
+    let api_token = ApiToken::read_from_token(token, context)?;
+    let mut user = LocalUserView::read(LocalUserViewQuery::Id(api_token.local_user_id), context)?;
+    // without admin scope, the user is treated like a normal user for all permission checks
+    if api_token.scope < ApiTokenScope::Admin {
+        user.local_user.admin = false;
+    }
+    api_token.update_last_used(context)?;
+    Ok((user, api_token))
+}
+
+/// Check if the request is permitted with an api token of the given scope. Account settings
+/// including api tokens can only be changed after logging in with password. Admin actions are
+/// handled by removing the admin flag from the user for tokens without admin scope.
+fn api_token_allows(scope: ApiTokenScope, method: &Method, path: &str) -> bool {
+    let Some(path) = path.strip_prefix("/api/v1") else {
+        return method == Method::GET;
+    };
+    if path.starts_with("/account") {
+        return false;
+    }
+    let required = if method == Method::GET || method == Method::HEAD {
+        ApiTokenScope::Read
+    } else if path.starts_with("/comment") {
+        ApiTokenScope::Comment
+    } else {
+        ApiTokenScope::Edit
+    };
+    scope >= required
+}
+
 /// Rewrite federation routes to use `FEDERATION_ROUTES_PREFIX`, to avoid conflicts
 /// with frontend routes. If a request is an Activitypub fetch as indicated by
 /// `Accept: application/activity+json` header, use the federation routes. Otherwise
@@ -12,6 +12,7 @@ use axum::{
     response::{IntoResponse, Response},
     routing::get,
 };
+use feeds::feed_routes;
 use ibis_database::{common::Auth, error::BackendResult, impls::IbisContext};
 use ibis_federate::{nodeinfo, routes::federation_routes, webfinger};
 use ibis_frontend::app::{App, shell};
@@ -25,6 +26,7 @@ use tower_http::{compression::CompressionLayer, cors::CorsLayer};
 use tower_layer::Layer;
 
 mod assets;
+mod feeds;
 mod middleware;
 pub(super) mod setup;
 
@@ -47,6 +49,7 @@ pub(super) async fn start_server(
         .with_state(leptos_options)
         .nest(FEDERATION_ROUTES_PREFIX, federation_routes())
         .nest("/api/v1", api_routes())
+        .nest("/feeds", feed_routes())
         .nest("", nodeinfo::config())
         .nest("", webfinger::config())
         .layer(FederationMiddleware::new(context))
@@ -64,7 +67,11 @@ pub(super) async fn start_server(
     if let Some(notify_start) = notify_start {
         notify_start.send(()).expect("send oneshot");
     }
-    axum::serve(listener, app_with_middleware.into_make_service()).await?;
+    axum::serve(
+        listener,
+        app_with_middleware.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
     Ok(())
 }
 
@@ -63,7 +63,7 @@ pub async fn setup(context: &Data<IbisContext>) -> Result<(), BackendError> {
     let form = DbArticleForm {
         title: MAIN_PAGE_NAME.to_string(),
         text: String::new(),
-        ap_id: generate_article_ap_id(MAIN_PAGE_NAME, &instance)?,
+        ap_id: generate_article_ap_id(MAIN_PAGE_NAME, &instance.ap_id)?,
         instance_id: instance.id,
         local: true,
         protected: true,
@@ -74,7 +74,7 @@ pub async fn setup(context: &Data<IbisContext>) -> Result<(), BackendError> {
     // also create an article so its included in most recently edited list
     submit_article_update(
         MAIN_PAGE_DEFAULT_TEXT.to_string(),
-        "Default main page".to_string(),
+        "Default main page".to_string().into(),
         EditVersion::default(),
         &article,
         admin.person.into(),
@@ -4,7 +4,6 @@ use ibis_database::{
     DbUrl,
     common::{
         article::{Edit, EditVersion},
-        instance::Instance,
         utils::{extract_domain, http_protocol_str},
     },
     error::BackendResult,
@@ -34,11 +33,11 @@ pub(super) fn generate_article_version(
     Err(anyhow!("failed to generate article version").into())
 }
 
-pub(crate) fn generate_article_ap_id(title: &str, instance: &Instance) -> BackendResult<DbUrl> {
+pub(crate) fn generate_article_ap_id(title: &str, instance_ap_id: &DbUrl) -> BackendResult<DbUrl> {
     Ok(Url::parse(&format!(
         "{}://{}/article/{}",
         http_protocol_str(),
-        extract_domain(&instance.ap_id.clone().into()),
+        extract_domain(instance_ap_id.inner()),
         title.replace(" ", "_")
     ))?
     .into())
@@ -66,6 +65,9 @@ mod test {
                 previous_version_id: Default::default(),
                 published: Utc::now(),
                 pending: false,
+                patrolled: false,
+                minor: false,
+                tags: vec![],
             })
         };
         Ok([
@@ -2,9 +2,13 @@
 
 use anyhow::Result;
 use ibis::start;
-use ibis_api_client::{ApiClient, user::RegisterUserParams};
+use ibis_api_client::{
+    ApiClient,
+    registration::CreateInviteCodeParams,
+    user::{LoginUserParams, RegisterUserParams},
+};
 use ibis_database::{
-    common::instance::Options,
+    common::instance::{Options, RegistrationMode},
     config::{IbisConfig, IbisConfigDatabase, IbisConfigFederation},
 };
 use log::LevelFilter;
@@ -28,6 +32,15 @@ static ACTIVE: AtomicI32 = AtomicI32::new(0);
 
 impl TestData {
     pub async fn start() -> Self {
+        Self::start_with_options(Options {
+            registration_open: true,
+            email_required: false,
+            ..Default::default()
+        })
+        .await
+    }
+
+    pub async fn start_with_options(options: Options) -> Self {
         static INIT: Once = Once::new();
         INIT.call_once(|| {
             env_logger::builder()
@@ -66,9 +79,9 @@ impl TestData {
         }
 
         let (alpha, beta, gamma) = join!(
-            IbisInstance::start(alpha_db_path, port_alpha, "alpha"),
-            IbisInstance::start(beta_db_path, port_beta, "beta"),
-            IbisInstance::start(gamma_db_path, port_gamma, "gamma")
+            IbisInstance::start(alpha_db_path, port_alpha, "alpha", options.clone()),
+            IbisInstance::start(beta_db_path, port_beta, "beta", options.clone()),
+            IbisInstance::start(gamma_db_path, port_gamma, "gamma", options)
         );
 
         Self(alpha, beta, gamma)
@@ -116,7 +129,7 @@ impl IbisInstance {
         })
     }
 
-    async fn start(db_path: String, port: i32, username: &str) -> Self {
+    async fn start(db_path: String, port: i32, username: &str, options: Options) -> Self {
         let connection_url = format!("postgresql://ibis:password@/ibis?host={db_path}");
 
         let hostname = format!("localhost:{port}");
@@ -127,14 +140,16 @@ impl IbisInstance {
             },
             federation: IbisConfigFederation {
                 domain: hostname.clone(),
+                // only accept the current key, so that tests notice if an old key is still used
+                // after rotation
+                key_rotation_grace_period_days: 0,
                 ..Default::default()
             },
-            options: Options {
-                registration_open: true,
-                email_required: false,
-            },
+            options,
             ..Default::default()
         };
+        let registration_mode = config.options.registration_mode;
+        let proof_of_work_difficulty = config.options.proof_of_work_difficulty;
         let api_client = ApiClient::new(Some(hostname.clone()));
         let (tx, rx) = oneshot::channel::<()>();
         let db_handle = tokio::task::spawn(async move {
@@ -145,12 +160,36 @@ impl IbisInstance {
         });
         // wait for the backend to start
         rx.await.unwrap();
-        let params = RegisterUserParams {
+        // if registration is not open, use an invite code from the admin
+        let invite_code = if registration_mode == RegistrationMode::Open {
+            None
+        } else {
+            let admin_client = ApiClient::new(Some(hostname.clone()));
+            let params = LoginUserParams {
+                username_or_email: "ibis".to_string(),
+                password: "ibis".to_string(),
+                totp_token: None,
+            };
+            admin_client.login(params).await.unwrap();
+            let params = CreateInviteCodeParams {
+                max_uses: 1,
+                expires_in_days: None,
+            };
+            Some(admin_client.create_invite_code(params).await.unwrap().code)
+        };
+        let mut params = RegisterUserParams {
             username: username.to_string(),
             password: "hunter22".to_string(),
             email: None,
             confirm_password: "hunter22".to_string(),
+            invite_code,
+            ..Default::default()
         };
+        if proof_of_work_difficulty > 0 {
+            let (challenge, nonce) = api_client.solve_challenge().await.unwrap();
+            params.challenge = Some(challenge);
+            params.challenge_nonce = Some(nonce);
+        }
         api_client.register(params).await.unwrap();
         Self {
             api_client,
@@ -4,7 +4,9 @@ mod common;
 
 use crate::common::{TEST_ARTICLE_DEFAULT_TEXT, TestData};
 use anyhow::Result;
+use chrono::Utc;
 use ibis_api_client::{
+    ApiClient,
     article::{
         CreateArticleParams,
         EditArticleParams,
@@ -12,18 +14,43 @@ use ibis_api_client::{
         GetArticleParams,
         ListArticlesParams,
         ProtectArticleParams,
+        RecentChangesParams,
     },
+    challenge::CHALLENGE_REQUIRED,
     comment::{CreateCommentParams, EditCommentParams},
-    instance::SearchArticleParams,
-    user::{GetUserParams, LoginUserParams, RegisterUserParams},
+    instance::{GetInstanceParams, SearchArticleParams},
+    notifications::{LiveEventsParams, WebPushSubscribeParams},
+    registration::{ApproveRegistrationParams, CreateInviteCodeParams},
+    user::{
+        ChangePasswordParams,
+        CreateApiTokenParams,
+        DeleteAccountParams,
+        DisableTotpParams,
+        GetContributionsParams,
+        GetUserParams,
+        LoginUserParams,
+        RegisterUserParams,
+        TOTP_REQUIRED,
+        UpdateUserParams,
+    },
+    webhook::CreateWebhookParams,
 };
-use ibis_database::common::{
-    article::ArticleView,
-    notifications::ApiNotificationData,
-    utils::extract_domain,
+use ibis_database::{
+    common::{
+        article::{ArticleView, EditTag, EditView},
+        block::BlockKind,
+        email::EmailTemplate,
+        instance::{Options, RegistrationMode},
+        notifications::ApiNotificationData,
+        user::{ApiTokenScope, EmailDigest, NotificationPreference},
+        utils::{extract_domain, solve_proof_of_work},
+        webhook::WebhookEvent,
+    },
+    totp::code_at,
 };
 use pretty_assertions::assert_eq;
 use retry_future::{LinearRetryStrategy, RetryFuture, RetryPolicy};
+use serde_json::Value;
 use std::time::Duration;
 use tokio::time::sleep;
 use url::Url;
@@ -68,6 +95,7 @@ async fn api_test_create_read_and_edit_local_article() -> Result<()> {
         new_text: "Lorem Ipsum 2\n".to_string(),
         summary: "summary".to_string(),
         previous_version_id: get_res.latest_version,
+        minor: false,
         resolve_conflict_id: None,
     };
     let edit_res = alpha
@@ -170,6 +198,7 @@ async fn api_test_synchronize_articles() -> Result<()> {
         new_text: "Lorem Ipsum 2\n".to_string(),
         summary: "summary".to_string(),
         previous_version_id: create_res.latest_version,
+        minor: false,
         resolve_conflict_id: None,
     };
     let edit_res = alpha
@@ -255,6 +284,7 @@ async fn api_test_edit_local_article() -> Result<()> {
         new_text: "Lorem Ipsum 2\n".to_string(),
         summary: "summary".to_string(),
         previous_version_id: get_res.latest_version,
+        minor: false,
         resolve_conflict_id: None,
     };
     let edit_res = beta
@@ -333,6 +363,7 @@ async fn api_test_edit_remote_article() -> Result<()> {
         new_text: "Lorem Ipsum 2\n".to_string(),
         summary: "summary".to_string(),
         previous_version_id: get_res.latest_version,
+        minor: false,
         resolve_conflict_id: None,
     };
     let edit_res = alpha
@@ -383,6 +414,7 @@ async fn api_test_local_edit_conflict() -> Result<()> {
         new_text: "Lorem Ipsum\n".to_string(),
         summary: "summary".to_string(),
         previous_version_id: create_res.latest_version.clone(),
+        minor: false,
         resolve_conflict_id: None,
     };
     let edit_res = alpha
@@ -399,6 +431,7 @@ async fn api_test_local_edit_conflict() -> Result<()> {
         new_text: "Ipsum Lorem\n".to_string(),
         summary: "summary".to_string(),
         previous_version_id: create_res.latest_version,
+        minor: true,
         resolve_conflict_id: None,
     };
     let edit_res = alpha.edit_article(&edit_params).await.unwrap().unwrap();
@@ -406,6 +439,7 @@ async fn api_test_local_edit_conflict() -> Result<()> {
         "<<<<<<< ours\nIpsum Lorem\n||||||| original\nsome example text\n=======\nLorem Ipsum\n>>>>>>> theirs\n",
         edit_res.three_way_merge
     );
+    assert!(edit_res.minor);
 
     let notifications = alpha.notifications_list().await.unwrap();
     assert_eq!(1, notifications.len());
@@ -424,6 +458,7 @@ async fn api_test_local_edit_conflict() -> Result<()> {
         new_text: "Lorem Ipsum and Ipsum Lorem\n".to_string(),
         summary: "summary".to_string(),
         previous_version_id: edit_res.previous_version_id,
+        minor: false,
         resolve_conflict_id: Some(edit_res.id),
     };
     let edit_res = alpha
@@ -480,6 +515,7 @@ async fn api_test_federated_edit_conflict() -> Result<()> {
         new_text: "first edit\n".to_string(),
         summary: "first edit".to_string(),
         previous_version_id: create_res.latest_version.clone(),
+        minor: false,
         resolve_conflict_id: None,
     };
     let edit_res = alpha
@@ -505,6 +541,7 @@ async fn api_test_federated_edit_conflict() -> Result<()> {
         new_text: "second edit\n".to_string(),
         summary: "second edit".to_string(),
         previous_version_id: create_res.latest_version,
+        minor: false,
         resolve_conflict_id: None,
     };
     let edit_res = gamma
@@ -536,6 +573,7 @@ async fn api_test_federated_edit_conflict() -> Result<()> {
         new_text: "second edit\n".to_string(),
         summary: "resolve conflict".to_string(),
         previous_version_id: conflict.previous_version_id.clone(),
+        minor: false,
         resolve_conflict_id: Some(conflict.id),
     };
     let edit_res = gamma
@@ -589,6 +627,7 @@ async fn api_test_overlapping_edits_no_conflict() -> Result<()> {
         .to_string(),
         summary: "summary".to_string(),
         previous_version_id: create_res.latest_version.clone(),
+        minor: false,
         resolve_conflict_id: None,
     };
     let edit_res = alpha
@@ -610,6 +649,7 @@ async fn api_test_overlapping_edits_no_conflict() -> Result<()> {
         .to_string(),
         summary: "summary".to_string(),
         previous_version_id: create_res.latest_version,
+        minor: false,
         resolve_conflict_id: None,
     };
     let edit_res = alpha
@@ -695,12 +735,14 @@ async fn api_test_user_registration_login() -> Result<()> {
         password: password.to_string(),
         email: None,
         confirm_password: password.to_string(),
+        ..Default::default()
     };
     alpha.register(register_data).await.unwrap();
 
     let login_data = LoginUserParams {
         username_or_email: username.to_string(),
         password: "asd123".to_string(),
+        totp_token: None,
     };
     let invalid_login = alpha.login(login_data).await;
     assert!(invalid_login.is_err());
@@ -708,6 +750,7 @@ async fn api_test_user_registration_login() -> Result<()> {
     let login_data = LoginUserParams {
         username_or_email: username.to_string(),
         password: password.to_string(),
+        totp_token: None,
     };
     alpha.login(login_data).await.unwrap();
 
@@ -777,6 +820,7 @@ async fn api_test_lock_article() -> Result<()> {
     let params = LoginUserParams {
         username_or_email: "ibis".to_string(),
         password: "ibis".to_string(),
+        totp_token: None,
     };
     alpha.login(params).await.unwrap();
     let lock_res = alpha.protect_article(&lock_params).await.unwrap();
@@ -791,6 +835,7 @@ async fn api_test_lock_article() -> Result<()> {
         new_text: "test".to_string(),
         summary: "test".to_string(),
         previous_version_id: resolve_res.latest_version,
+        minor: false,
         resolve_conflict_id: None,
     };
     let edit_res = gamma.edit_article_without_conflict(&edit_params).await;
@@ -866,6 +911,7 @@ async fn api_test_remove_article() -> Result<()> {
     let params = LoginUserParams {
         username_or_email: "ibis".to_string(),
         password: "ibis".to_string(),
+        totp_token: None,
     };
     alpha.login(params).await.unwrap();
 
@@ -935,6 +981,7 @@ async fn api_test_comment_create_edit() -> Result<()> {
         content: "top comment".to_string(),
         article_id: beta_article.article.id,
         parent_id: None,
+        ..Default::default()
     };
     let top_comment = beta.create_comment(&params).await.unwrap().comment;
     assert_eq!(top_comment.content, params.content);
@@ -958,6 +1005,7 @@ async fn api_test_comment_create_edit() -> Result<()> {
         content: "child comment".to_string(),
         article_id: article.article.id,
         parent_id: Some(article.comments[0].comment.id),
+        ..Default::default()
     };
     let child_comment = alpha.create_comment(&params).await.unwrap().comment;
     assert_eq!(child_comment.parent_id, Some(top_comment.id));
@@ -1004,6 +1052,7 @@ async fn api_test_comment_delete_restore() -> Result<()> {
         content: "my comment".to_string(),
         article_id: alpha_article.article.id,
         parent_id: None,
+        ..Default::default()
     };
     let comment = alpha.create_comment(&params).await.unwrap();
     let get_params = GetArticleParams {
@@ -1094,3 +1143,1746 @@ async fn api_test_create_remote_article() -> Result<()> {
 
     TestData::stop(alpha, beta, gamma)
 }
+
+#[tokio::test]
+async fn federation_test_person_and_instance_outbox() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    // create article and comment
+    let params = create_test_article_params();
+    let alpha_article = alpha.create_article(&params).await.unwrap();
+    let params = CreateCommentParams {
+        content: "my comment".to_string(),
+        article_id: alpha_article.article.id,
+        parent_id: None,
+        ..Default::default()
+    };
+    alpha.create_comment(&params).await.unwrap();
+
+    let client = reqwest::Client::new();
+    let fetch = async |url: &str| -> Result<Value> {
+        Ok(client
+            .get(url)
+            .header("Accept", "application/activity+json")
+            .send()
+            .await?
+            .json()
+            .await?)
+    };
+
+    // user outbox contains the comment and the article creation, newest first
+    let outbox = fetch(&format!("http://{}/user/alpha/outbox", alpha.hostname)).await?;
+    assert_eq!("OrderedCollection", outbox["type"]);
+    assert_eq!(2, outbox["totalItems"]);
+    let page = fetch(outbox["first"].as_str().unwrap()).await?;
+    assert_eq!("OrderedCollectionPage", page["type"]);
+    assert!(page["next"].is_null());
+    let items = page["orderedItems"].as_array().unwrap();
+    assert_eq!(2, items.len());
+    assert_eq!("Create", items[0]["type"]);
+    assert_eq!("Note", items[0]["object"]["type"]);
+    assert_eq!("Create", items[1]["type"]);
+    assert_eq!("Patch", items[1]["object"]["type"]);
+
+    // instance outbox announces the article creation and the default main page
+    let instance = fetch(&format!("http://{}/", alpha.hostname)).await?;
+    assert_eq!(
+        format!("http://{}/all_articles", alpha.hostname),
+        instance["articles"]
+    );
+    let outbox = fetch(instance["outbox"].as_str().unwrap()).await?;
+    assert_eq!(2, outbox["totalItems"]);
+    let page = fetch(outbox["first"].as_str().unwrap()).await?;
+    let items = page["orderedItems"].as_array().unwrap();
+    assert_eq!(2, items.len());
+    assert_eq!("Announce", items[0]["type"]);
+    assert_eq!(
+        alpha_article.article.ap_id.to_string(),
+        items[0]["object"]["object"]["object"]
+    );
+
+    // page numbers which are too large are rejected instead of overflowing
+    let res = client
+        .get(format!(
+            "http://{}/user/alpha/outbox?page={}",
+            alpha.hostname,
+            i64::MAX
+        ))
+        .header("Accept", "application/activity+json")
+        .send()
+        .await?;
+    assert!(!res.status().is_success());
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test]
+async fn federation_test_rotate_key() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    let beta_instance = alpha
+        .follow_instance_with_resolve(&beta.hostname)
+        .await
+        .unwrap();
+    let create_res = beta
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    let get_article_data = GetArticleParams {
+        title: Some(create_res.article.title.to_string()),
+        domain: Some(beta_instance.domain),
+        id: None,
+    };
+
+    // rotating keys requires admin
+    assert!(alpha.rotate_key(None).await.is_err());
+    let params = LoginUserParams {
+        username_or_email: "ibis".to_string(),
+        password: "ibis".to_string(),
+        totp_token: None,
+    };
+    alpha.login(params).await.unwrap();
+    let admin = alpha.site().await.unwrap().my_profile.unwrap().person;
+
+    // edit remote article, so that beta gets a copy of the admin with its current key
+    let edit = async |new_text: &str| {
+        let get_res = alpha.get_article(get_article_data.clone()).await.unwrap();
+        let edit_params = EditArticleParams {
+            article_id: get_res.article.id,
+            new_text: new_text.to_string(),
+            summary: "summary".to_string(),
+            previous_version_id: get_res.latest_version,
+            minor: false,
+            resolve_conflict_id: None,
+        };
+        alpha
+            .edit_article_without_conflict(&edit_params)
+            .await
+            .unwrap();
+    };
+    edit("Lorem Ipsum 2\n").await;
+
+    alpha.rotate_key(None).await.unwrap();
+    alpha.rotate_key(Some(admin.id)).await.unwrap();
+
+    // edit signed with the new key is accepted by beta. The previous key is rejected because the
+    // grace period is zero in tests.
+    edit("Lorem Ipsum 3\n").await;
+    let get_res = beta
+        .get_article(GetArticleParams {
+            title: Some(create_res.article.title.to_string()),
+            domain: None,
+            id: None,
+        })
+        .await
+        .unwrap();
+    let edits = beta.get_article_edits(get_res.article.id).await.unwrap();
+    assert_eq!(3, edits.len());
+    assert_eq!("Lorem Ipsum 3\n", get_res.article.text);
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test]
+async fn api_test_account_migration() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    // alpha follows instance beta and one of its articles
+    let beta_instance = alpha
+        .follow_instance_with_resolve(&beta.hostname)
+        .await
+        .unwrap();
+    let create_res = beta
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    let article = alpha
+        .resolve_article(create_res.article.ap_id.clone().into())
+        .await
+        .unwrap();
+    alpha
+        .follow_article(article.article.id, true)
+        .await
+        .unwrap();
+    let params = UpdateUserParams {
+        display_name: Some("Alpha User".to_string()),
+        email_notifications: Some(true),
+        email_digest: Some(EmailDigest::Daily),
+        locale: Some("de".to_string()),
+        ..Default::default()
+    };
+    alpha.update_user_profile(params).await.unwrap();
+
+    // export data and import it into gamma
+    let export = alpha.export_account().await.unwrap();
+    assert_eq!(
+        vec![beta_instance.ap_id.inner().clone()],
+        export.following_instances
+    );
+    assert_eq!(
+        vec![create_res.article.ap_id.inner().clone()],
+        export.following_articles
+    );
+    gamma.import_account(&export).await.unwrap();
+
+    let gamma_follows = gamma.get_follows().await.unwrap();
+    assert_eq!(1, gamma_follows.len());
+    assert_eq!(beta_instance.ap_id, gamma_follows[0].instance.ap_id);
+    let gamma_user = gamma.site().await.unwrap().my_profile.unwrap();
+    assert_eq!(
+        Some("Alpha User".to_string()),
+        gamma_user.person.display_name
+    );
+    assert!(gamma_user.local_user.email_notifications);
+    assert_eq!(EmailDigest::Daily, gamma_user.local_user.email_digest);
+    assert_eq!(Some("de".to_string()), gamma_user.local_user.locale);
+    assert_eq!(1, gamma_user.person.also_known_as.len());
+    assert_eq!(&export.ap_id, gamma_user.person.also_known_as[0].inner());
+
+    // moving to an account without alias fails
+    let beta_user = beta.site().await.unwrap().my_profile.unwrap();
+    assert!(
+        alpha
+            .move_account(beta_user.person.ap_id.into())
+            .await
+            .is_err()
+    );
+
+    // move alpha to gamma
+    alpha
+        .move_account(gamma_user.person.ap_id.clone().into())
+        .await
+        .unwrap();
+    let alpha_user = alpha.site().await.unwrap().my_profile.unwrap();
+    assert_eq!(
+        Some(gamma_user.person.ap_id.clone()),
+        alpha_user.person.moved_to
+    );
+
+    // beta also knows about the move
+    let params = GetUserParams {
+        name: "alpha".to_string(),
+        domain: Some(extract_domain(&export.ap_id)),
+    };
+    let alpha_on_beta = beta.get_user(params).await.unwrap();
+    assert_eq!(Some(gamma_user.person.ap_id), alpha_on_beta.moved_to);
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test]
+async fn api_test_wiki_export_import() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    beta.follow_instance_with_resolve(&alpha.hostname)
+        .await
+        .unwrap();
+
+    // create article with edit and comments on alpha, one of them from a remote user
+    let create_res = alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    let edit_params = EditArticleParams {
+        article_id: create_res.article.id,
+        new_text: "Lorem Ipsum 2\n".to_string(),
+        summary: "summary".to_string(),
+        previous_version_id: create_res.latest_version,
+        minor: false,
+        resolve_conflict_id: None,
+    };
+    alpha
+        .edit_article_without_conflict(&edit_params)
+        .await
+        .unwrap();
+    let beta_article = beta
+        .resolve_article(create_res.article.ap_id.inner().clone())
+        .await
+        .unwrap();
+    let params = CreateCommentParams {
+        content: "top comment".to_string(),
+        article_id: beta_article.article.id,
+        parent_id: None,
+        ..Default::default()
+    };
+    let top_comment = beta.create_comment(&params).await.unwrap().comment;
+    let params = CreateCommentParams {
+        content: "child comment".to_string(),
+        article_id: create_res.article.id,
+        parent_id: Some(
+            alpha
+                .get_article(GetArticleParams {
+                    id: Some(create_res.article.id),
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+                .comments[0]
+                .comment
+                .id,
+        ),
+        ..Default::default()
+    };
+    alpha.create_comment(&params).await.unwrap();
+
+    // export requires admin
+    assert!(alpha.export_wiki().await.is_err());
+    let admin_login = || LoginUserParams {
+        username_or_email: "ibis".to_string(),
+        password: "ibis".to_string(),
+        totp_token: None,
+    };
+    alpha.login(admin_login()).await.unwrap();
+    let archive = alpha.export_wiki().await.unwrap();
+
+    // import into gamma
+    assert!(gamma.import_wiki(archive.clone()).await.is_err());
+    gamma.login(admin_login()).await.unwrap();
+    gamma.import_wiki(archive).await.unwrap();
+
+    let get_params = GetArticleParams {
+        title: Some(create_res.article.title.clone()),
+        ..Default::default()
+    };
+    let alpha_article = alpha.get_article(get_params.clone()).await.unwrap();
+    let gamma_article = gamma.get_article(get_params).await.unwrap();
+    assert_eq!(alpha_article.article.text, gamma_article.article.text);
+    assert!(gamma_article.article.local);
+    assert_eq!(
+        gamma.hostname,
+        extract_domain(gamma_article.article.ap_id.inner())
+    );
+    assert_eq!(alpha_article.latest_version, gamma_article.latest_version);
+
+    // edit hashes are unchanged
+    let alpha_edits = alpha
+        .get_article_edits(alpha_article.article.id)
+        .await
+        .unwrap();
+    let gamma_edits = gamma
+        .get_article_edits(gamma_article.article.id)
+        .await
+        .unwrap();
+    assert_eq!(2, gamma_edits.len());
+    let hashes = |edits: Vec<EditView>| edits.into_iter().map(|e| e.edit.hash).collect::<Vec<_>>();
+    assert_eq!(hashes(alpha_edits), hashes(gamma_edits));
+
+    // comments with remote author and parent are restored
+    assert_eq!(2, gamma_article.comments.len());
+    let top = gamma_article
+        .comments
+        .iter()
+        .find(|c| c.comment.parent_id.is_none())
+        .unwrap();
+    assert_eq!(top_comment.ap_id, top.comment.ap_id);
+    assert!(!top.creator.local);
+    let child = gamma_article
+        .comments
+        .iter()
+        .find(|c| c.comment.parent_id.is_some())
+        .unwrap();
+    assert_eq!(Some(top.comment.id), child.comment.parent_id);
+    assert_eq!(1, child.comment.depth);
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test]
+async fn api_test_email_preview() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    // preview requires admin
+    assert!(
+        alpha
+            .email_preview(EmailTemplate::Verification, None)
+            .await
+            .is_err()
+    );
+    let params = LoginUserParams {
+        username_or_email: "ibis".to_string(),
+        password: "ibis".to_string(),
+        totp_token: None,
+    };
+    alpha.login(params).await.unwrap();
+
+    let preview = alpha
+        .email_preview(EmailTemplate::Notification, Some("de".to_string()))
+        .await
+        .unwrap();
+    assert_eq!("2 neue Benachrichtigungen", preview.subject);
+    assert!(preview.html.contains("Example Article"));
+
+    // falls back to english for missing translations
+    let preview = alpha
+        .email_preview(EmailTemplate::ResetPassword, Some("es".to_string()))
+        .await
+        .unwrap();
+    assert_eq!("Password reset", preview.subject);
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test]
+async fn api_test_notification_preferences() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    let params = UpdateUserParams {
+        notify_new_article: Some(NotificationPreference::Off),
+        ..Default::default()
+    };
+    beta.update_user_profile(params).await.unwrap();
+    beta.follow_instance_with_resolve(&alpha.hostname)
+        .await
+        .unwrap();
+
+    // no notification for new article
+    let create_res = alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    assert_eq!(0, beta.notifications_count().await.unwrap());
+
+    // edits are still notified by default
+    let beta_article = beta
+        .resolve_article(create_res.article.ap_id.inner().clone())
+        .await
+        .unwrap();
+    beta.follow_article(beta_article.article.id, true)
+        .await
+        .unwrap();
+    let mut edit_params = EditArticleParams {
+        article_id: create_res.article.id,
+        new_text: "Lorem Ipsum 2\n".to_string(),
+        summary: "summary".to_string(),
+        previous_version_id: create_res.latest_version,
+        minor: false,
+        resolve_conflict_id: None,
+    };
+    let edit_res = alpha
+        .edit_article_without_conflict(&edit_params)
+        .await
+        .unwrap();
+    let count = beta.notifications_count().await.unwrap();
+    assert!(count > 0);
+
+    // after disabling edit notifications there are no more notifications
+    let params = UpdateUserParams {
+        notify_article_edit: Some(NotificationPreference::Off),
+        ..Default::default()
+    };
+    beta.update_user_profile(params).await.unwrap();
+    let user = beta.site().await.unwrap().my_profile.unwrap();
+    assert_eq!(
+        NotificationPreference::Off,
+        user.local_user.notify_article_edit
+    );
+    assert_eq!(
+        NotificationPreference::Email,
+        user.local_user.notify_comment_reply
+    );
+    edit_params.new_text = "Lorem Ipsum 3\n".to_string();
+    edit_params.previous_version_id = edit_res.latest_version;
+    alpha
+        .edit_article_without_conflict(&edit_params)
+        .await
+        .unwrap();
+    assert_eq!(count, beta.notifications_count().await.unwrap());
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_mention_notification() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    beta.follow_instance_with_resolve(&alpha.hostname)
+        .await
+        .unwrap();
+    let create_res = alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+
+    // mention users on alpha and gamma in a comment from beta, gamma doesnt follow any instance
+    let beta_article = beta
+        .resolve_article(create_res.article.ap_id.inner().clone())
+        .await
+        .unwrap();
+    let params = CreateCommentParams {
+        content: format!(
+            "hello @alpha@{} and @gamma@{}",
+            alpha.hostname, gamma.hostname
+        ),
+        article_id: beta_article.article.id,
+        parent_id: None,
+        ..Default::default()
+    };
+    let comment = beta.create_comment(&params).await.unwrap().comment;
+    sleep(Duration::from_secs(1)).await;
+
+    for instance in [&alpha, &gamma] {
+        let notifications = instance.notifications_list().await.unwrap();
+        let mention = notifications.iter().find_map(|n| match &n.data {
+            ApiNotificationData::CommentMention(c) => Some(c),
+            _ => None,
+        });
+        assert_eq!(Some(&comment.ap_id), mention.map(|c| &c.ap_id));
+    }
+
+    // mention beta in an edit summary
+    let edit_params = EditArticleParams {
+        article_id: create_res.article.id,
+        new_text: "Lorem Ipsum 2\n".to_string(),
+        summary: format!("thanks @beta@{}", beta.hostname),
+        previous_version_id: create_res.latest_version,
+        minor: false,
+        resolve_conflict_id: None,
+    };
+    alpha
+        .edit_article_without_conflict(&edit_params)
+        .await
+        .unwrap();
+    let notifications = beta.notifications_list().await.unwrap();
+    assert!(notifications.iter().any(
+        |n| matches!(&n.data, ApiNotificationData::EditMention(e) if e.summary == edit_params.summary)
+    ));
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_live_events() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    /// Read from the event stream until an event with the given text arrives.
+    async fn wait_for_event(stream: &mut reqwest::Response, expected: &str) {
+        let mut received = String::new();
+        while !received.contains(expected) {
+            let chunk = tokio::time::timeout(Duration::from_secs(10), stream.chunk())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            received.push_str(&String::from_utf8_lossy(&chunk));
+        }
+    }
+
+    // new article from followed instance is pushed as notification
+    beta.follow_instance_with_resolve(&alpha.hostname)
+        .await
+        .unwrap();
+    let mut beta_events = beta.live_events(&Default::default()).await.unwrap();
+    let create_res = alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    wait_for_event(&mut beta_events, r#""type":"notification""#).await;
+
+    // edit is pushed to users viewing the article
+    let params = LiveEventsParams {
+        article_id: Some(create_res.article.id),
+    };
+    let mut alpha_events = alpha.live_events(&params).await.unwrap();
+    let edit_params = EditArticleParams {
+        article_id: create_res.article.id,
+        new_text: "Lorem Ipsum 2\n".to_string(),
+        summary: "summary".to_string(),
+        previous_version_id: create_res.latest_version,
+        minor: false,
+        resolve_conflict_id: None,
+    };
+    let edit_res = alpha
+        .edit_article_without_conflict(&edit_params)
+        .await
+        .unwrap();
+    let expected = serde_json::to_string(&edit_res.latest_version)?;
+    wait_for_event(
+        &mut alpha_events,
+        &format!(r#""latest_version":{expected}"#),
+    )
+    .await;
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_web_push() -> Result<()> {
+    use axum::{Router, http::HeaderMap, routing::post};
+    use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+    use ring::{
+        agreement::{ECDH_P256, EphemeralPrivateKey},
+        rand::SystemRandom,
+    };
+    use tokio::{net::TcpListener, sync::mpsc::unbounded_channel};
+
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    let key = beta.web_push_key().await.unwrap();
+    assert!(!key.public_key.is_empty());
+
+    // push service which passes on the headers of received messages
+    let (sender, mut receiver) = unbounded_channel();
+    let push_service = Router::new().route(
+        "/push",
+        post(move |headers: HeaderMap| {
+            let sender = sender.clone();
+            async move {
+                sender.send(headers).unwrap();
+                http::StatusCode::CREATED
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let endpoint = format!("http://{}/push", listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, push_service).await });
+
+    let rng = SystemRandom::new();
+    let browser_key = EphemeralPrivateKey::generate(&ECDH_P256, &rng).unwrap();
+    let params = WebPushSubscribeParams {
+        endpoint,
+        p256dh: BASE64_URL_SAFE_NO_PAD.encode(browser_key.compute_public_key().unwrap()),
+        auth: BASE64_URL_SAFE_NO_PAD.encode([1; 16]),
+    };
+    beta.web_push_subscribe(params).await.unwrap();
+
+    // new article from followed instance is sent as push message
+    beta.follow_instance_with_resolve(&alpha.hostname)
+        .await
+        .unwrap();
+    alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    let headers = tokio::time::timeout(Duration::from_secs(10), receiver.recv())
+        .await?
+        .unwrap();
+    assert_eq!(Some("aes128gcm"), headers["content-encoding"].to_str().ok());
+    assert!(headers["authorization"].to_str()?.starts_with("vapid t="));
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_webhooks() -> Result<()> {
+    use axum::{Router, body::Bytes, http::HeaderMap, routing::post};
+    use ring::hmac;
+    use tokio::{
+        net::TcpListener,
+        sync::mpsc::{UnboundedReceiver, unbounded_channel},
+    };
+
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    // receiver which passes on the headers and body of requests
+    let (sender, mut receiver) = unbounded_channel();
+    let receiver_app = Router::new().route(
+        "/hook",
+        post(move |headers: HeaderMap, body: Bytes| {
+            let sender = sender.clone();
+            async move {
+                sender.send((headers, body)).unwrap();
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/hook", listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, receiver_app).await });
+
+    // webhooks can only be created by admin
+    let params = CreateWebhookParams {
+        url,
+        secret: "secret".to_string(),
+        events: "article_created, comment_created".to_string(),
+    };
+    assert!(alpha.create_webhook(params.clone()).await.is_err());
+    let login_params = LoginUserParams {
+        username_or_email: "ibis".to_string(),
+        password: "ibis".to_string(),
+        totp_token: None,
+    };
+    alpha.login(login_params).await.unwrap();
+    let invalid_params = CreateWebhookParams {
+        events: "article_deleted".to_string(),
+        ..params.clone()
+    };
+    assert!(alpha.create_webhook(invalid_params).await.is_err());
+    let created = alpha.create_webhook(params).await.unwrap();
+    assert_eq!("secret", created.secret);
+    let webhook = created.webhook;
+    assert_eq!(
+        vec![WebhookEvent::ArticleCreated, WebhookEvent::CommentCreated],
+        webhook.events
+    );
+    // secret is not included when listing
+    assert_eq!("", alpha.list_webhooks().await.unwrap()[0].secret);
+
+    /// Wait for the next request, and check that its signature matches the secret.
+    async fn receive(receiver: &mut UnboundedReceiver<(HeaderMap, Bytes)>) -> Value {
+        let (headers, body) = tokio::time::timeout(Duration::from_secs(10), receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"secret");
+        let expected = hex::encode(hmac::sign(&key, &body));
+        assert_eq!(
+            Some(format!("sha256={expected}").as_str()),
+            headers["x-ibis-signature"].to_str().ok()
+        );
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    // edits are not sent because they are filtered out
+    let create_res = alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    let payload = receive(&mut receiver).await;
+    assert_eq!("article_created", payload["event"]);
+    assert_eq!(create_res.article.title, payload["data"]["title"]);
+
+    let params = CreateCommentParams {
+        content: "my comment".to_string(),
+        article_id: create_res.article.id,
+        parent_id: None,
+        ..Default::default()
+    };
+    alpha.create_comment(&params).await.unwrap();
+    let payload = receive(&mut receiver).await;
+    assert_eq!("comment_created", payload["event"]);
+    assert_eq!(params.content, payload["data"]["comment"]["content"]);
+
+    // delivery log is updated after the response
+    sleep(Duration::from_secs(1)).await;
+    let deliveries = alpha.list_webhook_deliveries(webhook.id).await.unwrap();
+    assert_eq!(2, deliveries.len());
+    assert_eq!(WebhookEvent::CommentCreated, deliveries[0].event);
+    assert!(
+        deliveries
+            .iter()
+            .all(|d| d.success && d.status == Some(200))
+    );
+
+    alpha.delete_webhook(webhook.id).await.unwrap();
+    assert!(alpha.list_webhooks().await.unwrap().is_empty());
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test]
+async fn api_test_atom_feeds() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    let create_res = alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    let edit_params = EditArticleParams {
+        article_id: create_res.article.id,
+        new_text: "Lorem Ipsum 2\n".to_string(),
+        summary: "fix <typo> & more".to_string(),
+        previous_version_id: create_res.latest_version,
+        minor: false,
+        resolve_conflict_id: None,
+    };
+    alpha
+        .edit_article_without_conflict(&edit_params)
+        .await
+        .unwrap();
+    let params = CreateCommentParams {
+        content: "my comment".to_string(),
+        article_id: create_res.article.id,
+        parent_id: None,
+        ..Default::default()
+    };
+    alpha.create_comment(&params).await.unwrap();
+
+    let client = reqwest::Client::new();
+    let fetch = async |path: &str| -> Result<String> {
+        let res = client
+            .get(format!("http://{}{path}", alpha.hostname))
+            .send()
+            .await?
+            .error_for_status()?;
+        assert_eq!(
+            Some("application/atom+xml; charset=utf-8"),
+            res.headers()["content-type"].to_str().ok()
+        );
+        Ok(res.text().await?)
+    };
+
+    // summaries are escaped, newest edit comes first
+    let feed = fetch("/feeds/changes").await?;
+    assert!(feed.contains(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#));
+    let edit_pos = feed.find("fix &lt;typo&gt; &amp; more").unwrap();
+    let create_pos = feed.find("create article").unwrap();
+    assert!(edit_pos < create_pos);
+
+    let feed = fetch(&format!("/feeds/article/{}", create_res.article.id.0)).await?;
+    assert_eq!(2, feed.matches("<entry>").count());
+
+    let feed = fetch(&format!(
+        "/feeds/article/{}/comments",
+        create_res.article.id.0
+    ))
+    .await?;
+    assert_eq!(1, feed.matches("<entry>").count());
+    assert!(feed.contains(&params.content));
+
+    let feed = fetch("/feeds/user/alpha").await?;
+    assert_eq!(2, feed.matches("<entry>").count());
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_recent_changes() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    let create_res = alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    let edit_params = EditArticleParams {
+        article_id: create_res.article.id,
+        new_text: "Lorem Ipsum 2\n".to_string(),
+        summary: "summary".to_string(),
+        previous_version_id: create_res.latest_version,
+        minor: false,
+        resolve_conflict_id: None,
+    };
+    alpha
+        .edit_article_without_conflict(&edit_params)
+        .await
+        .unwrap();
+
+    // both edits are listed newest first, after the edit of the default main page
+    let params = RecentChangesParams {
+        unpatrolled_only: Some(true),
+        ..Default::default()
+    };
+    let changes = alpha.recent_changes(&params).await.unwrap();
+    assert_eq!(3, changes.len());
+    assert_eq!("Default main page", changes[2].edit.summary);
+    assert_eq!(edit_params.summary, changes[0].edit.summary);
+    assert!(!changes[0].edit.patrolled);
+    let edit_id = changes[0].edit.id;
+
+    // only admin can patrol
+    assert!(alpha.patrol_edit(edit_id).await.is_err());
+    let params_remote = RecentChangesParams {
+        local: Some(false),
+        ..Default::default()
+    };
+    assert!(
+        alpha
+            .recent_changes(&params_remote)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let admin_login = LoginUserParams {
+        username_or_email: "ibis".to_string(),
+        password: "ibis".to_string(),
+        totp_token: None,
+    };
+    alpha.login(admin_login).await.unwrap();
+    alpha.patrol_edit(edit_id).await.unwrap();
+    let changes = alpha.recent_changes(&params).await.unwrap();
+    assert_eq!(2, changes.len());
+    assert!(changes.iter().all(|c| c.edit.id != edit_id));
+
+    // revert restores the previous text
+    alpha.revert_edit(edit_id).await.unwrap();
+    let article = alpha
+        .get_article(GetArticleParams {
+            title: None,
+            domain: None,
+            id: Some(create_res.article.id),
+        })
+        .await
+        .unwrap();
+    assert_eq!(TEST_ARTICLE_DEFAULT_TEXT, article.article.text);
+    let edits = alpha
+        .get_article_edits(create_res.article.id)
+        .await
+        .unwrap();
+    assert_eq!(3, edits.len());
+    assert_eq!(r#"Revert "summary" by @alpha"#, edits[2].edit.summary);
+
+    // reverting a second time fails because the text has changed since
+    assert!(alpha.revert_edit(edit_id).await.is_err());
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_minor_edits() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    beta.follow_instance_with_resolve(&alpha.hostname)
+        .await
+        .unwrap();
+    let create_res = alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    let beta_article = beta
+        .resolve_article(create_res.article.ap_id.inner().clone())
+        .await
+        .unwrap();
+    beta.follow_article(beta_article.article.id, true)
+        .await
+        .unwrap();
+    // summaries of edits which beta was notified about
+    let edit_notifications = async || -> Result<Vec<String>> {
+        Ok(beta
+            .notifications_list()
+            .await?
+            .into_iter()
+            .filter_map(|n| match n.data {
+                ApiNotificationData::Edit(e) => Some(e.summary),
+                _ => None,
+            })
+            .collect())
+    };
+
+    // followers are not notified about minor edits by default
+    let mut edit_params = EditArticleParams {
+        article_id: create_res.article.id,
+        new_text: "some [example](http://example.com) text.\n".to_string(),
+        summary: "typo".to_string(),
+        previous_version_id: create_res.latest_version,
+        minor: true,
+        resolve_conflict_id: None,
+    };
+    let edit_res = alpha
+        .edit_article_without_conflict(&edit_params)
+        .await
+        .unwrap();
+    assert!(!edit_notifications().await?.contains(&edit_params.summary));
+
+    // changes which are rendered the same are tagged as formatting, and always minor
+    edit_params.new_text = "some [example][1] text.\n\n[1]: http://example.com\n".to_string();
+    edit_params.summary = "link style".to_string();
+    edit_params.minor = false;
+    edit_params.previous_version_id = edit_res.latest_version;
+    let edit_res = alpha
+        .edit_article_without_conflict(&edit_params)
+        .await
+        .unwrap();
+    assert!(!edit_notifications().await?.contains(&edit_params.summary));
+
+    // minor flag and tags are federated
+    let edits = beta
+        .get_article_edits(beta_article.article.id)
+        .await
+        .unwrap();
+    assert_eq!(3, edits.len());
+    assert!(!edits[0].edit.minor);
+    assert!(edits[1].edit.minor);
+    assert!(edits[1].edit.tags.is_empty());
+    assert!(edits[2].edit.minor);
+    assert_eq!(vec![EditTag::Formatting], edits[2].edit.tags);
+
+    // normal edit is notified
+    edit_params.new_text = "some other text\n".to_string();
+    edit_params.summary = "rewrite".to_string();
+    edit_params.previous_version_id = edit_res.latest_version;
+    alpha
+        .edit_article_without_conflict(&edit_params)
+        .await
+        .unwrap();
+    assert!(edit_notifications().await?.contains(&edit_params.summary));
+
+    let params = RecentChangesParams {
+        hide_minor: Some(true),
+        ..Default::default()
+    };
+    let changes = alpha.recent_changes(&params).await.unwrap();
+    assert!(changes.iter().all(|c| !c.edit.minor));
+    assert_eq!(edit_params.summary, changes[0].edit.summary);
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_sessions() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    let sessions = alpha.list_sessions().await.unwrap();
+    assert_eq!(1, sessions.len());
+    assert!(sessions[0].current);
+
+    // login from a second device
+    let login_params = LoginUserParams {
+        username_or_email: "alpha".to_string(),
+        password: "hunter22".to_string(),
+        totp_token: None,
+    };
+    let device = ApiClient::new(Some(alpha.hostname.clone()));
+    device.login(login_params.clone()).await.unwrap();
+    let sessions = alpha.list_sessions().await.unwrap();
+    assert_eq!(2, sessions.len());
+    let other = sessions.iter().find(|s| !s.current).unwrap();
+
+    // revoking the session logs out the second device
+    alpha.revoke_session(other.id).await.unwrap();
+    assert!(device.list_sessions().await.is_err());
+    assert_eq!(1, alpha.list_sessions().await.unwrap().len());
+
+    // changing password logs out all other devices
+    device.login(login_params).await.unwrap();
+    alpha
+        .change_password(ChangePasswordParams {
+            new_password: "hunter33".to_string(),
+            confirm_new_password: "hunter33".to_string(),
+            old_password: "hunter22".to_string(),
+        })
+        .await
+        .unwrap();
+    assert!(device.list_sessions().await.is_err());
+    let sessions = alpha.list_sessions().await.unwrap();
+    assert_eq!(1, sessions.len());
+    assert!(sessions[0].current);
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_totp() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    // enable two-factor authentication
+    let setup = alpha.generate_totp().await.unwrap();
+    assert!(alpha.enable_totp("000000".to_string()).await.is_err());
+    let enabled_at = Utc::now();
+    let code = code_at(&setup.secret, enabled_at).unwrap();
+    let recovery_codes = alpha
+        .enable_totp(code.clone())
+        .await
+        .unwrap()
+        .recovery_codes;
+    assert_eq!(10, recovery_codes.len());
+    let my_profile = alpha.site().await.unwrap().my_profile.unwrap();
+    assert!(my_profile.local_user.totp_enabled);
+
+    // login requires the code after the password was accepted
+    let login = |totp_token: Option<String>| LoginUserParams {
+        username_or_email: "alpha".to_string(),
+        password: "hunter22".to_string(),
+        totp_token,
+    };
+    let device = ApiClient::new(Some(alpha.hostname.clone()));
+    let res = device.login(login(None)).await;
+    assert_eq!(TOTP_REQUIRED, res.unwrap_err().message());
+    assert!(
+        device
+            .login(login(Some("000000".to_string())))
+            .await
+            .is_err()
+    );
+
+    // the code used for enabling can't be used again, but the next one is accepted
+    assert!(device.login(login(Some(code))).await.is_err());
+    let code = code_at(&setup.secret, enabled_at + chrono::Duration::seconds(30)).unwrap();
+    device.login(login(Some(code.clone()))).await.unwrap();
+    assert!(device.login(login(Some(code))).await.is_err());
+
+    // recovery codes can only be used once
+    let recovery_code = Some(recovery_codes[0].clone());
+    device.login(login(recovery_code.clone())).await.unwrap();
+    assert!(device.login(login(recovery_code)).await.is_err());
+
+    // disable it again
+    let params = DisableTotpParams {
+        password: "hunter22".to_string(),
+        totp_token: recovery_codes[1].clone(),
+    };
+    alpha.disable_totp(params).await.unwrap();
+    device.login(login(None)).await.unwrap();
+
+    // after too many invalid codes even valid ones are rejected
+    let setup = beta.generate_totp().await.unwrap();
+    let code = code_at(&setup.secret, Utc::now()).unwrap();
+    let recovery_codes = beta.enable_totp(code).await.unwrap().recovery_codes;
+    let login = |totp_token: Option<String>| LoginUserParams {
+        username_or_email: "beta".to_string(),
+        password: "hunter22".to_string(),
+        totp_token,
+    };
+    let device = ApiClient::new(Some(beta.hostname.clone()));
+    for _ in 0..5 {
+        assert!(
+            device
+                .login(login(Some("000000".to_string())))
+                .await
+                .is_err()
+        );
+    }
+    let recovery_code = Some(recovery_codes[0].clone());
+    assert!(device.login(login(recovery_code)).await.is_err());
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_api_tokens() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    let create_token = async |scope| {
+        let params = CreateApiTokenParams {
+            name: format!("{scope:?} script"),
+            scope,
+        };
+        let token = alpha.create_api_token(params).await.unwrap().token;
+        ApiClient::new(Some(alpha.hostname.clone())).with_api_token(token)
+    };
+
+    // read only token can't make changes
+    let read = create_token(ApiTokenScope::Read).await;
+    let my_profile = read.site().await.unwrap().my_profile.unwrap();
+    assert_eq!("alpha", my_profile.person.username);
+    assert!(
+        read.create_article(&create_test_article_params())
+            .await
+            .is_err()
+    );
+
+    // edit token can create articles, but not change account settings
+    let edit = create_token(ApiTokenScope::Edit).await;
+    let article = edit
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    assert!(edit.list_api_tokens().await.is_err());
+
+    let api_tokens = alpha.list_api_tokens().await.unwrap();
+    assert_eq!(2, api_tokens.len());
+    assert!(api_tokens.iter().all(|t| t.last_used.is_some()));
+
+    // deleted token is not accepted anymore
+    let read_token = api_tokens
+        .iter()
+        .find(|t| t.scope == ApiTokenScope::Read)
+        .unwrap();
+    alpha.delete_api_token(read_token.id).await.unwrap();
+    assert!(read.site().await.unwrap().my_profile.is_none());
+
+    // changing the password revokes all tokens
+    alpha
+        .change_password(ChangePasswordParams {
+            new_password: "hunter33".to_string(),
+            confirm_new_password: "hunter33".to_string(),
+            old_password: "hunter22".to_string(),
+        })
+        .await
+        .unwrap();
+    assert!(edit.site().await.unwrap().my_profile.is_none());
+    assert!(alpha.list_api_tokens().await.unwrap().is_empty());
+
+    // admin actions require admin scope
+    alpha
+        .login(LoginUserParams {
+            username_or_email: "ibis".to_string(),
+            password: "ibis".to_string(),
+            totp_token: None,
+        })
+        .await
+        .unwrap();
+    let protect_params = ProtectArticleParams {
+        article_id: article.article.id,
+        protected: true,
+    };
+    let edit = create_token(ApiTokenScope::Edit).await;
+    assert!(edit.protect_article(&protect_params).await.is_err());
+    let admin = create_token(ApiTokenScope::Admin).await;
+    admin.protect_article(&protect_params).await.unwrap();
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_bot_accounts() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    let params = UpdateUserParams {
+        bot: Some(true),
+        ..Default::default()
+    };
+    alpha.update_user_profile(params).await.unwrap();
+    let my_profile = alpha.site().await.unwrap().my_profile.unwrap();
+    assert!(my_profile.person.bot);
+
+    // edits by bots are tagged and hidden from recent changes by default
+    let create_res = alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    let edits = alpha
+        .get_article_edits(create_res.article.id)
+        .await
+        .unwrap();
+    assert_eq!(vec![EditTag::Bot], edits[0].edit.tags);
+    assert!(!edits[0].edit.patrolled);
+    let changes = alpha
+        .recent_changes(&RecentChangesParams::default())
+        .await
+        .unwrap();
+    assert!(changes.iter().all(|c| c.edit.id != edits[0].edit.id));
+    let params = RecentChangesParams {
+        hide_bots: Some(false),
+        ..Default::default()
+    };
+    let changes = alpha.recent_changes(&params).await.unwrap();
+    assert_eq!(edits[0].edit.id, changes[0].edit.id);
+
+    // only admin can trust bots, then edits are patrolled automatically
+    let person_id = my_profile.person.id;
+    assert!(alpha.trust_bot(person_id, true).await.is_err());
+    let admin = ApiClient::new(Some(alpha.hostname.clone()));
+    admin
+        .login(LoginUserParams {
+            username_or_email: "ibis".to_string(),
+            password: "ibis".to_string(),
+            totp_token: None,
+        })
+        .await
+        .unwrap();
+    let admin_id = admin.site().await.unwrap().my_profile.unwrap().person.id;
+    assert!(admin.trust_bot(admin_id, true).await.is_err());
+    admin.trust_bot(person_id, true).await.unwrap();
+
+    let edit_params = EditArticleParams {
+        article_id: create_res.article.id,
+        new_text: "Lorem Ipsum 2\n".to_string(),
+        summary: "bot edit".to_string(),
+        previous_version_id: create_res.latest_version,
+        minor: false,
+        resolve_conflict_id: None,
+    };
+    alpha
+        .edit_article_without_conflict(&edit_params)
+        .await
+        .unwrap();
+    let edits = alpha
+        .get_article_edits(create_res.article.id)
+        .await
+        .unwrap();
+    assert_eq!(edit_params.summary, edits[1].edit.summary);
+    assert!(edits[1].edit.patrolled);
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_registration_application() -> Result<()> {
+    let options = Options {
+        registration_open: true,
+        email_required: false,
+        registration_mode: RegistrationMode::Application,
+        ..Default::default()
+    };
+    let TestData(alpha, beta, gamma) = TestData::start_with_options(options).await;
+    let register = |username: &str, application_answer: Option<&str>| RegisterUserParams {
+        username: username.to_string(),
+        password: "hunter22".to_string(),
+        confirm_password: "hunter22".to_string(),
+        application_answer: application_answer.map(ToString::to_string),
+        ..Default::default()
+    };
+    let login = |username: &str| LoginUserParams {
+        username_or_email: username.to_string(),
+        password: "hunter22".to_string(),
+        totp_token: None,
+    };
+
+    // application needs an answer, and login is only possible after approval
+    let client = ApiClient::new(Some(alpha.hostname.clone()));
+    assert!(client.register(register("applicant", None)).await.is_err());
+    let res = client
+        .register(register("applicant", Some("I like wikis")))
+        .await
+        .unwrap();
+    assert!(res.application_pending);
+    assert!(client.login(login("applicant")).await.is_err());
+
+    let admin = ApiClient::new(Some(alpha.hostname.clone()));
+    admin
+        .login(LoginUserParams {
+            username_or_email: "ibis".to_string(),
+            password: "ibis".to_string(),
+            totp_token: None,
+        })
+        .await
+        .unwrap();
+    assert!(alpha.list_registration_applications().await.is_err());
+    let applications = admin.list_registration_applications().await.unwrap();
+    assert_eq!(1, applications.len());
+    assert_eq!("applicant", applications[0].person.username);
+    assert_eq!("I like wikis", applications[0].application.answer);
+    let params = ApproveRegistrationParams {
+        id: applications[0].application.id,
+        approve: true,
+        deny_reason: None,
+    };
+    admin
+        .approve_registration_application(params)
+        .await
+        .unwrap();
+    client.login(login("applicant")).await.unwrap();
+
+    // denied applicant can't login
+    client
+        .register(register("spammer", Some("buy now")))
+        .await
+        .unwrap();
+    let applications = admin.list_registration_applications().await.unwrap();
+    let params = ApproveRegistrationParams {
+        id: applications[0].application.id,
+        approve: false,
+        deny_reason: Some("spam".to_string()),
+    };
+    admin
+        .approve_registration_application(params)
+        .await
+        .unwrap();
+    assert!(client.login(login("spammer")).await.is_err());
+    assert!(
+        admin
+            .list_registration_applications()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // only admin can create invite codes, which skip the application and can be used once
+    let params = CreateInviteCodeParams {
+        max_uses: 1,
+        expires_in_days: None,
+    };
+    assert!(alpha.create_invite_code(params.clone()).await.is_err());
+    let invite_code = admin.create_invite_code(params).await.unwrap();
+    let params = RegisterUserParams {
+        invite_code: Some(invite_code.code.clone()),
+        ..register("invited", None)
+    };
+    let res = client.register(params).await.unwrap();
+    assert!(!res.application_pending);
+    client.login(login("invited")).await.unwrap();
+    let params = RegisterUserParams {
+        invite_code: Some(invite_code.code),
+        ..register("invited2", None)
+    };
+    assert!(client.register(params).await.is_err());
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_proof_of_work() -> Result<()> {
+    let options = Options {
+        registration_open: true,
+        email_required: false,
+        proof_of_work_difficulty: 8,
+        proof_of_work_first_comment: true,
+        ..Default::default()
+    };
+    let TestData(alpha, beta, gamma) = TestData::start_with_options(options).await;
+
+    // registration needs a solved challenge, which can only be used once
+    let client = ApiClient::new(Some(alpha.hostname.clone()));
+    let register = |username: &str| RegisterUserParams {
+        username: username.to_string(),
+        password: "hunter22".to_string(),
+        confirm_password: "hunter22".to_string(),
+        ..Default::default()
+    };
+    let res = client.register(register("newuser")).await;
+    assert_eq!(CHALLENGE_REQUIRED, res.unwrap_err().message());
+    let challenge = client.get_challenge().await.unwrap();
+    assert_eq!(8, challenge.difficulty);
+    let params = RegisterUserParams {
+        challenge: Some(challenge.challenge.clone()),
+        challenge_nonce: Some(0),
+        ..register("newuser")
+    };
+    // a random nonce only solves the challenge with probability 1/256
+    let nonce = solve_proof_of_work(&challenge.challenge, challenge.difficulty);
+    if nonce != 0 {
+        assert!(client.register(params).await.is_err());
+    }
+    let params = RegisterUserParams {
+        challenge: Some(challenge.challenge.clone()),
+        challenge_nonce: Some(nonce),
+        ..register("newuser")
+    };
+    client.register(params).await.unwrap();
+    let params = RegisterUserParams {
+        challenge: Some(challenge.challenge),
+        challenge_nonce: Some(nonce),
+        ..register("newuser2")
+    };
+    assert!(client.register(params).await.is_err());
+
+    // the first comment also needs a challenge, but not later ones
+    let article = alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    let mut params = CreateCommentParams {
+        content: "first comment".to_string(),
+        article_id: article.article.id,
+        ..Default::default()
+    };
+    let res = alpha.create_comment(&params).await;
+    assert_eq!(CHALLENGE_REQUIRED, res.unwrap_err().message());
+    let (challenge, nonce) = alpha.solve_challenge().await.unwrap();
+    params.challenge = Some(challenge);
+    params.challenge_nonce = Some(nonce);
+    alpha.create_comment(&params).await.unwrap();
+    let params = CreateCommentParams {
+        content: "second comment".to_string(),
+        article_id: article.article.id,
+        ..Default::default()
+    };
+    alpha.create_comment(&params).await.unwrap();
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_delete_account() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+    beta.follow_instance_with_resolve(&alpha.hostname)
+        .await
+        .unwrap();
+
+    // create article and comment, which are federated to beta
+    let article = alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    let params = CreateCommentParams {
+        content: "my comment".to_string(),
+        article_id: article.article.id,
+        ..Default::default()
+    };
+    alpha.create_comment(&params).await.unwrap();
+    let params = CreateCommentParams {
+        content: "deleted comment".to_string(),
+        article_id: article.article.id,
+        ..Default::default()
+    };
+    let deleted_comment = alpha.create_comment(&params).await.unwrap();
+    let params = EditCommentParams {
+        id: deleted_comment.comment.id,
+        content: None,
+        deleted: Some(true),
+    };
+    alpha.edit_comment(&params).await.unwrap();
+    let beta_article = beta
+        .resolve_article(article.article.ap_id.inner().clone())
+        .await
+        .unwrap();
+    let user_params = GetUserParams {
+        name: "alpha".to_string(),
+        domain: Some(alpha.hostname.clone()),
+    };
+    beta.get_user(user_params.clone()).await.unwrap();
+
+    // export contains all data of the user
+    let data = alpha.export_personal_data().await.unwrap();
+    assert_eq!("alpha", data.person.username);
+    assert_eq!(1, data.edits.len());
+    assert_eq!(article.article.id, data.edits[0].article_id);
+    assert_eq!(2, data.comments.len());
+    assert!(data.comments[0].deleted);
+    assert_eq!("my comment", data.comments[1].content);
+    assert!(data.conflicts.is_empty());
+    assert!(data.oauth_accounts.is_empty());
+    assert_eq!(1, data.sessions.len());
+
+    // deletion needs the password
+    assert!(
+        alpha
+            .delete_account(DeleteAccountParams::default())
+            .await
+            .is_err()
+    );
+    let params = DeleteAccountParams {
+        password: Some("hunter22".to_string()),
+        ..Default::default()
+    };
+    alpha.delete_account(params).await.unwrap();
+    assert!(alpha.site().await.unwrap().my_profile.is_none());
+    let login = LoginUserParams {
+        username_or_email: "alpha".to_string(),
+        password: "hunter22".to_string(),
+        totp_token: None,
+    };
+    assert!(alpha.login(login).await.is_err());
+
+    // the username can't be registered again
+    let register = RegisterUserParams {
+        username: "alpha".to_string(),
+        password: "hunter22".to_string(),
+        confirm_password: "hunter22".to_string(),
+        ..Default::default()
+    };
+    assert!(alpha.register(register).await.is_err());
+
+    // edits are attributed to ghost, comments are emptied
+    let user_params_local = GetUserParams {
+        name: "alpha".to_string(),
+        domain: None,
+    };
+    assert!(alpha.get_user(user_params_local).await.is_err());
+    let edits = alpha.get_article_edits(article.article.id).await.unwrap();
+    assert_eq!("ghost", edits[0].creator.username);
+    let get_params = GetArticleParams {
+        id: Some(article.article.id),
+        ..Default::default()
+    };
+    let comments = alpha.get_article(get_params).await.unwrap().comments;
+    assert!(comments[0].comment.deleted);
+    assert!(comments[0].comment.content.is_empty());
+    assert_eq!("ghost", comments[0].creator.username);
+
+    // deletion is federated
+    assert!(beta.get_user(user_params).await.is_err());
+    let edits = beta
+        .get_article_edits(beta_article.article.id)
+        .await
+        .unwrap();
+    assert_eq!("ghost", edits[0].creator.username);
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_block_and_mute() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+    beta.follow_instance_with_resolve(&alpha.hostname)
+        .await
+        .unwrap();
+
+    // beta replies to a comment of alpha
+    let article = alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    let params = CreateCommentParams {
+        content: "alpha comment".to_string(),
+        article_id: article.article.id,
+        ..Default::default()
+    };
+    alpha.create_comment(&params).await.unwrap();
+    let beta_article = beta
+        .resolve_article(article.article.ap_id.inner().clone())
+        .await
+        .unwrap();
+    assert_eq!(1, beta_article.comments.len());
+    let reply_params = CreateCommentParams {
+        content: "beta reply".to_string(),
+        article_id: beta_article.article.id,
+        parent_id: Some(beta_article.comments[0].comment.id),
+        ..Default::default()
+    };
+    beta.create_comment(&reply_params).await.unwrap();
+    let count = alpha.notifications_count().await.unwrap();
+    assert!(count > 0);
+    let get_params = GetArticleParams {
+        id: Some(article.article.id),
+        ..Default::default()
+    };
+    let comments = alpha
+        .get_article(get_params.clone())
+        .await
+        .unwrap()
+        .comments;
+    assert_eq!(2, comments.len());
+    let beta_person = comments
+        .iter()
+        .find(|c| !c.creator.local)
+        .unwrap()
+        .creator
+        .clone();
+
+    // users can't block themselves or their own instance
+    let alpha_person = alpha.site().await.unwrap().my_profile.unwrap().person;
+    assert!(
+        alpha
+            .block_person(alpha_person.id, BlockKind::Block)
+            .await
+            .is_err()
+    );
+    let params = GetInstanceParams {
+        id: None,
+        hostname: Some(alpha.hostname.clone()),
+    };
+    let alpha_instance = alpha.get_instance(&params).await.unwrap();
+    assert!(
+        alpha
+            .block_instance(alpha_instance.instance.id, BlockKind::Block)
+            .await
+            .is_err()
+    );
+
+    // comments of blocked person are hidden and their replies dont notify
+    alpha
+        .block_person(beta_person.id, BlockKind::Block)
+        .await
+        .unwrap();
+    let blocks = alpha.list_blocks().await.unwrap();
+    assert_eq!(1, blocks.persons.len());
+    assert_eq!(beta_person.id, blocks.persons[0].person.id);
+    let comments = alpha
+        .get_article(get_params.clone())
+        .await
+        .unwrap()
+        .comments;
+    assert_eq!(1, comments.len());
+    assert_eq!("alpha comment", comments[0].comment.content);
+    beta.create_comment(&reply_params).await.unwrap();
+    assert_eq!(count, alpha.notifications_count().await.unwrap());
+    let beta_get_params = GetArticleParams {
+        id: Some(beta_article.article.id),
+        ..Default::default()
+    };
+    let comments = beta.get_article(beta_get_params).await.unwrap().comments;
+    assert_eq!(3, comments.len());
+
+    // muted person is visible, but still doesnt notify
+    alpha
+        .block_person(beta_person.id, BlockKind::Mute)
+        .await
+        .unwrap();
+    let comments = alpha
+        .get_article(get_params.clone())
+        .await
+        .unwrap()
+        .comments;
+    assert_eq!(3, comments.len());
+    beta.create_comment(&reply_params).await.unwrap();
+    assert_eq!(count, alpha.notifications_count().await.unwrap());
+
+    // blocking the whole instance hides the comments again
+    alpha.unblock_person(beta_person.id).await.unwrap();
+    let params = GetInstanceParams {
+        id: None,
+        hostname: Some(beta.hostname.clone()),
+    };
+    let beta_instance = alpha.get_instance(&params).await.unwrap();
+    alpha
+        .block_instance(beta_instance.instance.id, BlockKind::Block)
+        .await
+        .unwrap();
+    let blocks = alpha.list_blocks().await.unwrap();
+    assert!(blocks.persons.is_empty());
+    assert_eq!(1, blocks.instances.len());
+    let comments = alpha
+        .get_article(get_params.clone())
+        .await
+        .unwrap()
+        .comments;
+    assert_eq!(1, comments.len());
+    beta.create_comment(&reply_params).await.unwrap();
+    assert_eq!(count, alpha.notifications_count().await.unwrap());
+
+    // after unblocking, notifications arrive again
+    alpha
+        .unblock_instance(beta_instance.instance.id)
+        .await
+        .unwrap();
+    assert!(alpha.unblock_person(beta_person.id).await.is_err());
+    let comments = alpha.get_article(get_params).await.unwrap().comments;
+    assert_eq!(5, comments.len());
+    beta.create_comment(&reply_params).await.unwrap();
+    assert!(alpha.notifications_count().await.unwrap() > count);
+
+    TestData::stop(alpha, beta, gamma)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_test_person_contributions() -> Result<()> {
+    let TestData(alpha, beta, gamma) = TestData::start().await;
+
+    let create_res = alpha
+        .create_article(&create_test_article_params())
+        .await
+        .unwrap();
+    let edit_params = EditArticleParams {
+        article_id: create_res.article.id,
+        new_text: "Lorem Ipsum 2\n".to_string(),
+        summary: "summary".to_string(),
+        previous_version_id: create_res.latest_version,
+        minor: false,
+        resolve_conflict_id: None,
+    };
+    alpha
+        .edit_article_without_conflict(&edit_params)
+        .await
+        .unwrap();
+    let params = CreateCommentParams {
+        content: "my comment".to_string(),
+        article_id: create_res.article.id,
+        ..Default::default()
+    };
+    let comment = alpha.create_comment(&params).await.unwrap();
+    let person_id = alpha.site().await.unwrap().my_profile.unwrap().person.id;
+
+    // edits copied by a fork are not counted again
+    let fork_params = ForkArticleParams {
+        article_id: create_res.article.id,
+        new_title: format!("{} fork", create_res.article.title),
+    };
+    alpha.fork_article(&fork_params).await.unwrap();
+
+    // stats are calculated from edits and comments
+    let stats = alpha.get_person_stats(person_id).await.unwrap();
+    assert_eq!(2, stats.stats.edits);
+    assert_eq!(1, stats.stats.articles_created);
+    assert_eq!(1, stats.stats.comments);
+    assert!(stats.stats.bytes_added > 0);
+    assert!(stats.stats.bytes_removed > 0);
+    assert!(stats.stats.first_activity.is_some());
+    assert!(stats.stats.last_activity >= stats.stats.first_activity);
+    assert_eq!(1, stats.activity.len());
+    assert_eq!(Utc::now().date_naive(), stats.activity[0].day);
+    assert_eq!(3, stats.activity[0].count);
+
+    // deleting a comment keeps its publish time, but still updates the stats
+    let params = EditCommentParams {
+        id: comment.comment.id,
+        content: None,
+        deleted: Some(true),
+    };
+    alpha.edit_comment(&params).await.unwrap();
+    let stats = alpha.get_person_stats(person_id).await.unwrap();
+    assert_eq!(0, stats.stats.comments);
+
+    // contributions are paginated, newest first
+    let params = GetContributionsParams {
+        person_id,
+        page: None,
+    };
+    let contributions = alpha.get_person_contributions(params).await.unwrap();
+    assert_eq!(4, contributions.edits.len());
+    assert_eq!(edit_params.summary, contributions.edits[0].edit.summary);
+    assert!(contributions.comments.is_empty());
+    let params = GetContributionsParams {
+        person_id,
+        page: Some(2),
+    };
+    let contributions = alpha.get_person_contributions(params).await.unwrap();
+    assert!(contributions.edits.is_empty());
+    assert!(contributions.comments.is_empty());
+    let params = GetContributionsParams {
+        person_id,
+        page: Some(i64::MAX),
+    };
+    assert!(alpha.get_person_contributions(params).await.is_err());
+
+    TestData::stop(alpha, beta, gamma)
+}
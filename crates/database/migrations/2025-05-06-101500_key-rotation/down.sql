@@ -0,0 +1,5 @@
+alter table person drop column previous_public_key;
+alter table person drop column key_rotated_at;
+
+alter table instance drop column previous_public_key;
+alter table instance drop column key_rotated_at;
\ No newline at end of file
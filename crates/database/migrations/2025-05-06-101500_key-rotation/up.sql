@@ -0,0 +1,5 @@
+alter table person add column previous_public_key text;
+alter table person add column key_rotated_at timestamptz;
+
+alter table instance add column previous_public_key text;
+alter table instance add column key_rotated_at timestamptz;
\ No newline at end of file
@@ -0,0 +1,2 @@
+alter table person drop column also_known_as;
+alter table person drop column moved_to;
\ No newline at end of file
@@ -0,0 +1,2 @@
+alter table person add column also_known_as text[] not null default '{}';
+alter table person add column moved_to varchar(255);
\ No newline at end of file
@@ -0,0 +1,3 @@
+alter table local_user drop column email_digest;
+
+alter table notification drop column emailed_at;
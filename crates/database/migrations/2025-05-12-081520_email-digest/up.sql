@@ -0,0 +1,6 @@
+alter table local_user add column email_digest varchar(10) not null default 'immediate';
+
+alter table notification add column emailed_at timestamptz;
+
+-- existing notifications were already sent or skipped by the previous email logic
+update notification set emailed_at = published;
@@ -0,0 +1 @@
+alter table local_user add column locale varchar(10);
@@ -0,0 +1,7 @@
+alter table local_user drop column notify_comment_reply;
+alter table local_user drop column notify_article_comment;
+alter table local_user drop column notify_article_edit;
+alter table local_user drop column notify_new_article;
+alter table local_user drop column notify_mention;
+
+alter table notification drop column send_email;
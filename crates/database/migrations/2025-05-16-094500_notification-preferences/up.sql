@@ -0,0 +1,7 @@
+alter table local_user add column notify_comment_reply varchar(10) not null default 'email';
+alter table local_user add column notify_article_comment varchar(10) not null default 'email';
+alter table local_user add column notify_article_edit varchar(10) not null default 'email';
+alter table local_user add column notify_new_article varchar(10) not null default 'email';
+alter table local_user add column notify_mention varchar(10) not null default 'email';
+
+alter table notification add column send_email bool not null default true;
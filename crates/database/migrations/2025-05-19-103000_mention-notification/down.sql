@@ -0,0 +1 @@
+alter table notification drop column mention;
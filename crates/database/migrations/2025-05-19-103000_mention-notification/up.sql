@@ -0,0 +1 @@
+alter table notification add column mention bool not null default false;
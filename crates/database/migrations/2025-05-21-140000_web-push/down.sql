@@ -0,0 +1,2 @@
+drop table web_push_subscription;
+drop table web_push_key;
@@ -0,0 +1,14 @@
+create table web_push_subscription (
+    id serial primary key,
+    local_user_id int references local_user on update cascade on delete cascade not null,
+    endpoint text not null unique,
+    p256dh text not null,
+    auth text not null,
+    published timestamptz not null default now()
+);
+
+-- VAPID key pair of the instance, generated on first use
+create table web_push_key (
+    id int primary key default 1 check (id = 1),
+    private_key text not null
+);
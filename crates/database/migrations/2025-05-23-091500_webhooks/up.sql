@@ -0,0 +1,25 @@
+create table webhook (
+    id serial primary key,
+    url text not null,
+    -- key for the HMAC signature of the payload
+    secret text not null,
+    -- events which are sent to the webhook, empty for all events
+    events text[] not null default '{}',
+    published timestamptz not null default now()
+);
+
+create table webhook_delivery (
+    id serial primary key,
+    webhook_id int references webhook on update cascade on delete cascade not null,
+    event text not null,
+    payload text not null,
+    attempts int not null default 0,
+    success bool not null default false,
+    -- http status code of the last attempt
+    status int,
+    error text,
+    published timestamptz not null default now(),
+    updated timestamptz
+);
+
+create index on webhook_delivery (webhook_id, published);
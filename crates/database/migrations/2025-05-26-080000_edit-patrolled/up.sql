@@ -0,0 +1,4 @@
+alter table edit add column patrolled bool not null default false;
+
+-- only new edits need to be checked
+update edit set patrolled = true;
@@ -0,0 +1,3 @@
+alter table edit drop column minor;
+alter table edit drop column tags;
+alter table local_user drop column notify_minor_edit;
@@ -0,0 +1,5 @@
+alter table edit add column minor bool not null default false;
+-- set automatically, eg for reverts or formatting changes
+alter table edit add column tags text[] not null default '{}';
+
+alter table local_user add column notify_minor_edit varchar(10) not null default 'off';
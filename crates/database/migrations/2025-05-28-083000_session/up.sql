@@ -0,0 +1,12 @@
+create table session (
+    id serial primary key,
+    local_user_id int references local_user on update cascade on delete cascade not null,
+    -- stored in the jti claim of the login token
+    token uuid not null unique,
+    user_agent text,
+    ip text,
+    published timestamptz not null default now(),
+    last_seen timestamptz not null default now()
+);
+
+create index on session (local_user_id);
@@ -0,0 +1,3 @@
+alter table local_user drop column totp_secret;
+alter table local_user drop column totp_enabled;
+alter table local_user drop column totp_recovery_codes;
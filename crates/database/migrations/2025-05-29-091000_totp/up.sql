@@ -0,0 +1,3 @@
+alter table local_user add column totp_secret text;
+alter table local_user add column totp_enabled bool not null default false;
+alter table local_user add column totp_recovery_codes text[] not null default '{}';
@@ -0,0 +1,12 @@
+create table api_token (
+    id serial primary key,
+    local_user_id int references local_user on update cascade on delete cascade not null,
+    name text not null,
+    -- sha256 of the token, which is only shown to the user once
+    token_hash text not null unique,
+    scope varchar(10) not null,
+    published timestamptz not null default now(),
+    last_used timestamptz
+);
+
+create index on api_token (local_user_id);
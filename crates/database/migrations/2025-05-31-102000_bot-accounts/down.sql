@@ -0,0 +1,3 @@
+alter table person drop column bot;
+alter table person drop column bot_trusted;
+alter table local_user drop column notify_bot_edit;
@@ -0,0 +1,8 @@
+alter table person add column bot bool not null default false;
+-- edits by trusted bots are marked as patrolled automatically, set by admins
+alter table person add column bot_trusted bool not null default false;
+
+-- the wiki bot which federates articles, using the default name
+update person set bot = true where local and username = 'wikibot';
+
+alter table local_user add column notify_bot_edit varchar(10) not null default 'off';
@@ -0,0 +1,2 @@
+drop table registration_application;
+drop table invite_code;
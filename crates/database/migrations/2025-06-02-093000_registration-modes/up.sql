@@ -0,0 +1,20 @@
+create table invite_code (
+    id serial primary key,
+    code text not null unique,
+    creator_id int references local_user on update cascade on delete cascade not null,
+    max_uses int not null default 1,
+    uses int not null default 0,
+    expires timestamptz,
+    published timestamptz not null default now()
+);
+
+create table registration_application (
+    id serial primary key,
+    local_user_id int references local_user on update cascade on delete cascade not null unique,
+    answer text not null,
+    status varchar(10) not null default 'pending',
+    admin_id int references local_user on update cascade on delete set null,
+    deny_reason text,
+    published timestamptz not null default now(),
+    updated timestamptz
+);
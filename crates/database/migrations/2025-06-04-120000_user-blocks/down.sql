@@ -0,0 +1,3 @@
+drop table person_block;
+
+drop table instance_block;
@@ -0,0 +1,15 @@
+create table person_block (
+    local_user_id int references local_user on update cascade on delete cascade not null,
+    person_id int references person on update cascade on delete cascade not null,
+    kind varchar(10) not null default 'block',
+    published timestamptz not null default now(),
+    primary key (local_user_id, person_id)
+);
+
+create table instance_block (
+    local_user_id int references local_user on update cascade on delete cascade not null,
+    instance_id int references instance on update cascade on delete cascade not null,
+    kind varchar(10) not null default 'block',
+    published timestamptz not null default now(),
+    primary key (local_user_id, instance_id)
+);
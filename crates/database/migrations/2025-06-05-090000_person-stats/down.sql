@@ -0,0 +1,7 @@
+drop function person_stats_refresh;
+
+drop index idx_edit_creator_published;
+
+drop index idx_comment_creator_published;
+
+drop table person_stats;
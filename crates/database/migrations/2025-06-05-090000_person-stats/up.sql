@@ -0,0 +1,92 @@
+create table person_stats (
+    person_id int primary key references person on update cascade on delete cascade,
+    edits int not null default 0,
+    articles_created int not null default 0,
+    comments int not null default 0,
+    bytes_added bigint not null default 0,
+    bytes_removed bigint not null default 0,
+    first_activity timestamptz,
+    last_activity timestamptz,
+    updated timestamptz not null default now()
+);
+
+create index idx_edit_creator_published on edit (creator_id, published);
+
+create index idx_comment_creator_published on comment (creator_id, published);
+
+-- Recalculate contribution stats of a single person, or of all persons if target is null.
+-- The first edit of an article has the version of an empty diff as previous version, see
+-- `EditVersion::default()`.
+create function person_stats_refresh (target int)
+    returns void
+    language sql
+    as $$
+    insert into person_stats (person_id, edits, articles_created, comments, bytes_added, bytes_removed, first_activity, last_activity, updated)
+    select
+        p.id,
+        coalesce(e.edits, 0),
+        coalesce(e.articles_created, 0),
+        coalesce(c.comments, 0),
+        coalesce(d.bytes_added, 0),
+        coalesce(d.bytes_removed, 0),
+        least (e.first_edit, c.first_comment),
+        greatest (e.last_edit, c.last_comment),
+        now()
+    from
+        person p
+    left join (
+        select
+            creator_id,
+            count(*) as edits,
+            count(*) filter (where previous_version_id = 'e3b0c442-98fc-1c14-9afb-f4c8996fb924') as articles_created,
+            min(published) as first_edit,
+            max(published) as last_edit
+        from
+            edit
+        where
+            not pending
+            and (target is null or creator_id = target)
+        group by
+            creator_id) e on e.creator_id = p.id
+    left join (
+        select
+            creator_id,
+            count(*) as comments,
+            min(published) as first_comment,
+            max(published) as last_comment
+        from
+            comment
+        where
+            not deleted
+            and (target is null or creator_id = target)
+        group by
+            creator_id) c on c.creator_id = p.id
+    left join (
+        select
+            creator_id,
+            sum(octet_length(line) - 1) filter (where line like '+%'
+                and line != '+++ modified') as bytes_added,
+            sum(octet_length(line) - 1) filter (where line like '-%'
+                and line != '--- original') as bytes_removed
+        from
+            edit,
+            regexp_split_to_table(diff, E'\n') as line
+        where
+            not pending
+            and (target is null or creator_id = target)
+        group by
+            creator_id) d on d.creator_id = p.id
+    where
+        target is null
+        or p.id = target
+    on conflict (person_id)
+        do update set
+            edits = excluded.edits,
+            articles_created = excluded.articles_created,
+            comments = excluded.comments,
+            bytes_added = excluded.bytes_added,
+            bytes_removed = excluded.bytes_removed,
+            first_activity = excluded.first_activity,
+            last_activity = excluded.last_activity,
+            updated = excluded.updated;
+$$;
@@ -0,0 +1,123 @@
+create index idx_edit_creator_hash on edit (creator_id, hash);
+
+-- Recalculate contribution stats of a single person. If target is null, only persons without
+-- stats or with new edits or comments since the last calculation are updated.
+--
+-- Forking an article copies its edits with the same hash and creator, so only the earliest copy
+-- of each edit is counted. An article is created by the edit which has no previous edit in the
+-- same article.
+create or replace function person_stats_refresh (target int)
+    returns void
+    language sql
+    as $$
+    with targets as (
+        select
+            p.id
+        from
+            person p
+        left join person_stats s on s.person_id = p.id
+    where
+        p.id = target
+        or (target is null
+            and (s.person_id is null
+                or exists (
+                    select
+                        1
+                    from
+                        edit
+                    where
+                        creator_id = p.id
+                        and published > s.updated)
+                or exists (
+                    select
+                        1
+                    from
+                        comment
+                    where
+                        creator_id = p.id
+                        and published > s.updated)))
+),
+original_edit as (
+    select
+        e.*
+    from
+        edit e
+        join targets t on t.id = e.creator_id
+    where
+        not e.pending
+        and not exists (
+            select
+                1
+            from
+                edit o
+            where
+                o.creator_id = e.creator_id
+                and o.hash = e.hash
+                and o.id < e.id))
+insert into person_stats (person_id, edits, articles_created, comments, bytes_added, bytes_removed, first_activity, last_activity, updated)
+select
+    t.id,
+    coalesce(e.edits, 0),
+    coalesce(e.articles_created, 0),
+    coalesce(c.comments, 0),
+    coalesce(d.bytes_added, 0),
+    coalesce(d.bytes_removed, 0),
+    least (e.first_edit, c.first_comment),
+    greatest (e.last_edit, c.last_comment),
+    now()
+from
+    targets t
+    left join (
+        select
+            creator_id,
+            count(*) as edits,
+            count(*) filter (where not exists (
+                select
+                    1
+                from
+                    edit p
+                where
+                    p.article_id = original_edit.article_id
+                    and p.hash = original_edit.previous_version_id)) as articles_created,
+            min(published) as first_edit,
+            max(published) as last_edit
+        from
+            original_edit
+        group by
+            creator_id) e on e.creator_id = t.id
+    left join (
+        select
+            creator_id,
+            count(*) as comments,
+            min(published) as first_comment,
+            max(published) as last_comment
+        from
+            comment
+            join targets on targets.id = comment.creator_id
+        where
+            not deleted
+        group by
+            creator_id) c on c.creator_id = t.id
+    left join (
+        select
+            creator_id,
+            sum(octet_length(line) - 1) filter (where line like '+%'
+                and line != '+++ modified') as bytes_added,
+            sum(octet_length(line) - 1) filter (where line like '-%'
+                and line != '--- original') as bytes_removed
+        from
+            original_edit,
+            regexp_split_to_table(diff, E'\n') as line
+        group by
+            creator_id) d on d.creator_id = t.id
+on conflict (person_id)
+    do update set
+        edits = excluded.edits,
+        articles_created = excluded.articles_created,
+        comments = excluded.comments,
+        bytes_added = excluded.bytes_added,
+        bytes_removed = excluded.bytes_removed,
+        first_activity = excluded.first_activity,
+        last_activity = excluded.last_activity,
+        updated = excluded.updated;
+$$;
@@ -0,0 +1 @@
+alter table conflict add column minor bool not null default false;
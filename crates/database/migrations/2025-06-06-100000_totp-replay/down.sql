@@ -0,0 +1,4 @@
+alter table local_user
+    drop column totp_last_step,
+    drop column totp_failed_attempts,
+    drop column totp_failed_at;
@@ -0,0 +1,4 @@
+alter table local_user
+    add column totp_last_step bigint,
+    add column totp_failed_attempts int not null default 0,
+    add column totp_failed_at timestamptz;
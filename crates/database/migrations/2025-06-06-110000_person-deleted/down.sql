@@ -0,0 +1 @@
+alter table person drop column deleted;
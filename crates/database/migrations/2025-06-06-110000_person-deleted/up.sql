@@ -0,0 +1 @@
+alter table person add column deleted bool not null default false;
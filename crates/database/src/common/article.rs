@@ -24,13 +24,13 @@ use {
 pub struct Conflict {
     pub id: ConflictId,
     pub hash: EditVersion,
-    #[serde(skip)]
     pub diff: String,
     pub summary: String,
     pub creator_id: PersonId,
     pub article_id: ArticleId,
     pub previous_version_id: EditVersion,
     pub published: DateTime<Utc>,
+    pub minor: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -90,8 +90,40 @@ pub struct Edit {
     pub previous_version_id: EditVersion,
     pub published: DateTime<Utc>,
     pub pending: bool,
+    /// Edit was checked by an admin on the recent changes page
+    pub patrolled: bool,
+    /// Small change which followers are not notified about by default
+    pub minor: bool,
+    pub tags: Vec<EditTag>,
+}
+
+/// Describes what kind of change an edit makes. Tags are set automatically when the edit is
+/// created.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "ssr",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow)
+)]
+#[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::Text))]
+#[serde(rename_all = "kebab-case")]
+pub enum EditTag {
+    /// Undoes the changes of an earlier edit
+    Revert,
+    /// Made by a bot account
+    Bot,
+    /// Only changes whitespace or markdown formatting
+    Formatting,
+    /// Merges concurrent edits
+    ConflictResolution,
 }
 
+text_enum!(EditTag, "edit tag", {
+    Revert => "revert",
+    Bot => "bot",
+    Formatting => "formatting",
+    ConflictResolution => "conflict-resolution",
+});
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(feature = "ssr", derive(Queryable))]
 #[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::pg::Pg)))]
@@ -139,6 +171,7 @@ pub struct ApiConflict {
     pub article: Article,
     pub previous_version_id: EditVersion,
     pub published: DateTime<Utc>,
+    pub minor: bool,
 }
 
 pub fn can_edit_article(article: &Article, is_admin: bool) -> Result<(), anyhow::Error> {
@@ -0,0 +1,75 @@
+use super::{
+    instance::Instance,
+    newtypes::{InstanceId, LocalUserId, PersonId},
+    user::Person,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ssr")]
+use {
+    diesel::{Queryable, Selectable},
+    ibis_database_schema::{instance_block, person_block},
+};
+
+/// Person whose content the user doesn't want to see.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = person_block, check_for_backend(diesel::pg::Pg)))]
+pub struct PersonBlock {
+    pub local_user_id: LocalUserId,
+    pub person_id: PersonId,
+    pub kind: BlockKind,
+    pub published: DateTime<Utc>,
+}
+
+/// Instance whose users the user doesn't want to see.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = instance_block, check_for_backend(diesel::pg::Pg)))]
+pub struct InstanceBlock {
+    pub local_user_id: LocalUserId,
+    pub instance_id: InstanceId,
+    pub kind: BlockKind,
+    pub published: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(Queryable))]
+#[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::pg::Pg)))]
+pub struct PersonBlockView {
+    pub block: PersonBlock,
+    pub person: Person,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(Queryable))]
+#[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::pg::Pg)))]
+pub struct InstanceBlockView {
+    pub block: InstanceBlock,
+    pub instance: Instance,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BlockList {
+    pub persons: Vec<PersonBlockView>,
+    pub instances: Vec<InstanceBlockView>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "ssr",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow)
+)]
+#[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::Text))]
+pub enum BlockKind {
+    /// Don't send any notifications for actions of the target
+    Mute,
+    /// Also hide comments of the target in discussions
+    #[default]
+    Block,
+}
+
+text_enum!(BlockKind, "block kind", {
+    Mute => "mute",
+    Block => "block",
+});
@@ -0,0 +1,57 @@
+use super::{article::EditView, comment::CommentViewWithArticle, newtypes::PersonId};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ssr")]
+use {
+    diesel::{Queryable, QueryableByName, Selectable},
+    ibis_database_schema::person_stats,
+};
+
+/// Aggregated contributions of a person, recalculated periodically.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = person_stats, check_for_backend(diesel::pg::Pg)))]
+pub struct PersonStats {
+    pub person_id: PersonId,
+    /// Edits which are not pending, including the ones creating an article
+    pub edits: i32,
+    pub articles_created: i32,
+    /// Comments which are not deleted
+    pub comments: i32,
+    /// Size of lines added by all edits
+    pub bytes_added: i64,
+    /// Size of lines removed by all edits
+    pub bytes_removed: i64,
+    pub first_activity: Option<DateTime<Utc>>,
+    pub last_activity: Option<DateTime<Utc>>,
+    /// When the stats were last calculated
+    pub updated: DateTime<Utc>,
+    /// Set by database triggers when edits or comments of the person change
+    #[serde(skip)]
+    pub dirty: bool,
+}
+
+/// Number of edits and comments on a single day (UTC).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(QueryableByName))]
+#[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::pg::Pg)))]
+pub struct ActivityDay {
+    #[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::Date))]
+    pub day: NaiveDate,
+    #[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub count: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PersonStatsView {
+    pub stats: PersonStats,
+    /// Days with any activity during the last year, oldest first
+    pub activity: Vec<ActivityDay>,
+}
+
+/// One page of edits and comments by a person, newest first.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PersonContributions {
+    pub edits: Vec<EditView>,
+    pub comments: Vec<CommentViewWithArticle>,
+}
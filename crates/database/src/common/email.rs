@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+/// Emails which are rendered from templates in `crates/database/templates/email`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailTemplate {
+    Verification,
+    ResetPassword,
+    Notification,
+    RegistrationApplication,
+}
+
+impl EmailTemplate {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmailTemplate::Verification => "verification",
+            EmailTemplate::ResetPassword => "reset_password",
+            EmailTemplate::Notification => "notification",
+            EmailTemplate::RegistrationApplication => "registration_application",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EmailPreview {
+    pub subject: String,
+    pub html: String,
+}
@@ -0,0 +1,16 @@
+use super::{article::EditVersion, newtypes::ArticleId};
+use serde::{Deserialize, Serialize};
+
+/// Event which is pushed to the browser via Server-Sent Events, so that it can update without
+/// polling.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    /// A new notification was created for the logged-in user
+    Notification,
+    /// The text of the article changed
+    ArticleUpdated {
+        article_id: ArticleId,
+        latest_version: EditVersion,
+    },
+}
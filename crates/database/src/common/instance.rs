@@ -10,7 +10,6 @@ use smart_default::SmartDefault;
 use url::Url;
 #[cfg(feature = "ssr")]
 use {
-    crate::config::OAuthProvider,
     diesel::{Identifiable, Queryable, Selectable},
     doku::Document,
     ibis_database_schema::instance,
@@ -35,6 +34,11 @@ pub struct Instance {
     #[cfg(feature = "ssr")]
     pub instances_url: DbUrl,
     pub name: Option<String>,
+    /// Public key which was in use before the last key rotation
+    #[serde(skip)]
+    pub previous_public_key: Option<String>,
+    #[serde(skip)]
+    pub key_rotated_at: Option<DateTime<Utc>>,
 }
 
 impl Instance {
@@ -68,10 +72,50 @@ pub struct Options {
     #[default = true]
     #[cfg_attr(feature = "ssr", doku(example = "true"))]
     pub registration_open: bool,
+    /// How new users can register if registration is open: `open` for anyone, `invite` with an
+    /// invite code, or `application` which needs to be approved by an admin. A valid invite code
+    /// also skips the application.
+    #[default(RegistrationMode::Open)]
+    #[cfg_attr(feature = "ssr", doku(example = "open"))]
+    pub registration_mode: RegistrationMode,
+    /// Question which users have to answer in their registration application
+    #[default("Why do you want to join this wiki?")]
+    #[cfg_attr(feature = "ssr", doku(example = "Why do you want to join this wiki?"))]
+    pub registration_question: String,
+    /// Minimum number of edits before users can create invite codes. If not set, only admins can
+    /// create invite codes.
+    #[default(None)]
+    #[cfg_attr(feature = "ssr", doku(example = "50"))]
+    pub invite_code_min_edits: Option<u32>,
     /// Whether new users have to provide an email address to register
     #[default = false]
     #[cfg_attr(feature = "ssr", doku(example = "false"))]
     pub email_required: bool,
+    /// Whether admins have to enable two-factor authentication before they can perform admin
+    /// actions
+    #[default = false]
+    #[cfg_attr(feature = "ssr", doku(example = "false"))]
+    pub admin_totp_required: bool,
+    /// Require a proof-of-work challenge to register, which is solved by the browser. This is the
+    /// number of leading zero bits in the hash, and each additional bit doubles the work. 0
+    /// disables the challenge.
+    #[default = 0]
+    #[cfg_attr(feature = "ssr", doku(example = "20"))]
+    pub proof_of_work_difficulty: u32,
+    /// Also require the proof-of-work challenge for the first comment of each user
+    #[default = false]
+    #[cfg_attr(feature = "ssr", doku(example = "false"))]
+    pub proof_of_work_first_comment: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "ssr", derive(Document))]
+pub enum RegistrationMode {
+    #[default]
+    Open,
+    Invite,
+    Application,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -93,16 +137,3 @@ pub struct OAuthProviderPublic {
     pub client_id: String,
     pub scopes: String,
 }
-
-#[cfg(feature = "ssr")]
-impl From<OAuthProvider> for OAuthProviderPublic {
-    fn from(provider: OAuthProvider) -> Self {
-        Self {
-            issuer: provider.issuer,
-            display_name: provider.display_name,
-            authorization_endpoint: provider.authorization_endpoint,
-            client_id: provider.client_id,
-            scopes: provider.scopes,
-        }
-    }
-}
@@ -1,10 +1,67 @@
+/// Implements `ALL`, `as_str()` and `FromStr` for a fieldless enum, and stores it as text in the
+/// database.
+macro_rules! text_enum {
+    ($name:ident, $description:literal, { $($variant:ident => $value:literal),* $(,)? }) => {
+        impl $name {
+            pub const ALL: [$name; [$($value),*].len()] = [$($name::$variant),*];
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $value),*
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = anyhow::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::ALL
+                    .into_iter()
+                    .find(|v| v.as_str() == s)
+                    .ok_or_else(|| anyhow::anyhow!(concat!("Invalid ", $description, " {}"), s))
+            }
+        }
+
+        #[cfg(feature = "ssr")]
+        impl diesel::serialize::ToSql<diesel::sql_types::Text, diesel::pg::Pg> for $name {
+            fn to_sql(
+                &self,
+                out: &mut diesel::serialize::Output<diesel::pg::Pg>,
+            ) -> diesel::serialize::Result {
+                <str as diesel::serialize::ToSql<diesel::sql_types::Text, diesel::pg::Pg>>::to_sql(
+                    self.as_str(),
+                    &mut out.reborrow(),
+                )
+            }
+        }
+
+        #[cfg(feature = "ssr")]
+        impl<DB: diesel::backend::Backend> diesel::deserialize::FromSql<diesel::sql_types::Text, DB>
+            for $name
+        where
+            String: diesel::deserialize::FromSql<diesel::sql_types::Text, DB>,
+        {
+            fn from_sql(value: DB::RawValue<'_>) -> diesel::deserialize::Result<Self> {
+                Ok(String::from_sql(value)?.parse()?)
+            }
+        }
+    };
+}
+
 pub mod article;
+pub mod block;
 pub mod comment;
+pub mod contributions;
+pub mod email;
+pub mod events;
 pub mod instance;
 pub mod newtypes;
 pub mod notifications;
+pub mod registration;
 pub mod user;
 pub mod utils;
+pub mod webhook;
 
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -25,4 +25,15 @@ pub enum ApiNotificationData {
     },
     Comment(Comment),
     Edit(Edit),
+    /// User was mentioned in a comment
+    CommentMention(Comment),
+    /// User was mentioned in an edit summary
+    EditMention(Edit),
+}
+
+/// Public VAPID key of the instance, which browsers need to subscribe to push messages.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebPushKey {
+    /// Base64url encoded
+    pub public_key: String,
 }
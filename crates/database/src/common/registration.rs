@@ -0,0 +1,68 @@
+use super::{
+    newtypes::{InviteCodeId, LocalUserId, RegistrationApplicationId},
+    user::Person,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ssr")]
+use {
+    diesel::{Queryable, Selectable},
+    ibis_database_schema::{invite_code, registration_application},
+};
+
+/// Code which allows registering an account if registration is invite-only.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = invite_code, check_for_backend(diesel::pg::Pg)))]
+pub struct InviteCode {
+    pub id: InviteCodeId,
+    pub code: String,
+    pub creator_id: LocalUserId,
+    pub max_uses: i32,
+    pub uses: i32,
+    pub expires: Option<DateTime<Utc>>,
+    pub published: DateTime<Utc>,
+}
+
+/// Answer to the registration question, which an admin needs to approve before the user can login.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = registration_application, check_for_backend(diesel::pg::Pg)))]
+pub struct RegistrationApplication {
+    pub id: RegistrationApplicationId,
+    pub local_user_id: LocalUserId,
+    pub answer: String,
+    pub status: RegistrationApplicationStatus,
+    /// Admin who approved or denied the application
+    pub admin_id: Option<LocalUserId>,
+    pub deny_reason: Option<String>,
+    pub published: DateTime<Utc>,
+    pub updated: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(Queryable))]
+#[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::pg::Pg)))]
+pub struct RegistrationApplicationView {
+    pub application: RegistrationApplication,
+    pub person: Person,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "ssr",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow)
+)]
+#[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::Text))]
+pub enum RegistrationApplicationStatus {
+    #[default]
+    Pending,
+    Approved,
+    Denied,
+}
+
+text_enum!(RegistrationApplicationStatus, "registration application status", {
+    Pending => "pending",
+    Approved => "approved",
+    Denied => "denied",
+});
@@ -1,4 +1,4 @@
-use super::newtypes::{LocalUserId, PersonId};
+use super::newtypes::{ApiTokenId, LocalUserId, PersonId, SessionId};
 use crate::{DbUrl, common::utils::extract_domain};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -6,7 +6,7 @@ use url::Url;
 #[cfg(feature = "ssr")]
 use {
     diesel::{Identifiable, Queryable, Selectable},
-    ibis_database_schema::{local_user, person},
+    ibis_database_schema::{api_token, local_user, oauth_account, person},
 };
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -30,6 +30,98 @@ pub struct LocalUser {
     pub email: Option<String>,
     pub email_verified: bool,
     pub email_notifications: bool,
+    pub email_digest: EmailDigest,
+    /// Language for emails, eg `de` or `pt-BR`
+    pub locale: Option<String>,
+    /// Replies to own comments
+    pub notify_comment_reply: NotificationPreference,
+    /// Comments on followed articles
+    pub notify_article_comment: NotificationPreference,
+    /// Edits of followed articles
+    pub notify_article_edit: NotificationPreference,
+    /// New articles on followed instances
+    pub notify_new_article: NotificationPreference,
+    /// Mentions in comments and articles
+    pub notify_mention: NotificationPreference,
+    /// Minor edits of followed articles
+    pub notify_minor_edit: NotificationPreference,
+    /// Base32 encoded secret for two-factor authentication, which is only active if
+    /// [LocalUser::totp_enabled] is set
+    #[serde(skip)]
+    pub totp_secret: Option<String>,
+    pub totp_enabled: bool,
+    /// Hashes of unused recovery codes
+    #[serde(skip)]
+    pub totp_recovery_codes: Vec<String>,
+    /// Edits of followed articles by bot accounts
+    pub notify_bot_edit: NotificationPreference,
+    /// Time step of the last accepted two-factor code, older codes are rejected
+    #[serde(skip)]
+    pub totp_last_step: Option<i64>,
+    /// Number of invalid two-factor codes since [LocalUser::totp_failed_at] was reset
+    #[serde(skip)]
+    pub totp_failed_attempts: i32,
+    #[serde(skip)]
+    pub totp_failed_at: Option<DateTime<Utc>>,
+}
+
+/// How a user wants to be informed about a type of event.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "ssr",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow)
+)]
+#[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::Text))]
+pub enum NotificationPreference {
+    /// No notification
+    Off,
+    /// Only show notification on the website
+    InApp,
+    /// Show notification on the website and send email, depending on
+    /// [LocalUser::email_notifications]
+    #[default]
+    Email,
+}
+
+text_enum!(NotificationPreference, "notification preference", {
+    Off => "off",
+    InApp => "in_app",
+    Email => "email",
+});
+
+/// How often notification emails are sent to a user.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "ssr",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow)
+)]
+#[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::Text))]
+pub enum EmailDigest {
+    /// One email for each notification
+    #[default]
+    Immediate,
+    Hourly,
+    Daily,
+    Weekly,
+}
+
+text_enum!(EmailDigest, "email digest", {
+    Immediate => "immediate",
+    Hourly => "hourly",
+    Daily => "daily",
+    Weekly => "weekly",
+});
+
+impl EmailDigest {
+    /// Minimum time between two digest emails, or `None` if notifications are sent immediately.
+    pub fn interval(&self) -> Option<chrono::Duration> {
+        match self {
+            EmailDigest::Immediate => None,
+            EmailDigest::Hourly => Some(chrono::Duration::hours(1)),
+            EmailDigest::Daily => Some(chrono::Duration::days(1)),
+            EmailDigest::Weekly => Some(chrono::Duration::weeks(1)),
+        }
+    }
 }
 
 /// Federation related data from a local or remote user.
@@ -50,6 +142,21 @@ pub struct Person {
     pub local: bool,
     pub display_name: Option<String>,
     pub bio: Option<String>,
+    /// Public key which was in use before the last key rotation
+    #[serde(skip)]
+    pub previous_public_key: Option<String>,
+    #[serde(skip)]
+    pub key_rotated_at: Option<DateTime<Utc>>,
+    /// Other accounts of the same user, which are allowed to move to this account
+    pub also_known_as: Vec<DbUrl>,
+    /// Set if the user moved to another account
+    pub moved_to: Option<DbUrl>,
+    /// Automated account, whose edits are hidden by default
+    pub bot: bool,
+    /// Edits of this bot are marked as patrolled automatically, set by admins
+    pub bot_trusted: bool,
+    /// Account was deleted. The row is kept so that the username can't be registered again.
+    pub deleted: bool,
 }
 
 impl Person {
@@ -66,3 +173,71 @@ impl Person {
         }
     }
 }
+
+/// Device where the user is logged in.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ApiSession {
+    pub id: SessionId,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub published: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// Session which was used for this request
+    pub current: bool,
+}
+
+/// An auth account method.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = oauth_account, check_for_backend(diesel::pg::Pg)))]
+pub struct OAuthAccount {
+    pub local_user_id: LocalUserId,
+    pub oauth_issuer_url: DbUrl,
+    pub oauth_user_id: String,
+    pub published: DateTime<Utc>,
+    pub updated: Option<DateTime<Utc>>,
+}
+
+/// Long-lived credential for scripts and bots, which is sent in the `Authorization` header.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = api_token, check_for_backend(diesel::pg::Pg)))]
+pub struct ApiToken {
+    pub id: ApiTokenId,
+    pub local_user_id: LocalUserId,
+    pub name: String,
+    #[serde(skip)]
+    pub token_hash: String,
+    pub scope: ApiTokenScope,
+    pub published: DateTime<Utc>,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// Actions which are allowed with an api token. Each scope includes the ones before it. Account
+/// settings can't be changed with any token.
+#[derive(
+    Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+#[cfg_attr(
+    feature = "ssr",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow)
+)]
+#[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::Text))]
+pub enum ApiTokenScope {
+    /// Only read data
+    #[default]
+    Read,
+    /// Write comments
+    Comment,
+    /// Create and edit articles, and all other actions except for admin actions
+    Edit,
+    /// Perform admin actions, if the user is an admin
+    Admin,
+}
+
+text_enum!(ApiTokenScope, "api token scope", {
+    Read => "read",
+    Comment => "comment",
+    Edit => "edit",
+    Admin => "admin",
+});
@@ -1,3 +1,4 @@
+use sha2::{Digest, Sha256};
 use url::Url;
 
 pub fn extract_domain(url: &Url) -> String {
@@ -15,3 +16,23 @@ pub fn http_protocol_str() -> &'static str {
         "https"
     }
 }
+
+/// Check if the hash of challenge and nonce starts with the required number of zero bits.
+pub fn proof_of_work_valid(challenge: &str, nonce: u64, difficulty: u32) -> bool {
+    let hash = Sha256::digest(format!("{challenge}:{nonce}").as_bytes());
+    let mut zeros = 0;
+    for byte in hash {
+        zeros += byte.leading_zeros();
+        if byte != 0 {
+            break;
+        }
+    }
+    zeros >= difficulty
+}
+
+/// Find a nonce for the challenge. This needs about `2^difficulty` hashes on average.
+pub fn solve_proof_of_work(challenge: &str, difficulty: u32) -> u64 {
+    (0..)
+        .find(|nonce| proof_of_work_valid(challenge, *nonce, difficulty))
+        .unwrap_or_default()
+}
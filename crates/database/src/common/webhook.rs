@@ -0,0 +1,78 @@
+use super::newtypes::WebhookId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ssr")]
+use {
+    diesel::{Queryable, Selectable},
+    ibis_database_schema::{webhook, webhook_delivery},
+};
+
+/// Url which is notified about changes on the local instance, configured by the admin.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = webhook, check_for_backend(diesel::pg::Pg)))]
+pub struct Webhook {
+    pub id: WebhookId,
+    pub url: String,
+    /// Key for the HMAC signature in the `X-Ibis-Signature` header. Only returned once when the
+    /// webhook is created.
+    #[serde(skip_serializing, default)]
+    pub secret: String,
+    /// Events which are sent to the webhook, empty for all events
+    pub events: Vec<WebhookEvent>,
+    pub published: DateTime<Utc>,
+}
+
+/// Response when creating a webhook, which includes the secret.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CreatedWebhook {
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+impl Webhook {
+    pub fn accepts(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "ssr",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow)
+)]
+#[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::Text))]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    ArticleCreated,
+    ArticleEdited,
+    ArticleRemoved,
+    CommentCreated,
+}
+
+text_enum!(WebhookEvent, "webhook event", {
+    ArticleCreated => "article_created",
+    ArticleEdited => "article_edited",
+    ArticleRemoved => "article_removed",
+    CommentCreated => "comment_created",
+});
+
+/// Log entry for an event which was sent to a webhook.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = webhook_delivery, check_for_backend(diesel::pg::Pg)))]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub webhook_id: WebhookId,
+    pub event: WebhookEvent,
+    /// JSON body of the request
+    pub payload: String,
+    pub attempts: i32,
+    pub success: bool,
+    /// Http status code of the last attempt
+    pub status: Option<i32>,
+    /// Error of the last attempt, if the request failed without response
+    pub error: Option<String>,
+    pub published: DateTime<Utc>,
+    pub updated: Option<DateTime<Utc>>,
+}
@@ -67,6 +67,11 @@ pub struct IbisConfigEmail {
     /// Sender address for email sent by ibis
     #[doku(example = "ibis@example.com")]
     pub from_address: String,
+    /// Directory with custom email templates, which take precedence over the default templates.
+    /// Use the same filenames as in crates/database/templates/email, eg `verification.de.html`.
+    #[serde(default)]
+    #[doku(example = "templates/email")]
+    pub template_dir: Option<String>,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq, Clone, Document, SmartDefault)]
@@ -108,6 +113,11 @@ pub struct IbisConfigFederation {
     #[default(None)]
     #[doku(example = "evil.com,bad.org")]
     pub blocklist: Option<String>,
+    /// Number of days for which the previous key of an actor is still accepted for signatures
+    /// after the key was rotated
+    #[default(7)]
+    #[doku(example = "7")]
+    pub key_rotation_grace_period_days: u32,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq, Clone, Document)]
@@ -116,17 +126,25 @@ pub struct IbisConfigFederation {
 pub struct OAuthProvider {
     /// The OAuth 2.0 provider name displayed to the user on the Login page
     pub display_name: String,
-    /// The issuer url of the OAUTH provider.
+    /// The issuer url of the OAUTH provider. For OpenID Connect providers the endpoints below
+    /// are discovered automatically from `/.well-known/openid-configuration`.
     pub issuer: Url,
     /// The authorization endpoint is used to interact with the resource owner and obtain an
-    /// authorization grant. This is usually provided by the OAUTH provider.
-    pub authorization_endpoint: Url,
+    /// authorization grant. Only needed if the provider doesn't support discovery.
+    #[serde(default)]
+    pub authorization_endpoint: Option<Url>,
     /// The token endpoint is used by the client to obtain an access token by presenting its
-    /// authorization grant or refresh token. This is usually provided by the OAUTH provider.
-    pub token_endpoint: Url,
+    /// authorization grant or refresh token. Only needed if the provider doesn't support discovery.
+    #[serde(default)]
+    pub token_endpoint: Option<Url>,
     /// The UserInfo Endpoint is an OAuth 2.0 Protected Resource that returns Claims about the
     /// authenticated End-User. This is defined in the OIDC specification.
-    pub userinfo_endpoint: Url,
+    #[serde(default)]
+    pub userinfo_endpoint: Option<Url>,
+    /// Keys of the provider which are used to validate the signature of ID tokens. This is
+    /// defined in the OIDC specification.
+    #[serde(default)]
+    pub jwks_uri: Option<Url>,
     /// The client_id is provided by the OAuth 2.0 provider and is a unique identifier to this
     /// service
     pub client_id: String,
@@ -12,7 +12,9 @@ use std::{str::FromStr, sync::OnceLock};
 use uuid::Uuid;
 
 pub mod notification;
+pub mod registration_application;
 pub mod reset_password;
+pub mod template;
 pub mod verification;
 
 async fn send_email(
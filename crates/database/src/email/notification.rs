@@ -1,71 +1,138 @@
-use super::send_email;
+use super::{
+    send_email,
+    template::{NotificationItem, render_email},
+};
 use crate::{
-    common::utils::http_protocol_str,
+    common::{email::EmailTemplate, user::EmailDigest, utils::http_protocol_str},
     error::BackendResult,
-    impls::{IbisContext, notifications::Notification},
+    impls::{
+        IbisContext,
+        notifications::{Notification, NotificationData},
+    },
 };
+use chrono::{Duration, Utc};
 use ibis_markdown::{render_article_markdown, render_comment_markdown};
+use log::warn;
+use minijinja::{Value, context};
 
+/// Send notifications to users who want an email for each of them. Notifications for other users
+/// are collected by [send_email_digests].
 pub(crate) async fn send_notification_email(
     notifs: Vec<Notification>,
     context: &IbisContext,
 ) -> BackendResult<()> {
-    // TODO: reduce number of db reads
-    for n in notifs {
-        let data = Notification::read_data(n.id, context)?;
-        if let (Some(email), true) = (data.local_user.email, data.local_user.email_notifications) {
-            let article_title = data.article.title();
-            let creator_title = data.creator.title();
-            let notifications_link = format!(
-                "{}://{}/notifications",
-                http_protocol_str(),
-                &context.conf.federation.domain
-            );
+    if notifs.is_empty() {
+        return Ok(());
+    }
+    let ids = notifs.into_iter().map(|n| n.id).collect();
+    for data in Notification::read_data(ids, context)? {
+        let local_user = &data.local_user;
+        let (Some(email), true, true, EmailDigest::Immediate) = (
+            &local_user.email,
+            local_user.email_notifications,
+            data.notification.send_email,
+            local_user.email_digest,
+        ) else {
+            continue;
+        };
+        let Some(item) = render_notification(&data) else {
+            continue;
+        };
+        let content = render_email(
+            EmailTemplate::Notification,
+            local_user.locale.as_deref(),
+            context! {
+                items => vec![item],
+                notifications_link => notifications_link(context),
+            },
+            context,
+        )?;
+        // failed emails are retried with the next digest
+        if let Err(e) = send_email(&content.subject, email, content.html, context).await {
+            warn!("Failed to send notification email to {email}: {e}");
+            continue;
+        }
+        Notification::mark_emailed(vec![data.notification.id], context)?;
+    }
+    Ok(())
+}
 
-            let (subject, html) = if let Some(comment) = data.comment {
-                let comment_text = render_comment_markdown(&comment.content);
-                (
-                    format!("New comment on article {article_title}"),
-                    format!(
-                        r#"<h1>Comment</h1>
-                    <br>
-                    <div>{creator_title} commented on "{article_title}": {comment_text}</div>
-                    <br> 
-                    <a href="{notifications_link}">inbox</a>"#,
-                    ),
-                )
-            } else if let Some(edit) = data.edit {
-                let edit_diff = edit.diff;
-                (
-                    format!("New edit on article {article_title}"),
-                    format!(
-                        r#"<h1>Edit</h1><br>
-                    <div>{creator_title} edited "{article_title}": 
-                    <pre><code>{edit_diff}</code></pre>
-                    </div>
-                    <br> 
-                    <a href="{notifications_link}">inbox</a>"#,
-                    ),
-                )
-            } else if data.conflict.is_some() {
-                // Edit conflict, dont send notification as it should be shown on
-                // website directly after user action
-                continue;
-            } else {
-                let article_text = render_article_markdown(&data.article.text);
-                (
-                    format!("New article {article_title}"),
-                    format!(
-                        r#"<h1>New article</h1>
-                    <br>
-                    <div>{creator_title} created "{article_title}": {article_text}</div>
-                    <br> 
-                    <a href="{notifications_link}">inbox</a>"#,
-                    ),
-                )
-            };
-            send_email(&subject, &email, html, context).await?;
+/// Combine all unsent notifications of a user into a single email. This is only done once the
+/// oldest of them is older than the digest interval selected by the user, so that there is at
+/// most one email per interval.
+pub async fn send_email_digests(context: &IbisContext) -> BackendResult<()> {
+    Notification::skip_disabled_emails(context)?;
+    let notifs = Notification::read_unsent_emails(context)?;
+    for user_notifs in notifs.chunk_by(|a, b| a.local_user.id == b.local_user.id) {
+        let local_user = &user_notifs[0].local_user;
+        // For users with immediate emails this only contains notifications where sending failed,
+        // or which were created before switching from digest emails.
+        let interval = local_user
+            .email_digest
+            .interval()
+            .unwrap_or(Duration::hours(1));
+        let Some(email) = &local_user.email else {
+            continue;
+        };
+        if user_notifs[0].notification.published > Utc::now() - interval {
+            continue;
         }
+
+        let items: Vec<_> = user_notifs.iter().filter_map(render_notification).collect();
+        let content = render_email(
+            EmailTemplate::Notification,
+            local_user.locale.as_deref(),
+            context! {
+                items,
+                notifications_link => notifications_link(context),
+            },
+            context,
+        )?;
+        if let Err(e) = send_email(&content.subject, email, content.html, context).await {
+            warn!("Failed to send email digest to {email}: {e}");
+            continue;
+        }
+        let ids = user_notifs.iter().map(|n| n.notification.id).collect();
+        Notification::mark_emailed(ids, context)?;
     }
     Ok(())
 }
+
+fn render_notification(data: &NotificationData) -> Option<NotificationItem> {
+    let mention = data.notification.mention;
+    let (kind, content) = if let Some(comment) = &data.comment {
+        (
+            if mention { "mention" } else { "comment" },
+            Value::from_safe_string(render_comment_markdown(&comment.content)),
+        )
+    } else if let Some(edit) = &data.edit {
+        if mention {
+            ("mention", Value::from(edit.summary.clone()))
+        } else {
+            ("edit", Value::from(edit.diff.clone()))
+        }
+    } else if data.conflict.is_some() {
+        // Edit conflict, dont send notification as it should be shown on
+        // website directly after user action
+        return None;
+    } else {
+        (
+            "article",
+            Value::from_safe_string(render_article_markdown(&data.article.text)),
+        )
+    };
+    Some(NotificationItem {
+        kind,
+        creator: data.creator.title(),
+        article: data.article.title(),
+        content,
+    })
+}
+
+fn notifications_link(context: &IbisContext) -> String {
+    format!(
+        "{}://{}/notifications",
+        http_protocol_str(),
+        &context.conf.federation.domain
+    )
+}
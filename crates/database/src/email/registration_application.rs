@@ -0,0 +1,37 @@
+use super::{send_email, template::render_email};
+use crate::{
+    common::{email::EmailTemplate, user::LocalUserView, utils::http_protocol_str},
+    error::BackendResult,
+    impls::IbisContext,
+};
+use minijinja::context;
+
+/// Inform the user that an admin approved or denied their registration application.
+pub async fn send_application_result_email(
+    user: &LocalUserView,
+    approved: bool,
+    deny_reason: Option<&str>,
+    context: &IbisContext,
+) -> BackendResult<()> {
+    let Some(email) = &user.local_user.email else {
+        return Ok(());
+    };
+    let login_link = format!(
+        "{}://{}/login",
+        http_protocol_str(),
+        &context.conf.federation.domain
+    );
+    let content = render_email(
+        EmailTemplate::RegistrationApplication,
+        user.local_user.locale.as_deref(),
+        context! {
+            username => user.person.username,
+            approved,
+            deny_reason,
+            login_link,
+        },
+        context,
+    )?;
+    send_email(&content.subject, email, content.html, context).await?;
+    Ok(())
+}
@@ -1,6 +1,11 @@
-use super::send_email;
+use super::{send_email, template::render_email};
 use crate::{
-    common::{newtypes::LocalUserId, user::LocalUserView, utils::http_protocol_str},
+    common::{
+        email::EmailTemplate,
+        newtypes::LocalUserId,
+        user::LocalUserView,
+        utils::http_protocol_str,
+    },
     error::BackendResult,
     impls::{IbisContext, user::LocalUserViewQuery},
 };
@@ -12,6 +17,7 @@ use diesel::{
     *,
 };
 use ibis_database_schema::password_reset_request;
+use minijinja::context;
 use uuid::Uuid;
 
 #[derive(PartialEq, Eq, Debug, Queryable, Selectable, Identifiable)]
@@ -54,11 +60,16 @@ impl PasswordResetRequest {
             domain,
             &token.token
         );
-        let body = format!(
-            r#"<h1>Password Reset Request for {}</h1><br><a href=\"{reset_link}\">Click here to reset your password</a>"#,
-            local_user_view.person.username
-        );
-        send_email("Password reset", &email, body, context).await?;
+        let content = render_email(
+            EmailTemplate::ResetPassword,
+            local_user_view.local_user.locale.as_deref(),
+            context! {
+                username => local_user_view.person.username,
+                reset_link,
+            },
+            context,
+        )?;
+        send_email(&content.subject, &email, content.html, context).await?;
 
         Ok(())
     }
@@ -0,0 +1,258 @@
+//! Email content is rendered from Jinja templates. The default templates are embedded in the
+//! binary, and can be overridden by placing files with the same name in `email.template_dir`.
+//! Translations use the locale as part of the filename, eg `verification.de.html`.
+
+use crate::{
+    common::{
+        email::{EmailPreview, EmailTemplate},
+        utils::http_protocol_str,
+    },
+    error::BackendResult,
+    impls::IbisContext,
+};
+use minijinja::{Environment, Error, ErrorKind, Value, context};
+use serde::Serialize;
+use std::{fs::read_to_string, path::PathBuf};
+
+const DEFAULT_LOCALE: &str = "en";
+
+const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
+    ("base.html", include_str!("../../templates/email/base.html")),
+    (
+        "verification.html",
+        include_str!("../../templates/email/verification.html"),
+    ),
+    (
+        "verification.de.html",
+        include_str!("../../templates/email/verification.de.html"),
+    ),
+    (
+        "verification.fr.html",
+        include_str!("../../templates/email/verification.fr.html"),
+    ),
+    (
+        "reset_password.html",
+        include_str!("../../templates/email/reset_password.html"),
+    ),
+    (
+        "reset_password.de.html",
+        include_str!("../../templates/email/reset_password.de.html"),
+    ),
+    (
+        "reset_password.fr.html",
+        include_str!("../../templates/email/reset_password.fr.html"),
+    ),
+    (
+        "notification.html",
+        include_str!("../../templates/email/notification.html"),
+    ),
+    (
+        "notification.de.html",
+        include_str!("../../templates/email/notification.de.html"),
+    ),
+    (
+        "notification.fr.html",
+        include_str!("../../templates/email/notification.fr.html"),
+    ),
+    (
+        "registration_application.html",
+        include_str!("../../templates/email/registration_application.html"),
+    ),
+    (
+        "registration_application.de.html",
+        include_str!("../../templates/email/registration_application.de.html"),
+    ),
+    (
+        "registration_application.fr.html",
+        include_str!("../../templates/email/registration_application.fr.html"),
+    ),
+];
+
+/// Single entry in a notification email.
+#[derive(Serialize)]
+pub(crate) struct NotificationItem {
+    /// One of `comment`, `edit`, `mention` or `article`
+    pub kind: &'static str,
+    pub creator: String,
+    pub article: String,
+    /// Rendered comment or article, the diff of an edit, or the edit summary for mentions
+    pub content: Value,
+}
+
+/// Render the template in the given locale, falling back to the default language if there is
+/// no translation.
+pub(crate) fn render_email(
+    template: EmailTemplate,
+    locale: Option<&str>,
+    ctx: Value,
+    context: &IbisContext,
+) -> BackendResult<EmailPreview> {
+    let env = environment(context);
+    let (template, lang) = select_template(&env, template, locale)?;
+    let domain = &context.conf.federation.domain;
+    let ctx = context! {
+        domain,
+        site_url => format!("{}://{domain}/", http_protocol_str()),
+        lang,
+        ..ctx
+    };
+    let mut captured = template.render_captured(&ctx)?;
+    let subject = captured.with_state_mut(|state| state.render_block("subject"))?;
+    let html = captured.into_output();
+    // subject is plain text, so undo html escaping
+    let subject = html2text::from_read(subject.as_bytes(), usize::MAX)?
+        .trim()
+        .to_string();
+    Ok(EmailPreview { subject, html })
+}
+
+/// Render a template with example data, so that admins can check their customizations.
+pub fn preview_email(
+    template: EmailTemplate,
+    locale: Option<&str>,
+    context: &IbisContext,
+) -> BackendResult<EmailPreview> {
+    let link = format!(
+        "{}://{}",
+        http_protocol_str(),
+        &context.conf.federation.domain
+    );
+    let ctx = match template {
+        EmailTemplate::Verification => context! {
+            verify_link => format!("{link}/account/verify_email?token=example"),
+        },
+        EmailTemplate::ResetPassword => context! {
+            username => "example",
+            reset_link => format!("{link}/account/reset_password?token=example"),
+        },
+        EmailTemplate::Notification => context! {
+            items => vec![
+                NotificationItem {
+                    kind: "comment",
+                    creator: "@example".to_string(),
+                    article: "Example Article".to_string(),
+                    content: Value::from_safe_string("<p>Example comment</p>".to_string()),
+                },
+                NotificationItem {
+                    kind: "edit",
+                    creator: "@example".to_string(),
+                    article: "Example Article".to_string(),
+                    content: Value::from("@@ -1 +1 @@\n-old text\n+new text\n"),
+                },
+            ],
+            notifications_link => format!("{link}/notifications"),
+        },
+        EmailTemplate::RegistrationApplication => context! {
+            username => "example",
+            approved => false,
+            deny_reason => "Example reason",
+            login_link => format!("{link}/login"),
+        },
+    };
+    render_email(template, locale, ctx, context)
+}
+
+fn environment(context: &IbisContext) -> Environment<'static> {
+    let template_dir = context
+        .conf
+        .email
+        .as_ref()
+        .and_then(|e| e.template_dir.clone())
+        .map(PathBuf::from);
+    let mut env = Environment::new();
+    env.set_loader(move |name| {
+        if let Some(path) = template_dir.as_ref().map(|dir| dir.join(name)) {
+            if path.is_file() {
+                return read_to_string(&path).map(Some).map_err(|e| {
+                    Error::new(ErrorKind::InvalidOperation, "failed to read template")
+                        .with_source(e)
+                });
+            }
+        }
+        Ok(DEFAULT_TEMPLATES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, t)| t.to_string()))
+    });
+    env
+}
+
+/// Find the most specific translation, eg for `pt-BR` try `pt-BR`, then `pt`, then the default.
+fn select_template<'env>(
+    env: &'env Environment<'static>,
+    template: EmailTemplate,
+    locale: Option<&str>,
+) -> BackendResult<(minijinja::Template<'env, 'env>, String)> {
+    // the locale becomes part of the filename, so make sure it cant escape from the directory
+    let locale = locale.filter(|l| l.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+    let mut locales = vec![];
+    if let Some(locale) = locale {
+        locales.push(locale);
+        if let Some((lang, _)) = locale.split_once('-') {
+            locales.push(lang);
+        }
+    }
+    for locale in locales {
+        let name = format!("{}.{locale}.html", template.name());
+        match env.get_template(&name) {
+            Ok(t) => return Ok((t, locale.to_string())),
+            Err(e) if e.kind() == ErrorKind::TemplateNotFound => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let name = format!("{}.html", template.name());
+    Ok((env.get_template(&name)?, DEFAULT_LOCALE.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::IbisConfig;
+
+    #[test]
+    fn test_render_email_templates() -> BackendResult<()> {
+        let context = IbisContext::init(IbisConfig::read()?, false)?;
+
+        let preview = preview_email(EmailTemplate::Notification, None, &context)?;
+        assert_eq!("2 new notifications", preview.subject);
+        assert!(preview.html.contains(r#"<html lang="en">"#));
+        assert!(preview.html.contains("<p>Example comment</p>"));
+
+        let item = NotificationItem {
+            kind: "edit",
+            creator: "@example".to_string(),
+            article: "Rock'n'Roll".to_string(),
+            content: Value::from("<script>"),
+        };
+        let ctx = context! { items => vec![item] };
+        let email = render_email(EmailTemplate::Notification, None, ctx, &context)?;
+        assert_eq!("New edit on article Rock'n'Roll", email.subject);
+        assert!(email.html.contains("&lt;script&gt;"));
+
+        let item = NotificationItem {
+            kind: "mention",
+            creator: "@example".to_string(),
+            article: "Example Article".to_string(),
+            content: Value::from("thanks @alice"),
+        };
+        let ctx = context! { items => vec![item] };
+        let email = render_email(EmailTemplate::Notification, Some("de"), ctx, &context)?;
+        assert_eq!("Neue Erwähnung im Artikel Example Article", email.subject);
+
+        let preview = preview_email(EmailTemplate::Verification, Some("de-AT"), &context)?;
+        assert_eq!("Registrierung bei Ibis", preview.subject);
+        assert!(preview.html.contains(r#"<html lang="de">"#));
+
+        let preview = preview_email(EmailTemplate::ResetPassword, Some("fr"), &context)?;
+        assert_eq!("Réinitialisation du mot de passe", preview.subject);
+
+        let preview = preview_email(EmailTemplate::RegistrationApplication, None, &context)?;
+        assert_eq!("Registration denied", preview.subject);
+        assert!(preview.html.contains("Reason: Example reason"));
+
+        // unknown or invalid locales use the default template
+        let preview = preview_email(EmailTemplate::ResetPassword, Some("../x"), &context)?;
+        assert_eq!("Password reset", preview.subject);
+        Ok(())
+    }
+}
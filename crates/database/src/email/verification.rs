@@ -1,6 +1,11 @@
-use super::send_email;
+use super::{send_email, template::render_email};
 use crate::{
-    common::{newtypes::LocalUserId, user::LocalUser, utils::http_protocol_str},
+    common::{
+        email::EmailTemplate,
+        newtypes::LocalUserId,
+        user::LocalUser,
+        utils::http_protocol_str,
+    },
     error::BackendResult,
     impls::IbisContext,
 };
@@ -11,6 +16,7 @@ use diesel::{
     *,
 };
 use ibis_database_schema::{email_verification, local_user};
+use minijinja::context;
 use std::ops::DerefMut;
 
 #[derive(Clone, Queryable, Selectable, Identifiable, Debug)]
@@ -54,13 +60,13 @@ pub async fn send_verification_email(
         .values(form)
         .execute(conn.deref_mut())?;
 
-    let body = format!(
-        r#"Please click the link below to verify your email address for the account on {}. Ignore this email if the account isn't yours.<br><br>
-        <a href="{verify_link}">Verify your email</a>"#,
-        domain
-    );
-
-    send_email("Registration for Ibis", new_email, body, context).await?;
+    let email = render_email(
+        EmailTemplate::Verification,
+        to_user.locale.as_deref(),
+        context! { verify_link },
+        context,
+    )?;
+    send_email(&email.subject, new_email, email.html, context).await?;
     Ok(())
 }
 
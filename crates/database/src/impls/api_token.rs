@@ -0,0 +1,123 @@
+use super::IbisContext;
+use crate::{
+    common::{
+        newtypes::{ApiTokenId, LocalUserId},
+        user::{ApiToken, ApiTokenScope},
+    },
+    error::BackendResult,
+};
+use anyhow::anyhow;
+use chrono::{TimeDelta, Utc};
+use diesel::{
+    ExpressionMethods,
+    Insertable,
+    QueryDsl,
+    RunQueryDsl,
+    delete,
+    dsl::{insert_into, now},
+    update,
+};
+use ibis_database_schema::api_token;
+use ring::rand::{SecureRandom, SystemRandom};
+use sha2::{Digest, Sha256};
+use std::ops::DerefMut;
+
+/// All api tokens start with this, so that they can be distinguished from login tokens.
+pub const API_TOKEN_PREFIX: &str = "ibis_";
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = api_token, check_for_backend(diesel::pg::Pg))]
+struct ApiTokenInsertForm {
+    local_user_id: LocalUserId,
+    name: String,
+    token_hash: String,
+    scope: ApiTokenScope,
+}
+
+impl ApiToken {
+    /// Create a new token for the user. Returns the token itself, which is only stored as hash.
+    pub fn create(
+        local_user_id: LocalUserId,
+        name: String,
+        scope: ApiTokenScope,
+        context: &IbisContext,
+    ) -> BackendResult<(Self, String)> {
+        let mut bytes = [0u8; 32];
+        SystemRandom::new()
+            .fill(&mut bytes)
+            .map_err(|_| anyhow!("Failed to generate api token"))?;
+        let token = format!("{API_TOKEN_PREFIX}{}", hex::encode(bytes));
+        let form = ApiTokenInsertForm {
+            local_user_id,
+            name,
+            token_hash: hash_token(&token),
+            scope,
+        };
+        let mut conn = context.db_pool.get()?;
+        let api_token = insert_into(api_token::table)
+            .values(form)
+            .get_result(conn.deref_mut())?;
+        Ok((api_token, token))
+    }
+
+    pub fn read_from_token(token: &str, context: &IbisContext) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        Ok(api_token::table
+            .filter(api_token::token_hash.eq(hash_token(token)))
+            .get_result(conn.deref_mut())?)
+    }
+
+    /// Store the time when the token was last used, at most every few minutes.
+    pub fn update_last_used(&self, context: &IbisContext) -> BackendResult<()> {
+        if self
+            .last_used
+            .is_some_and(|l| Utc::now() - l < TimeDelta::minutes(5))
+        {
+            return Ok(());
+        }
+        let mut conn = context.db_pool.get()?;
+        update(api_token::table.find(self.id))
+            .set(api_token::last_used.eq(now))
+            .execute(conn.deref_mut())?;
+        Ok(())
+    }
+
+    pub fn list(local_user_id: LocalUserId, context: &IbisContext) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(api_token::table
+            .filter(api_token::local_user_id.eq(local_user_id))
+            .order(api_token::published.desc())
+            .get_results(conn.deref_mut())?)
+    }
+
+    pub fn delete(
+        id: ApiTokenId,
+        local_user_id: LocalUserId,
+        context: &IbisContext,
+    ) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        let rows = delete(
+            api_token::table
+                .find(id)
+                .filter(api_token::local_user_id.eq(local_user_id)),
+        )
+        .execute(conn.deref_mut())?;
+        if rows == 0 {
+            return Err(anyhow!("Api token not found").into());
+        }
+        Ok(())
+    }
+
+    /// Revoke all tokens of the user.
+    pub fn delete_all(local_user_id: LocalUserId, context: &IbisContext) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        delete(api_token::table.filter(api_token::local_user_id.eq(local_user_id)))
+            .execute(conn.deref_mut())?;
+        Ok(())
+    }
+}
+
+/// Tokens are random so a plain hash is sufficient, and allows looking them up directly.
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
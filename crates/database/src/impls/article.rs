@@ -4,11 +4,14 @@ use crate::{
     common::{
         article::{Article, ArticleView, EditVersion},
         comment::Comment,
-        newtypes::{ArticleId, InstanceId, PersonId},
+        events::LiveEvent,
+        newtypes::{ArticleId, InstanceId, LocalUserId, PersonId},
         user::LocalUserView,
+        webhook::WebhookEvent,
     },
     error::BackendResult,
-    impls::IbisContext,
+    impls::{IbisContext, upsert_inserted},
+    webhook::send_webhooks,
 };
 use chrono::{DateTime, Utc};
 use diesel::{
@@ -74,24 +77,43 @@ impl Article {
             .get_result(conn.deref_mut())?;
 
         Notification::notify_article(&article, creator_id, context).await?;
+        send_webhooks(WebhookEvent::ArticleCreated, &article, context)?;
         Ok(article)
     }
 
+    /// Used for articles received from other instances.
     pub fn create_or_update(form: DbArticleForm, context: &IbisContext) -> BackendResult<Self> {
         let mut conn = context.db_pool.get()?;
-        Ok(insert_into(article::table)
+        let (article, inserted): (Article, bool) = insert_into(article::table)
             .values(&form)
             .on_conflict(article::dsl::ap_id)
             .do_update()
             .set(&form)
-            .get_result(conn.deref_mut())?)
+            .returning((article::all_columns, upsert_inserted()))
+            .get_result(conn.deref_mut())?;
+        if inserted {
+            send_webhooks(WebhookEvent::ArticleCreated, &article, context)?;
+        }
+        Ok(article)
     }
 
     pub fn update_text(id: ArticleId, text: &str, context: &IbisContext) -> BackendResult<Self> {
         let mut conn = context.db_pool.get()?;
-        Ok(diesel::update(article::dsl::article.find(id))
+        let article: Article = diesel::update(article::dsl::article.find(id))
             .set((article::dsl::text.eq(text), article::dsl::updated.eq(now)))
-            .get_result(conn.deref_mut())?)
+            .get_result(conn.deref_mut())?;
+        article.publish_update(context)?;
+        Ok(article)
+    }
+
+    /// Let users who are currently viewing the article know that it was changed.
+    pub fn publish_update(&self, context: &IbisContext) -> BackendResult<()> {
+        let event = LiveEvent::ArticleUpdated {
+            article_id: self.id,
+            latest_version: self.latest_edit_version(context)?,
+        };
+        context.publish(None, event);
+        Ok(())
     }
 
     pub fn update_protected(
@@ -111,9 +133,13 @@ impl Article {
         context: &IbisContext,
     ) -> BackendResult<Self> {
         let mut conn = context.db_pool.get()?;
-        Ok(diesel::update(article::dsl::article.find(id))
+        let article: Article = diesel::update(article::dsl::article.find(id))
             .set(article::dsl::removed.eq(removed))
-            .get_result(conn.deref_mut())?)
+            .get_result(conn.deref_mut())?;
+        if removed {
+            send_webhooks(WebhookEvent::ArticleRemoved, &article, context)?;
+        }
+        Ok(article)
     }
 
     pub fn read(id: ArticleId, context: &IbisContext) -> BackendResult<Self> {
@@ -161,7 +187,7 @@ impl Article {
                 article_follow::local_user_id.nullable().is_not_null(),
             ))
             .get_result(conn.deref_mut())?;
-        let comments = Comment::read_for_article(article.id, context)?;
+        let comments = Comment::read_for_article(article.id, user, context)?;
         let latest_version = article.latest_edit_version(context)?;
         Ok(ArticleView {
             article,
@@ -257,10 +283,25 @@ impl Article {
         );
         insert_into(article_follow::table)
             .values(form)
+            .on_conflict_do_nothing()
             .execute(conn.deref_mut())?;
         Ok(())
     }
 
+    /// Articles which are followed by the given user.
+    pub fn read_followed(
+        local_user_id_: LocalUserId,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<Self>> {
+        use article_follow::dsl::{article_id, local_user_id};
+        let mut conn = context.db_pool.get()?;
+        Ok(article_follow::table
+            .inner_join(article::table.on(article_id.eq(article::id)))
+            .filter(local_user_id.eq(local_user_id_))
+            .select(article::all_columns)
+            .get_results(conn.deref_mut())?)
+    }
+
     pub fn unfollow(
         article_id_: ArticleId,
         follower: &LocalUserView,
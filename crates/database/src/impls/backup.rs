@@ -0,0 +1,431 @@
+use crate::{
+    DbUrl,
+    common::{
+        article::{Article, Edit, EditTag, EditVersion},
+        comment::Comment,
+        instance::Instance,
+        newtypes::{CommentId, PersonId},
+        user::Person,
+        utils::http_protocol_str,
+    },
+    error::{BackendError, BackendResult},
+    impls::{
+        IbisContext,
+        article::DbArticleForm,
+        comment::DbCommentInsertForm,
+        edit::DbEditForm,
+        instance::DbInstanceUpdateForm,
+        user::{LocalUserInsertForm, PersonInsertForm, PersonUpdateForm},
+    },
+    utils::generate_keypair,
+};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use diesel::{
+    Connection,
+    ExpressionMethods,
+    OptionalExtension,
+    PgConnection,
+    QueryDsl,
+    RunQueryDsl,
+    delete,
+    dsl::not,
+    insert_into,
+    update,
+};
+use ibis_database_schema::{article, comment, edit, instance, local_user, person};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::DerefMut,
+};
+use url::Url;
+
+/// Format version of wiki backups. Needs to be incremented on incompatible changes.
+pub const WIKI_BACKUP_VERSION: u32 = 1;
+
+/// All local articles with their full history, and everything else which is needed to restore
+/// them on another instance.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WikiBackup {
+    pub instance: BackupInstance,
+    /// Authors of edits and comments
+    pub persons: Vec<BackupPerson>,
+    pub articles: Vec<BackupArticle>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackupInstance {
+    pub ap_id: DbUrl,
+    pub domain: String,
+    pub name: Option<String>,
+    pub topic: Option<String>,
+}
+
+/// Public profile of a user, without any credentials.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackupPerson {
+    pub username: String,
+    pub ap_id: DbUrl,
+    pub inbox_url: String,
+    pub public_key: String,
+    pub local: bool,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub last_refreshed_at: DateTime<Utc>,
+    #[serde(default)]
+    pub bot: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackupArticle {
+    pub title: String,
+    /// Stored as separate markdown file in the archive
+    #[serde(skip)]
+    pub text: String,
+    pub ap_id: DbUrl,
+    pub protected: bool,
+    pub removed: bool,
+    pub published: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+    pub edits: Vec<BackupEdit>,
+    pub comments: Vec<BackupComment>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackupEdit {
+    pub hash: EditVersion,
+    pub ap_id: DbUrl,
+    pub creator: DbUrl,
+    pub diff: String,
+    pub summary: String,
+    pub previous_version_id: EditVersion,
+    pub published: DateTime<Utc>,
+    #[serde(default)]
+    pub minor: bool,
+    #[serde(default)]
+    pub tags: Vec<EditTag>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackupComment {
+    pub ap_id: DbUrl,
+    pub creator: DbUrl,
+    pub parent: Option<DbUrl>,
+    pub content: String,
+    pub depth: i32,
+    pub local: bool,
+    pub deleted: bool,
+    pub published: DateTime<Utc>,
+    pub updated: Option<DateTime<Utc>>,
+}
+
+impl WikiBackup {
+    pub fn read(context: &IbisContext) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        let conn = conn.deref_mut();
+        let local_instance = Instance::read_local(context)?;
+        let articles: Vec<Article> = article::table
+            .filter(article::local)
+            .filter(not(article::pending))
+            .order(article::id)
+            .get_results(conn)?;
+
+        let mut person_ids = HashSet::new();
+        let mut article_data = vec![];
+        for article in articles {
+            let edits: Vec<Edit> = edit::table
+                .filter(edit::article_id.eq(article.id))
+                .filter(not(edit::pending))
+                .order(edit::id)
+                .get_results(conn)?;
+            // Parents are always created before replies, so this order allows restoring them
+            let comments: Vec<Comment> = comment::table
+                .filter(comment::article_id.eq(article.id))
+                .order(comment::id)
+                .get_results(conn)?;
+            person_ids.extend(edits.iter().map(|e| e.creator_id));
+            person_ids.extend(comments.iter().map(|c| c.creator_id));
+            article_data.push((article, edits, comments));
+        }
+
+        let persons: Vec<Person> = person::table
+            .filter(person::id.eq_any(person_ids))
+            .order(person::id)
+            .get_results(conn)?;
+        let person_ap_ids: HashMap<PersonId, DbUrl> =
+            persons.iter().map(|p| (p.id, p.ap_id.clone())).collect();
+        let comment_ap_ids: HashMap<CommentId, DbUrl> = article_data
+            .iter()
+            .flat_map(|(_, _, comments)| comments.iter().map(|c| (c.id, c.ap_id.clone())))
+            .collect();
+        let creator = |id: &PersonId| -> BackendResult<DbUrl> {
+            Ok(person_ap_ids
+                .get(id)
+                .ok_or(anyhow!("Missing creator {}", id.0))?
+                .clone())
+        };
+
+        let articles = article_data
+            .into_iter()
+            .map(|(article, edits, comments)| {
+                let edits = edits
+                    .into_iter()
+                    .map(|e| {
+                        Ok(BackupEdit {
+                            creator: creator(&e.creator_id)?,
+                            hash: e.hash,
+                            ap_id: e.ap_id,
+                            diff: e.diff,
+                            summary: e.summary,
+                            previous_version_id: e.previous_version_id,
+                            published: e.published,
+                            minor: e.minor,
+                            tags: e.tags,
+                        })
+                    })
+                    .collect::<BackendResult<_>>()?;
+                let comments = comments
+                    .into_iter()
+                    .map(|c| {
+                        Ok(BackupComment {
+                            creator: creator(&c.creator_id)?,
+                            parent: c.parent_id.and_then(|p| comment_ap_ids.get(&p).cloned()),
+                            ap_id: c.ap_id,
+                            content: c.content,
+                            depth: c.depth,
+                            local: c.local,
+                            deleted: c.deleted,
+                            published: c.published,
+                            updated: c.updated,
+                        })
+                    })
+                    .collect::<BackendResult<_>>()?;
+                Ok(BackupArticle {
+                    title: article.title,
+                    text: article.text,
+                    ap_id: article.ap_id,
+                    protected: article.protected,
+                    removed: article.removed,
+                    published: article.published,
+                    updated: article.updated,
+                    edits,
+                    comments,
+                })
+            })
+            .collect::<BackendResult<_>>()?;
+
+        let persons = persons
+            .into_iter()
+            .map(|p| BackupPerson {
+                username: p.username,
+                ap_id: p.ap_id,
+                inbox_url: p.inbox_url,
+                public_key: p.public_key,
+                local: p.local,
+                display_name: p.display_name,
+                bio: p.bio,
+                last_refreshed_at: p.last_refreshed_at,
+                bot: p.bot,
+            })
+            .collect();
+
+        Ok(WikiBackup {
+            instance: BackupInstance {
+                ap_id: local_instance.ap_id,
+                domain: local_instance.domain,
+                name: local_instance.name,
+                topic: local_instance.topic,
+            },
+            persons,
+            articles,
+        })
+    }
+
+    /// Write the backup into the local instance. Urls of the exported instance are rewritten to
+    /// the local domain, so that a backup can also be used to set up a copy of the wiki.
+    /// Existing articles with the same url are replaced including their history, while edit
+    /// hashes stay unchanged.
+    pub fn restore(self, context: &IbisContext) -> BackendResult<()> {
+        let local_instance = Instance::read_local(context)?;
+        let rewrite = UrlRewrite {
+            from: self.instance.ap_id.to_string(),
+            to: local_instance.ap_id.to_string(),
+        };
+        let mut conn = context.db_pool.get()?;
+        conn.deref_mut().transaction::<_, BackendError, _>(|conn| {
+            let form = DbInstanceUpdateForm {
+                name: self.instance.name,
+                topic: self.instance.topic,
+            };
+            if form.name.is_some() || form.topic.is_some() {
+                update(instance::table.find(local_instance.id))
+                    .set(form)
+                    .execute(conn)?;
+            }
+
+            let mut persons = HashMap::new();
+            for p in self.persons {
+                let person = restore_person(p.clone(), &rewrite, context, conn)?;
+                persons.insert(p.ap_id, person.id);
+            }
+            let creator = |ap_id: &DbUrl| -> BackendResult<PersonId> {
+                Ok(*persons
+                    .get(ap_id)
+                    .ok_or(anyhow!("Missing creator {ap_id}"))?)
+            };
+
+            for a in self.articles {
+                let form = DbArticleForm {
+                    title: a.title,
+                    text: a.text,
+                    ap_id: rewrite.url(&a.ap_id)?,
+                    instance_id: local_instance.id,
+                    local: true,
+                    protected: a.protected,
+                    updated: a.updated,
+                    pending: false,
+                };
+                let article: Article = insert_into(article::table)
+                    .values(&form)
+                    .on_conflict(article::ap_id)
+                    .do_update()
+                    .set(&form)
+                    .get_result(conn)?;
+                update(article::table.find(article.id))
+                    .set((
+                        article::published.eq(a.published),
+                        article::removed.eq(a.removed),
+                    ))
+                    .execute(conn)?;
+
+                delete(comment::table.filter(comment::article_id.eq(article.id))).execute(conn)?;
+                delete(edit::table.filter(edit::article_id.eq(article.id))).execute(conn)?;
+                for e in a.edits {
+                    let form = DbEditForm {
+                        creator_id: creator(&e.creator)?,
+                        hash: e.hash,
+                        ap_id: rewrite.url(&e.ap_id)?,
+                        diff: e.diff,
+                        summary: e.summary,
+                        article_id: article.id,
+                        previous_version_id: e.previous_version_id,
+                        published: e.published,
+                        pending: false,
+                        minor: e.minor,
+                        tags: e.tags,
+                    };
+                    insert_into(edit::table).values(form).execute(conn)?;
+                }
+
+                let mut comments: HashMap<DbUrl, CommentId> = HashMap::new();
+                for c in a.comments {
+                    let parent_id = c
+                        .parent
+                        .map(|p| {
+                            comments
+                                .get(&p)
+                                .ok_or(anyhow!("Missing parent comment {p}"))
+                        })
+                        .transpose()?
+                        .copied();
+                    let form = DbCommentInsertForm {
+                        creator_id: creator(&c.creator)?,
+                        article_id: article.id,
+                        parent_id,
+                        content: c.content,
+                        depth: c.depth,
+                        ap_id: Some(rewrite.url(&c.ap_id)?),
+                        local: c.local,
+                        deleted: c.deleted,
+                        published: c.published,
+                        updated: c.updated,
+                    };
+                    let comment: Comment =
+                        insert_into(comment::table).values(form).get_result(conn)?;
+                    comments.insert(c.ap_id, comment.id);
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Local users are created with an account which has no password or email, so that they behave
+/// like other local users but nobody can login with them. Existing users are kept with their keys,
+/// and only get the profile updated.
+fn restore_person(
+    p: BackupPerson,
+    rewrite: &UrlRewrite,
+    context: &IbisContext,
+    conn: &mut PgConnection,
+) -> BackendResult<Person> {
+    let ap_id = rewrite.url(&p.ap_id)?;
+    let existing: Option<Person> = person::table
+        .filter(person::ap_id.eq(&ap_id))
+        .get_result(conn)
+        .optional()?;
+    if let Some(existing) = existing {
+        if p.display_name.is_none() && p.bio.is_none() {
+            return Ok(existing);
+        }
+        let form = PersonUpdateForm {
+            display_name: p.display_name,
+            bio: p.bio,
+            ..Default::default()
+        };
+        return Ok(update(person::table.find(existing.id))
+            .set(form)
+            .get_result(conn)?);
+    }
+    let (inbox_url, public_key, private_key) = if p.local {
+        let keypair = generate_keypair()?;
+        let domain = &context.conf.federation.domain;
+        let inbox_url = format!("{}://{domain}/inbox", http_protocol_str());
+        (inbox_url, keypair.public_key, Some(keypair.private_key))
+    } else {
+        (p.inbox_url, p.public_key, None)
+    };
+    let form = PersonInsertForm {
+        username: p.username,
+        ap_id,
+        inbox_url,
+        public_key,
+        private_key,
+        last_refreshed_at: p.last_refreshed_at,
+        local: p.local,
+        display_name: p.display_name,
+        bio: p.bio,
+        also_known_as: vec![],
+        moved_to: None,
+        bot: p.bot,
+    };
+    let person: Person = insert_into(person::table).values(form).get_result(conn)?;
+    if person.local {
+        let form = LocalUserInsertForm {
+            password_encrypted: None,
+            person_id: person.id,
+            admin: false,
+            email: None,
+            email_verified: false,
+        };
+        insert_into(local_user::table).values(form).execute(conn)?;
+    }
+    Ok(person)
+}
+
+/// Replaces the url prefix of the exported instance with the one of the local instance.
+struct UrlRewrite {
+    from: String,
+    to: String,
+}
+
+impl UrlRewrite {
+    fn url(&self, url: &DbUrl) -> BackendResult<DbUrl> {
+        let url = url.to_string();
+        Ok(match url.strip_prefix(&self.from) {
+            Some(path) => Url::parse(&format!("{}{path}", self.to))?.into(),
+            None => Url::parse(&url)?.into(),
+        })
+    }
+}
@@ -0,0 +1,156 @@
+use super::IbisContext;
+use crate::{
+    common::{
+        block::{BlockKind, BlockList, InstanceBlock, PersonBlock},
+        newtypes::{InstanceId, LocalUserId, PersonId},
+        user::Person,
+        utils::extract_domain,
+    },
+    error::BackendResult,
+};
+use anyhow::anyhow;
+use diesel::{
+    ExpressionMethods,
+    QueryDsl,
+    RunQueryDsl,
+    delete,
+    dsl::insert_into,
+    upsert::excluded,
+};
+use ibis_database_schema::{instance, instance_block, person, person_block};
+use std::ops::DerefMut;
+
+impl PersonBlock {
+    /// Block or mute the person, or change the kind of an existing block.
+    pub fn create(
+        local_user_id: LocalUserId,
+        person_id: PersonId,
+        kind: BlockKind,
+        context: &IbisContext,
+    ) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        Ok(insert_into(person_block::table)
+            .values((
+                person_block::local_user_id.eq(local_user_id),
+                person_block::person_id.eq(person_id),
+                person_block::kind.eq(kind),
+            ))
+            .on_conflict((person_block::local_user_id, person_block::person_id))
+            .do_update()
+            .set(person_block::kind.eq(excluded(person_block::kind)))
+            .get_result(conn.deref_mut())?)
+    }
+
+    pub fn delete(
+        local_user_id: LocalUserId,
+        person_id: PersonId,
+        context: &IbisContext,
+    ) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        let rows = delete(
+            person_block::table
+                .filter(person_block::local_user_id.eq(local_user_id))
+                .filter(person_block::person_id.eq(person_id)),
+        )
+        .execute(conn.deref_mut())?;
+        if rows == 0 {
+            return Err(anyhow!("Person is not blocked").into());
+        }
+        Ok(())
+    }
+}
+
+impl InstanceBlock {
+    /// Block or mute all users of the instance, or change the kind of an existing block.
+    pub fn create(
+        local_user_id: LocalUserId,
+        instance_id: InstanceId,
+        kind: BlockKind,
+        context: &IbisContext,
+    ) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        Ok(insert_into(instance_block::table)
+            .values((
+                instance_block::local_user_id.eq(local_user_id),
+                instance_block::instance_id.eq(instance_id),
+                instance_block::kind.eq(kind),
+            ))
+            .on_conflict((instance_block::local_user_id, instance_block::instance_id))
+            .do_update()
+            .set(instance_block::kind.eq(excluded(instance_block::kind)))
+            .get_result(conn.deref_mut())?)
+    }
+
+    pub fn delete(
+        local_user_id: LocalUserId,
+        instance_id: InstanceId,
+        context: &IbisContext,
+    ) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        let rows = delete(
+            instance_block::table
+                .filter(instance_block::local_user_id.eq(local_user_id))
+                .filter(instance_block::instance_id.eq(instance_id)),
+        )
+        .execute(conn.deref_mut())?;
+        if rows == 0 {
+            return Err(anyhow!("Instance is not blocked").into());
+        }
+        Ok(())
+    }
+}
+
+impl BlockList {
+    pub fn read(local_user_id: LocalUserId, context: &IbisContext) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        let persons = person_block::table
+            .inner_join(person::table)
+            .filter(person_block::local_user_id.eq(local_user_id))
+            .order(person_block::published.desc())
+            .select((person_block::all_columns, person::all_columns))
+            .get_results(conn.deref_mut())?;
+        let instances = instance_block::table
+            .inner_join(instance::table)
+            .filter(instance_block::local_user_id.eq(local_user_id))
+            .order(instance_block::published.desc())
+            .select((instance_block::all_columns, instance::all_columns))
+            .get_results(conn.deref_mut())?;
+        Ok(BlockList { persons, instances })
+    }
+
+    /// Check if the creator's content is hidden from the user, either because the creator or
+    /// their instance is blocked. Mutes are ignored.
+    pub fn hides(&self, creator: &Person) -> bool {
+        let domain = extract_domain(creator.ap_id.inner());
+        self.persons
+            .iter()
+            .any(|p| p.block.kind == BlockKind::Block && p.person.id == creator.id)
+            || self
+                .instances
+                .iter()
+                .any(|i| i.block.kind == BlockKind::Block && i.instance.domain == domain)
+    }
+
+    /// Local users who blocked or muted the person or their instance, and shouldn't be notified
+    /// about the person's actions.
+    pub fn local_users_muting(
+        creator_id: PersonId,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<LocalUserId>> {
+        let creator = Person::read(creator_id, context)?;
+        let domain = extract_domain(creator.ap_id.inner());
+        let mut conn = context.db_pool.get()?;
+        let mut local_users: Vec<LocalUserId> = person_block::table
+            .filter(person_block::person_id.eq(creator_id))
+            .select(person_block::local_user_id)
+            .get_results(conn.deref_mut())?;
+        local_users.extend(
+            instance_block::table
+                .inner_join(instance::table)
+                .filter(instance::domain.eq(domain))
+                .select(instance_block::local_user_id)
+                .get_results::<LocalUserId>(conn.deref_mut())?,
+        );
+        Ok(local_users)
+    }
+}
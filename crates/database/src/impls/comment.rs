@@ -2,12 +2,16 @@ use super::notifications::Notification;
 use crate::{
     DbUrl,
     common::{
+        article::Article,
+        block::BlockList,
         comment::{Comment, CommentView, CommentViewWithArticle},
         newtypes::{ArticleId, CommentId, PersonId},
-        user::Person,
+        user::{LocalUserView, Person},
+        webhook::WebhookEvent,
     },
     error::BackendResult,
-    impls::IbisContext,
+    impls::{IbisContext, upsert_inserted},
+    webhook::send_webhooks,
 };
 use chrono::{DateTime, Utc};
 use diesel::{
@@ -16,11 +20,12 @@ use diesel::{
     Insertable,
     QueryDsl,
     RunQueryDsl,
-    dsl::insert_into,
+    dsl::{count, insert_into, not},
     update,
 };
 use ibis_database_schema::{article, comment, person};
-use std::ops::DerefMut;
+use serde_json::json;
+use std::{collections::HashSet, ops::DerefMut};
 
 #[derive(Insertable, AsChangeset, Debug)]
 #[diesel(table_name = comment, check_for_backend(diesel::pg::Pg))]
@@ -49,14 +54,21 @@ pub struct DbCommentUpdateForm {
 impl Comment {
     pub async fn create(form: DbCommentInsertForm, context: &IbisContext) -> BackendResult<Self> {
         let mut conn = context.db_pool.get()?;
-        let comment: Comment = insert_into(comment::table)
+        let (comment, inserted): (Comment, bool) = insert_into(comment::table)
             .values(&form)
             .on_conflict(comment::dsl::ap_id)
             .do_update()
             .set(&form)
+            .returning((comment::all_columns, upsert_inserted()))
             .get_result(conn.deref_mut())?;
 
         Notification::notify_comment(&comment, context).await?;
+        if inserted {
+            // removed articles can't be read, then the article is left out of the payload
+            let article = Article::read(comment.article_id, context).ok();
+            let data = json!({ "article": article, "comment": comment });
+            send_webhooks(WebhookEvent::CommentCreated, &data, context)?;
+        }
         Ok(comment)
     }
 
@@ -103,17 +115,96 @@ impl Comment {
             .get_result(conn.deref_mut())?)
     }
 
+    /// Most recent comments by the given person, newest first. Used for the person outbox.
+    pub fn list_for_person(
+        person_id: PersonId,
+        limit: i64,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(comment::table
+            .inner_join(article::table)
+            .filter(comment::creator_id.eq(person_id))
+            .filter(not(comment::deleted))
+            .filter(not(article::removed))
+            .order_by(comment::published.desc())
+            .limit(limit)
+            .select(comment::all_columns)
+            .get_results(conn.deref_mut())?)
+    }
+
+    /// All comments by the given person including deleted ones and those on removed articles, for
+    /// the personal data export.
+    pub fn list_all_for_person(
+        person_id: PersonId,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(comment::table
+            .filter(comment::creator_id.eq(person_id))
+            .order_by(comment::published.desc())
+            .get_results(conn.deref_mut())?)
+    }
+
+    pub fn count_for_person(person_id: PersonId, context: &IbisContext) -> BackendResult<i64> {
+        let mut conn = context.db_pool.get()?;
+        Ok(comment::table
+            .inner_join(article::table)
+            .filter(comment::creator_id.eq(person_id))
+            .filter(not(comment::deleted))
+            .filter(not(article::removed))
+            .select(count(comment::id))
+            .first(conn.deref_mut())?)
+    }
+
+    /// Comments by the given person with their articles, newest first. Used for the
+    /// contributions page.
+    pub fn list_views_for_person(
+        person_id: PersonId,
+        limit: i64,
+        offset: i64,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<CommentViewWithArticle>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(comment::table
+            .inner_join(person::table)
+            .inner_join(article::table)
+            .filter(comment::creator_id.eq(person_id))
+            .filter(not(comment::deleted))
+            .filter(not(article::removed))
+            .order_by(comment::published.desc())
+            .limit(limit)
+            .offset(offset)
+            .get_results(conn.deref_mut())?)
+    }
+
+    /// Comments of blocked persons and instances are hidden from the user, together with their
+    /// replies.
     pub fn read_for_article(
         article_id: ArticleId,
+        user: Option<&LocalUserView>,
         context: &IbisContext,
     ) -> BackendResult<Vec<CommentView>> {
         let mut conn = context.db_pool.get()?;
-        let comments = comment::table
+        let mut comments = comment::table
             .inner_join(person::table)
             .filter(comment::article_id.eq(article_id))
             .order_by(comment::published.desc())
             .get_results::<CommentView>(conn.deref_mut())?;
 
+        if let Some(user) = user {
+            let blocks = BlockList::read(user.local_user.id, context)?;
+            let mut hidden = HashSet::new();
+            // parents are always published before their replies
+            for view in comments.iter().rev() {
+                let parent_hidden = view.comment.parent_id.is_some_and(|p| hidden.contains(&p));
+                if parent_hidden || blocks.hides(&view.creator) {
+                    hidden.insert(view.comment.id);
+                }
+            }
+            comments.retain(|view| !hidden.contains(&view.comment.id));
+        }
+
         // Clear content of deleted comments. comments themselves are returned
         // so that tree can be rendered.
         Ok(comments
@@ -2,6 +2,7 @@ use super::notifications::NotificationInsertForm;
 use crate::{
     common::{
         article::{Conflict, EditVersion},
+        events::LiveEvent,
         newtypes::{ArticleId, ConflictId, PersonId},
         user::LocalUser,
     },
@@ -21,6 +22,7 @@ pub struct DbConflictForm {
     pub creator_id: PersonId,
     pub article_id: ArticleId,
     pub previous_version_id: EditVersion,
+    pub minor: bool,
 }
 
 impl Conflict {
@@ -40,11 +42,15 @@ impl Conflict {
             comment_id: None,
             edit_id: None,
             conflict_id: Some(conflict.id),
+            // conflicts are shown directly on the website
+            send_email: false,
+            mention: false,
         };
 
         insert_into(notification::table)
             .values(&form)
             .execute(&mut conn)?;
+        context.publish(Some(local_user.id), LiveEvent::Notification);
 
         Ok(conflict)
     }
@@ -61,6 +67,15 @@ impl Conflict {
             .get_result(conn.deref_mut())?)
     }
 
+    /// All unresolved conflicts of the given person, for the personal data export.
+    pub fn list_for_person(person_id: PersonId, context: &IbisContext) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(conflict::table
+            .filter(conflict::creator_id.eq(person_id))
+            .order(conflict::published.desc())
+            .get_results(conn.deref_mut())?)
+    }
+
     /// Delete merge conflict which was created by specific user
     pub fn delete(
         id: ConflictId,
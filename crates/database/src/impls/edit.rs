@@ -2,8 +2,8 @@ use super::notifications::Notification;
 use crate::{
     DbUrl,
     common::{
-        article::{Article, Edit, EditVersion, EditView},
-        newtypes::{ArticleId, PersonId},
+        article::{Article, Edit, EditTag, EditVersion, EditView},
+        newtypes::{ArticleId, EditId, InstanceId, PersonId},
         user::LocalUserView,
     },
     error::BackendResult,
@@ -17,7 +17,7 @@ use diesel::{
     Insertable,
     QueryDsl,
     RunQueryDsl,
-    dsl::not,
+    dsl::{count, not},
     insert_into,
 };
 use diffy::create_patch;
@@ -37,6 +37,25 @@ pub struct DbEditForm {
     pub previous_version_id: EditVersion,
     pub published: DateTime<Utc>,
     pub pending: bool,
+    pub minor: bool,
+    pub tags: Vec<EditTag>,
+}
+
+/// Describes the changes of a new edit.
+#[derive(Debug, Clone, Default)]
+pub struct EditDescription {
+    pub summary: String,
+    pub minor: bool,
+    pub tags: Vec<EditTag>,
+}
+
+impl From<String> for EditDescription {
+    fn from(summary: String) -> Self {
+        EditDescription {
+            summary,
+            ..Default::default()
+        }
+    }
 }
 
 impl DbEditForm {
@@ -44,7 +63,7 @@ impl DbEditForm {
         original_article: &Article,
         creator_id: PersonId,
         updated_text: &str,
-        summary: String,
+        description: EditDescription,
         previous_version_id: EditVersion,
         pending: bool,
     ) -> BackendResult<Self> {
@@ -58,9 +77,11 @@ impl DbEditForm {
             creator_id,
             article_id: original_article.id,
             previous_version_id,
-            summary,
+            summary: description.summary,
             published: Utc::now(),
             pending,
+            minor: description.minor,
+            tags: description.tags,
         })
     }
 
@@ -90,6 +111,22 @@ impl Edit {
             .get_result(conn.deref_mut())?)
     }
 
+    pub fn read_by_id(id: EditId, context: &IbisContext) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        Ok(edit::table.find(id).get_result(conn.deref_mut())?)
+    }
+
+    pub fn update_patrolled(
+        id: EditId,
+        patrolled: bool,
+        context: &IbisContext,
+    ) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        Ok(diesel::update(edit::table.find(id))
+            .set(edit::patrolled.eq(patrolled))
+            .get_result(conn.deref_mut())?)
+    }
+
     pub fn read_view(version: &EditVersion, context: &IbisContext) -> BackendResult<EditView> {
         let mut conn = context.db_pool.get()?;
         Ok(edit::table
@@ -115,10 +152,155 @@ impl Edit {
             .get_results(conn.deref_mut())?)
     }
 
+    /// Most recent edits by the given person, newest first. Used for the person outbox.
+    pub fn list_for_person(
+        person_id: PersonId,
+        limit: i64,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(edit::table
+            .inner_join(article::table)
+            .filter(edit::creator_id.eq(person_id))
+            .filter(not(edit::pending))
+            .filter(not(article::removed))
+            .order(edit::published.desc())
+            .limit(limit)
+            .select(edit::all_columns)
+            .get_results(conn.deref_mut())?)
+    }
+
+    /// All edits by the given person including pending ones and those on removed articles, for
+    /// the personal data export.
+    pub fn list_all_for_person(
+        person_id: PersonId,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(edit::table
+            .filter(edit::creator_id.eq(person_id))
+            .order(edit::published.desc())
+            .get_results(conn.deref_mut())?)
+    }
+
+    pub fn count_for_person(person_id: PersonId, context: &IbisContext) -> BackendResult<i64> {
+        let mut conn = context.db_pool.get()?;
+        Ok(edit::table
+            .inner_join(article::table)
+            .filter(edit::creator_id.eq(person_id))
+            .filter(not(edit::pending))
+            .filter(not(article::removed))
+            .select(count(edit::id))
+            .first(conn.deref_mut())?)
+    }
+
+    /// Edits by the given person, newest first. Used for the contributions page.
+    pub fn list_views_for_person(
+        person_id: PersonId,
+        limit: i64,
+        offset: i64,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<EditView>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(edit::table
+            .inner_join(article::table)
+            .inner_join(person::table)
+            .filter(edit::creator_id.eq(person_id))
+            .filter(not(edit::pending))
+            .filter(not(article::removed))
+            .order(edit::published.desc())
+            .limit(limit)
+            .offset(offset)
+            .get_results(conn.deref_mut())?)
+    }
+
+    /// Most recent edits of articles hosted on the local instance, newest first. Used for the
+    /// instance outbox.
+    pub fn list_for_local_articles(
+        limit: i64,
+        offset: i64,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(edit::table
+            .inner_join(article::table)
+            .filter(article::local)
+            .filter(not(edit::pending))
+            .filter(not(article::removed))
+            .order(edit::published.desc())
+            .limit(limit)
+            .offset(offset)
+            .select(edit::all_columns)
+            .get_results(conn.deref_mut())?)
+    }
+
+    pub fn count_for_local_articles(context: &IbisContext) -> BackendResult<i64> {
+        let mut conn = context.db_pool.get()?;
+        Ok(edit::table
+            .inner_join(article::table)
+            .filter(article::local)
+            .filter(not(edit::pending))
+            .filter(not(article::removed))
+            .select(count(edit::id))
+            .first(conn.deref_mut())?)
+    }
+
     pub fn list_views(
         params: ViewEditParams,
         user: &Option<LocalUserView>,
         context: &IbisContext,
+    ) -> BackendResult<Vec<EditView>> {
+        Self::query_views(params, user, None, context)
+    }
+
+    /// Most recent edits, newest first. Edits of removed articles are left out. Used for feeds.
+    pub fn list_recent_views(
+        params: ViewEditParams,
+        limit: i64,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<EditView>> {
+        Self::query_views(params, &None, Some(limit), context)
+    }
+
+    /// Edits of all articles for the recent changes page, newest first.
+    pub fn list_recent_changes(
+        filter: &RecentChangesFilter,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<EditView>> {
+        let mut conn = context.db_pool.get()?;
+        let mut query = edit::table
+            .inner_join(article::table)
+            .inner_join(person::table)
+            .filter(not(edit::pending))
+            .filter(not(article::removed))
+            .filter(edit::published.ge(filter.since))
+            .into_boxed();
+        if let Some(local) = filter.local {
+            query = query.filter(article::local.eq(local));
+        }
+        if let Some(instance_id) = filter.instance_id {
+            query = query.filter(article::instance_id.eq(instance_id));
+        }
+        if filter.hide_bots {
+            query = query.filter(not(person::bot));
+        }
+        if filter.unpatrolled_only {
+            query = query.filter(not(edit::patrolled));
+        }
+        if filter.hide_minor {
+            query = query.filter(not(edit::minor));
+        }
+        Ok(query
+            .order(edit::published.desc())
+            .limit(filter.limit)
+            .get_results(conn.deref_mut())?)
+    }
+
+    fn query_views(
+        params: ViewEditParams,
+        user: &Option<LocalUserView>,
+        recent: Option<i64>,
+        context: &IbisContext,
     ) -> BackendResult<Vec<EditView>> {
         let mut conn = context.db_pool.get()?;
         let person_id = user.as_ref().map(|u| u.person.id).unwrap_or(PersonId(-1));
@@ -132,13 +314,37 @@ impl Edit {
         let query = match params {
             ViewEditParams::PersonId(person_id) => query.filter(edit::creator_id.eq(person_id)),
             ViewEditParams::ArticleId(article_id) => query.filter(edit::article_id.eq(article_id)),
+            ViewEditParams::All => query,
+        };
+        let query = match recent {
+            Some(limit) => query
+                .filter(not(article::removed))
+                .order(edit::published.desc())
+                .limit(limit),
+            None => query.order(edit::published),
         };
 
-        Ok(query.order(edit::published).get_results(conn.deref_mut())?)
+        Ok(query.get_results(conn.deref_mut())?)
     }
 }
 
 pub enum ViewEditParams {
     PersonId(PersonId),
     ArticleId(ArticleId),
+    /// Edits of all articles
+    All,
+}
+
+#[derive(Debug)]
+pub struct RecentChangesFilter {
+    /// Only edits of local articles if true, only edits of remote articles if false
+    pub local: Option<bool>,
+    pub instance_id: Option<InstanceId>,
+    /// Leave out edits by bot accounts
+    pub hide_bots: bool,
+    pub unpatrolled_only: bool,
+    pub hide_minor: bool,
+    /// Start of the time window
+    pub since: DateTime<Utc>,
+    pub limit: i64,
 }
@@ -69,6 +69,25 @@ impl Instance {
             .get_result(conn.deref_mut())?)
     }
 
+    /// Replace the key of an instance. The old public key is kept, so that signatures which were
+    /// made before the rotation can still be verified for the grace period.
+    pub fn update_key(
+        id: InstanceId,
+        public_key: String,
+        private_key: Option<String>,
+        context: &IbisContext,
+    ) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        Ok(update(instance::table.find(id))
+            .set((
+                instance::previous_public_key.eq(instance::public_key.nullable()),
+                instance::public_key.eq(public_key),
+                instance::private_key.eq(private_key),
+                instance::key_rotated_at.eq(Utc::now()),
+            ))
+            .get_result(conn.deref_mut())?)
+    }
+
     pub fn read_from_ap_id(ap_id: &DbUrl, context: &IbisContext) -> BackendResult<Instance> {
         let mut conn = context.db_pool.get()?;
         Ok(instance::table
@@ -0,0 +1,34 @@
+use super::IbisContext;
+use crate::common::{events::LiveEvent, newtypes::LocalUserId};
+use tokio::sync::broadcast::{Receiver, Sender, channel};
+
+/// Events which are not received by any client within this many messages get lost.
+const CHANNEL_CAPACITY: usize = 100;
+
+/// Live event together with its recipients.
+#[derive(Clone, Debug)]
+pub struct LiveEventMessage {
+    /// Only send to this user, or to everyone if `None`
+    pub local_user_id: Option<LocalUserId>,
+    pub event: LiveEvent,
+}
+
+pub(crate) fn live_events_channel() -> Sender<LiveEventMessage> {
+    channel(CHANNEL_CAPACITY).0
+}
+
+impl IbisContext {
+    pub fn publish(&self, local_user_id: Option<LocalUserId>, event: LiveEvent) {
+        // fails if nobody is listening, which is fine
+        self.live_events
+            .send(LiveEventMessage {
+                local_user_id,
+                event,
+            })
+            .ok();
+    }
+
+    pub fn subscribe(&self) -> Receiver<LiveEventMessage> {
+        self.live_events.subscribe()
+    }
+}
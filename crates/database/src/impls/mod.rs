@@ -4,23 +4,36 @@ use diesel::{
     QueryDsl,
     RunQueryDsl,
     define_sql_function,
+    dsl::sql,
+    expression::SqlLiteral,
     r2d2::{ConnectionManager, Pool},
     sql_types,
 };
 use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
 use ibis_database_schema::jwt_secret;
+use live_events::{LiveEventMessage, live_events_channel};
 use reqwest::Client;
 use std::{env::var, ops::DerefMut};
+use tokio::sync::broadcast::Sender;
 
+pub mod api_token;
 pub mod article;
+pub mod backup;
+pub mod block;
 pub mod comment;
 pub mod conflict;
 pub mod edit;
 pub mod instance;
 pub mod instance_stats;
+pub mod live_events;
 pub mod notifications;
+pub mod person_stats;
+pub mod registration;
 pub mod sent_activity;
+pub mod session;
 pub mod user;
+pub mod web_push;
+pub mod webhook;
 
 pub type DbPool = Pool<ConnectionManager<PgConnection>>;
 
@@ -31,6 +44,7 @@ pub struct IbisContext {
     pub db_pool: DbPool,
     pub conf: IbisConfig,
     pub client: Client,
+    live_events: Sender<LiveEventMessage>,
 }
 
 impl IbisContext {
@@ -56,6 +70,7 @@ impl IbisContext {
             db_pool,
             conf: config,
             client,
+            live_events: live_events_channel(),
         })
     }
 }
@@ -67,6 +82,14 @@ pub fn read_jwt_secret(context: &IbisContext) -> BackendResult<String> {
         .first(conn.deref_mut())?)
 }
 
+/// Returned by an upsert, true if the row was inserted and false if it was updated. This relies on
+/// `xmax` being zero for rows which were not modified yet.
+fn upsert_inserted() -> SqlLiteral<sql_types::Bool> {
+    sql("xmax = 0")
+}
+
 define_sql_function!(fn lower(x: sql_types::Text) -> sql_types::Text);
 
+define_sql_function!(fn array_remove(a: sql_types::Array<sql_types::Text>, e: sql_types::Text) -> sql_types::Array<sql_types::Text>);
+
 define_sql_function!(fn coalesce<T: sql_types::SqlType + sql_types::SingleValue>(x: sql_types::Nullable<T>, y: T) -> T);
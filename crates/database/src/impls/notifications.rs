@@ -1,7 +1,9 @@
 use crate::{
     common::{
-        article::{Article, Conflict, Edit},
+        article::{Article, Conflict, Edit, EditTag},
+        block::BlockList,
         comment::Comment,
+        events::LiveEvent,
         newtypes::{
             ArticleId,
             CommentId,
@@ -12,18 +14,21 @@ use crate::{
             PersonId,
         },
         notifications::{ApiNotification, ApiNotificationData},
-        user::{LocalUser, LocalUserView, Person},
+        user::{LocalUser, LocalUserView, NotificationPreference, Person},
     },
     email::notification::send_notification_email,
     error::BackendResult,
     impls::IbisContext,
+    web_push::send_web_push,
 };
 use chrono::{DateTime, Utc};
 use diesel::{
+    BoolExpressionMethods,
     ExpressionMethods,
     Insertable,
     JoinOnDsl,
     NullableExpressionMethods,
+    PgExpressionMethods,
     QueryDsl,
     Queryable,
     RunQueryDsl,
@@ -55,6 +60,11 @@ pub struct Notification {
     edit_id: Option<EditId>,
     pub published: DateTime<Utc>,
     conflict_id: Option<ConflictId>,
+    emailed_at: Option<DateTime<Utc>>,
+    /// Depends on the notification preference of the user
+    pub(crate) send_email: bool,
+    /// User was mentioned in the comment or edit summary
+    pub(crate) mention: bool,
 }
 
 #[derive(Debug, Insertable)]
@@ -66,6 +76,8 @@ pub(crate) struct NotificationInsertForm {
     pub comment_id: Option<CommentId>,
     pub edit_id: Option<EditId>,
     pub conflict_id: Option<ConflictId>,
+    pub send_email: bool,
+    pub mention: bool,
 }
 
 #[derive(Queryable, Debug)]
@@ -93,13 +105,57 @@ impl Notification {
     }
 
     pub(crate) fn read_data(
-        id: NotificationId,
+        ids: Vec<NotificationId>,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<NotificationData>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(Notification::joins()
+            .filter(notification::id.eq_any(ids))
+            .get_results(&mut conn)?)
+    }
+
+    /// Notifications which need to be sent by email, ordered by user and time.
+    pub(crate) fn read_unsent_emails(
         context: &IbisContext,
-    ) -> BackendResult<NotificationData> {
+    ) -> BackendResult<Vec<NotificationData>> {
         let mut conn = context.db_pool.get()?;
         Ok(Notification::joins()
-            .filter(notification::id.eq(id))
-            .get_result(&mut conn)?)
+            .filter(notification::emailed_at.is_null())
+            // edit conflicts are shown on the website directly
+            .filter(notification::conflict_id.is_null())
+            .filter(notification::send_email)
+            .filter(local_user::email_notifications)
+            .filter(local_user::email.is_not_null())
+            .order_by((notification::local_user_id, notification::published))
+            .get_results(&mut conn)?)
+    }
+
+    pub(crate) fn mark_emailed(
+        ids: Vec<NotificationId>,
+        context: &IbisContext,
+    ) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        update(notification::table.filter(notification::id.eq_any(ids)))
+            .set(notification::emailed_at.eq(now))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// Mark notifications of users who don't get emails as handled, so that they are not all sent
+    /// at once if the user enables email notifications later.
+    pub(crate) fn skip_disabled_emails(context: &IbisContext) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        let users = local_user::table
+            .filter(not(local_user::email_notifications).or(local_user::email.is_null()))
+            .select(local_user::id);
+        update(
+            notification::table
+                .filter(notification::emailed_at.is_null())
+                .filter(notification::local_user_id.eq_any(users)),
+        )
+        .set(notification::emailed_at.eq(now))
+        .execute(&mut conn)?;
+        Ok(())
     }
     pub async fn list(
         user: &LocalUserView,
@@ -116,10 +172,18 @@ impl Notification {
             .into_iter()
             .map(|n| {
                 use ApiNotificationData::*;
+                let mention = n.notification.mention;
                 let (published, data) = if let Some(c) = n.comment {
-                    (c.published, Comment(c))
+                    (
+                        c.published,
+                        if mention {
+                            CommentMention(c)
+                        } else {
+                            Comment(c)
+                        },
+                    )
                 } else if let Some(e) = n.edit {
-                    (e.published, Edit(e))
+                    (e.published, if mention { EditMention(e) } else { Edit(e) })
                 } else if let Some(c) = n.conflict {
                     (
                         c.published,
@@ -191,25 +255,24 @@ impl Notification {
         let followers = instance_follow::table
             .inner_join(person::table.inner_join(local_user::table))
             .filter(instance_follow::instance_id.eq(article.instance_id))
-            .select((local_user::person_id, local_user::id))
-            .get_results::<(PersonId, LocalUserId)>(&mut conn)?;
+            .select(local_user::all_columns)
+            .get_results::<LocalUser>(&mut conn)?;
+        let muting = BlockList::local_users_muting(creator_id, context)?;
         let notifs: Vec<_> = followers
             .into_iter()
             // exclude creator so he doesnt get notified about his own edit/comment
-            .flat_map(|(person_id, local_user_id)| {
-                if person_id != creator_id {
-                    Some(local_user_id)
-                } else {
-                    None
-                }
-            })
-            .map(|local_user_id| NotificationInsertForm {
-                local_user_id,
+            .filter(|local_user| local_user.person_id != creator_id)
+            .filter(|local_user| !muting.contains(&local_user.id))
+            .filter(|local_user| local_user.notify_new_article != NotificationPreference::Off)
+            .map(|local_user| NotificationInsertForm {
+                local_user_id: local_user.id,
                 article_id: article.id,
                 creator_id,
                 comment_id: None,
                 edit_id: None,
                 conflict_id: None,
+                send_email: local_user.notify_new_article == NotificationPreference::Email,
+                mention: false,
             })
             .collect();
 
@@ -217,12 +280,13 @@ impl Notification {
             .values(&notifs)
             .on_conflict_do_nothing()
             .get_results(&mut conn)?;
-        send_notification_email(notifs, context).await?;
+        Self::send(notifs, context).await?;
         Ok(())
     }
 
     pub async fn notify_comment(comment: &Comment, context: &IbisContext) -> BackendResult<()> {
         let mut conn = context.db_pool.get()?;
+        let muting = BlockList::local_users_muting(comment.creator_id, context)?;
 
         // notify author of parent comment
         {
@@ -243,8 +307,12 @@ impl Notification {
                 .select(local_user::all_columns.nullable())
                 .get_result(conn.deref_mut())?;
             if let Some(parent_comment_creator) = parent_comment_creator {
-                // Dont notify when replying to own comment
-                if parent_comment_creator.person_id != comment.creator_id {
+                let preference = parent_comment_creator.notify_comment_reply;
+                // Dont notify when replying to own comment, or if the replying user is blocked
+                if parent_comment_creator.person_id != comment.creator_id
+                    && !muting.contains(&parent_comment_creator.id)
+                    && preference != NotificationPreference::Off
+                {
                     let form = NotificationInsertForm {
                         local_user_id: parent_comment_creator.id,
                         article_id: comment.article_id,
@@ -252,11 +320,14 @@ impl Notification {
                         comment_id: Some(comment.id),
                         edit_id: None,
                         conflict_id: None,
+                        send_email: preference == NotificationPreference::Email,
+                        mention: false,
                     };
-                    insert_into(notification::table)
+                    let notifs = insert_into(notification::table)
                         .values(&form)
                         .on_conflict_do_nothing()
-                        .execute(&mut conn)?;
+                        .get_results(&mut conn)?;
+                    Self::send(notifs, context).await?;
                 }
             }
         }
@@ -265,6 +336,7 @@ impl Notification {
         Self::notify(
             comment.article_id,
             comment.creator_id,
+            |local_user| local_user.notify_article_comment,
             |local_user_id| NotificationInsertForm {
                 local_user_id,
                 article_id: comment.article_id,
@@ -272,6 +344,8 @@ impl Notification {
                 comment_id: Some(comment.id),
                 edit_id: None,
                 conflict_id: None,
+                send_email: true,
+                mention: false,
             },
             context,
         )
@@ -284,6 +358,15 @@ impl Notification {
         Self::notify(
             edit.article_id,
             edit.creator_id,
+            |local_user| {
+                if edit.tags.contains(&EditTag::Bot) {
+                    local_user.notify_bot_edit
+                } else if edit.minor {
+                    local_user.notify_minor_edit
+                } else {
+                    local_user.notify_article_edit
+                }
+            },
             |local_user_id| NotificationInsertForm {
                 local_user_id,
                 article_id: edit.article_id,
@@ -291,6 +374,8 @@ impl Notification {
                 comment_id: None,
                 edit_id: Some(edit.id),
                 conflict_id: None,
+                send_email: true,
+                mention: false,
             },
             context,
         )
@@ -298,41 +383,159 @@ impl Notification {
         Ok(())
     }
 
-    async fn notify<F>(
+    /// Notify local users who were mentioned in a comment. If the user already has a notification
+    /// for the comment, it is marked as mention instead of creating another one.
+    pub async fn notify_comment_mentions(
+        comment: &Comment,
+        mentioned: &[Person],
+        context: &IbisContext,
+    ) -> BackendResult<()> {
+        Self::notify_mentions(
+            comment.creator_id,
+            mentioned,
+            |local_user_id| NotificationInsertForm {
+                local_user_id,
+                article_id: comment.article_id,
+                creator_id: comment.creator_id,
+                comment_id: Some(comment.id),
+                edit_id: None,
+                conflict_id: None,
+                send_email: true,
+                mention: true,
+            },
+            context,
+        )
+        .await
+    }
+
+    /// Notify local users who were mentioned in an edit summary.
+    pub async fn notify_edit_mentions(
+        edit: &Edit,
+        mentioned: &[Person],
+        context: &IbisContext,
+    ) -> BackendResult<()> {
+        Self::notify_mentions(
+            edit.creator_id,
+            mentioned,
+            |local_user_id| NotificationInsertForm {
+                local_user_id,
+                article_id: edit.article_id,
+                creator_id: edit.creator_id,
+                comment_id: None,
+                edit_id: Some(edit.id),
+                conflict_id: None,
+                send_email: true,
+                mention: true,
+            },
+            context,
+        )
+        .await
+    }
+
+    async fn notify_mentions<F>(
+        creator_id: PersonId,
+        mentioned: &[Person],
+        map_fn: F,
+        context: &IbisContext,
+    ) -> BackendResult<()>
+    where
+        F: Fn(LocalUserId) -> NotificationInsertForm,
+    {
+        let mut conn = context.db_pool.get()?;
+        let person_ids: Vec<_> = mentioned
+            .iter()
+            .map(|p| p.id)
+            // dont notify the user about mentioning himself
+            .filter(|id| *id != creator_id)
+            .collect();
+        let local_users = local_user::table
+            .filter(local_user::person_id.eq_any(person_ids))
+            .get_results::<LocalUser>(&mut conn)?;
+        let muting = BlockList::local_users_muting(creator_id, context)?;
+        let mut notifs = vec![];
+        for local_user in local_users {
+            if local_user.notify_mention == NotificationPreference::Off
+                || muting.contains(&local_user.id)
+            {
+                continue;
+            }
+            let form = NotificationInsertForm {
+                send_email: local_user.notify_mention == NotificationPreference::Email,
+                ..map_fn(local_user.id)
+            };
+            // users following the article already got a notification, mark it as mention
+            let updated = update(
+                notification::table
+                    .filter(notification::local_user_id.eq(form.local_user_id))
+                    .filter(notification::article_id.eq(form.article_id))
+                    .filter(notification::comment_id.is_not_distinct_from(form.comment_id))
+                    .filter(notification::edit_id.is_not_distinct_from(form.edit_id)),
+            )
+            .set(notification::mention.eq(true))
+            .execute(&mut conn)?;
+            if updated == 0 {
+                let notif: Notification = insert_into(notification::table)
+                    .values(&form)
+                    .get_result(&mut conn)?;
+                notifs.push(notif);
+            }
+        }
+        Self::send(notifs, context).await?;
+        Ok(())
+    }
+
+    /// Deliver newly created notifications to the users, with a live event, by push message and
+    /// by email.
+    async fn send(notifs: Vec<Notification>, context: &IbisContext) -> BackendResult<()> {
+        let mut local_user_ids = vec![];
+        for local_user_id in notifs.iter().map(|n| n.local_user_id) {
+            if local_user_ids.contains(&local_user_id) {
+                continue;
+            }
+            local_user_ids.push(local_user_id);
+            context.publish(Some(local_user_id), LiveEvent::Notification);
+        }
+        send_web_push(&notifs, context);
+        send_notification_email(notifs, context).await
+    }
+
+    async fn notify<P, F>(
         article_id: ArticleId,
         creator_id: PersonId,
+        preference: P,
         map_fn: F,
         context: &IbisContext,
     ) -> BackendResult<()>
     where
-        F: FnMut(LocalUserId) -> NotificationInsertForm,
+        P: Fn(&LocalUser) -> NotificationPreference,
+        F: Fn(LocalUserId) -> NotificationInsertForm,
     {
         let mut conn = context.db_pool.get()?;
         // get followers for this article
         let followers = article_follow::table
             .inner_join(local_user::table)
             .filter(article_follow::article_id.eq(article_id))
-            .select((local_user::person_id, local_user::id))
-            .get_results::<(PersonId, LocalUserId)>(&mut conn)?;
+            .select(local_user::all_columns)
+            .get_results::<LocalUser>(&mut conn)?;
+        let muting = BlockList::local_users_muting(creator_id, context)?;
         // create insert form with edit/comment it
         let notifs: Vec<_> = followers
             .into_iter()
             // exclude creator so he doesnt get notified about his own edit/comment
-            .flat_map(|(person_id, local_user_id)| {
-                if person_id != creator_id {
-                    Some(local_user_id)
-                } else {
-                    None
-                }
+            .filter(|local_user| local_user.person_id != creator_id)
+            .filter(|local_user| !muting.contains(&local_user.id))
+            .filter(|local_user| preference(local_user) != NotificationPreference::Off)
+            .map(|local_user| NotificationInsertForm {
+                send_email: preference(&local_user) == NotificationPreference::Email,
+                ..map_fn(local_user.id)
             })
-            .map(map_fn)
             .collect();
         // insert all of them
         let notifs = insert_into(notification::table)
             .values(&notifs)
             .on_conflict_do_nothing()
             .get_results(&mut conn)?;
-        send_notification_email(notifs, context).await?;
+        Self::send(notifs, context).await?;
         Ok(())
     }
 }
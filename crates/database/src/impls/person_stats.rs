@@ -0,0 +1,67 @@
+use super::IbisContext;
+use crate::{
+    common::{
+        contributions::{ActivityDay, PersonStats},
+        newtypes::PersonId,
+    },
+    error::BackendResult,
+};
+use diesel::{
+    OptionalExtension,
+    PgConnection,
+    QueryDsl,
+    RunQueryDsl,
+    sql_query,
+    sql_types::{Integer, Nullable},
+};
+use ibis_database_schema::person_stats;
+use std::ops::DerefMut;
+
+impl PersonStats {
+    /// Stats are calculated for all persons by a scheduled task. If edits or comments of the
+    /// person changed since then, or they were not included yet, they are recalculated on read.
+    pub fn read(person_id: PersonId, context: &IbisContext) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        let stats: Option<Self> = person_stats::table
+            .find(person_id)
+            .get_result(conn.deref_mut())
+            .optional()?;
+        if let Some(stats) = stats.filter(|s| !s.dirty) {
+            return Ok(stats);
+        }
+        Self::refresh(Some(person_id), conn.deref_mut())?;
+        Ok(person_stats::table
+            .find(person_id)
+            .get_result(conn.deref_mut())?)
+    }
+
+    /// Recalculate the stats of a single person. If none is given, only persons whose edits or
+    /// comments changed since the last calculation are updated.
+    pub fn refresh(person_id: Option<PersonId>, conn: &mut PgConnection) -> BackendResult<()> {
+        sql_query("select person_stats_refresh($1)")
+            .bind::<Nullable<Integer>, _>(person_id.map(|p| p.0))
+            .execute(conn)?;
+        Ok(())
+    }
+}
+
+impl ActivityDay {
+    /// Number of edits and comments per day during the last year, for the activity heatmap. Like
+    /// in the stats, edits copied by forking an article are only counted once.
+    pub fn list_for_person(person_id: PersonId, context: &IbisContext) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(sql_query(
+            "select day, count(*) as count from (
+                select (published at time zone 'UTC')::date as day from edit
+                where creator_id = $1 and not pending and published > now() - interval '1 year'
+                and not exists (select 1 from edit o where o.creator_id = edit.creator_id
+                    and o.hash = edit.hash and o.id < edit.id)
+                union all
+                select (published at time zone 'UTC')::date as day from comment
+                where creator_id = $1 and not deleted and published > now() - interval '1 year'
+            ) activity group by day order by day",
+        )
+        .bind::<Integer, _>(person_id.0)
+        .get_results(conn.deref_mut())?)
+    }
+}
@@ -0,0 +1,181 @@
+use super::IbisContext;
+use crate::{
+    common::{
+        newtypes::{InviteCodeId, LocalUserId, RegistrationApplicationId},
+        registration::{
+            InviteCode,
+            RegistrationApplication,
+            RegistrationApplicationStatus,
+            RegistrationApplicationView,
+        },
+        user::LocalUserView,
+    },
+    error::{BackendError, BackendResult},
+};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use diesel::{
+    BoolExpressionMethods,
+    Connection,
+    ExpressionMethods,
+    Insertable,
+    JoinOnDsl,
+    OptionalExtension,
+    PgConnection,
+    QueryDsl,
+    RunQueryDsl,
+    delete,
+    dsl::{insert_into, now},
+    update,
+};
+use ibis_database_schema::{invite_code, local_user, person, registration_application};
+use std::ops::DerefMut;
+use uuid::Uuid;
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = invite_code, check_for_backend(diesel::pg::Pg))]
+pub struct InviteCodeInsertForm {
+    pub creator_id: LocalUserId,
+    pub max_uses: i32,
+    pub expires: Option<DateTime<Utc>>,
+}
+
+impl InviteCode {
+    pub fn create(form: &InviteCodeInsertForm, context: &IbisContext) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        Ok(insert_into(invite_code::table)
+            .values((
+                form,
+                invite_code::code.eq(Uuid::new_v4().simple().to_string()),
+            ))
+            .get_result(conn.deref_mut())?)
+    }
+
+    pub fn list(creator_id: LocalUserId, context: &IbisContext) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(invite_code::table
+            .filter(invite_code::creator_id.eq(creator_id))
+            .order(invite_code::published.desc())
+            .get_results(conn.deref_mut())?)
+    }
+
+    pub fn delete(
+        id: InviteCodeId,
+        creator_id: LocalUserId,
+        context: &IbisContext,
+    ) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        let rows = delete(
+            invite_code::table
+                .find(id)
+                .filter(invite_code::creator_id.eq(creator_id)),
+        )
+        .execute(conn.deref_mut())?;
+        if rows == 0 {
+            return Err(anyhow!("Invite code not found").into());
+        }
+        Ok(())
+    }
+
+    /// Count one use of the code, if it is not expired or used up.
+    fn redeem(code: &str, conn: &mut PgConnection) -> BackendResult<()> {
+        let rows = update(
+            invite_code::table
+                .filter(invite_code::code.eq(code))
+                .filter(invite_code::uses.lt(invite_code::max_uses))
+                .filter(
+                    invite_code::expires
+                        .is_null()
+                        .or(invite_code::expires.gt(now)),
+                ),
+        )
+        .set(invite_code::uses.eq(invite_code::uses + 1))
+        .execute(conn)?;
+        if rows == 0 {
+            return Err(anyhow!("Invite code is invalid or expired").into());
+        }
+        Ok(())
+    }
+}
+
+impl LocalUserView {
+    /// Create the account of a new user. The invite code is redeemed and the registration
+    /// application submitted in the same transaction, so that no code is used up if the account
+    /// can't be created.
+    pub fn register(
+        username: String,
+        password: Option<String>,
+        email: Option<String>,
+        invite_code: Option<&str>,
+        application_answer: Option<String>,
+        context: &IbisContext,
+    ) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        conn.deref_mut().transaction::<_, BackendError, _>(|conn| {
+            if let Some(invite_code) = invite_code {
+                InviteCode::redeem(invite_code, conn)?;
+            }
+            let user = LocalUserView::insert(username, password, false, email, conn, context)?;
+            if let Some(answer) = application_answer {
+                insert_into(registration_application::table)
+                    .values((
+                        registration_application::local_user_id.eq(user.local_user.id),
+                        registration_application::answer.eq(answer),
+                    ))
+                    .execute(conn)?;
+            }
+            Ok(user)
+        })
+    }
+}
+
+impl RegistrationApplication {
+    /// Users who registered without application, or before applications existed, have none.
+    pub fn read_for_user(
+        local_user_id: LocalUserId,
+        context: &IbisContext,
+    ) -> BackendResult<Option<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(registration_application::table
+            .filter(registration_application::local_user_id.eq(local_user_id))
+            .get_result(conn.deref_mut())
+            .optional()?)
+    }
+
+    /// Pending applications, oldest first.
+    pub fn list_pending(context: &IbisContext) -> BackendResult<Vec<RegistrationApplicationView>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(registration_application::table
+            .inner_join(
+                local_user::table.on(registration_application::local_user_id.eq(local_user::id)),
+            )
+            .inner_join(person::table.on(local_user::person_id.eq(person::id)))
+            .filter(registration_application::status.eq(RegistrationApplicationStatus::Pending))
+            .order(registration_application::published)
+            .select((registration_application::all_columns, person::all_columns))
+            .get_results(conn.deref_mut())?)
+    }
+
+    /// Approve or deny an application which is still pending.
+    pub fn decide(
+        id: RegistrationApplicationId,
+        status: RegistrationApplicationStatus,
+        admin_id: LocalUserId,
+        deny_reason: Option<String>,
+        context: &IbisContext,
+    ) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        Ok(update(
+            registration_application::table.find(id).filter(
+                registration_application::status.eq(RegistrationApplicationStatus::Pending),
+            ),
+        )
+        .set((
+            registration_application::status.eq(status),
+            registration_application::admin_id.eq(admin_id),
+            registration_application::deny_reason.eq(deny_reason),
+            registration_application::updated.eq(now),
+        ))
+        .get_result(conn.deref_mut())?)
+    }
+}
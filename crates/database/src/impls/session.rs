@@ -0,0 +1,117 @@
+use super::IbisContext;
+use crate::{
+    common::newtypes::{LocalUserId, SessionId},
+    error::BackendResult,
+};
+use anyhow::anyhow;
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::{
+    ExpressionMethods,
+    Insertable,
+    QueryDsl,
+    Queryable,
+    RunQueryDsl,
+    Selectable,
+    delete,
+    dsl::{insert_into, now},
+    update,
+};
+use ibis_database_schema::session;
+use std::ops::DerefMut;
+use uuid::Uuid;
+
+/// Login of a user on a specific device. Each login token contains the token of its session, so
+/// that it can be revoked by deleting the session.
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = session, check_for_backend(diesel::pg::Pg))]
+pub struct Session {
+    pub id: SessionId,
+    pub local_user_id: LocalUserId,
+    pub token: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub published: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = session, check_for_backend(diesel::pg::Pg))]
+pub struct SessionInsertForm {
+    pub local_user_id: LocalUserId,
+    pub token: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+impl Session {
+    pub fn create(form: &SessionInsertForm, context: &IbisContext) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        Ok(insert_into(session::table)
+            .values(form)
+            .get_result(conn.deref_mut())?)
+    }
+
+    pub fn read_from_token(token: Uuid, context: &IbisContext) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        Ok(session::table
+            .filter(session::token.eq(token))
+            .get_result(conn.deref_mut())?)
+    }
+
+    /// Store the time when the session was last used. To avoid a database write for every
+    /// request, this is only updated after a few minutes.
+    pub fn update_last_seen(&self, context: &IbisContext) -> BackendResult<()> {
+        if Utc::now() - self.last_seen < TimeDelta::minutes(5) {
+            return Ok(());
+        }
+        let mut conn = context.db_pool.get()?;
+        update(session::table.find(self.id))
+            .set(session::last_seen.eq(now))
+            .execute(conn.deref_mut())?;
+        Ok(())
+    }
+
+    /// All sessions of the user, most recently used first.
+    pub fn list(local_user_id: LocalUserId, context: &IbisContext) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(session::table
+            .filter(session::local_user_id.eq(local_user_id))
+            .order(session::last_seen.desc())
+            .get_results(conn.deref_mut())?)
+    }
+
+    pub fn delete(
+        id: SessionId,
+        local_user_id: LocalUserId,
+        context: &IbisContext,
+    ) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        let rows = delete(
+            session::table
+                .find(id)
+                .filter(session::local_user_id.eq(local_user_id)),
+        )
+        .execute(conn.deref_mut())?;
+        if rows == 0 {
+            return Err(anyhow!("Session not found").into());
+        }
+        Ok(())
+    }
+
+    /// Sign out the user on all devices, except for the given session.
+    pub fn delete_all(
+        local_user_id: LocalUserId,
+        except: Option<SessionId>,
+        context: &IbisContext,
+    ) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        let except = except.unwrap_or(SessionId(-1));
+        delete(
+            session::table
+                .filter(session::local_user_id.eq(local_user_id))
+                .filter(session::id.ne(except)),
+        )
+        .execute(conn.deref_mut())?;
+        Ok(())
+    }
+}
@@ -3,11 +3,19 @@ use crate::{
     common::{
         instance::InstanceFollow,
         newtypes::{LocalUserId, PersonId},
-        user::{LocalUser, LocalUserView, Person},
+        user::{
+            EmailDigest,
+            LocalUser,
+            LocalUserView,
+            NotificationPreference,
+            OAuthAccount,
+            Person,
+        },
         utils::http_protocol_str,
     },
-    error::BackendResult,
-    impls::{IbisContext, coalesce, lower},
+    error::{BackendError, BackendResult},
+    impls::{IbisContext, array_remove, coalesce, lower},
+    totp::{LOCKOUT, MAX_FAILED_ATTEMPTS},
     utils::generate_keypair,
 };
 use anyhow::anyhow;
@@ -16,24 +24,35 @@ use chrono::{DateTime, Utc};
 use diesel::{
     AsChangeset,
     BoolExpressionMethods,
+    Connection,
     ExpressionMethods,
     Insertable,
     JoinOnDsl,
+    NullableExpressionMethods,
+    PgArrayExpressionMethods,
+    PgConnection,
     PgTextExpressionMethods,
     QueryDsl,
-    Queryable,
     RunQueryDsl,
-    Selectable,
-    dsl::not,
+    dsl::{not, sql},
+    expression::SqlLiteral,
     insert_into,
+    sql_query,
+    sql_types::{Bool, Integer, Timestamptz},
 };
 use ibis_database_schema::{
+    comment,
+    conflict,
+    edit,
     instance,
     instance_follow,
     local_user,
+    notification,
     oauth_account,
     person,
+    person_block,
     person_follow,
+    person_stats,
 };
 use std::ops::DerefMut;
 use url::Url;
@@ -48,10 +67,23 @@ pub struct LocalUserInsertForm {
     pub email_verified: bool,
 }
 
-#[derive(Debug, Clone, Insertable, AsChangeset)]
+#[derive(Debug, Clone, Default, Insertable, AsChangeset)]
 #[diesel(table_name = local_user, check_for_backend(diesel::pg::Pg))]
 pub struct LocalUserUpdateForm {
     pub email_notifications: Option<bool>,
+    pub email_digest: Option<EmailDigest>,
+    pub locale: Option<String>,
+    pub notify_comment_reply: Option<NotificationPreference>,
+    pub notify_article_comment: Option<NotificationPreference>,
+    pub notify_article_edit: Option<NotificationPreference>,
+    pub notify_new_article: Option<NotificationPreference>,
+    pub notify_mention: Option<NotificationPreference>,
+    pub notify_minor_edit: Option<NotificationPreference>,
+    pub totp_secret: Option<Option<String>>,
+    pub totp_enabled: Option<bool>,
+    pub totp_recovery_codes: Option<Vec<String>>,
+    pub notify_bot_edit: Option<NotificationPreference>,
+    pub totp_last_step: Option<Option<i64>>,
 }
 
 #[derive(Debug, Clone, Insertable, AsChangeset)]
@@ -66,25 +98,18 @@ pub struct PersonInsertForm {
     pub local: bool,
     pub display_name: Option<String>,
     pub bio: Option<String>,
+    pub also_known_as: Vec<DbUrl>,
+    pub moved_to: Option<DbUrl>,
+    pub bot: bool,
 }
 
-#[derive(Debug, Clone, Insertable, AsChangeset)]
+#[derive(Debug, Clone, Default, Insertable, AsChangeset)]
 #[diesel(table_name = person, check_for_backend(diesel::pg::Pg))]
 pub struct PersonUpdateForm {
     pub display_name: Option<String>,
     pub bio: Option<String>,
-}
-
-#[derive(Clone, PartialEq, Eq, Debug, Queryable, Selectable)]
-#[ diesel(table_name = oauth_account)]
-#[diesel(check_for_backend(diesel::pg::Pg))]
-/// An auth account method.
-pub struct OAuthAccount {
-    pub local_user_id: LocalUserId,
-    pub oauth_issuer_url: DbUrl,
-    pub oauth_user_id: String,
-    pub published: DateTime<Utc>,
-    pub updated: Option<DateTime<Utc>>,
+    pub bot: Option<bool>,
+    pub bot_trusted: Option<bool>,
 }
 
 #[derive(Debug, Clone, Insertable, AsChangeset)]
@@ -136,6 +161,7 @@ impl Person {
         let mut conn = context.db_pool.get()?;
         let mut query = person::table
             .filter(person::username.eq(username))
+            .filter(not(person::deleted))
             .select(person::all_columns)
             .into_boxed();
         query = if let Some(domain) = domain {
@@ -161,6 +187,25 @@ impl Person {
         Ok(())
     }
 
+    /// Replace the key of a person. The old public key is kept, so that signatures which were
+    /// made before the rotation can still be verified for the grace period.
+    pub fn update_key(
+        id: PersonId,
+        public_key: String,
+        private_key: Option<String>,
+        context: &IbisContext,
+    ) -> BackendResult<Person> {
+        let mut conn = context.db_pool.get()?;
+        Ok(diesel::update(person::table.find(id))
+            .set((
+                person::previous_public_key.eq(person::public_key.nullable()),
+                person::public_key.eq(public_key),
+                person::private_key.eq(private_key),
+                person::key_rotated_at.eq(Utc::now()),
+            ))
+            .get_result(conn.deref_mut())?)
+    }
+
     pub fn read_following(
         id_: PersonId,
         context: &IbisContext,
@@ -177,15 +222,26 @@ impl Person {
 
     /// Ghost user serves as placeholder for deleted accounts
     pub fn ghost(context: &IbisContext) -> BackendResult<Person> {
-        Self::get_or_create_person_with_name("ghost", context)
+        Self::get_or_create_person_with_name("ghost", false, context)
     }
 
     pub fn wikibot(context: &IbisContext) -> BackendResult<Person> {
-        Self::get_or_create_person_with_name(&context.conf.setup.wiki_bot_name, context)
+        let person =
+            Self::get_or_create_person_with_name(&context.conf.setup.wiki_bot_name, true, context)?;
+        // the account may have been created before bot accounts existed, or with another name
+        // than the default one which was marked as bot by the migration
+        if !person.bot {
+            let mut conn = context.db_pool.get()?;
+            return Ok(diesel::update(person::table.find(person.id))
+                .set(person::bot.eq(true))
+                .get_result(conn.deref_mut())?);
+        }
+        Ok(person)
     }
 
     fn get_or_create_person_with_name(
         username: &str,
+        bot: bool,
         context: &IbisContext,
     ) -> BackendResult<Person> {
         let read = Person::read_from_name(username, &None, context);
@@ -210,6 +266,9 @@ impl Person {
                 local: true,
                 display_name: None,
                 bio: None,
+                also_known_as: vec![],
+                moved_to: None,
+                bot,
             };
             Person::create(&person_form, context)
         }
@@ -228,6 +287,124 @@ impl Person {
         debug_assert_eq!(1, rows);
         Ok(())
     }
+
+    pub fn unfollow(
+        person: &Person,
+        follower: &Person,
+        context: &IbisContext,
+    ) -> BackendResult<()> {
+        use person_follow::dsl::{follower_id, person_id};
+        let mut conn = context.db_pool.get()?;
+        diesel::delete(
+            person_follow::table.filter(person_id.eq(person.id).and(follower_id.eq(follower.id))),
+        )
+        .execute(conn.deref_mut())?;
+        Ok(())
+    }
+
+    /// Users which are following the given user.
+    pub fn read_followers(id_: PersonId, context: &IbisContext) -> BackendResult<Vec<Person>> {
+        use person_follow::dsl::{follower_id, person_id};
+        let mut conn = context.db_pool.get()?;
+        Ok(person_follow::table
+            .inner_join(person::table.on(follower_id.eq(person::id)))
+            .filter(person_id.eq(id_))
+            .select(person::all_columns)
+            .get_results(conn.deref_mut())?)
+    }
+
+    /// Users which are followed by the given user.
+    pub fn read_followed_persons(
+        id_: PersonId,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<Person>> {
+        use person_follow::dsl::{follower_id, person_id};
+        let mut conn = context.db_pool.get()?;
+        Ok(person_follow::table
+            .inner_join(person::table.on(person_id.eq(person::id)))
+            .filter(follower_id.eq(id_))
+            .select(person::all_columns)
+            .get_results(conn.deref_mut())?)
+    }
+
+    /// Allow the account with the given id to move to this account.
+    pub fn add_alias(id: PersonId, alias: DbUrl, context: &IbisContext) -> BackendResult<Person> {
+        let mut conn = context.db_pool.get()?;
+        let mut also_known_as = person::table
+            .find(id)
+            .select(person::also_known_as)
+            .get_result::<Vec<DbUrl>>(conn.deref_mut())?;
+        if !also_known_as.contains(&alias) {
+            also_known_as.push(alias);
+        }
+        Ok(diesel::update(person::table.find(id))
+            .set(person::also_known_as.eq(also_known_as))
+            .get_result(conn.deref_mut())?)
+    }
+
+    pub fn set_moved_to(id: PersonId, moved_to: DbUrl, context: &IbisContext) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        diesel::update(person::table.find(id))
+            .set(person::moved_to.eq(moved_to))
+            .execute(conn.deref_mut())?;
+        Ok(())
+    }
+
+    /// Remove the local account, follows and notifications of the person. Edits are attributed to
+    /// the ghost user so that article history stays intact, and comments are emptied so that
+    /// replies remain visible. The person itself is kept as a tombstone without profile or keys,
+    /// so that nobody else can register the username and take over its ap_id.
+    pub fn delete(id: PersonId, context: &IbisContext) -> BackendResult<()> {
+        let ghost = Person::ghost(context)?;
+        if ghost.id == id {
+            return Err(anyhow!("Ghost user can't be deleted").into());
+        }
+        let mut conn = context.db_pool.get()?;
+        conn.deref_mut().transaction::<_, BackendError, _>(|conn| {
+            diesel::update(edit::table.filter(edit::creator_id.eq(id)))
+                .set(edit::creator_id.eq(ghost.id))
+                .execute(conn)?;
+            diesel::update(comment::table.filter(comment::creator_id.eq(id)))
+                .set((
+                    comment::creator_id.eq(ghost.id),
+                    comment::content.eq(""),
+                    comment::deleted.eq(true),
+                    comment::updated.eq(Utc::now()),
+                ))
+                .execute(conn)?;
+            diesel::delete(local_user::table.filter(local_user::person_id.eq(id))).execute(conn)?;
+            diesel::delete(instance_follow::table.filter(instance_follow::follower_id.eq(id)))
+                .execute(conn)?;
+            diesel::delete(
+                person_follow::table.filter(
+                    person_follow::person_id
+                        .eq(id)
+                        .or(person_follow::follower_id.eq(id)),
+                ),
+            )
+            .execute(conn)?;
+            diesel::delete(person_block::table.filter(person_block::person_id.eq(id)))
+                .execute(conn)?;
+            diesel::delete(notification::table.filter(notification::creator_id.eq(id)))
+                .execute(conn)?;
+            diesel::delete(conflict::table.filter(conflict::creator_id.eq(id))).execute(conn)?;
+            diesel::delete(person_stats::table.find(id)).execute(conn)?;
+            diesel::update(person::table.find(id))
+                .set((
+                    person::deleted.eq(true),
+                    person::display_name.eq(None::<String>),
+                    person::bio.eq(None::<String>),
+                    person::public_key.eq(""),
+                    person::private_key.eq(None::<String>),
+                    person::previous_public_key.eq(None::<String>),
+                    person::key_rotated_at.eq(None::<DateTime<Utc>>),
+                    person::also_known_as.eq(Vec::<String>::new()),
+                    person::moved_to.eq(None::<String>),
+                ))
+                .execute(conn)?;
+            Ok(())
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -247,6 +424,18 @@ impl LocalUserView {
         context: &IbisContext,
     ) -> BackendResult<Self> {
         let mut conn = context.db_pool.get()?;
+        Self::insert(username, password, admin, email, conn.deref_mut(), context)
+    }
+
+    /// Same as [LocalUserView::create], so that it can be used inside of a transaction.
+    pub(crate) fn insert(
+        username: String,
+        password: Option<String>,
+        admin: bool,
+        email: Option<String>,
+        conn: &mut PgConnection,
+        context: &IbisContext,
+    ) -> BackendResult<Self> {
         let domain = &context.conf.federation.domain;
         let ap_id = Url::parse(&format!(
             "{}://{domain}/user/{username}",
@@ -265,11 +454,14 @@ impl LocalUserView {
             local: true,
             display_name: None,
             bio: None,
+            also_known_as: vec![],
+            moved_to: None,
+            bot: false,
         };
 
         let person = insert_into(person::table)
             .values(person_form)
-            .get_result::<Person>(conn.deref_mut())?;
+            .get_result::<Person>(conn)?;
 
         let local_user_form = LocalUserInsertForm {
             password_encrypted: password.map(|p| hash(p, DEFAULT_COST)).transpose()?,
@@ -281,7 +473,7 @@ impl LocalUserView {
 
         let local_user = insert_into(local_user::table)
             .values(local_user_form)
-            .get_result::<LocalUser>(conn.deref_mut())?;
+            .get_result::<LocalUser>(conn)?;
 
         Ok(Self { local_user, person })
     }
@@ -358,6 +550,68 @@ impl LocalUser {
             .execute(conn.deref_mut())?;
         Ok(())
     }
+
+    /// Store the time step of an accepted two-factor code. Returns false if a code of the same or
+    /// a later step was already used, or if codes are locked after too many invalid ones.
+    pub fn use_totp_step(id: LocalUserId, step: i64, context: &IbisContext) -> BackendResult<bool> {
+        let mut conn = context.db_pool.get()?;
+        let updated = diesel::update(
+            local_user::table.find(id).filter(totp_not_locked()).filter(
+                local_user::totp_last_step
+                    .is_null()
+                    .or(local_user::totp_last_step.lt(step)),
+            ),
+        )
+        .set((
+            local_user::totp_last_step.eq(step),
+            local_user::totp_failed_attempts.eq(0),
+        ))
+        .execute(conn.deref_mut())?;
+        Ok(updated == 1)
+    }
+
+    /// Remove a recovery code with the given hash. Returns false if there is no such code, for
+    /// example because it was just used by a concurrent request.
+    pub fn use_recovery_code(
+        id: LocalUserId,
+        hash: &str,
+        context: &IbisContext,
+    ) -> BackendResult<bool> {
+        let mut conn = context.db_pool.get()?;
+        let updated = diesel::update(
+            local_user::table
+                .find(id)
+                .filter(totp_not_locked())
+                .filter(local_user::totp_recovery_codes.contains(vec![hash])),
+        )
+        .set((
+            local_user::totp_recovery_codes.eq(array_remove(local_user::totp_recovery_codes, hash)),
+            local_user::totp_failed_attempts.eq(0),
+        ))
+        .execute(conn.deref_mut())?;
+        Ok(updated == 1)
+    }
+
+    /// Count an invalid two-factor code. The count starts again after the lockout is over.
+    pub fn totp_failed(id: LocalUserId, context: &IbisContext) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        sql_query(
+            "update local_user set totp_failed_attempts = case when totp_failed_at > $2
+                then totp_failed_attempts + 1 else 1 end, totp_failed_at = now() where id = $1",
+        )
+        .bind::<Integer, _>(id.0)
+        .bind::<Timestamptz, _>(Utc::now() - LOCKOUT)
+        .execute(conn.deref_mut())?;
+        Ok(())
+    }
+}
+
+/// Negation of [crate::totp::is_locked] in SQL.
+fn totp_not_locked() -> SqlLiteral<Bool> {
+    sql(&format!(
+        "(totp_failed_attempts < {MAX_FAILED_ATTEMPTS} or totp_failed_at + interval '{} seconds' <= now())",
+        LOCKOUT.num_seconds()
+    ))
 }
 
 impl OAuthAccount {
@@ -367,4 +621,11 @@ impl OAuthAccount {
             .values(form)
             .get_result(conn.deref_mut())?)
     }
+
+    pub fn list(local_user_id: LocalUserId, context: &IbisContext) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(oauth_account::table
+            .filter(oauth_account::local_user_id.eq(local_user_id))
+            .get_results(conn.deref_mut())?)
+    }
 }
@@ -0,0 +1,118 @@
+use super::IbisContext;
+use crate::{common::newtypes::LocalUserId, error::BackendResult};
+use chrono::{DateTime, Utc};
+use diesel::{
+    ExpressionMethods,
+    Insertable,
+    QueryDsl,
+    Queryable,
+    RunQueryDsl,
+    Selectable,
+    delete,
+    dsl::insert_into,
+};
+use ibis_database_schema::{web_push_key, web_push_subscription};
+use std::ops::DerefMut;
+
+/// Browser which should receive push messages for the user.
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = web_push_subscription, check_for_backend(diesel::pg::Pg))]
+pub struct WebPushSubscription {
+    pub id: i32,
+    pub local_user_id: LocalUserId,
+    /// Url of the push service where messages are sent to
+    pub endpoint: String,
+    /// Public key of the browser, base64url encoded
+    pub p256dh: String,
+    /// Authentication secret of the browser, base64url encoded
+    pub auth: String,
+    pub published: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = web_push_subscription, check_for_backend(diesel::pg::Pg))]
+pub struct WebPushSubscriptionInsertForm {
+    pub local_user_id: LocalUserId,
+    pub endpoint: String,
+    pub p256dh: String,
+    pub auth: String,
+}
+
+impl WebPushSubscription {
+    /// Store the subscription, replacing an existing one for the same browser.
+    pub fn create(
+        form: &WebPushSubscriptionInsertForm,
+        context: &IbisContext,
+    ) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        insert_into(web_push_subscription::table)
+            .values(form)
+            .on_conflict(web_push_subscription::endpoint)
+            .do_update()
+            .set((
+                web_push_subscription::local_user_id.eq(form.local_user_id),
+                web_push_subscription::p256dh.eq(&form.p256dh),
+                web_push_subscription::auth.eq(&form.auth),
+            ))
+            .execute(conn.deref_mut())?;
+        Ok(())
+    }
+
+    pub fn delete(
+        endpoint: &str,
+        local_user_id: LocalUserId,
+        context: &IbisContext,
+    ) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        delete(
+            web_push_subscription::table
+                .filter(web_push_subscription::endpoint.eq(endpoint))
+                .filter(web_push_subscription::local_user_id.eq(local_user_id)),
+        )
+        .execute(conn.deref_mut())?;
+        Ok(())
+    }
+
+    /// Remove a subscription which was rejected by the push service.
+    pub(crate) fn expire(id: i32, context: &IbisContext) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        delete(web_push_subscription::table.find(id)).execute(conn.deref_mut())?;
+        Ok(())
+    }
+
+    pub(crate) fn list(
+        local_user_ids: Vec<LocalUserId>,
+        context: &IbisContext,
+    ) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(web_push_subscription::table
+            .filter(web_push_subscription::local_user_id.eq_any(local_user_ids))
+            .get_results(conn.deref_mut())?)
+    }
+}
+
+/// Read the private VAPID key of the instance, or store the generated one if there is none yet.
+pub(crate) fn read_or_create_vapid_key<F>(
+    generate: F,
+    context: &IbisContext,
+) -> BackendResult<String>
+where
+    F: FnOnce() -> BackendResult<String>,
+{
+    let mut conn = context.db_pool.get()?;
+    let key = web_push_key::table
+        .select(web_push_key::private_key)
+        .first(conn.deref_mut())
+        .ok();
+    if let Some(key) = key {
+        return Ok(key);
+    }
+    insert_into(web_push_key::table)
+        .values(web_push_key::private_key.eq(generate()?))
+        .on_conflict_do_nothing()
+        .execute(conn.deref_mut())?;
+    // read again in case another thread inserted a key in the meantime
+    Ok(web_push_key::table
+        .select(web_push_key::private_key)
+        .first(conn.deref_mut())?)
+}
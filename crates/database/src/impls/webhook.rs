@@ -0,0 +1,102 @@
+use super::IbisContext;
+use crate::{
+    common::{
+        newtypes::WebhookId,
+        webhook::{Webhook, WebhookDelivery, WebhookEvent},
+    },
+    error::BackendResult,
+};
+use diesel::{
+    ExpressionMethods,
+    Insertable,
+    QueryDsl,
+    RunQueryDsl,
+    delete,
+    dsl::{insert_into, now},
+    update,
+};
+use ibis_database_schema::{webhook, webhook_delivery};
+use std::ops::DerefMut;
+
+/// Number of log entries which are returned for a webhook.
+const DELIVERY_LOG_LIMIT: i64 = 50;
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = webhook, check_for_backend(diesel::pg::Pg))]
+pub struct WebhookInsertForm {
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<WebhookEvent>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = webhook_delivery, check_for_backend(diesel::pg::Pg))]
+pub(crate) struct WebhookDeliveryInsertForm {
+    pub webhook_id: WebhookId,
+    pub event: WebhookEvent,
+    pub payload: String,
+}
+
+impl Webhook {
+    pub fn create(form: &WebhookInsertForm, context: &IbisContext) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        Ok(insert_into(webhook::table)
+            .values(form)
+            .get_result(conn.deref_mut())?)
+    }
+
+    pub fn list(context: &IbisContext) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(webhook::table
+            .order(webhook::id)
+            .get_results(conn.deref_mut())?)
+    }
+
+    pub fn delete(id: WebhookId, context: &IbisContext) -> BackendResult<()> {
+        let mut conn = context.db_pool.get()?;
+        delete(webhook::table.find(id)).execute(conn.deref_mut())?;
+        Ok(())
+    }
+}
+
+impl WebhookDelivery {
+    pub(crate) fn create(
+        form: &WebhookDeliveryInsertForm,
+        context: &IbisContext,
+    ) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        Ok(insert_into(webhook_delivery::table)
+            .values(form)
+            .get_result(conn.deref_mut())?)
+    }
+
+    /// Store the result of a delivery attempt.
+    pub(crate) fn update_attempt(
+        id: i32,
+        status: Option<i32>,
+        error: Option<String>,
+        context: &IbisContext,
+    ) -> BackendResult<Self> {
+        let mut conn = context.db_pool.get()?;
+        let success = error.is_none() && status.is_some_and(|s| (200..300).contains(&s));
+        Ok(update(webhook_delivery::table.find(id))
+            .set((
+                webhook_delivery::attempts.eq(webhook_delivery::attempts + 1),
+                webhook_delivery::success.eq(success),
+                webhook_delivery::status.eq(status),
+                webhook_delivery::error.eq(error),
+                webhook_delivery::updated.eq(now),
+            ))
+            .get_result(conn.deref_mut())?)
+    }
+
+    /// Latest deliveries to the webhook, newest first.
+    pub fn list(webhook_id: WebhookId, context: &IbisContext) -> BackendResult<Vec<Self>> {
+        let mut conn = context.db_pool.get()?;
+        Ok(webhook_delivery::table
+            .filter(webhook_delivery::webhook_id.eq(webhook_id))
+            .order(webhook_delivery::id.desc())
+            .limit(DELIVERY_LOG_LIMIT)
+            .get_results(conn.deref_mut())?)
+    }
+}
@@ -12,9 +12,17 @@ pub mod error;
 #[cfg(feature = "ssr")]
 pub mod impls;
 #[cfg(feature = "ssr")]
+pub mod proof_of_work;
+#[cfg(feature = "ssr")]
 pub mod scheduled_tasks;
 #[cfg(feature = "ssr")]
+pub mod totp;
+#[cfg(feature = "ssr")]
 pub mod utils;
+#[cfg(feature = "ssr")]
+pub mod web_push;
+#[cfg(feature = "ssr")]
+pub mod webhook;
 
 #[repr(transparent)]
 #[cfg_attr(
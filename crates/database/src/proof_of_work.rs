@@ -0,0 +1,106 @@
+//! Self-hosted bot protection with hashcash-style challenges. The server issues a signed challenge,
+//! and the client has to find a nonce so that the hash of both has a number of leading zero bits.
+//! Challenges are stateless, replays need to be prevented by the caller.
+
+use crate::{common::utils::proof_of_work_valid, error::BackendResult};
+use anyhow::anyhow;
+use chrono::{DateTime, TimeDelta, Utc};
+use ring::{
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+
+/// How long a challenge can be used after it was issued.
+pub const CHALLENGE_VALIDITY: TimeDelta = TimeDelta::minutes(10);
+
+/// Create a new challenge in the format `expires.random.signature`. The difficulty is part of
+/// the signature, so that challenges become invalid if it is changed.
+pub fn create_challenge(
+    secret: &str,
+    difficulty: u32,
+    now: DateTime<Utc>,
+) -> BackendResult<String> {
+    let mut random = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut random)
+        .map_err(|_| anyhow!("Failed to generate challenge"))?;
+    let payload = format!(
+        "{}.{}",
+        (now + CHALLENGE_VALIDITY).timestamp(),
+        hex::encode(random)
+    );
+    let signature = hmac::sign(&key(secret), signed_data(&payload, difficulty).as_bytes());
+    Ok(format!("{payload}.{}", hex::encode(signature)))
+}
+
+/// Check that the challenge was issued by this server, is not expired, and is solved by the nonce.
+pub fn verify_challenge(
+    secret: &str,
+    challenge: &str,
+    nonce: u64,
+    difficulty: u32,
+    now: DateTime<Utc>,
+) -> BackendResult<()> {
+    let invalid = || anyhow!("Invalid proof-of-work challenge");
+    let (payload, signature) = challenge.rsplit_once('.').ok_or_else(invalid)?;
+    let signature = hex::decode(signature).map_err(|_| invalid())?;
+    hmac::verify(
+        &key(secret),
+        signed_data(payload, difficulty).as_bytes(),
+        &signature,
+    )
+    .map_err(|_| invalid())?;
+    let expires = payload
+        .split_once('.')
+        .and_then(|(e, _)| e.parse::<i64>().ok())
+        .ok_or_else(invalid)?;
+    if expires < now.timestamp() {
+        return Err(anyhow!("Proof-of-work challenge is expired").into());
+    }
+    if !proof_of_work_valid(challenge, nonce, difficulty) {
+        return Err(anyhow!("Proof-of-work challenge is not solved").into());
+    }
+    Ok(())
+}
+
+fn key(secret: &str) -> hmac::Key {
+    hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes())
+}
+
+fn signed_data(payload: &str, difficulty: u32) -> String {
+    format!("{payload}.{difficulty}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::utils::solve_proof_of_work;
+
+    #[test]
+    fn test_proof_of_work() -> BackendResult<()> {
+        let now = Utc::now();
+        let challenge = create_challenge("secret", 8, now)?;
+        let nonce = solve_proof_of_work(&challenge, 8);
+        verify_challenge("secret", &challenge, nonce, 8, now)?;
+
+        // wrong secret, changed difficulty, expired or unsolved
+        assert!(verify_challenge("other", &challenge, nonce, 8, now).is_err());
+        assert!(verify_challenge("secret", &challenge, nonce, 7, now).is_err());
+        let later = now + CHALLENGE_VALIDITY + TimeDelta::seconds(1);
+        assert!(verify_challenge("secret", &challenge, nonce, 8, later).is_err());
+        let wrong_nonce = (0..)
+            .find(|n| !proof_of_work_valid(&challenge, *n, 8))
+            .unwrap_or_default();
+        assert!(verify_challenge("secret", &challenge, wrong_nonce, 8, now).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_leading_zeros() {
+        // sha256("abc:3") starts with 0x0f, sha256("abc:4959") with 0x0006
+        assert!(proof_of_work_valid("abc", 3, 4));
+        assert!(!proof_of_work_valid("abc", 3, 5));
+        assert!(proof_of_work_valid("abc", 4959, 13));
+        assert!(!proof_of_work_valid("abc", 4959, 14));
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{error::BackendResult, impls::DbPool};
+use crate::{
+    common::contributions::PersonStats,
+    email::notification::send_email_digests,
+    error::BackendResult,
+    impls::{DbPool, IbisContext},
+};
 use clokwerk::{Scheduler, TimeUnits};
 use diesel::{
     ExpressionMethods,
@@ -8,22 +13,34 @@ use diesel::{
     sql_query,
     sql_types::Timestamptz,
 };
-use ibis_database_schema::sent_activity;
+use ibis_database_schema::{sent_activity, session};
 use log::{error, info};
 use std::time::Duration;
+use tokio::runtime::Handle;
 
-pub fn start(pool: DbPool) {
+pub fn start(context: IbisContext, runtime: Handle) {
     let mut scheduler = Scheduler::new();
+    let pool = context.db_pool.clone();
 
     active_counts(&pool).inspect_err(|e| error!("{e}")).ok();
     cleanup_sent_activities(&pool)
         .inspect_err(|e| error!("{e}"))
         .ok();
+    cleanup_sessions(&pool).inspect_err(|e| error!("{e}")).ok();
+    person_stats(&pool).inspect_err(|e| error!("{e}")).ok();
     scheduler.every(1.hour()).run(move || {
         active_counts(&pool).inspect_err(|e| error!("{e}")).ok();
+        person_stats(&pool).inspect_err(|e| error!("{e}")).ok();
         cleanup_sent_activities(&pool)
             .inspect_err(|e| error!("{e}"))
             .ok();
+        cleanup_sessions(&pool).inspect_err(|e| error!("{e}")).ok();
+    });
+    scheduler.every(1.hour()).run(move || {
+        runtime
+            .block_on(send_email_digests(&context))
+            .inspect_err(|e| error!("{e}"))
+            .ok();
     });
 
     let _ = scheduler.watch_thread(Duration::from_secs(60));
@@ -44,6 +61,14 @@ fn active_counts(pool: &DbPool) -> BackendResult<()> {
     Ok(())
 }
 
+fn person_stats(pool: &DbPool) -> BackendResult<()> {
+    info!("Updating person stats");
+    let mut conn = pool.get()?;
+    PersonStats::refresh(None, &mut conn)?;
+    info!("Done with person stats");
+    Ok(())
+}
+
 fn cleanup_sent_activities(pool: &DbPool) -> BackendResult<()> {
     use diesel::dsl::IntervalDsl;
     info!("Cleanup sent activities");
@@ -59,6 +84,17 @@ fn cleanup_sent_activities(pool: &DbPool) -> BackendResult<()> {
     Ok(())
 }
 
+/// Delete sessions whose login token has expired.
+fn cleanup_sessions(pool: &DbPool) -> BackendResult<()> {
+    use diesel::dsl::IntervalDsl;
+    let mut conn = pool.get()?;
+
+    let now = diesel::dsl::now.into_sql::<Timestamptz>();
+    diesel::delete(session::table.filter(session::published.lt(now - IntervalDsl::days(365))))
+        .execute(&mut conn)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -68,7 +104,13 @@ mod test {
     fn test_scheduled_tasks() -> BackendResult<()> {
         let context = IbisContext::init(IbisConfig::read()?, false)?;
         active_counts(&context.db_pool)?;
+        person_stats(&context.db_pool)?;
         cleanup_sent_activities(&context.db_pool)?;
+        cleanup_sessions(&context.db_pool)?;
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(send_email_digests(&context))?;
         Ok(())
     }
 }
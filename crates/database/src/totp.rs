@@ -0,0 +1,199 @@
+//! Time-based one-time passwords (RFC 6238) for two-factor authentication, compatible with common
+//! authenticator apps. Secrets are stored base32 encoded, as they are shown to the user.
+
+use crate::{common::user::LocalUser, error::BackendResult};
+use anyhow::anyhow;
+use chrono::{DateTime, Duration, Utc};
+use ring::{
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+/// Validity period of a single code in seconds.
+const STEP: i64 = 30;
+
+/// Number of digits in each code.
+const DIGITS: u32 = 6;
+
+/// Also accept codes from the previous and next step, to allow for clock drift.
+const ALLOWED_DRIFT: i64 = 1;
+
+/// Number of recovery codes which are generated when two-factor authentication is enabled.
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Number of invalid codes after which no more codes are accepted for [LOCKOUT].
+pub const MAX_FAILED_ATTEMPTS: i32 = 5;
+
+/// Time after the last invalid code until codes are accepted again.
+pub const LOCKOUT: Duration = Duration::minutes(15);
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Generate a new random secret, base32 encoded.
+pub fn generate_secret() -> BackendResult<String> {
+    let mut secret = [0u8; 20];
+    SystemRandom::new()
+        .fill(&mut secret)
+        .map_err(|_| anyhow!("Failed to generate secret"))?;
+    Ok(base32_encode(&secret))
+}
+
+/// Link which can be opened by authenticator apps, or encoded as QR code.
+pub fn otpauth_uri(secret: &str, username: &str, issuer: &str) -> BackendResult<Url> {
+    let mut url = Url::parse(&format!("otpauth://totp/{issuer}:{username}"))?;
+    url.query_pairs_mut()
+        .append_pair("secret", secret)
+        .append_pair("issuer", issuer)
+        .append_pair("digits", &DIGITS.to_string())
+        .append_pair("period", &STEP.to_string());
+    Ok(url)
+}
+
+/// Check if the code entered by the user is valid for the given secret at this time. Returns the
+/// time step of the code, which must be stored so that the code can't be used again.
+pub fn verify_code(secret: &str, code: &str, now: DateTime<Utc>) -> BackendResult<Option<i64>> {
+    let secret = base32_decode(secret)?;
+    let code = code.trim();
+    if code.len() != DIGITS as usize {
+        return Ok(None);
+    }
+    let Ok(code) = code.parse::<u32>() else {
+        return Ok(None);
+    };
+    let step = now.timestamp() / STEP;
+    Ok((step - ALLOWED_DRIFT..=step + ALLOWED_DRIFT)
+        .find(|s| generate_code(&secret, *s as u64) == code))
+}
+
+/// No codes are accepted after too many invalid ones, to prevent guessing.
+pub fn is_locked(user: &LocalUser, now: DateTime<Utc>) -> bool {
+    user.totp_failed_attempts >= MAX_FAILED_ATTEMPTS
+        && user.totp_failed_at.is_some_and(|t| t + LOCKOUT > now)
+}
+
+/// Code which an authenticator app shows at the given time.
+pub fn code_at(secret: &str, time: DateTime<Utc>) -> BackendResult<String> {
+    let secret = base32_decode(secret)?;
+    let code = generate_code(&secret, (time.timestamp() / STEP) as u64);
+    Ok(format!("{code:0width$}", width = DIGITS as usize))
+}
+
+/// Generate random one-time recovery codes, which can be used to login without the authenticator
+/// app. Returns the codes to show to the user, and the hashes to store in the database.
+pub fn generate_recovery_codes() -> BackendResult<(Vec<String>, Vec<String>)> {
+    let rng = SystemRandom::new();
+    let mut codes = vec![];
+    for _ in 0..RECOVERY_CODE_COUNT {
+        let mut bytes = [0u8; 5];
+        rng.fill(&mut bytes)
+            .map_err(|_| anyhow!("Failed to generate recovery code"))?;
+        let code = base32_encode(&bytes).to_lowercase();
+        codes.push(format!("{}-{}", &code[..4], &code[4..]));
+    }
+    let hashes = codes.iter().map(|c| hash_recovery_code(c)).collect();
+    Ok((codes, hashes))
+}
+
+/// Recovery codes are random so a plain hash is sufficient, and allows finding a matching code
+/// without checking each one with bcrypt.
+pub fn hash_recovery_code(code: &str) -> String {
+    let code = code.trim().to_lowercase().replace('-', "");
+    hex::encode(Sha256::digest(code.as_bytes()))
+}
+
+/// HOTP value for the given counter (RFC 4226).
+fn generate_code(secret: &[u8], counter: u64) -> u32 {
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret);
+    let hash = hmac::sign(&key, &counter.to_be_bytes());
+    let hash = hash.as_ref();
+    let offset = (hash[hash.len() - 1] & 0xf) as usize;
+    let value = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    value % 10u32.pow(DIGITS)
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in data {
+        buffer = (buffer << 8) | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(data: &str) -> BackendResult<Vec<u8>> {
+    let mut out = vec![];
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in data.trim_end_matches('=').bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|a| *a == c.to_ascii_uppercase())
+            .ok_or(anyhow!("Invalid base32 character"))?;
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rfc_vectors() -> BackendResult<()> {
+        // test vectors from RFC 6238 for SHA1, truncated to six digits
+        let secret = base32_encode(b"12345678901234567890");
+        let time = |t| DateTime::from_timestamp(t, 0).ok_or(anyhow!("Invalid time"));
+        for (t, code) in [
+            (59, "287082"),
+            (1111111109, "081804"),
+            (1234567890, "005924"),
+            (2000000000, "279037"),
+        ] {
+            assert_eq!(Some(t / STEP), verify_code(&secret, code, time(t)?)?);
+        }
+        assert_eq!("081804", code_at(&secret, time(1111111109)?)?);
+        assert_eq!(Some(1), verify_code(&secret, "287082", time(89)?)?);
+        assert_eq!(None, verify_code(&secret, "287083", time(59)?)?);
+        assert_eq!(None, verify_code(&secret, "abcdef", time(59)?)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_base32() -> BackendResult<()> {
+        let secret = generate_secret()?;
+        assert_eq!(32, secret.len());
+        assert_eq!(secret, base32_encode(&base32_decode(&secret)?));
+        assert_eq!("MZXW6YQ", base32_encode(b"foob"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_recovery_codes() -> BackendResult<()> {
+        let (codes, hashes) = generate_recovery_codes()?;
+        assert_eq!(RECOVERY_CODE_COUNT, codes.len());
+        assert_eq!(hashes[0], hash_recovery_code(&codes[0].to_uppercase()));
+        assert_ne!(hashes[0], hashes[1]);
+        Ok(())
+    }
+}
@@ -0,0 +1,490 @@
+//! Delivery of notifications with the Web Push protocol (RFC 8030). Messages are encrypted for
+//! the browser (RFC 8291), and the instance identifies itself with a VAPID key (RFC 8292).
+
+use crate::{
+    common::{
+        email::EmailTemplate,
+        newtypes::NotificationId,
+        notifications::WebPushKey,
+        utils::http_protocol_str,
+    },
+    email::template::{NotificationItem, render_email},
+    error::BackendResult,
+    impls::{
+        IbisContext,
+        notifications::{Notification, NotificationData},
+        web_push::{WebPushSubscription, read_or_create_vapid_key},
+    },
+};
+use anyhow::anyhow;
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use chrono::{Duration, Utc};
+use log::warn;
+use minijinja::{Value, context};
+use reqwest::{Client, StatusCode, redirect::Policy};
+use ring::{
+    aead::{AES_128_GCM, Aad, LessSafeKey, Nonce, UnboundKey},
+    agreement::{ECDH_P256, EphemeralPrivateKey, UnparsedPublicKey, agree_ephemeral},
+    hkdf::{HKDF_SHA256, KeyType, Salt},
+    rand::{SecureRandom, SystemRandom},
+    signature::{ECDSA_P256_SHA256_FIXED_SIGNING, EcdsaKeyPair, KeyPair},
+};
+use serde::Serialize;
+use serde_json::json;
+use std::net::{IpAddr, SocketAddr};
+use tokio::net::lookup_host;
+use url::Url;
+
+/// Maximum record size, the whole message is sent as a single record.
+const RECORD_SIZE: u32 = 4096;
+
+/// How long the push service should keep the message if the browser is offline.
+const TTL: Duration = Duration::days(1);
+
+/// Longest comment excerpt which is included in the message.
+const MAX_BODY_LENGTH: usize = 200;
+
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Serialize)]
+struct PushMessage {
+    title: String,
+    body: String,
+    url: String,
+}
+
+/// The server sends requests to the push endpoint, so it must not point to the local network.
+/// Checked when subscribing and again before sending, as DNS records may change in between. The
+/// request is then sent to the checked addresses.
+pub async fn check_push_endpoint(endpoint: &str) -> BackendResult<()> {
+    resolve_push_endpoint(endpoint).await?;
+    Ok(())
+}
+
+/// Returns the host of the endpoint with its checked addresses, which are then used for the
+/// request so that it can't be resolved to a different address.
+async fn resolve_push_endpoint(endpoint: &str) -> BackendResult<Option<(String, Vec<SocketAddr>)>> {
+    // tests use a local push service
+    if cfg!(debug_assertions) {
+        return Ok(None);
+    }
+    let url = Url::parse(endpoint)?;
+    if url.scheme() != "https" {
+        return Err(anyhow!("Push endpoint must use https").into());
+    }
+    let host = url.host_str().ok_or(anyhow!("Push endpoint has no host"))?;
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addrs: Vec<_> = lookup_host((host, port)).await?.collect();
+    if addrs.is_empty() {
+        return Err(anyhow!("Push endpoint can't be resolved").into());
+    }
+    if addrs.iter().any(|addr| !is_public_address(addr.ip())) {
+        return Err(anyhow!("Push endpoint must be a public address").into());
+    }
+    Ok(Some((host.to_string(), addrs)))
+}
+
+/// Equivalent of the unstable [IpAddr::is_global].
+fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // "this network", shared address space, protocol assignments, benchmarking and
+                // reserved
+                || a == 0
+                || (a == 100 && (b & 0b1100_0000) == 64)
+                || (a == 192 && b == 0 && c == 0)
+                || (a == 198 && (b & 0b1111_1110) == 18)
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public_address(IpAddr::V4(ip));
+            }
+            let [a, b, ..] = ip.segments();
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // unique local, link local and documentation
+                || (a & 0xfe00) == 0xfc00
+                || (a & 0xffc0) == 0xfe80
+                || (a == 0x2001 && b == 0xdb8))
+        }
+    }
+}
+
+/// Send push messages for new notifications to all browsers which the users subscribed with.
+/// This returns immediately, while the messages are sent in the background.
+pub(crate) fn send_web_push(notifs: &[Notification], context: &IbisContext) {
+    if notifs.is_empty() {
+        return;
+    }
+    let ids = notifs.iter().map(|n| n.id).collect();
+    let context = context.clone();
+    tokio::spawn(async move {
+        send_web_push_inner(ids, &context)
+            .await
+            .inspect_err(|e| warn!("Failed to send push messages: {e}"))
+            .ok();
+    });
+}
+
+async fn send_web_push_inner(ids: Vec<NotificationId>, context: &IbisContext) -> BackendResult<()> {
+    let data = Notification::read_data(ids, context)?;
+    let local_user_ids = data.iter().map(|d| d.local_user.id).collect();
+    let subscriptions = WebPushSubscription::list(local_user_ids, context)?;
+    if subscriptions.is_empty() {
+        return Ok(());
+    }
+    let key = vapid_key_pair(context)?;
+    for data in data {
+        let Some(message) = push_message(&data, context)? else {
+            continue;
+        };
+        let message = serde_json::to_vec(&message)?;
+        for subscription in subscriptions
+            .iter()
+            .filter(|s| s.local_user_id == data.local_user.id)
+        {
+            if let Err(e) = send_message(&message, subscription, &key, context).await {
+                warn!(
+                    "Failed to send push message to {}: {e}",
+                    subscription.endpoint
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Public part of the VAPID key, which browsers need to subscribe.
+pub fn vapid_public_key(context: &IbisContext) -> BackendResult<WebPushKey> {
+    let key = vapid_key_pair(context)?;
+    Ok(WebPushKey {
+        public_key: BASE64_URL_SAFE_NO_PAD.encode(key.public_key()),
+    })
+}
+
+fn push_message(
+    data: &NotificationData,
+    context: &IbisContext,
+) -> BackendResult<Option<PushMessage>> {
+    let (kind, body) = if let Some(comment) = &data.comment {
+        let kind = if data.notification.mention {
+            "mention"
+        } else {
+            "comment"
+        };
+        (kind, comment.content.clone())
+    } else if let Some(edit) = &data.edit {
+        let kind = if data.notification.mention {
+            "mention"
+        } else {
+            "edit"
+        };
+        (kind, edit.summary.clone())
+    } else if data.conflict.is_some() {
+        // edit conflicts are shown on the website directly
+        return Ok(None);
+    } else {
+        ("article", data.article.title())
+    };
+    // the title is the same as the email subject
+    let item = NotificationItem {
+        kind,
+        creator: data.creator.title(),
+        article: data.article.title(),
+        content: Value::from(()),
+    };
+    let email = render_email(
+        EmailTemplate::Notification,
+        data.local_user.locale.as_deref(),
+        context! { items => vec![item] },
+        context,
+    )?;
+    let body: String = body.chars().take(MAX_BODY_LENGTH).collect();
+    Ok(Some(PushMessage {
+        title: email.subject,
+        body: format!("{}: {body}", data.creator.title()),
+        url: format!(
+            "{}://{}/notifications",
+            http_protocol_str(),
+            &context.conf.federation.domain
+        ),
+    }))
+}
+
+async fn send_message(
+    message: &[u8],
+    subscription: &WebPushSubscription,
+    key: &EcdsaKeyPair,
+    context: &IbisContext,
+) -> BackendResult<()> {
+    // Redirects are not followed, as they could lead to the local network
+    let mut client = Client::builder().redirect(Policy::none()).timeout(TIMEOUT);
+    if let Some((host, addrs)) = resolve_push_endpoint(&subscription.endpoint).await? {
+        client = client.resolve_to_addrs(&host, &addrs);
+    }
+    let p256dh = BASE64_URL_SAFE_NO_PAD.decode(subscription.p256dh.trim_end_matches('='))?;
+    let auth = BASE64_URL_SAFE_NO_PAD.decode(subscription.auth.trim_end_matches('='))?;
+    let body = encrypt(message, &p256dh, &auth)?;
+    let authorization = vapid_authorization(&subscription.endpoint, key, context)?;
+    let res = client
+        .build()?
+        .post(&subscription.endpoint)
+        .header("TTL", TTL.num_seconds())
+        .header("Content-Encoding", "aes128gcm")
+        .header("Content-Type", "application/octet-stream")
+        .header("Authorization", authorization)
+        .body(body)
+        .send()
+        .await?;
+    match res.status() {
+        // the browser unsubscribed
+        StatusCode::NOT_FOUND | StatusCode::GONE => {
+            WebPushSubscription::expire(subscription.id, context)?;
+        }
+        status if !status.is_success() => {
+            return Err(anyhow!("Push service returned {status}").into());
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn vapid_key_pair(context: &IbisContext) -> BackendResult<EcdsaKeyPair> {
+    let rng = SystemRandom::new();
+    let generate = || {
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng)?;
+        Ok(BASE64_URL_SAFE_NO_PAD.encode(pkcs8.as_ref()))
+    };
+    let pkcs8 = BASE64_URL_SAFE_NO_PAD.decode(read_or_create_vapid_key(generate, context)?)?;
+    Ok(EcdsaKeyPair::from_pkcs8(
+        &ECDSA_P256_SHA256_FIXED_SIGNING,
+        &pkcs8,
+        &rng,
+    )?)
+}
+
+/// Signed JWT which proves to the push service that the message comes from this instance.
+fn vapid_authorization(
+    endpoint: &str,
+    key: &EcdsaKeyPair,
+    context: &IbisContext,
+) -> BackendResult<String> {
+    let endpoint: Url = endpoint.parse()?;
+    let audience = endpoint.origin().ascii_serialization();
+    let subject = match &context.conf.email {
+        Some(email) => format!("mailto:{}", email.from_address),
+        None => format!(
+            "{}://{}",
+            http_protocol_str(),
+            context.conf.federation.domain
+        ),
+    };
+    let header = json!({ "typ": "JWT", "alg": "ES256" });
+    let claims = json!({
+        "aud": audience,
+        "exp": (Utc::now() + Duration::hours(12)).timestamp(),
+        "sub": subject,
+    });
+    let unsigned = format!(
+        "{}.{}",
+        BASE64_URL_SAFE_NO_PAD.encode(header.to_string()),
+        BASE64_URL_SAFE_NO_PAD.encode(claims.to_string())
+    );
+    let signature = key.sign(&SystemRandom::new(), unsigned.as_bytes())?;
+    Ok(format!(
+        "vapid t={unsigned}.{}, k={}",
+        BASE64_URL_SAFE_NO_PAD.encode(signature.as_ref()),
+        BASE64_URL_SAFE_NO_PAD.encode(key.public_key())
+    ))
+}
+
+/// Length of HKDF output.
+struct Len(usize);
+
+impl KeyType for Len {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+fn hkdf(salt: &[u8], ikm: &[u8], info: &[u8], len: usize) -> BackendResult<Vec<u8>> {
+    let mut out = vec![0; len];
+    Salt::new(HKDF_SHA256, salt)
+        .extract(ikm)
+        .expand(&[info], Len(len))?
+        .fill(&mut out)?;
+    Ok(out)
+}
+
+/// Keys for content encryption, derived as described in RFC 8291 section 3.4.
+fn derive_keys(
+    ecdh_secret: &[u8],
+    auth: &[u8],
+    salt: &[u8],
+    ua_public: &[u8],
+    as_public: &[u8],
+) -> BackendResult<(Vec<u8>, Vec<u8>)> {
+    let key_info = [b"WebPush: info\0", ua_public, as_public].concat();
+    let ikm = hkdf(auth, ecdh_secret, &key_info, 32)?;
+    let cek = hkdf(salt, &ikm, b"Content-Encoding: aes128gcm\0", 16)?;
+    let nonce = hkdf(salt, &ikm, b"Content-Encoding: nonce\0", 12)?;
+    Ok((cek, nonce))
+}
+
+/// Encrypt the message for the browser with the `aes128gcm` content encoding.
+fn encrypt(message: &[u8], ua_public: &[u8], auth: &[u8]) -> BackendResult<Vec<u8>> {
+    let rng = SystemRandom::new();
+    let mut salt = [0; 16];
+    rng.fill(&mut salt)?;
+    let private_key = EphemeralPrivateKey::generate(&ECDH_P256, &rng)?;
+    let as_public = private_key.compute_public_key()?;
+    let ecdh_secret = agree_ephemeral(
+        private_key,
+        &UnparsedPublicKey::new(&ECDH_P256, ua_public),
+        |secret| secret.to_vec(),
+    )?;
+    encrypt_record(
+        message,
+        &ecdh_secret,
+        &salt,
+        ua_public,
+        as_public.as_ref(),
+        auth,
+    )
+}
+
+/// Encrypt the message as a single record, and prepend the header with the salt and the public
+/// key of the server.
+fn encrypt_record(
+    message: &[u8],
+    ecdh_secret: &[u8],
+    salt: &[u8],
+    ua_public: &[u8],
+    as_public: &[u8],
+    auth: &[u8],
+) -> BackendResult<Vec<u8>> {
+    let (cek, nonce) = derive_keys(ecdh_secret, auth, salt, ua_public, as_public)?;
+
+    // single record, so it ends with the padding delimiter for the last record
+    let mut content = [message, &[2]].concat();
+    let key = LessSafeKey::new(UnboundKey::new(&AES_128_GCM, &cek)?);
+    key.seal_in_place_append_tag(
+        Nonce::try_assume_unique_for_key(&nonce)?,
+        Aad::empty(),
+        &mut content,
+    )?;
+
+    let mut body = salt.to_vec();
+    body.extend_from_slice(&RECORD_SIZE.to_be_bytes());
+    body.push(as_public.len() as u8);
+    body.extend_from_slice(as_public);
+    body.extend_from_slice(&content);
+    Ok(body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Decrypt the message like a browser would.
+    fn decrypt(
+        body: &[u8],
+        private_key: EphemeralPrivateKey,
+        ua_public: &[u8],
+        auth: &[u8],
+    ) -> BackendResult<Vec<u8>> {
+        let salt = &body[..16];
+        let id_len = body[20] as usize;
+        let as_public = &body[21..21 + id_len];
+        let ecdh_secret = agree_ephemeral(
+            private_key,
+            &UnparsedPublicKey::new(&ECDH_P256, as_public),
+            |secret| secret.to_vec(),
+        )?;
+        let (cek, nonce) = derive_keys(&ecdh_secret, auth, salt, ua_public, as_public)?;
+        let mut content = body[21 + id_len..].to_vec();
+        let key = LessSafeKey::new(UnboundKey::new(&AES_128_GCM, &cek)?);
+        let plain = key.open_in_place(
+            Nonce::try_assume_unique_for_key(&nonce)?,
+            Aad::empty(),
+            &mut content,
+        )?;
+        Ok(plain.to_vec())
+    }
+
+    #[test]
+    fn test_web_push_encryption() -> BackendResult<()> {
+        let rng = SystemRandom::new();
+        let ua_private = EphemeralPrivateKey::generate(&ECDH_P256, &rng)?;
+        let ua_public = ua_private.compute_public_key()?;
+        let mut auth = [0; 16];
+        rng.fill(&mut auth)?;
+
+        let message = br#"{"title":"New comment"}"#;
+        let body = encrypt(message, ua_public.as_ref(), &auth)?;
+        assert_eq!(&RECORD_SIZE.to_be_bytes(), &body[16..20]);
+
+        let decrypted = decrypt(&body, ua_private, ua_public.as_ref(), &auth)?;
+        assert_eq!(Some(&2), decrypted.last());
+        assert_eq!(message, &decrypted[..decrypted.len() - 1]);
+        Ok(())
+    }
+
+    /// Example from RFC 8291 Appendix A. Ring doesn't allow importing the private keys, so this
+    /// starts from the shared ECDH secret.
+    #[test]
+    fn test_web_push_encryption_rfc_example() -> BackendResult<()> {
+        let decode = |s: &str| BASE64_URL_SAFE_NO_PAD.decode(s);
+        let message = b"When I grow up, I want to be a watermelon";
+        let as_public = decode(
+            "BP4z9KsN6nGRTbVYI_c7VJSPQTBtkgcy27mlmlMoZIIgDll6e3vCYLocInmYWAmS6TlzAC8wEqKK6PBru3jl7A8",
+        )?;
+        let ua_public = decode(
+            "BCVxsr7N_eNgVRqvHtD0zTZsEc6-VV-JvLexhqUzORcxaOzi6-AYWXvTBHm4bjyPjs7Vd8pZGH6SRpkNtoIAiw4",
+        )?;
+        let ecdh_secret = decode("kyrL1jIIOHEzg3sM2ZWRHDRB62YACZhhSlknJ672kSs")?;
+        let salt = decode("DGv6ra1nlYgDCS1FRnbzlw")?;
+        let auth = decode("BTBZMqHH6r4Tts7J_aSIgg")?;
+
+        let (cek, nonce) = derive_keys(&ecdh_secret, &auth, &salt, &ua_public, &as_public)?;
+        assert_eq!(decode("oIhVW04MRdy2XN9CiKLxTg")?, cek);
+        assert_eq!(decode("4h_95klXJ5E_qnoN")?, nonce);
+
+        let body = encrypt_record(message, &ecdh_secret, &salt, &ua_public, &as_public, &auth)?;
+        let expected = "DGv6ra1nlYgDCS1FRnbzlwAAEABBBP4z9KsN6nGRTbVYI_c7VJSPQTBtkgcy27mlmlMoZIIgDll6e3vCYLocInmYWAmS6TlzAC8wEqKK6PBru3jl7A_yl95bQpu6cVPTpK4Mqgkf1CXztLVBSt2Ks3oZwbuwXPXLWyouBWLVWGNWQexSgSxsj_Qulcy4a-fN";
+        assert_eq!(decode(expected)?, body);
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_public_address() {
+        let public = |ip: &str| ip.parse().map(is_public_address).unwrap_or_default();
+        assert!(public("93.184.215.14"));
+        assert!(public("2606:2800:21f:cb07:6820:80da:af6b:8b2c"));
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!public(ip), "{ip}");
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Delivery of events to webhooks which are configured by the admin. Requests are sent in the
+//! background and retried on failure, with each attempt recorded in the delivery log.
+
+use crate::{
+    common::webhook::{Webhook, WebhookDelivery, WebhookEvent},
+    error::BackendResult,
+    impls::{IbisContext, webhook::WebhookDeliveryInsertForm},
+};
+use chrono::Utc;
+use log::warn;
+use ring::hmac;
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+
+/// Maximum number of requests for a single delivery.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry, which is multiplied by four for each further retry.
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Send the event with its data to all webhooks which accept it. This returns immediately, while
+/// the requests are sent in the background.
+pub fn send_webhooks<T: Serialize>(
+    event: WebhookEvent,
+    data: &T,
+    context: &IbisContext,
+) -> BackendResult<()> {
+    let webhooks: Vec<_> = Webhook::list(context)?
+        .into_iter()
+        .filter(|w| w.accepts(event))
+        .collect();
+    if webhooks.is_empty() {
+        return Ok(());
+    }
+    let payload = json!({
+        "event": event,
+        "instance": context.conf.federation.domain,
+        "published": Utc::now(),
+        "data": data,
+    })
+    .to_string();
+    for webhook in webhooks {
+        let form = WebhookDeliveryInsertForm {
+            webhook_id: webhook.id,
+            event,
+            payload: payload.clone(),
+        };
+        let delivery = WebhookDelivery::create(&form, context)?;
+        let context = context.clone();
+        tokio::spawn(async move {
+            deliver(&webhook, delivery, &context)
+                .await
+                .inspect_err(|e| warn!("Failed to deliver webhook to {}: {e}", webhook.url))
+                .ok();
+        });
+    }
+    Ok(())
+}
+
+async fn deliver(
+    webhook: &Webhook,
+    mut delivery: WebhookDelivery,
+    context: &IbisContext,
+) -> BackendResult<()> {
+    let signature = sign(&webhook.secret, &delivery.payload);
+    let mut delay = RETRY_DELAY;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let res = context
+            .client
+            .post(&webhook.url)
+            .timeout(TIMEOUT)
+            .header("Content-Type", "application/json")
+            .header("X-Ibis-Event", delivery.event.as_str())
+            .header("X-Ibis-Delivery", delivery.id)
+            .header("X-Ibis-Signature", &signature)
+            .body(delivery.payload.clone())
+            .send()
+            .await;
+        let (status, error) = match res {
+            Ok(res) => (Some(i32::from(res.status().as_u16())), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        delivery = WebhookDelivery::update_attempt(delivery.id, status, error, context)?;
+        if delivery.success || attempt == MAX_ATTEMPTS {
+            break;
+        }
+        tokio::time::sleep(delay).await;
+        delay *= 4;
+    }
+    Ok(())
+}
+
+/// HMAC-SHA256 of the payload, so that the receiver can verify that it was sent by this instance.
+fn sign(secret: &str, payload: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, payload.as_bytes());
+    format!("sha256={}", hex::encode(tag.as_ref()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_webhook_signature() {
+        // example from https://en.wikipedia.org/wiki/HMAC#Examples
+        let signature = sign("key", "The quick brown fox jumps over the lazy dog");
+        assert_eq!(
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8",
+            signature
+        );
+    }
+}
@@ -1,5 +1,18 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    api_token (id) {
+        id -> Int4,
+        local_user_id -> Int4,
+        name -> Text,
+        token_hash -> Text,
+        #[max_length = 10]
+        scope -> Varchar,
+        published -> Timestamptz,
+        last_used -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     article (id) {
         id -> Int4,
@@ -51,6 +64,7 @@ diesel::table! {
         article_id -> Int4,
         previous_version_id -> Uuid,
         published -> Timestamptz,
+        minor -> Bool,
     }
 }
 
@@ -67,6 +81,9 @@ diesel::table! {
         previous_version_id -> Uuid,
         published -> Timestamptz,
         pending -> Bool,
+        patrolled -> Bool,
+        minor -> Bool,
+        tags -> Array<Text>,
     }
 }
 
@@ -98,6 +115,18 @@ diesel::table! {
         #[max_length = 255]
         instances_url -> Varchar,
         name -> Nullable<Text>,
+        previous_public_key -> Nullable<Text>,
+        key_rotated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    instance_block (local_user_id, instance_id) {
+        local_user_id -> Int4,
+        instance_id -> Int4,
+        #[max_length = 10]
+        kind -> Varchar,
+        published -> Timestamptz,
     }
 }
 
@@ -120,6 +149,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    invite_code (id) {
+        id -> Int4,
+        code -> Text,
+        creator_id -> Int4,
+        max_uses -> Int4,
+        uses -> Int4,
+        expires -> Nullable<Timestamptz>,
+        published -> Timestamptz,
+    }
+}
+
 diesel::table! {
     jwt_secret (id) {
         id -> Int4,
@@ -136,6 +177,30 @@ diesel::table! {
         email -> Nullable<Text>,
         email_verified -> Bool,
         email_notifications -> Bool,
+        #[max_length = 10]
+        email_digest -> Varchar,
+        #[max_length = 10]
+        locale -> Nullable<Varchar>,
+        #[max_length = 10]
+        notify_comment_reply -> Varchar,
+        #[max_length = 10]
+        notify_article_comment -> Varchar,
+        #[max_length = 10]
+        notify_article_edit -> Varchar,
+        #[max_length = 10]
+        notify_new_article -> Varchar,
+        #[max_length = 10]
+        notify_mention -> Varchar,
+        #[max_length = 10]
+        notify_minor_edit -> Varchar,
+        totp_secret -> Nullable<Text>,
+        totp_enabled -> Bool,
+        totp_recovery_codes -> Array<Text>,
+        #[max_length = 10]
+        notify_bot_edit -> Varchar,
+        totp_last_step -> Nullable<Int8>,
+        totp_failed_attempts -> Int4,
+        totp_failed_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -149,6 +214,9 @@ diesel::table! {
         edit_id -> Nullable<Int4>,
         published -> Timestamptz,
         conflict_id -> Nullable<Int4>,
+        emailed_at -> Nullable<Timestamptz>,
+        send_email -> Bool,
+        mention -> Bool,
     }
 }
 
@@ -187,6 +255,24 @@ diesel::table! {
         display_name -> Nullable<Varchar>,
         #[max_length = 1000]
         bio -> Nullable<Varchar>,
+        previous_public_key -> Nullable<Text>,
+        key_rotated_at -> Nullable<Timestamptz>,
+        also_known_as -> Array<Text>,
+        #[max_length = 255]
+        moved_to -> Nullable<Varchar>,
+        bot -> Bool,
+        bot_trusted -> Bool,
+        deleted -> Bool,
+    }
+}
+
+diesel::table! {
+    person_block (local_user_id, person_id) {
+        local_user_id -> Int4,
+        person_id -> Int4,
+        #[max_length = 10]
+        kind -> Varchar,
+        published -> Timestamptz,
     }
 }
 
@@ -197,6 +283,35 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    person_stats (person_id) {
+        person_id -> Int4,
+        edits -> Int4,
+        articles_created -> Int4,
+        comments -> Int4,
+        bytes_added -> Int8,
+        bytes_removed -> Int8,
+        first_activity -> Nullable<Timestamptz>,
+        last_activity -> Nullable<Timestamptz>,
+        updated -> Timestamptz,
+        dirty -> Bool,
+    }
+}
+
+diesel::table! {
+    registration_application (id) {
+        id -> Int4,
+        local_user_id -> Int4,
+        answer -> Text,
+        #[max_length = 10]
+        status -> Varchar,
+        admin_id -> Nullable<Int4>,
+        deny_reason -> Nullable<Text>,
+        published -> Timestamptz,
+        updated -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     sent_activity (id) {
         #[max_length = 255]
@@ -206,6 +321,62 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    session (id) {
+        id -> Int4,
+        local_user_id -> Int4,
+        token -> Uuid,
+        user_agent -> Nullable<Text>,
+        ip -> Nullable<Text>,
+        published -> Timestamptz,
+        last_seen -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    web_push_key (id) {
+        id -> Int4,
+        private_key -> Text,
+    }
+}
+
+diesel::table! {
+    web_push_subscription (id) {
+        id -> Int4,
+        local_user_id -> Int4,
+        endpoint -> Text,
+        p256dh -> Text,
+        auth -> Text,
+        published -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    webhook (id) {
+        id -> Int4,
+        url -> Text,
+        secret -> Text,
+        events -> Array<Text>,
+        published -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    webhook_delivery (id) {
+        id -> Int4,
+        webhook_id -> Int4,
+        event -> Text,
+        payload -> Text,
+        attempts -> Int4,
+        success -> Bool,
+        status -> Nullable<Int4>,
+        error -> Nullable<Text>,
+        published -> Timestamptz,
+        updated -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::joinable!(api_token -> local_user (local_user_id));
 diesel::joinable!(article -> instance (instance_id));
 diesel::joinable!(article_follow -> article (article_id));
 diesel::joinable!(article_follow -> local_user (local_user_id));
@@ -216,8 +387,11 @@ diesel::joinable!(conflict -> person (creator_id));
 diesel::joinable!(edit -> article (article_id));
 diesel::joinable!(edit -> person (creator_id));
 diesel::joinable!(email_verification -> local_user (local_user_id));
+diesel::joinable!(instance_block -> instance (instance_id));
+diesel::joinable!(instance_block -> local_user (local_user_id));
 diesel::joinable!(instance_follow -> instance (instance_id));
 diesel::joinable!(instance_follow -> person (follower_id));
+diesel::joinable!(invite_code -> local_user (creator_id));
 diesel::joinable!(local_user -> person (person_id));
 diesel::joinable!(notification -> article (article_id));
 diesel::joinable!(notification -> comment (comment_id));
@@ -227,8 +401,15 @@ diesel::joinable!(notification -> local_user (local_user_id));
 diesel::joinable!(notification -> person (creator_id));
 diesel::joinable!(oauth_account -> local_user (local_user_id));
 diesel::joinable!(password_reset_request -> local_user (local_user_id));
+diesel::joinable!(person_block -> local_user (local_user_id));
+diesel::joinable!(person_block -> person (person_id));
+diesel::joinable!(person_stats -> person (person_id));
+diesel::joinable!(session -> local_user (local_user_id));
+diesel::joinable!(web_push_subscription -> local_user (local_user_id));
+diesel::joinable!(webhook_delivery -> webhook (webhook_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_token,
     article,
     article_follow,
     comment,
@@ -236,14 +417,24 @@ diesel::allow_tables_to_appear_in_same_query!(
     edit,
     email_verification,
     instance,
+    instance_block,
     instance_follow,
     instance_stats,
+    invite_code,
     jwt_secret,
     local_user,
     notification,
     oauth_account,
     password_reset_request,
     person,
+    person_block,
     person_follow,
+    person_stats,
+    registration_application,
     sent_activity,
+    session,
+    web_push_key,
+    web_push_subscription,
+    webhook,
+    webhook_delivery,
 );
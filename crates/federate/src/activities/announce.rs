@@ -34,20 +34,28 @@ pub struct AnnounceActivity {
 }
 
 impl AnnounceActivity {
-    pub async fn send(
+    pub(crate) fn new(
         object: AnnouncableActivities,
+        instance: &InstanceWrapper,
         context: &Data<IbisContext>,
-    ) -> BackendResult<()> {
+    ) -> BackendResult<Self> {
         let id = generate_activity_id(context)?;
-        let instance: InstanceWrapper = Instance::read_local(context)?.into();
-        let announce = AnnounceActivity {
+        Ok(AnnounceActivity {
             actor: instance.id().into(),
             to: vec![public()],
             cc: vec![instance.followers_url()?],
             object,
             kind: AnnounceType::Announce,
             id,
-        };
+        })
+    }
+
+    pub async fn send(
+        object: AnnouncableActivities,
+        context: &Data<IbisContext>,
+    ) -> BackendResult<()> {
+        let instance: InstanceWrapper = Instance::read_local(context)?.into();
+        let announce = Self::new(object, &instance, context)?;
 
         // Send to followers of instance
         let follower_inboxes: Vec<_> = Instance::read_followers(instance.id, context)?
@@ -25,12 +25,15 @@ use ibis_database::{
     common::{
         article::{Article, Edit, can_edit_article},
         instance::Instance,
+        webhook::WebhookEvent,
     },
     error::{BackendError, BackendResult},
     impls::{IbisContext, article::DbArticleForm},
+    webhook::send_webhooks,
 };
 use log::warn;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use url::Url;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -161,6 +164,10 @@ impl ActivityHandler for CreateOrEditArticle {
             Ok(applied) => {
                 let edit = EditWrapper::from_json(self.object.clone(), context).await?;
                 let article = Article::update_text(edit.article_id, &applied, context)?;
+                if self.kind != CreateOrEditType::Create {
+                    let data = json!({ "article": article, "edit": edit });
+                    send_webhooks(WebhookEvent::ArticleEdited, &data, context)?;
+                }
                 if article.local {
                     AnnounceActivity::send(AnnouncableActivities::EditArticle(self), context)
                         .await?;
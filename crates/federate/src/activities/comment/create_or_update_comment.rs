@@ -1,12 +1,14 @@
 use crate::{
+    activities::announce::AnnounceActivity,
     generate_activity_id,
+    mentions::MentionOrValue,
     objects::{
         comment::{ApubComment, CommentWrapper},
         instance::InstanceWrapper,
         user::PersonWrapper,
     },
     routes::AnnouncableActivities,
-    send_activity_to_instance,
+    send_ibis_activity,
 };
 use activitypub_federation::{
     config::Data,
@@ -44,9 +46,11 @@ pub struct CreateOrUpdateComment {
 }
 
 impl CreateOrUpdateComment {
-    pub async fn send(comment: &CommentWrapper, context: &Data<IbisContext>) -> BackendResult<()> {
-        let instance: InstanceWrapper = Instance::read_for_comment(comment.id, context)?.into();
-
+    pub(crate) async fn new(
+        comment: &CommentWrapper,
+        instance: &InstanceWrapper,
+        context: &Data<IbisContext>,
+    ) -> BackendResult<Self> {
         let kind = if comment.updated.is_none() {
             CreateOrUpdateType::Create
         } else {
@@ -54,17 +58,48 @@ impl CreateOrUpdateComment {
         };
         let object = comment.clone().into_json(context).await?;
         let id = generate_activity_id(context)?;
-        let activity = Self {
+        Ok(Self {
             actor: object.attributed_to.clone(),
             object,
             to: vec![public(), instance.ap_id.clone().into()],
             cc: vec![],
             kind,
             id,
-        };
+        })
+    }
+
+    pub async fn send(comment: &CommentWrapper, context: &Data<IbisContext>) -> BackendResult<()> {
+        let instance: InstanceWrapper = Instance::read_for_comment(comment.id, context)?.into();
+        let activity = Self::new(comment, &instance, context).await?;
+        // remote users who were mentioned may not follow the article instance, so send the
+        // activity to them directly
+        let mut inboxes: Vec<Url> = vec![];
+        for tag in &activity.object.tag {
+            let MentionOrValue::Mention(mention) = tag else {
+                continue;
+            };
+            let Ok(person) = Person::read_from_ap_id(&mention.href.inner().clone().into(), context)
+            else {
+                continue;
+            };
+            let inbox = person.inbox_url.parse()?;
+            if !person.local && !inboxes.contains(&inbox) {
+                inboxes.push(inbox);
+            }
+        }
         let activity = AnnouncableActivities::CreateOrUpdateComment(activity);
         let creator: PersonWrapper = Person::read(comment.creator_id, context)?.into();
-        send_activity_to_instance(&creator, activity, &instance, context).await?;
+        if instance.local {
+            AnnounceActivity::send(activity.clone(), context).await?;
+        } else {
+            let inbox = instance.inbox_url.parse()?;
+            if !inboxes.contains(&inbox) {
+                inboxes.push(inbox);
+            }
+        }
+        if !inboxes.is_empty() {
+            send_ibis_activity(&creator, activity, inboxes, context).await?;
+        }
         Ok(())
     }
 }
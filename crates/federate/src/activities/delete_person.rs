@@ -0,0 +1,94 @@
+use crate::{generate_activity_id, objects::user::PersonWrapper, send_ibis_activity};
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::{activity::DeleteType, public},
+    protocol::{
+        helpers::deserialize_one_or_many,
+        verification::{verify_domains_match, verify_urls_match},
+    },
+    traits::ActivityHandler,
+};
+use ibis_database::{
+    common::{instance::Instance, user::Person},
+    error::{BackendError, BackendResult},
+    impls::IbisContext,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Sent when a user deletes their account, so that other instances remove it as well.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletePerson {
+    pub actor: ObjectId<PersonWrapper>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub to: Vec<Url>,
+    pub object: ObjectId<PersonWrapper>,
+    #[serde(rename = "type")]
+    pub kind: DeleteType,
+    pub id: Url,
+    /// Content of the user is removed as well. This field is required, so that the activity
+    /// can be distinguished from other `Delete` activities.
+    pub remove_data: bool,
+}
+
+impl DeletePerson {
+    /// Notify all known instances, as well as followers of the person and of the local instance.
+    /// This needs to happen before the person is deleted locally, as the private key is needed
+    /// to sign the activity.
+    pub async fn send(person: &PersonWrapper, context: &Data<IbisContext>) -> BackendResult<()> {
+        let delete = DeletePerson {
+            actor: person.ap_id.clone().into(),
+            to: vec![public()],
+            object: person.ap_id.clone().into(),
+            kind: Default::default(),
+            id: generate_activity_id(context)?,
+            remove_data: true,
+        };
+        let local_instance = Instance::read_local(context)?;
+        let mut inboxes: Vec<_> = Instance::list(context)?
+            .iter()
+            .map(|i| i.inbox_url())
+            .chain(
+                Person::read_followers(person.id, context)?
+                    .into_iter()
+                    .chain(Instance::read_followers(local_instance.id, context)?)
+                    .filter(|p| !p.local)
+                    .map(|p| p.inbox_url()),
+            )
+            .collect();
+        inboxes.sort();
+        inboxes.dedup();
+        send_ibis_activity(person, delete, inboxes, context).await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for DeletePerson {
+    type DataType = IbisContext;
+    type Error = BackendError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _context: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_urls_match(self.actor.inner(), self.object.inner())?;
+        verify_domains_match(self.actor.inner(), &self.id)?;
+        Ok(())
+    }
+
+    async fn receive(self, context: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        let person = Person::read_from_ap_id(&self.object.into(), context)?;
+        if !person.local {
+            Person::delete(person.id, context)?;
+        }
+        Ok(())
+    }
+}
@@ -12,10 +12,9 @@ use activitypub_federation::{
     protocol::helpers::deserialize_skip_error,
     traits::{ActivityHandler, Actor},
 };
-use anyhow::anyhow;
 use either::Either;
 use ibis_database::{
-    common::instance::Instance,
+    common::{instance::Instance, user::Person},
     error::{BackendError, BackendResult},
     impls::IbisContext,
 };
@@ -88,7 +87,11 @@ impl ActivityHandler for Accept {
                 Instance::follow(&person, &instance, false, context)?;
                 Ok(())
             }
-            Either::Right(_) => Err(anyhow!("person follow not supported").into()),
+            Either::Right(person) => {
+                let follower = self.object.actor.dereference_local(context).await?;
+                Person::follow(&person, &follower, context)?;
+                Ok(())
+            }
         }
     }
 }
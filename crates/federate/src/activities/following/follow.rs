@@ -2,7 +2,7 @@ use super::InstanceOrPerson;
 use crate::{
     activities::following::accept::Accept,
     generate_activity_id,
-    objects::{instance::InstanceWrapper, user::PersonWrapper},
+    objects::user::PersonWrapper,
     send_ibis_activity,
 };
 use activitypub_federation::{
@@ -38,11 +38,11 @@ pub struct Follow {
 impl Follow {
     pub fn new(
         actor: &PersonWrapper,
-        to: &InstanceWrapper,
+        to: &impl Actor,
         context: &Data<IbisContext>,
     ) -> BackendResult<Self> {
         let id = generate_activity_id(context)?;
-        let to: ObjectId<InstanceOrPerson> = to.ap_id.clone().into();
+        let to: ObjectId<InstanceOrPerson> = to.id().into();
         Ok(Follow {
             actor: actor.ap_id.clone().into(),
             to: Some([to.clone()]),
@@ -54,7 +54,7 @@ impl Follow {
 
     pub async fn send(
         actor: &PersonWrapper,
-        to: &InstanceWrapper,
+        to: &impl Actor,
         context: &Data<IbisContext>,
     ) -> BackendResult<()> {
         let follow = Self::new(actor, to, context)?;
@@ -1,8 +1,25 @@
 use crate::objects::{instance::InstanceWrapper, user::PersonWrapper};
+use activitypub_federation::config::Data;
 use either::Either;
+use follow::Follow;
+use ibis_database::{common::user::Person, error::BackendResult, impls::IbisContext};
 
 pub mod accept;
 pub mod follow;
 pub mod undo_follow;
 
 type InstanceOrPerson = Either<InstanceWrapper, PersonWrapper>;
+
+/// Follow a local user directly, or send a follow request to a remote one. In the latter case the
+/// follow is stored once it gets accepted.
+pub async fn follow_person(
+    follower: &PersonWrapper,
+    person: &PersonWrapper,
+    context: &Data<IbisContext>,
+) -> BackendResult<()> {
+    if person.local {
+        Person::follow(person, follower, context)
+    } else {
+        Follow::send(follower, person, context).await
+    }
+}
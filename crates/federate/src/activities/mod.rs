@@ -1,5 +1,6 @@
 use crate::{
     activities::article::create_or_edit_article::CreateOrEditArticle,
+    mentions::resolve_mentions,
     objects::{instance::InstanceWrapper, user::PersonWrapper},
     routes::AnnouncableActivities,
 };
@@ -8,33 +9,46 @@ use announce::AnnounceActivity;
 use article::update_article::UpdateArticle;
 use ibis_database::{
     common::{
-        article::{Article, Edit, EditVersion},
+        article::{Article, Edit, EditTag, EditVersion},
         instance::Instance,
+        webhook::WebhookEvent,
     },
     error::BackendResult,
-    impls::{IbisContext, edit::DbEditForm},
+    impls::{
+        IbisContext,
+        edit::{DbEditForm, EditDescription},
+        notifications::Notification,
+    },
+    webhook::send_webhooks,
 };
+use serde_json::json;
 
 pub mod announce;
 pub mod article;
 pub mod comment;
+pub mod delete_person;
 pub mod following;
+pub mod move_person;
 pub mod reject;
+pub mod update_actor;
 
 pub async fn submit_article_update(
     new_text: String,
-    summary: String,
+    mut description: EditDescription,
     previous_version: EditVersion,
     article: &Article,
     person: PersonWrapper,
     is_create: bool,
     context: &Data<IbisContext>,
 ) -> BackendResult<()> {
+    if person.bot && !description.tags.contains(&EditTag::Bot) {
+        description.tags.push(EditTag::Bot);
+    }
     let mut form = DbEditForm::new(
         article,
         person.id,
         &new_text,
-        summary,
+        description,
         previous_version,
         false,
     )?;
@@ -42,6 +56,17 @@ pub async fn submit_article_update(
     // insert edit to remote instance as pending, so only the creator can see it
     form.pending = !article.local;
     let edit = Edit::create(&form, context).await?;
+    if person.bot_trusted {
+        Edit::update_patrolled(edit.id, true, context)?;
+    }
+    if !edit.pending {
+        let mentioned = resolve_mentions(&edit.summary, &person, context).await;
+        Notification::notify_edit_mentions(&edit, &mentioned, context).await?;
+        if !is_create {
+            let data = json!({ "article": article, "edit": edit });
+            send_webhooks(WebhookEvent::ArticleEdited, &data, context)?;
+        }
+    }
 
     let local_instance: InstanceWrapper = Instance::read_local(context)?.into();
     let article_instance: InstanceWrapper = Instance::read(article.instance_id, context)?.into();
@@ -0,0 +1,117 @@
+use crate::{
+    activities::following::follow_person,
+    generate_activity_id,
+    objects::user::PersonWrapper,
+    send_ibis_activity,
+};
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::{activity::MoveType, public},
+    protocol::{helpers::deserialize_one_or_many, verification::verify_urls_match},
+    traits::ActivityHandler,
+};
+use anyhow::anyhow;
+use ibis_database::{
+    common::{instance::Instance, user::Person},
+    error::{BackendError, BackendResult},
+    impls::IbisContext,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Sent when a user moves to a new account, so that followers can follow the new account instead.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MovePerson {
+    pub actor: ObjectId<PersonWrapper>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub to: Vec<Url>,
+    pub object: ObjectId<PersonWrapper>,
+    pub target: ObjectId<PersonWrapper>,
+    #[serde(rename = "type")]
+    pub kind: MoveType,
+    pub id: Url,
+}
+
+impl MovePerson {
+    /// Mark the local user as moved, migrate local followers and notify all known instances.
+    pub async fn send(
+        person: &PersonWrapper,
+        target: &PersonWrapper,
+        context: &Data<IbisContext>,
+    ) -> BackendResult<()> {
+        verify_alias(person, target)?;
+        Person::set_moved_to(person.id, target.ap_id.clone(), context)?;
+        migrate_followers(person, target, context).await?;
+
+        let move_ = MovePerson {
+            actor: person.ap_id.clone().into(),
+            to: vec![public()],
+            object: person.ap_id.clone().into(),
+            target: target.ap_id.clone().into(),
+            kind: Default::default(),
+            id: generate_activity_id(context)?,
+        };
+        let inboxes = Instance::list(context)?
+            .iter()
+            .map(|i| i.inbox_url())
+            .collect();
+        send_ibis_activity(person, move_, inboxes, context).await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for MovePerson {
+    type DataType = IbisContext;
+    type Error = BackendError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _context: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_urls_match(self.actor.inner(), self.object.inner())?;
+        Ok(())
+    }
+
+    async fn receive(self, context: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        let person = self.actor.dereference(context).await?;
+        // Fetch the latest version to make sure the alias is included
+        let target = self.target.dereference_forced(context).await?;
+        verify_alias(&person, &target)?;
+        Person::set_moved_to(person.id, target.ap_id.clone(), context)?;
+        migrate_followers(&person, &target, context).await?;
+        Ok(())
+    }
+}
+
+/// The new account needs to list the old one in `alsoKnownAs`, to prove that both belong to the
+/// same user.
+fn verify_alias(person: &PersonWrapper, target: &PersonWrapper) -> BackendResult<()> {
+    if !target.also_known_as.contains(&person.ap_id) {
+        return Err(anyhow!("Target account does not list {} as alias", person.ap_id).into());
+    }
+    Ok(())
+}
+
+/// Let local followers of the old account follow the new account instead.
+async fn migrate_followers(
+    person: &PersonWrapper,
+    target: &PersonWrapper,
+    context: &Data<IbisContext>,
+) -> BackendResult<()> {
+    for follower in Person::read_followers(person.id, context)? {
+        if !follower.local {
+            continue;
+        }
+        follow_person(&follower.clone().into(), target, context).await?;
+        Person::unfollow(person, &follower, context)?;
+    }
+    Ok(())
+}
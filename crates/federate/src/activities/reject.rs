@@ -87,6 +87,7 @@ impl ActivityHandler for RejectEdit {
             creator_id: creator.id,
             article_id: article.id,
             previous_version_id: self.object.previous_version,
+            minor: self.object.minor,
         };
         Conflict::create(&form, context)?;
         Ok(())
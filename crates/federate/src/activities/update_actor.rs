@@ -0,0 +1,122 @@
+use crate::{
+    generate_activity_id,
+    objects::{
+        instance::{ApubInstance, InstanceWrapper},
+        user::{ApubUser, PersonWrapper},
+    },
+    send_ibis_activity,
+};
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::{activity::UpdateType, public},
+    protocol::{helpers::deserialize_one_or_many, verification::verify_urls_match},
+    traits::{ActivityHandler, Actor, Object},
+};
+use either::Either;
+use ibis_database::{
+    common::instance::Instance,
+    error::{BackendError, BackendResult},
+    impls::IbisContext,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ApubActor {
+    User(ApubUser),
+    Instance(ApubInstance),
+}
+
+/// Sent when a local user or instance changes, in particular after its key was rotated. This
+/// way other instances get the new key without waiting for the next refetch.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateActor {
+    pub actor: ObjectId<Either<PersonWrapper, InstanceWrapper>>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub to: Vec<Url>,
+    pub object: ApubActor,
+    #[serde(rename = "type")]
+    pub kind: UpdateType,
+    pub id: Url,
+}
+
+impl UpdateActor {
+    pub async fn send_person(
+        person: PersonWrapper,
+        context: &Data<IbisContext>,
+    ) -> BackendResult<()> {
+        let object = ApubActor::User(person.clone().into_json(context).await?);
+        Self::send(&person, object, context).await
+    }
+
+    pub async fn send_instance(
+        instance: InstanceWrapper,
+        context: &Data<IbisContext>,
+    ) -> BackendResult<()> {
+        let object = ApubActor::Instance(instance.clone().into_json(context).await?);
+        Self::send(&instance, object, context).await
+    }
+
+    /// Send to all known instances, as there is no way to tell which of them have a copy of the
+    /// actor.
+    async fn send(
+        actor: &impl Actor,
+        object: ApubActor,
+        context: &Data<IbisContext>,
+    ) -> BackendResult<()> {
+        let update = UpdateActor {
+            actor: actor.id().into(),
+            to: vec![public()],
+            object,
+            kind: Default::default(),
+            id: generate_activity_id(context)?,
+        };
+        let inboxes = Instance::list(context)?
+            .iter()
+            .map(|i| i.inbox_url())
+            .collect();
+        send_ibis_activity(actor, update, inboxes, context).await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for UpdateActor {
+    type DataType = IbisContext;
+    type Error = BackendError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _context: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        // actors can only update themselves
+        let object_id = match &self.object {
+            ApubActor::User(user) => user.id.inner(),
+            ApubActor::Instance(instance) => instance.id.inner(),
+        };
+        verify_urls_match(self.actor.inner(), object_id)?;
+        Ok(())
+    }
+
+    async fn receive(self, context: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        match self.object {
+            ApubActor::User(user) => {
+                PersonWrapper::verify(&user, self.actor.inner(), context).await?;
+                PersonWrapper::from_json(user, context).await?;
+            }
+            ApubActor::Instance(instance) => {
+                InstanceWrapper::verify(&instance, self.actor.inner(), context).await?;
+                InstanceWrapper::from_json(instance, context).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 pub mod articles_collection;
 pub(crate) mod edits_collection;
-pub(crate) mod empty_outbox;
 pub mod instance_collection;
 pub(crate) mod instance_follower;
+pub mod outbox;
@@ -0,0 +1,228 @@
+use crate::{
+    activities::{
+        announce::AnnounceActivity,
+        article::create_or_edit_article::CreateOrEditArticle,
+        comment::create_or_update_comment::CreateOrUpdateComment,
+    },
+    objects::{comment::CommentWrapper, instance::InstanceWrapper, user::PersonWrapper},
+    routes::AnnouncableActivities,
+};
+use activitypub_federation::{
+    config::Data,
+    kinds::collection::{OrderedCollectionPageType, OrderedCollectionType},
+};
+use anyhow::anyhow;
+use ibis_database::{
+    common::{
+        article::{Article, Edit, EditVersion},
+        comment::Comment,
+        instance::Instance,
+        user::Person,
+    },
+    error::BackendResult,
+    impls::IbisContext,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::cmp::Reverse;
+use url::Url;
+
+/// Number of activities returned in a single outbox page.
+const OUTBOX_PAGE_SIZE: i64 = 20;
+
+/// Highest page which can be requested, as person outboxes read all items up to the page.
+const MAX_OUTBOX_PAGE: i64 = 1000;
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct OutboxParams {
+    pub page: Option<i64>,
+}
+
+/// Outbox collection which only contains the total count and a link to the first page.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApubOutbox {
+    r#type: OrderedCollectionType,
+    id: Url,
+    total_items: i64,
+    first: Url,
+}
+
+#[skip_serializing_none]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApubOutboxPage {
+    r#type: OrderedCollectionPageType,
+    id: Url,
+    part_of: Url,
+    next: Option<Url>,
+    prev: Option<Url>,
+    ordered_items: Vec<OutboxActivity>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum OutboxActivity {
+    Announce(AnnounceActivity),
+    AnnouncableActivities(AnnouncableActivities),
+}
+
+/// Either the outbox collection itself, or one of its pages, depending on the `page` param.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum OutboxOrPage {
+    Outbox(ApubOutbox),
+    Page(ApubOutboxPage),
+}
+
+/// Outbox of a local user, containing their most recent edits and comments.
+pub async fn person_outbox(
+    person: &Person,
+    params: OutboxParams,
+    context: &Data<IbisContext>,
+) -> BackendResult<OutboxOrPage> {
+    let id = Url::parse(&format!("{}/outbox", person.ap_id))?;
+    let Some(page) = params.page else {
+        let total_items = Edit::count_for_person(person.id, context)?
+            + Comment::count_for_person(person.id, context)?;
+        return Ok(OutboxOrPage::Outbox(ApubOutbox::new(id, total_items)));
+    };
+
+    // Edits and comments are stored in separate tables, so read enough of each to fill all
+    // pages up to the requested one and merge them by date. One extra item is read to find out
+    // if there is a next page.
+    let offset = page_offset(page)?;
+    let limit = offset + OUTBOX_PAGE_SIZE;
+    let edits = Edit::list_for_person(person.id, limit + 1, context)?;
+    let comments = Comment::list_for_person(person.id, limit + 1, context)?;
+    let mut items: Vec<_> = edits
+        .into_iter()
+        .map(|e| (e.published, EditOrComment::Edit(e)))
+        .chain(
+            comments
+                .into_iter()
+                .map(|c| (c.published, EditOrComment::Comment(c))),
+        )
+        .collect();
+    items.sort_by_key(|i| Reverse(i.0));
+    let has_next = items.len() as i64 > limit;
+
+    let person_wrapper: PersonWrapper = person.clone().into();
+    let mut activities = vec![];
+    for (_, item) in items
+        .into_iter()
+        .skip(offset as usize)
+        .take(OUTBOX_PAGE_SIZE as usize)
+    {
+        let activity = match item {
+            EditOrComment::Edit(edit) => {
+                let article = Article::read(edit.article_id, context)?;
+                let instance = Instance::read(article.instance_id, context)?.into();
+                AnnouncableActivities::EditArticle(
+                    edit_activity(edit, &person_wrapper, &instance, context).await?,
+                )
+            }
+            EditOrComment::Comment(comment) => {
+                let instance = Instance::read_for_comment(comment.id, context)?.into();
+                AnnouncableActivities::CreateOrUpdateComment(
+                    CreateOrUpdateComment::new(&CommentWrapper(comment), &instance, context)
+                        .await?,
+                )
+            }
+        };
+        activities.push(OutboxActivity::AnnouncableActivities(activity));
+    }
+    Ok(OutboxOrPage::Page(ApubOutboxPage::new(
+        id, page, has_next, activities,
+    )))
+}
+
+/// Outbox of the local instance, containing announcements of article creations and edits.
+pub async fn instance_outbox(
+    params: OutboxParams,
+    context: &Data<IbisContext>,
+) -> BackendResult<OutboxOrPage> {
+    let instance: InstanceWrapper = Instance::read_local(context)?.into();
+    let id = Url::parse(&format!("{}outbox", instance.ap_id))?;
+    let Some(page) = params.page else {
+        let total_items = Edit::count_for_local_articles(context)?;
+        return Ok(OutboxOrPage::Outbox(ApubOutbox::new(id, total_items)));
+    };
+
+    // read one extra item to find out if there is a next page
+    let offset = page_offset(page)?;
+    let mut edits = Edit::list_for_local_articles(OUTBOX_PAGE_SIZE + 1, offset, context)?;
+    let has_next = edits.len() as i64 > OUTBOX_PAGE_SIZE;
+    edits.truncate(OUTBOX_PAGE_SIZE as usize);
+
+    let mut activities = vec![];
+    for edit in edits {
+        let creator: PersonWrapper = Person::read(edit.creator_id, context)?.into();
+        let activity = edit_activity(edit, &creator, &instance, context).await?;
+        let announce = AnnounceActivity::new(
+            AnnouncableActivities::EditArticle(activity),
+            &instance,
+            context,
+        )?;
+        activities.push(OutboxActivity::Announce(announce));
+    }
+    Ok(OutboxOrPage::Page(ApubOutboxPage::new(
+        id, page, has_next, activities,
+    )))
+}
+
+enum EditOrComment {
+    Edit(Edit),
+    Comment(Comment),
+}
+
+async fn edit_activity(
+    edit: Edit,
+    creator: &PersonWrapper,
+    instance: &InstanceWrapper,
+    context: &Data<IbisContext>,
+) -> BackendResult<CreateOrEditArticle> {
+    let is_create = edit.previous_version_id == EditVersion::default();
+    CreateOrEditArticle::new(edit.into(), creator, instance, is_create, context).await
+}
+
+fn page_offset(page: i64) -> BackendResult<i64> {
+    if page > MAX_OUTBOX_PAGE {
+        return Err(anyhow!("Outbox page must be at most {MAX_OUTBOX_PAGE}").into());
+    }
+    Ok((page.max(1) - 1) * OUTBOX_PAGE_SIZE)
+}
+
+fn page_url(outbox_id: &Url, page: i64) -> Url {
+    let mut url = outbox_id.clone();
+    url.query_pairs_mut()
+        .clear()
+        .append_pair("page", &page.to_string());
+    url
+}
+
+impl ApubOutbox {
+    fn new(id: Url, total_items: i64) -> Self {
+        let first = page_url(&id, 1);
+        ApubOutbox {
+            r#type: Default::default(),
+            id,
+            total_items,
+            first,
+        }
+    }
+}
+
+impl ApubOutboxPage {
+    fn new(outbox_id: Url, page: i64, has_next: bool, ordered_items: Vec<OutboxActivity>) -> Self {
+        let page = page.max(1);
+        ApubOutboxPage {
+            r#type: Default::default(),
+            id: page_url(&outbox_id, page),
+            next: has_next.then(|| page_url(&outbox_id, page + 1)),
+            prev: (page > 1).then(|| page_url(&outbox_id, page - 1)),
+            part_of: outbox_id,
+            ordered_items,
+        }
+    }
+}
@@ -26,6 +26,7 @@ use url::Url;
 
 pub mod activities;
 pub mod collections;
+pub mod mentions;
 pub mod nodeinfo;
 pub mod objects;
 pub mod routes;
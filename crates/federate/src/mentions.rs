@@ -0,0 +1,109 @@
+use crate::objects::user::PersonWrapper;
+use activitypub_federation::{
+    config::Data,
+    fetch::{object_id::ObjectId, webfinger::webfinger_resolve_actor},
+    kinds::link::MentionType,
+};
+use ibis_database::{
+    common::{user::Person, utils::extract_domain},
+    impls::IbisContext,
+};
+use ibis_markdown::extract_mentions;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+
+/// Upper limit for users which can be notified by a single comment or edit.
+pub(crate) const MAX_MENTIONS: usize = 10;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mention {
+    pub href: ObjectId<PersonWrapper>,
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: MentionType,
+}
+
+/// Entry in the `tag` field of an object. Other tag types like hashtags are ignored.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum MentionOrValue {
+    Mention(Mention),
+    Value(serde_json::Value),
+}
+
+impl From<&Person> for MentionOrValue {
+    fn from(person: &Person) -> Self {
+        MentionOrValue::Mention(Mention {
+            href: person.ap_id.clone().into(),
+            name: Some(person.title()),
+            kind: Default::default(),
+        })
+    }
+}
+
+/// Users mentioned in the text which are already known to this instance. Mentions without domain
+/// refer to the instance of the author.
+pub fn read_mentions(text: &str, author: &Person, context: &Data<IbisContext>) -> Vec<Person> {
+    let mut persons = vec![];
+    for (name, domain) in mention_targets(text, author, context) {
+        let domain = (domain != context.domain()).then_some(domain);
+        if let Ok(person) = Person::read_from_name(&name, &domain, context) {
+            if !persons.contains(&person) {
+                persons.push(person);
+            }
+        }
+    }
+    persons
+}
+
+/// Same as [read_mentions], but users from other instances which are not known yet are fetched
+/// with webfinger.
+pub async fn resolve_mentions(
+    text: &str,
+    author: &Person,
+    context: &Data<IbisContext>,
+) -> Vec<Person> {
+    let mut persons = vec![];
+    for (name, domain) in mention_targets(text, author, context) {
+        let person = if domain == context.domain() {
+            Person::read_from_name(&name, &None, context).ok()
+        } else if let Ok(person) = Person::read_from_name(&name, &Some(domain.clone()), context) {
+            Some(person)
+        } else {
+            let identifier = format!("{name}@{domain}");
+            match webfinger_resolve_actor::<IbisContext, PersonWrapper>(&identifier, context).await
+            {
+                Ok(p) => Some(p.deref().clone()),
+                Err(e) => {
+                    warn!("Failed to resolve mention of {identifier}: {e}");
+                    None
+                }
+            }
+        };
+        if let Some(person) = person {
+            if !persons.contains(&person) {
+                persons.push(person);
+            }
+        }
+    }
+    persons
+}
+
+fn mention_targets(
+    text: &str,
+    author: &Person,
+    context: &Data<IbisContext>,
+) -> Vec<(String, String)> {
+    let author_domain = if author.local {
+        context.domain().to_string()
+    } else {
+        extract_domain(author.ap_id.inner())
+    };
+    extract_mentions(text)
+        .into_iter()
+        .take(MAX_MENTIONS)
+        .map(|m| (m.name, m.domain.unwrap_or_else(|| author_domain.clone())))
+        .collect()
+}
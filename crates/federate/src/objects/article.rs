@@ -154,6 +154,7 @@ impl Object for ArticleWrapper {
         if let Some(edit) = edits.first() {
             Notification::notify_article(&article, edit.creator_id, context).await?;
         }
+        article.publish_update(context)?;
 
         Ok(article.into())
     }
@@ -1,5 +1,8 @@
 use super::{DbArticleOrComment, Source, read_from_string_or_source, user::PersonWrapper};
-use crate::validate::validate_comment_max_depth;
+use crate::{
+    mentions::{MAX_MENTIONS, MentionOrValue, read_mentions},
+    validate::validate_comment_max_depth,
+};
 use activitypub_federation::{
     config::Data,
     fetch::object_id::ObjectId,
@@ -15,7 +18,7 @@ use chrono::{DateTime, Utc};
 use ibis_database::{
     common::{article::Article, comment::Comment, user::Person},
     error::BackendError,
-    impls::{IbisContext, comment::DbCommentInsertForm},
+    impls::{IbisContext, comment::DbCommentInsertForm, notifications::Notification},
 };
 use ibis_markdown::render_comment_markdown;
 use serde::{Deserialize, Serialize};
@@ -40,6 +43,9 @@ pub struct ApubComment {
     pub(crate) media_type: Option<MediaTypeMarkdownOrHtml>,
     #[serde(deserialize_with = "deserialize_skip_error", default)]
     pub(crate) source: Option<Source>,
+    /// Users mentioned in the comment
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) tag: Vec<MentionOrValue>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -83,6 +89,10 @@ impl Object for CommentWrapper {
             let article = Article::read(self.article_id, context)?;
             article.ap_id.into()
         };
+        let tag = read_mentions(&self.content, &creator, context)
+            .iter()
+            .map(Into::into)
+            .collect();
         Ok(ApubComment {
             kind: NoteType::Note,
             id: self.ap_id.clone().into(),
@@ -94,6 +104,7 @@ impl Object for CommentWrapper {
             updated: self.updated,
             media_type: Some(MediaTypeMarkdownOrHtml::Html),
             source: Some(Source::new(self.content.clone())),
+            tag,
         })
     }
 
@@ -137,6 +148,21 @@ impl Object for CommentWrapper {
             depth,
         };
 
-        Ok(Comment::create(form, context).await?.into())
+        let comment = Comment::create(form, context).await?;
+
+        // only local users need to be notified, remote ones are handled by their own instance
+        let mentioned: Vec<_> = json
+            .tag
+            .iter()
+            .filter_map(|t| match t {
+                MentionOrValue::Mention(m) => Some(m.href.inner().clone().into()),
+                MentionOrValue::Value(_) => None,
+            })
+            .take(MAX_MENTIONS)
+            .filter_map(|ap_id| Person::read_from_ap_id(&ap_id, context).ok())
+            .filter(|p| p.local)
+            .collect();
+        Notification::notify_comment_mentions(&comment, &mentioned, context).await?;
+        Ok(comment.into())
     }
 }
@@ -1,4 +1,5 @@
 use super::{article::ArticleWrapper, user::PersonWrapper};
+use crate::mentions::read_mentions;
 use activitypub_federation::{
     config::Data,
     fetch::object_id::ObjectId,
@@ -8,11 +9,11 @@ use activitypub_federation::{
 use chrono::{DateTime, Utc};
 use ibis_database::{
     common::{
-        article::{Article, Edit, EditVersion},
+        article::{Article, Edit, EditTag, EditVersion},
         user::Person,
     },
     error::BackendError,
-    impls::{IbisContext, edit::DbEditForm},
+    impls::{IbisContext, edit::DbEditForm, notifications::Notification},
 };
 use log::warn;
 use serde::{Deserialize, Serialize};
@@ -41,6 +42,11 @@ pub struct ApubEdit {
     pub object: ObjectId<ArticleWrapper>,
     pub attributed_to: ObjectId<PersonWrapper>,
     pub published: DateTime<Utc>,
+    #[serde(default)]
+    pub minor: bool,
+    /// Tags which are unknown to this version are ignored
+    #[serde(default)]
+    pub edit_tags: Vec<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -88,6 +94,8 @@ impl Object for EditWrapper {
             object: article.ap_id.into(),
             attributed_to: creator.ap_id.into(),
             published: self.published,
+            minor: self.minor,
+            edit_tags: self.tags.iter().map(|t| t.as_str().to_string()).collect(),
         })
     }
 
@@ -114,6 +122,14 @@ impl Object for EditWrapper {
                 Person::ghost(context)?.into()
             }
         };
+        let mut tags: Vec<_> = json
+            .edit_tags
+            .iter()
+            .filter_map(|t| t.parse::<EditTag>().ok())
+            .collect();
+        if creator.bot && !tags.contains(&EditTag::Bot) {
+            tags.push(EditTag::Bot);
+        }
         let form = DbEditForm {
             creator_id: creator.id,
             ap_id: json.id.into(),
@@ -124,8 +140,15 @@ impl Object for EditWrapper {
             previous_version_id: json.previous_version,
             published: json.published,
             pending: false,
+            minor: json.minor,
+            tags,
         };
         let edit = Edit::create(&form, context).await?;
+        if creator.bot_trusted {
+            Edit::update_patrolled(edit.id, true, context)?;
+        }
+        let mentioned = read_mentions(&edit.summary, &creator, context);
+        Notification::notify_edit_mentions(&edit, &mentioned, context).await?;
         Ok(edit.into())
     }
 }
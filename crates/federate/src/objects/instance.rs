@@ -38,7 +38,11 @@ pub struct ApubInstance {
     /// displayname
     name: Option<String>,
     summary: Option<String>,
-    outbox: Option<CollectionId<ArticleCollection>>,
+    /// Paginated collection of recent article changes. Older ibis versions published the
+    /// article collection here instead.
+    outbox: Option<Url>,
+    /// Collection of all articles, used to synchronize articles between ibis instances
+    articles: Option<CollectionId<ArticleCollection>>,
     // This field is mandatory to prevent fetching communities from Lemmy etc
     instances: CollectionId<InstanceCollection>,
     inbox: Url,
@@ -121,7 +125,8 @@ impl Object for InstanceWrapper {
             kind: Default::default(),
             id: self.ap_id.clone().into(),
             summary: self.topic.clone(),
-            outbox: self.articles_url.clone().map(Into::into),
+            outbox: Some(Url::parse(&format!("{}outbox", self.ap_id))?),
+            articles: self.articles_url.clone().map(Into::into),
             instances: self.instances_url.clone().into(),
             inbox: Url::parse(&self.inbox_url)?,
             public_key: self.public_key(),
@@ -151,7 +156,10 @@ impl Object for InstanceWrapper {
             domain,
             ap_id: json.id.into(),
             topic: json.summary,
-            articles_url: json.outbox.map(Into::into),
+            articles_url: json
+                .articles
+                .map(Into::into)
+                .or(json.outbox.map(Into::into)),
             instances_url: json.instances.into(),
             inbox_url: inbox_url.to_string(),
             public_key: json.public_key.public_key_pem,
@@ -160,6 +168,13 @@ impl Object for InstanceWrapper {
             local: false,
             name: json.name,
         };
+        // Remember the old key if the instance rotated it, so that activities which are still
+        // signed with the old key are accepted for the grace period
+        if let Ok(existing) = Instance::read_from_ap_id(&form.ap_id, context) {
+            if !existing.local && existing.public_key != form.public_key {
+                Instance::update_key(existing.id, form.public_key.clone(), None, context)?;
+            }
+        }
         let instance = Instance::create(&form, context)?;
 
         // TODO: very inefficient to sync all articles every time
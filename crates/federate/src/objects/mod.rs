@@ -10,6 +10,7 @@ pub mod article;
 pub mod comment;
 pub mod edit;
 pub mod instance;
+pub mod previous_key;
 pub mod user;
 
 type DbArticleOrComment = Either<ArticleWrapper, CommentWrapper>;
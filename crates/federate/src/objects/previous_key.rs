@@ -0,0 +1,136 @@
+use super::{instance::InstanceWrapper, user::PersonWrapper};
+use activitypub_federation::{
+    config::Data,
+    error::Error,
+    traits::{Actor, Object},
+};
+use chrono::{DateTime, Duration, Utc};
+use ibis_database::{error::BackendError, impls::IbisContext};
+use std::fmt::Debug;
+use url::Url;
+
+/// Actor wrapper which verifies signatures with the previous key of the actor instead of the
+/// current one. This way activities which were signed before a key rotation (for example
+/// retries of queued activities) are still accepted during the grace period.
+#[derive(Clone, Debug)]
+pub struct PreviousKey<T> {
+    actor: T,
+    public_key: String,
+}
+
+/// Actor whose key may have been rotated.
+pub trait RotatedKey {
+    fn previous_public_key(&self) -> Option<&String>;
+    fn key_rotated_at(&self) -> Option<DateTime<Utc>>;
+}
+
+impl RotatedKey for PersonWrapper {
+    fn previous_public_key(&self) -> Option<&String> {
+        self.previous_public_key.as_ref()
+    }
+
+    fn key_rotated_at(&self) -> Option<DateTime<Utc>> {
+        self.key_rotated_at
+    }
+}
+
+impl RotatedKey for InstanceWrapper {
+    fn previous_public_key(&self) -> Option<&String> {
+        self.previous_public_key.as_ref()
+    }
+
+    fn key_rotated_at(&self) -> Option<DateTime<Utc>> {
+        self.key_rotated_at
+    }
+}
+
+impl<T: RotatedKey> PreviousKey<T> {
+    /// Fails with an invalid signature error if the key was never rotated, or if the grace period
+    /// is over.
+    fn new(actor: T, context: &IbisContext) -> Result<Self, BackendError> {
+        let grace_period = Duration::days(
+            context
+                .conf
+                .federation
+                .key_rotation_grace_period_days
+                .into(),
+        );
+        let public_key = match (actor.previous_public_key(), actor.key_rotated_at()) {
+            (Some(key), Some(rotated_at)) if rotated_at + grace_period > Utc::now() => key.clone(),
+            _ => return Err(Error::ActivitySignatureInvalid.into()),
+        };
+        Ok(PreviousKey { actor, public_key })
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Object for PreviousKey<T>
+where
+    T: Object<DataType = IbisContext, Error = BackendError> + RotatedKey + Debug + Send + Sync,
+    T::Kind: Send + Sync,
+{
+    type DataType = IbisContext;
+    type Kind = T::Kind;
+    type Error = BackendError;
+
+    fn last_refreshed_at(&self) -> Option<DateTime<Utc>> {
+        self.actor.last_refreshed_at()
+    }
+
+    async fn read_from_id(
+        object_id: Url,
+        context: &Data<Self::DataType>,
+    ) -> Result<Option<Self>, Self::Error> {
+        T::read_from_id(object_id, context)
+            .await?
+            .map(|actor| PreviousKey::new(actor, context))
+            .transpose()
+    }
+
+    async fn into_json(self, context: &Data<Self::DataType>) -> Result<Self::Kind, Self::Error> {
+        self.actor.into_json(context).await
+    }
+
+    async fn verify(
+        json: &Self::Kind,
+        expected_domain: &Url,
+        context: &Data<Self::DataType>,
+    ) -> Result<(), Self::Error> {
+        T::verify(json, expected_domain, context).await
+    }
+
+    async fn from_json(
+        json: Self::Kind,
+        context: &Data<Self::DataType>,
+    ) -> Result<Self, Self::Error> {
+        let actor = T::from_json(json, context).await?;
+        PreviousKey::new(actor, context)
+    }
+}
+
+impl<T> Actor for PreviousKey<T>
+where
+    T: Actor
+        + Object<DataType = IbisContext, Error = BackendError>
+        + RotatedKey
+        + Debug
+        + Send
+        + Sync,
+    T::Kind: Send + Sync,
+{
+    fn id(&self) -> Url {
+        self.actor.id()
+    }
+
+    fn public_key_pem(&self) -> &str {
+        &self.public_key
+    }
+
+    fn private_key_pem(&self) -> Option<String> {
+        None
+    }
+
+    fn inbox(&self) -> Url {
+        self.actor.inbox()
+    }
+}
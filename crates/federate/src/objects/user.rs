@@ -2,7 +2,6 @@ use super::{Endpoints, Source, read_from_string_or_source_opt};
 use activitypub_federation::{
     config::Data,
     fetch::object_id::ObjectId,
-    kinds::actor::PersonType,
     protocol::{
         helpers::deserialize_skip_error,
         public_key::PublicKey,
@@ -28,20 +27,32 @@ use url::Url;
 #[serde(rename_all = "camelCase")]
 pub struct ApubUser {
     #[serde(rename = "type")]
-    kind: PersonType,
-    id: ObjectId<PersonWrapper>,
+    kind: UserType,
+    pub(crate) id: ObjectId<PersonWrapper>,
     preferred_username: String,
     /// displayname
     name: Option<String>,
     summary: Option<String>,
     inbox: Url,
     public_key: PublicKey,
-    /// mandatory in activitypub, we serve an empty one
+    /// Paginated collection of recent edits and comments
     outbox: String,
     pub(crate) media_type: Option<MediaTypeMarkdownOrHtml>,
     #[serde(deserialize_with = "deserialize_skip_error", default)]
     pub(crate) source: Option<Source>,
     pub(crate) endpoints: Option<Endpoints>,
+    /// Other accounts of the user which are allowed to move here
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) also_known_as: Vec<Url>,
+    /// New account of the user, if it was moved
+    pub(crate) moved_to: Option<Url>,
+}
+
+/// Bot accounts use the `Service` type.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum UserType {
+    Person,
+    Service,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -77,12 +88,17 @@ impl Object for PersonWrapper {
     ) -> Result<Option<Self>, Self::Error> {
         Ok(Person::read_from_ap_id(&object_id.into(), context)
             .ok()
+            .filter(|p| !p.deleted)
             .map(Into::into))
     }
 
     async fn into_json(self, _context: &Data<Self::DataType>) -> Result<Self::Kind, Self::Error> {
         Ok(ApubUser {
-            kind: Default::default(),
+            kind: if self.bot {
+                UserType::Service
+            } else {
+                UserType::Person
+            },
             id: self.ap_id.clone().into(),
             preferred_username: self.username.clone(),
             inbox: Url::parse(&self.inbox_url)?,
@@ -93,6 +109,12 @@ impl Object for PersonWrapper {
             media_type: Some(MediaTypeMarkdownOrHtml::Html),
             source: self.bio.clone().map(Source::new),
             endpoints: None,
+            also_known_as: self
+                .also_known_as
+                .iter()
+                .map(|a| a.inner().clone())
+                .collect(),
+            moved_to: self.moved_to.clone().map(Into::into),
         })
     }
 
@@ -121,7 +143,17 @@ impl Object for PersonWrapper {
             local: false,
             display_name: json.name,
             bio,
+            also_known_as: json.also_known_as.into_iter().map(Into::into).collect(),
+            moved_to: json.moved_to.map(Into::into),
+            bot: json.kind == UserType::Service,
         };
+        // Remember the old key if the user rotated it, so that activities which are still signed
+        // with the old key are accepted for the grace period
+        if let Ok(existing) = Person::read_from_ap_id(&form.ap_id, context) {
+            if !existing.local && existing.public_key != form.public_key {
+                Person::update_key(existing.id, form.public_key.clone(), None, context)?;
+            }
+        }
         Person::create(&form, context).map(Into::into)
     }
 }
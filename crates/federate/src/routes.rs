@@ -12,20 +12,24 @@ use crate::{
             delete_comment::DeleteComment,
             undo_delete_comment::UndoDeleteComment,
         },
+        delete_person::DeletePerson,
         following::{accept::Accept, follow::Follow, undo_follow::UndoFollow},
+        move_person::MovePerson,
         reject::RejectEdit,
+        update_actor::UpdateActor,
     },
     collections::{
         articles_collection::ArticleCollection,
         edits_collection::EditCollection,
-        empty_outbox::EmptyOutbox,
         instance_collection::InstanceCollection,
         instance_follower::InstanceFollower,
+        outbox::{OutboxParams, instance_outbox, person_outbox},
     },
     objects::{
         article::ArticleWrapper,
         comment::CommentWrapper,
         instance::InstanceWrapper,
+        previous_key::PreviousKey,
         user::PersonWrapper,
     },
 };
@@ -35,12 +39,17 @@ use activitypub_federation::{
         json::FederationJson,
     },
     config::Data,
+    error::Error as ActivityPubError,
+    fetch::object_id::ObjectId,
     protocol::context::WithContext,
-    traits::{ActivityHandler, Collection, Object},
+    traits::{ActivityHandler, Actor, Collection, Object},
 };
+use anyhow::anyhow;
 use axum::{
     Router,
-    extract::Path,
+    body::{Body, Bytes, to_bytes},
+    extract::{FromRequest, Path, Query, Request},
+    http::request::Parts,
     response::IntoResponse,
     routing::{get, post},
 };
@@ -55,11 +64,13 @@ use ibis_database::{
         user::Person,
         utils::http_protocol_str,
     },
-    error::BackendResult,
+    error::{BackendError, BackendResult},
     impls::{IbisContext, sent_activity::SentActivity},
 };
+use moka::sync::Cache;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::{sync::LazyLock, time::Duration};
 use url::Url;
 
 pub fn federation_routes() -> Router<()> {
@@ -103,9 +114,12 @@ async fn http_get_activity(
 }
 
 #[debug_handler]
-async fn http_get_instance_outbox(context: Data<IbisContext>) -> BackendResult<impl IntoResponse> {
-    let articles = ArticleCollection::read_local(&(), &context).await?;
-    Ok(FederationJson(WithContext::new_default(articles)))
+async fn http_get_instance_outbox(
+    Query(params): Query<OutboxParams>,
+    context: Data<IbisContext>,
+) -> BackendResult<impl IntoResponse> {
+    let outbox = instance_outbox(params, &context).await?;
+    Ok(FederationJson(WithContext::new_default(outbox)))
 }
 
 #[debug_handler]
@@ -129,10 +143,11 @@ async fn http_get_person(
 #[debug_handler]
 async fn http_get_person_outbox(
     Path(name): Path<String>,
+    Query(params): Query<OutboxParams>,
     context: Data<IbisContext>,
 ) -> BackendResult<impl IntoResponse> {
     let person = Person::read_from_name(&name, &None, &context)?;
-    let outbox = EmptyOutbox::new(format!("{}/outbox", person.ap_id));
+    let outbox = person_outbox(&person, params, &context).await?;
     Ok(FederationJson(WithContext::new_default(outbox)))
 }
 
@@ -191,6 +206,10 @@ pub enum InboxActivities {
     UndoFollow(UndoFollow),
     Accept(Accept),
     RejectEdit(RejectEdit),
+    UpdateActor(UpdateActor),
+    MovePerson(MovePerson),
+    // before the announcable activities, as it would otherwise be parsed as `DeleteComment`
+    DeletePerson(DeletePerson),
     AnnounceActivity(AnnounceActivity),
     AnnouncableActivities(AnnouncableActivities),
 }
@@ -208,14 +227,89 @@ pub enum AnnouncableActivities {
     UndoDeleteComment(UndoDeleteComment),
 }
 
+/// Maximum size of activities, same as the default body limit of axum.
+const INBOX_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
 #[debug_handler]
-pub async fn http_post_inbox(
-    context: Data<IbisContext>,
-    activity_data: ActivityData,
-) -> impl IntoResponse {
-    receive_activity::<WithContext<InboxActivities>, Either<PersonWrapper, InstanceWrapper>, _>(
-        activity_data,
-        &context,
+pub async fn http_post_inbox(context: Data<IbisContext>, request: Request) -> BackendResult<()> {
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, INBOX_BODY_LIMIT).await?;
+    match receive::<Either<PersonWrapper, InstanceWrapper>>(&parts, &body, &context).await {
+        Err(e) if is_invalid_signature(&e) => {
+            // The actor may have rotated its key. Activities which were signed before the
+            // rotation are accepted with the previous key during the grace period.
+            type PreviousKeyActor =
+                Either<PreviousKey<PersonWrapper>, PreviousKey<InstanceWrapper>>;
+            match receive::<PreviousKeyActor>(&parts, &body, &context).await {
+                Err(e) if is_invalid_signature(&e) => {
+                    // Otherwise the key we have stored may be outdated, so fetch the actor again
+                    // to get the new key.
+                    let activity: ActivityActor = serde_json::from_slice(&body)?;
+                    refetch_known_actor(activity.actor, &context).await?;
+                    receive::<Either<PersonWrapper, InstanceWrapper>>(&parts, &body, &context).await
+                }
+                res => res,
+            }
+        }
+        res => res,
+    }
+}
+
+/// Fetch an actor again to update its key. This is only done for actors which are already stored
+/// locally, and at most once per interval for each actor, because the actor id comes from an
+/// activity whose signature could not be verified.
+async fn refetch_known_actor(
+    actor: ObjectId<Either<PersonWrapper, InstanceWrapper>>,
+    context: &Data<IbisContext>,
+) -> BackendResult<()> {
+    static REFETCHED: LazyLock<Cache<Url, ()>> = LazyLock::new(|| {
+        Cache::builder()
+            .max_capacity(10_000)
+            .time_to_live(Duration::from_secs(600))
+            .build()
+    });
+
+    let invalid = || -> BackendError { ActivityPubError::ActivitySignatureInvalid.into() };
+    if actor.dereference_local(context).await.is_err() {
+        return Err(invalid());
+    }
+    let url = actor.inner().clone();
+    if REFETCHED.contains_key(&url) {
+        return Err(invalid());
+    }
+    REFETCHED.insert(url, ());
+    actor.dereference_forced(context).await?;
+    Ok(())
+}
+
+async fn receive<ActorT>(
+    parts: &Parts,
+    body: &Bytes,
+    context: &Data<IbisContext>,
+) -> BackendResult<()>
+where
+    ActorT: Object<DataType = IbisContext, Error = BackendError> + Actor + Send + 'static,
+    for<'de2> <ActorT as Object>::Kind: Deserialize<'de2>,
+{
+    // `ActivityData` can only be created from a request, so build a new one for each attempt
+    let mut request = Request::new(Body::from(body.clone()));
+    *request.method_mut() = parts.method.clone();
+    *request.uri_mut() = parts.uri.clone();
+    *request.headers_mut() = parts.headers.clone();
+    let activity_data = ActivityData::from_request(request, &())
+        .await
+        .map_err(|_| anyhow!("Failed to read activity"))?;
+    receive_activity::<WithContext<InboxActivities>, ActorT, _>(activity_data, context).await
+}
+
+fn is_invalid_signature(error: &BackendError) -> bool {
+    matches!(
+        error.0.downcast_ref::<ActivityPubError>(),
+        Some(ActivityPubError::ActivitySignatureInvalid)
     )
-    .await
+}
+
+#[derive(Deserialize)]
+struct ActivityActor {
+    actor: ObjectId<Either<PersonWrapper, InstanceWrapper>>,
 }
@@ -40,6 +40,18 @@ pub fn validate_display_name(name: &Option<String>) -> BackendResult<()> {
     Ok(())
 }
 
+pub fn validate_locale(locale: &Option<String>) -> BackendResult<()> {
+    #[expect(clippy::expect_used)]
+    static LOCALE_REGEX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^[a-z]{2,3}(-[a-zA-Z0-9]{2,4})?$").expect("compile regex"));
+    if let Some(locale) = locale {
+        if !LOCALE_REGEX.is_match(locale) {
+            return Err(anyhow!("Invalid locale").into());
+        }
+    }
+    Ok(())
+}
+
 pub fn validate_comment_max_depth(depth: i32) -> BackendResult<()> {
     if depth > 50 {
         return Err(anyhow!("Max comment depth reached").into());
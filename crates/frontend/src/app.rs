@@ -13,6 +13,7 @@ use crate::pages::{
         about::About,
         details::InstanceDetails,
         explore::Explore,
+        recent_changes::RecentChanges,
         search::Search,
         settings::AdminSettings,
     },
@@ -32,7 +33,12 @@ use ibis_api_client::{CLIENT, errors::ErrorPopup};
 use ibis_frontend_components::{
     nav::Nav,
     protected_route::IbisProtectedRoute,
-    utils::{dark_mode::DarkMode, formatting::instance_title, resources::site},
+    utils::{
+        dark_mode::DarkMode,
+        formatting::instance_title,
+        live_events::LiveEvents,
+        resources::site,
+    },
 };
 use leptos::prelude::*;
 use leptos_meta::{provide_meta_context, *};
@@ -51,6 +57,12 @@ pub fn shell(options: LeptosOptions) -> impl IntoView {
                 <AutoReload options=options.clone() />
                 <HydrationScripts options />
                 <MetaTags />
+                <link
+                    rel="alternate"
+                    type="application/atom+xml"
+                    title="Recent changes"
+                    href="/feeds/changes"
+                />
             </head>
             <body>
                 <App />
@@ -70,6 +82,7 @@ pub fn App() -> impl IntoView {
     provide_context(darkmode.clone());
 
     ErrorPopup::init();
+    LiveEvents::init();
 
     view! {
         <Html attr:data-theme=darkmode.theme {..} class="h-full" />
@@ -114,6 +127,7 @@ pub fn App() -> impl IntoView {
                         <IbisProtectedRoute path=path!("/create-article") view=CreateArticle />
 
                         <Route path=path!("/explore") view=Explore />
+                        <Route path=path!("/recent_changes") view=RecentChanges />
                         <Route path=path!("/instance/:hostname") view=InstanceDetails />
                         <IbisProtectedRoute path=path!("/admin") view=AdminSettings />
                         <Route path=path!("/about") view=About />
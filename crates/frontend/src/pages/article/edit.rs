@@ -54,6 +54,7 @@ pub fn EditArticle() -> impl IntoView {
         trigger_resize: _,
     } = use_textarea_autosize(textarea_ref);
     let (summary, set_summary) = signal(String::new());
+    let minor = signal(false);
     let (wait_for_response, set_wait_for_response) = signal(false);
     let button_is_disabled =
         Signal::derive(move || wait_for_response.get() || summary.get().is_empty());
@@ -81,6 +82,7 @@ pub fn EditArticle() -> impl IntoView {
                     article_id: article.article.id,
                     new_text,
                     summary,
+                    minor: minor.0.get_untracked(),
                     previous_version_id,
                     resolve_conflict_id,
                 };
@@ -119,6 +121,7 @@ pub fn EditArticle() -> impl IntoView {
                                     if let EditResponse::Conflict(conflict) = edit_response.get() {
                                         article.article.text = conflict.three_way_merge;
                                         set_summary.set(conflict.summary);
+                                        minor.1.set(conflict.minor);
                                     }
                                     set_content.set(article.article.text.clone());
                                     let article_ = article.clone();
@@ -155,6 +158,14 @@ pub fn EditArticle() -> impl IntoView {
                                                         set_summary.update(|p| *p = val);
                                                     }
                                                 />
+                                                <label class="flex gap-2 items-center me-4">
+                                                    <input
+                                                        type="checkbox"
+                                                        class="checkbox"
+                                                        bind:checked=minor
+                                                    />
+                                                    "Minor edit"
+                                                </label>
 
                                                 <button
                                                     class="btn btn-primary"
@@ -9,18 +9,30 @@ use leptos::prelude::*;
 #[component]
 pub fn ArticleHistory() -> impl IntoView {
     let article = article_resource();
+    let hide_minor = signal(false);
 
     view! {
         <ArticleNav article=article active_tab=ActiveTab::History />
+        <label class="flex gap-2 items-center my-2">
+            <input type="checkbox" class="checkbox" bind:checked=hide_minor />
+            "Hide minor edits"
+        </label>
         <SuspenseError result=article>
             {move || Suspend::new(async move {
                 let edits = article_edits_resource(article).await;
                 edits
                     .await
                     .map(|edits| {
-                        view! {
-                            // TODO: move edits resource here? but leads to strange crash
-                            <EditList edits=edits for_article=true />
+                        move || {
+                            let edits = edits
+                                .iter()
+                                .filter(|e| !hide_minor.0.get() || !e.edit.minor)
+                                .cloned()
+                                .collect();
+                            view! {
+                                // TODO: move edits resource here? but leads to strange crash
+                                <EditList edits=edits for_article=true />
+                            }
                         }
                     })
             })}
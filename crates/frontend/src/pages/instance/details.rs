@@ -5,6 +5,7 @@ use ibis_api_client::{
     instance::GetInstanceParams,
 };
 use ibis_frontend_components::{
+    block_buttons::{BlockButtons, BlockTarget},
     instance_follow_button::InstanceFollowButton,
     suspense_error::SuspenseError,
     utils::formatting::{article_path, instance_title_with_domain, instance_updated},
@@ -45,6 +46,7 @@ pub fn InstanceDetails() -> impl IntoView {
                             },
                         );
                         let title = instance_title_with_domain(&instance_.instance);
+                        let (instance_id, local) = (instance_.instance.id, instance_.instance.local);
                         view! {
                             <Title text=title.clone() />
                             <div class="grid gap-3 mt-4">
@@ -52,6 +54,9 @@ pub fn InstanceDetails() -> impl IntoView {
                                     <h1 class="w-full font-serif text-4xl font-bold">{title}</h1>
                                     {instance_updated(&instance_)}
                                     <InstanceFollowButton instance=instance />
+                                    <Show when=move || !local>
+                                        <BlockButtons target=BlockTarget::Instance(instance_id) />
+                                    </Show>
                                 </div>
 
                                 <div class="divider"></div>
@@ -1,5 +1,6 @@
 pub mod about;
 pub mod details;
 pub mod explore;
+pub mod recent_changes;
 pub mod search;
 pub mod settings;
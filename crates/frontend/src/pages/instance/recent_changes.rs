@@ -0,0 +1,158 @@
+use ibis_api_client::{CLIENT, article::RecentChangesParams, errors::FrontendResultExt};
+use ibis_database::common::{article::EditView, newtypes::EditId};
+use ibis_frontend_components::{
+    edit_list::EditBadges,
+    suspense_error::SuspenseError,
+    utils::{
+        formatting::{article_link, edit_path, edit_time, user_link},
+        resources::{is_admin, is_logged_in},
+    },
+};
+use leptos::prelude::*;
+use leptos_meta::Title;
+
+#[component]
+pub fn RecentChanges() -> impl IntoView {
+    let origin = signal("all".to_string());
+    let hide_bots = signal(true);
+    let unpatrolled_only = signal(false);
+    let hide_minor = signal(false);
+    let days = signal("7".to_string());
+
+    let edits = Resource::new(
+        move || RecentChangesParams {
+            local: match origin.0.get().as_str() {
+                "local" => Some(true),
+                "remote" => Some(false),
+                _ => None,
+            },
+            hide_bots: Some(hide_bots.0.get()),
+            unpatrolled_only: Some(unpatrolled_only.0.get()),
+            hide_minor: Some(hide_minor.0.get()),
+            days: days.0.get().parse().ok(),
+            ..Default::default()
+        },
+        |params| async move { CLIENT.recent_changes(&params).await },
+    );
+
+    let patrol_action = Action::new(move |edit_id: &EditId| {
+        let edit_id = *edit_id;
+        async move {
+            CLIENT
+                .patrol_edit(edit_id)
+                .await
+                .error_popup(|_| edits.refetch());
+        }
+    });
+    let revert_action = Action::new(move |edit_id: &EditId| {
+        let edit_id = *edit_id;
+        async move {
+            CLIENT
+                .revert_edit(edit_id)
+                .await
+                .error_popup(|_| edits.refetch());
+        }
+    });
+
+    view! {
+        <Title text="Recent changes" />
+        <h1 class="my-4 font-serif text-4xl font-bold">Recent changes</h1>
+        <div class="flex flex-row flex-wrap gap-4 items-center mb-4">
+            <select class="select select-secondary" bind:value=origin>
+                <option value="all">All instances</option>
+                <option value="local">Local</option>
+                <option value="remote">Remote</option>
+            </select>
+            <select class="select select-secondary" bind:value=days>
+                <option value="1">Last day</option>
+                <option value="7">Last week</option>
+                <option value="30">Last month</option>
+            </select>
+            <label class="flex gap-2 items-center">
+                <input type="checkbox" class="checkbox" bind:checked=hide_bots />
+                "Hide bots"
+            </label>
+            <label class="flex gap-2 items-center">
+                <input type="checkbox" class="checkbox" bind:checked=unpatrolled_only />
+                "Only unpatrolled"
+            </label>
+            <label class="flex gap-2 items-center">
+                <input type="checkbox" class="checkbox" bind:checked=hide_minor />
+                "Hide minor edits"
+            </label>
+        </div>
+        <SuspenseError result=edits>
+            {move || Suspend::new(async move {
+                edits
+                    .await
+                    .map(|edits| {
+                        view! {
+                            <ul>
+                                {edits
+                                    .into_iter()
+                                    .map(|edit| recent_change(edit, patrol_action, revert_action))
+                                    .collect::<Vec<_>>()}
+                            </ul>
+                        }
+                    })
+            })}
+        </SuspenseError>
+    }
+}
+
+fn recent_change(
+    edit: EditView,
+    patrol_action: Action<EditId, ()>,
+    revert_action: Action<EditId, ()>,
+) -> impl IntoView {
+    let edit_id = edit.edit.id;
+    let patrolled = edit.edit.patrolled;
+    view! {
+        <li class="m-2 card card-compact bg-base-100 card-bordered rounded-s">
+            <div class="card-body">
+                <div class="flex w-full">
+                    <a
+                        class="text-lg grow link link-primary"
+                        href=edit_path(&edit.edit, &edit.article)
+                    >
+                        {edit.edit.summary.clone()}
+                    </a>
+                    <EditBadges edit=edit.edit.clone() />
+                    <Show when=move || !patrolled>
+                        <span class="badge badge-warning">Unpatrolled</span>
+                    </Show>
+                </div>
+                <p>
+                    {article_link(&edit.article)} " " {edit_time(edit.edit.published)} " by "
+                    {user_link(&edit.creator)}
+                </p>
+                <details>
+                    <summary class="cursor-pointer">Diff</summary>
+                    <pre class="overflow-x-auto text-wrap">{edit.edit.diff.clone()}</pre>
+                </details>
+                <div class="justify-end card-actions">
+                    <Show when=is_logged_in>
+                        <button
+                            class="btn btn-sm btn-outline"
+                            on:click=move |_| {
+                                revert_action.dispatch(edit_id);
+                            }
+                        >
+                            Revert
+                        </button>
+                    </Show>
+                    <Show when=move || is_admin() && !patrolled>
+                        <button
+                            class="btn btn-sm btn-outline"
+                            on:click=move |_| {
+                                patrol_action.dispatch(edit_id);
+                            }
+                        >
+                            Mark as patrolled
+                        </button>
+                    </Show>
+                </div>
+            </div>
+        </li>
+    }
+}
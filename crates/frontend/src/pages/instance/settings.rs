@@ -1,5 +1,16 @@
-use ibis_api_client::{CLIENT, errors::FrontendResultExt, instance::UpdateInstanceParams};
+use ibis_api_client::{
+    CLIENT,
+    errors::FrontendResultExt,
+    instance::UpdateInstanceParams,
+    registration::ApproveRegistrationParams,
+    webhook::CreateWebhookParams,
+};
+use ibis_database::common::{
+    newtypes::{RegistrationApplicationId, WebhookId},
+    registration::RegistrationApplicationView,
+};
 use ibis_frontend_components::{suspense_error::SuspenseError, utils::resources::site};
+use itertools::Itertools;
 use leptos::prelude::*;
 use leptos_meta::Title;
 
@@ -82,5 +93,192 @@ pub fn AdminSettings() -> impl IntoView {
             })}
 
         </SuspenseError>
+        <RegistrationApplications />
+        <Webhooks />
+    }
+}
+
+/// Pending registration applications, which need to be approved before the user can login.
+#[component]
+fn RegistrationApplications() -> impl IntoView {
+    let applications = Resource::new(
+        || (),
+        |_| async move { CLIENT.list_registration_applications().await },
+    );
+    let decide_action = Action::new(move |params: &ApproveRegistrationParams| {
+        let params = params.clone();
+        async move {
+            CLIENT
+                .approve_registration_application(params)
+                .await
+                .error_popup(|_| applications.refetch());
+        }
+    });
+
+    view! {
+        <h2 class="flex-auto my-6 font-serif text-2xl font-bold grow">Registration applications</h2>
+        <SuspenseError result=applications>
+            {move || Suspend::new(async move {
+                applications
+                    .await
+                    .map(|applications| {
+                        if applications.is_empty() {
+                            return view! { <p>"No pending applications"</p> }.into_any();
+                        }
+                        applications
+                            .into_iter()
+                            .map(|a| application_row(a, decide_action))
+                            .collect_view()
+                            .into_any()
+                    })
+            })}
+        </SuspenseError>
+    }
+}
+
+fn application_row(
+    view: RegistrationApplicationView,
+    decide_action: Action<ApproveRegistrationParams, ()>,
+) -> impl IntoView {
+    let id = view.application.id;
+    let deny_reason = signal(String::new());
+    let decide = move |id: RegistrationApplicationId, approve: bool| {
+        let deny_reason = Some(deny_reason.0.get()).filter(|r| !approve && !r.is_empty());
+        decide_action.dispatch(ApproveRegistrationParams {
+            id,
+            approve,
+            deny_reason,
+        });
+    };
+    view! {
+        <div class="mb-4">
+            <div class="font-bold">{view.person.username}</div>
+            <p class="mb-2 whitespace-pre-wrap">{view.application.answer}</p>
+            <div class="flex flex-row items-center gap-2">
+                <button class="btn btn-sm btn-primary" on:click=move |_| decide(id, true)>
+                    Approve
+                </button>
+                <input
+                    type="text"
+                    class="w-80 input input-sm input-secondary input-bordered"
+                    placeholder="Deny reason (optional)"
+                    bind:value=deny_reason
+                />
+                <button class="btn btn-sm btn-outline" on:click=move |_| decide(id, false)>
+                    Deny
+                </button>
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn Webhooks() -> impl IntoView {
+    let webhooks = Resource::new(|| (), |_| async move { CLIENT.list_webhooks().await });
+    let url = signal(String::new());
+    let secret = signal(String::new());
+    let events = signal(String::new());
+
+    let create_action = Action::new(move |params: &CreateWebhookParams| {
+        let params = params.clone();
+        async move {
+            CLIENT
+                .create_webhook(params)
+                .await
+                .error_popup(|_| webhooks.refetch());
+        }
+    });
+    let delete_action = Action::new(move |id: &WebhookId| {
+        let id = *id;
+        async move {
+            CLIENT
+                .delete_webhook(id)
+                .await
+                .error_popup(|_| webhooks.refetch());
+        }
+    });
+
+    view! {
+        <h2 class="flex-auto my-6 font-serif text-2xl font-bold grow">Webhooks</h2>
+        <SuspenseError result=webhooks>
+            {move || Suspend::new(async move {
+                webhooks
+                    .await
+                    .map(|webhooks| {
+                        webhooks
+                            .into_iter()
+                            .map(|w| {
+                                let id = w.id;
+                                let events = if w.events.is_empty() {
+                                    "all events".to_string()
+                                } else {
+                                    w.events.iter().map(|e| e.as_str()).join(", ")
+                                };
+                                view! {
+                                    <div class="flex flex-row items-center mb-2">
+                                        <span class="block w-80 break-all">{w.url}</span>
+                                        <span class="block mx-2 grow">{events}</span>
+                                        <button
+                                            class="btn btn-sm btn-outline"
+                                            on:click=move |_| {
+                                                delete_action.dispatch(id);
+                                            }
+                                        >
+                                            Delete
+                                        </button>
+                                    </div>
+                                }
+                            })
+                            .collect_view()
+                    })
+            })}
+        </SuspenseError>
+        <div class="flex flex-row mb-2">
+            <label class="block w-20" for="webhook_url">
+                Url
+            </label>
+            <input
+                type="text"
+                id="webhook_url"
+                class="w-80 input input-secondary input-bordered"
+                bind:value=url
+            />
+        </div>
+        <div class="flex flex-row mb-2">
+            <label class="block w-20" for="webhook_secret">
+                Secret
+            </label>
+            <input
+                type="text"
+                id="webhook_secret"
+                class="w-80 input input-secondary input-bordered"
+                bind:value=secret
+            />
+        </div>
+        <div class="flex flex-row mb-2">
+            <label class="block w-20" for="webhook_events">
+                Events
+            </label>
+            <input
+                type="text"
+                id="webhook_events"
+                class="w-80 input input-secondary input-bordered"
+                placeholder="article_created,comment_created"
+                bind:value=events
+            />
+        </div>
+        <button
+            class="btn btn-primary"
+            on:click=move |_| {
+                let params = CreateWebhookParams {
+                    url: url.0.get(),
+                    secret: secret.0.get(),
+                    events: events.0.get(),
+                };
+                create_action.dispatch(params);
+            }
+        >
+            Add webhook
+        </button>
     }
 }
@@ -1,9 +1,31 @@
 use ibis_api_client::{
     CLIENT,
-    errors::FrontendResultExt,
-    user::{ChangePasswordParams, UpdateUserParams},
+    errors::{FrontendError, FrontendResult, FrontendResultExt},
+    registration::CreateInviteCodeParams,
+    user::{
+        ChangePasswordParams,
+        CreateApiTokenParams,
+        DeleteAccountParams,
+        DisableTotpParams,
+        TotpSetup,
+        UpdateUserParams,
+    },
+};
+use ibis_database::common::{
+    block::BlockKind,
+    instance::RegistrationMode,
+    newtypes::{ApiTokenId, InstanceId, InviteCodeId, PersonId, SessionId},
+    registration::InviteCode,
+    user::{ApiSession, ApiToken, ApiTokenScope, EmailDigest, NotificationPreference},
+};
+use ibis_frontend_components::{
+    oauth_login_button::OauthLinkButtons,
+    suspense_error::SuspenseError,
+    utils::{
+        formatting::time_ago,
+        resources::{config, site},
+    },
 };
-use ibis_frontend_components::{suspense_error::SuspenseError, utils::resources::site};
 use leptos::prelude::*;
 use leptos_meta::Title;
 
@@ -29,6 +51,9 @@ pub fn UserEditProfile() -> impl IntoView {
             });
         }
     });
+    let web_push_action = Action::new_local(move |_: &()| async move {
+        enable_web_push().await.error_popup(|_| set_saved.set(true));
+    });
     let site = site();
 
     // TODO: It would make sense to use a table for the labels and inputs, but for some reason
@@ -47,9 +72,35 @@ pub fn UserEditProfile() -> impl IntoView {
                         let bio = signal(my_profile.person.bio.clone().unwrap_or_default());
                         let email = signal(my_profile.local_user.email.clone().unwrap_or_default());
                         let email_notifications = signal(my_profile.local_user.email_notifications);
+                        let locale = signal(my_profile.local_user.locale.clone().unwrap_or_default());
+                        let email_digest = signal(
+                            my_profile.local_user.email_digest.as_str().to_string(),
+                        );
+                        let preference = |p: NotificationPreference| signal(p.as_str().to_string());
+                        let notify_comment_reply = preference(
+                            my_profile.local_user.notify_comment_reply,
+                        );
+                        let notify_article_comment = preference(
+                            my_profile.local_user.notify_article_comment,
+                        );
+                        let notify_article_edit = preference(
+                            my_profile.local_user.notify_article_edit,
+                        );
+                        let notify_new_article = preference(
+                            my_profile.local_user.notify_new_article,
+                        );
+                        let notify_mention = preference(my_profile.local_user.notify_mention);
+                        let notify_minor_edit = preference(
+                            my_profile.local_user.notify_minor_edit,
+                        );
+                        let notify_bot_edit = preference(my_profile.local_user.notify_bot_edit);
+                        let bot = signal(my_profile.person.bot);
                         let new_password = signal(String::new());
                         let confirm_new_password = signal(String::new());
                         let old_password = signal(String::new());
+                        let totp_enabled = my_profile.local_user.totp_enabled;
+                        let totp_required = my_profile.local_user.admin
+                            && config().admin_totp_required;
                         view! {
                             <h1 class="flex-auto my-6 font-serif text-4xl font-bold grow">
                                 Edit Profile
@@ -88,6 +139,18 @@ pub fn UserEditProfile() -> impl IntoView {
                                     bind:value=email
                                 />
                             </div>
+                            <div class="flex flex-row mb-2">
+                                <label class="block w-40" for="locale">
+                                    "Email language"
+                                </label>
+                                <input
+                                    type="text"
+                                    id="locale"
+                                    class="w-80 input input-secondary input-bordered"
+                                    placeholder="en"
+                                    bind:value=locale
+                                />
+                            </div>
                             <div class="flex flex-row mb-2">
                                 <label class="block w-40" for="email_notifications">
                                     Send email notifications
@@ -99,6 +162,70 @@ pub fn UserEditProfile() -> impl IntoView {
                                     bind:checked=email_notifications
                                 />
                             </div>
+                            <div class="flex flex-row mb-2">
+                                <label class="block w-40" for="bot">
+                                    Bot account
+                                </label>
+                                <input type="checkbox" id="bot" class="checkbox" bind:checked=bot />
+                            </div>
+                            <div class="flex flex-row mb-2">
+                                <label class="block w-40" for="email_digest">
+                                    Email frequency
+                                </label>
+                                <select
+                                    id="email_digest"
+                                    class="w-80 select select-secondary"
+                                    bind:value=email_digest
+                                >
+                                    {EmailDigest::ALL
+                                        .map(|d| {
+                                            view! { <option value=d.as_str()>{d.as_str()}</option> }
+                                        })
+                                        .collect_view()}
+                                </select>
+                            </div>
+                            <h2 class="flex-auto my-6 font-serif text-2xl font-bold grow">
+                                Notifications
+                            </h2>
+                            <PreferenceSelect
+                                label="Replies to my comments"
+                                id="notify_comment_reply"
+                                value=notify_comment_reply
+                            />
+                            <PreferenceSelect
+                                label="Comments on followed articles"
+                                id="notify_article_comment"
+                                value=notify_article_comment
+                            />
+                            <PreferenceSelect
+                                label="Edits of followed articles"
+                                id="notify_article_edit"
+                                value=notify_article_edit
+                            />
+                            <PreferenceSelect
+                                label="Minor edits of followed articles"
+                                id="notify_minor_edit"
+                                value=notify_minor_edit
+                            />
+                            <PreferenceSelect
+                                label="Edits by bots"
+                                id="notify_bot_edit"
+                                value=notify_bot_edit
+                            />
+                            <PreferenceSelect
+                                label="New articles on followed instances"
+                                id="notify_new_article"
+                                value=notify_new_article
+                            />
+                            <PreferenceSelect label="Mentions" id="notify_mention" value=notify_mention />
+                            <button
+                                class="mb-2 btn btn-secondary"
+                                on:click=move |_| {
+                                    web_push_action.dispatch_local(());
+                                }
+                            >
+                                Enable push notifications on this device
+                            </button>
                             <button
                                 class="btn btn-primary"
                                 on:click=move |_| {
@@ -107,6 +234,20 @@ pub fn UserEditProfile() -> impl IntoView {
                                         bio: Some(bio.0.get()),
                                         email: Some(email.0.get()),
                                         email_notifications: Some(email_notifications.0.get()),
+                                        email_digest: email_digest.0.get().parse().ok(),
+                                        locale: Some(locale.0.get()),
+                                        notify_comment_reply: notify_comment_reply.0.get().parse().ok(),
+                                        notify_article_comment: notify_article_comment
+                                            .0
+                                            .get()
+                                            .parse()
+                                            .ok(),
+                                        notify_article_edit: notify_article_edit.0.get().parse().ok(),
+                                        notify_new_article: notify_new_article.0.get().parse().ok(),
+                                        notify_mention: notify_mention.0.get().parse().ok(),
+                                        notify_minor_edit: notify_minor_edit.0.get().parse().ok(),
+                                        notify_bot_edit: notify_bot_edit.0.get().parse().ok(),
+                                        bot: Some(bot.0.get()),
                                     };
                                     submit_action.dispatch(form);
                                 }
@@ -166,6 +307,35 @@ pub fn UserEditProfile() -> impl IntoView {
                                 Save
                             </button>
 
+                            <div class="divider"></div>
+
+                            <TwoFactorAuth enabled=totp_enabled required=totp_required />
+
+                            <div class="divider"></div>
+
+                            <Sessions />
+
+                            <div class="divider"></div>
+
+                            <ApiTokens />
+
+                            <div class="divider"></div>
+
+                            <Blocks />
+
+                            <Show when=move || {
+                                config().registration_mode != RegistrationMode::Open
+                            }>
+                                <div class="divider"></div>
+                                <InviteCodes />
+                            </Show>
+
+                            <OauthLinkButtons />
+
+                            <div class="divider"></div>
+
+                            <PersonalData totp_enabled=totp_enabled />
+
                             <Show when=move || saved.get()>
                                 <div class="toast">
                                     <div class="alert alert-info">
@@ -180,3 +350,726 @@ pub fn UserEditProfile() -> impl IntoView {
         </SuspenseError>
     }
 }
+
+/// Enable two-factor authentication with an authenticator app, or disable it again.
+#[component]
+fn TwoFactorAuth(enabled: bool, required: bool) -> impl IntoView {
+    let (enabled, set_enabled) = signal(enabled);
+    let setup = signal(None::<TotpSetup>);
+    let recovery_codes = signal(Vec::<String>::new());
+    let totp_token = signal(String::new());
+    let password = signal(String::new());
+
+    let generate_action = Action::new(move |_: &()| async move {
+        CLIENT
+            .generate_totp()
+            .await
+            .error_popup(|s| setup.1.set(Some(s)));
+    });
+    let enable_action = Action::new(move |totp_token: &String| {
+        let totp_token = totp_token.clone();
+        async move {
+            CLIENT.enable_totp(totp_token).await.error_popup(|res| {
+                recovery_codes.1.set(res.recovery_codes);
+                setup.1.set(None);
+                set_enabled.set(true);
+            });
+        }
+    });
+    let disable_action = Action::new(move |params: &DisableTotpParams| {
+        let params = params.clone();
+        async move {
+            CLIENT.disable_totp(params).await.error_popup(|_| {
+                recovery_codes.1.set(vec![]);
+                set_enabled.set(false);
+            });
+        }
+    });
+
+    view! {
+        <h2 class="flex-auto my-6 font-serif text-2xl font-bold grow">
+            Two-factor authentication
+        </h2>
+        <Show when=move || required && !enabled.get()>
+            <div class="mb-2 alert alert-warning">
+                Admins of this instance need to enable two-factor authentication to perform admin actions.
+            </div>
+        </Show>
+        <Show when=move || !recovery_codes.0.get().is_empty()>
+            <p class="mb-2">
+                "Store these recovery codes in a safe place. Each of them can be used once to login if you lose access to your authenticator app."
+            </p>
+            <ul class="mb-2 font-mono">
+                {move || {
+                    recovery_codes
+                        .0
+                        .get()
+                        .into_iter()
+                        .map(|c| view! { <li>{c}</li> })
+                        .collect_view()
+                }}
+            </ul>
+        </Show>
+        <Show
+            when=move || enabled.get()
+            fallback=move || {
+                match setup.0.get() {
+                    None => {
+                        view! {
+                            <button
+                                class="btn btn-primary"
+                                on:click=move |_| {
+                                    generate_action.dispatch(());
+                                }
+                            >
+                                Enable
+                            </button>
+                        }
+                            .into_any()
+                    }
+                    Some(setup) => {
+                        view! {
+                            <p class="mb-2">
+                                "Add this secret to your authenticator app, then enter the code which it shows."
+                            </p>
+                            <p class="mb-2">
+                                <code>{setup.secret}</code>
+                                " "
+                                <a class="link" href=setup.uri.to_string()>
+                                    Open in authenticator app
+                                </a>
+                            </p>
+                            <div class="flex flex-row mb-2">
+                                <label class="block w-40" for="totp_token">
+                                    Code
+                                </label>
+                                <input
+                                    type="text"
+                                    id="totp_token"
+                                    class="w-80 input input-secondary input-bordered"
+                                    autocomplete="one-time-code"
+                                    bind:value=totp_token
+                                />
+                            </div>
+                            <button
+                                class="btn btn-primary"
+                                on:click=move |_| {
+                                    enable_action.dispatch(totp_token.0.get());
+                                }
+                            >
+                                Confirm
+                            </button>
+                        }
+                            .into_any()
+                    }
+                }
+            }
+        >
+            <div class="flex flex-row mb-2">
+                <label class="block w-40" for="totp_password">
+                    Password
+                </label>
+                <input
+                    type="password"
+                    id="totp_password"
+                    class="w-80 input input-secondary input-bordered"
+                    bind:value=password
+                />
+            </div>
+            <div class="flex flex-row mb-2">
+                <label class="block w-40" for="totp_disable_token">
+                    Code
+                </label>
+                <input
+                    type="text"
+                    id="totp_disable_token"
+                    class="w-80 input input-secondary input-bordered"
+                    autocomplete="one-time-code"
+                    bind:value=totp_token
+                />
+            </div>
+            <button
+                class="btn btn-warning"
+                on:click=move |_| {
+                    let params = DisableTotpParams {
+                        password: password.0.get(),
+                        totp_token: totp_token.0.get(),
+                    };
+                    disable_action.dispatch(params);
+                }
+            >
+                Disable
+            </button>
+        </Show>
+    }
+}
+
+/// Devices where the user is logged in, with buttons to sign out each of them.
+#[component]
+fn Sessions() -> impl IntoView {
+    let sessions = Resource::new(|| (), |_| async move { CLIENT.list_sessions().await });
+    let revoke_action = Action::new(move |session_id: &SessionId| {
+        let session_id = *session_id;
+        async move {
+            CLIENT
+                .revoke_session(session_id)
+                .await
+                .error_popup(|_| sessions.refetch());
+        }
+    });
+    view! {
+        <h2 class="flex-auto my-6 font-serif text-2xl font-bold grow">Sessions</h2>
+        <SuspenseError result=sessions>
+            {move || Suspend::new(async move {
+                sessions
+                    .await
+                    .map(|sessions| {
+                        view! {
+                            <table class="table">
+                                <thead>
+                                    <tr>
+                                        <th>Device</th>
+                                        <th>IP address</th>
+                                        <th>Last active</th>
+                                        <th></th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {sessions
+                                        .into_iter()
+                                        .map(|s| session_row(s, revoke_action))
+                                        .collect_view()}
+                                </tbody>
+                            </table>
+                        }
+                    })
+            })}
+        </SuspenseError>
+    }
+}
+
+fn session_row(session: ApiSession, revoke_action: Action<SessionId, ()>) -> impl IntoView {
+    let id = session.id;
+    let current = session.current;
+    view! {
+        <tr>
+            <td>{session.user_agent.unwrap_or_else(|| "Unknown".to_string())}</td>
+            <td>{session.ip}</td>
+            <td>{time_ago(session.last_seen)}</td>
+            <td>
+                <Show
+                    when=move || !current
+                    fallback=|| view! { <span class="badge badge-info">This device</span> }
+                >
+                    <button
+                        class="btn btn-sm btn-outline"
+                        on:click=move |_| {
+                            revoke_action.dispatch(id);
+                        }
+                    >
+                        Sign out
+                    </button>
+                </Show>
+            </td>
+        </tr>
+    }
+}
+
+/// Persons and instances which the user blocked or muted, with buttons to undo it.
+#[component]
+fn Blocks() -> impl IntoView {
+    let blocks = Resource::new(|| (), |_| async move { CLIENT.list_blocks().await });
+    let unblock_person_action = Action::new(move |person_id: &PersonId| {
+        let person_id = *person_id;
+        async move {
+            CLIENT
+                .unblock_person(person_id)
+                .await
+                .error_popup(|_| blocks.refetch());
+        }
+    });
+    let unblock_instance_action = Action::new(move |instance_id: &InstanceId| {
+        let instance_id = *instance_id;
+        async move {
+            CLIENT
+                .unblock_instance(instance_id)
+                .await
+                .error_popup(|_| blocks.refetch());
+        }
+    });
+    view! {
+        <h2 class="flex-auto my-6 font-serif text-2xl font-bold grow">Blocked and muted</h2>
+        <SuspenseError result=blocks>
+            {move || Suspend::new(async move {
+                blocks
+                    .await
+                    .map(|blocks| {
+                        let empty = blocks.persons.is_empty() && blocks.instances.is_empty();
+                        let persons = blocks
+                            .persons
+                            .into_iter()
+                            .map(|b| {
+                                let id = b.person.id;
+                                block_row(
+                                    b.person.title(),
+                                    b.block.kind,
+                                    Callback::new(move |_| {
+                                        unblock_person_action.dispatch(id);
+                                    }),
+                                )
+                            })
+                            .collect_view();
+                        let instances = blocks
+                            .instances
+                            .into_iter()
+                            .map(|b| {
+                                let id = b.instance.id;
+                                block_row(
+                                    b.instance.domain,
+                                    b.block.kind,
+                                    Callback::new(move |_| {
+                                        unblock_instance_action.dispatch(id);
+                                    }),
+                                )
+                            })
+                            .collect_view();
+                        view! {
+                            <Show
+                                when=move || !empty
+                                fallback=|| view! { <p>Nobody is blocked or muted.</p> }
+                            >
+                                <p class="mb-2">
+                                    "Muted users don't send you any notifications. Comments of blocked users are also hidden."
+                                </p>
+                            </Show>
+                            <table class="table">
+                                <tbody>{persons}{instances}</tbody>
+                            </table>
+                        }
+                    })
+            })}
+        </SuspenseError>
+    }
+}
+
+fn block_row(name: String, kind: BlockKind, unblock: Callback<()>) -> impl IntoView {
+    let kind = match kind {
+        BlockKind::Mute => "Muted",
+        BlockKind::Block => "Blocked",
+    };
+    view! {
+        <tr>
+            <td>{name}</td>
+            <td>
+                <span class="badge badge-outline">{kind}</span>
+            </td>
+            <td>
+                <button class="btn btn-sm btn-outline" on:click=move |_| unblock.run(())>
+                    Unblock
+                </button>
+            </td>
+        </tr>
+    }
+}
+
+/// Personal tokens for scripts and bots, with a form to create new ones.
+#[component]
+fn ApiTokens() -> impl IntoView {
+    let api_tokens = Resource::new(|| (), |_| async move { CLIENT.list_api_tokens().await });
+    let name = signal(String::new());
+    let scope = signal(ApiTokenScope::Read.as_str().to_string());
+    // the new token is only returned once, so show it until the page is left
+    let new_token = signal(None::<String>);
+    let create_action = Action::new(move |params: &CreateApiTokenParams| {
+        let params = params.clone();
+        async move {
+            CLIENT.create_api_token(params).await.error_popup(|res| {
+                new_token.1.set(Some(res.token));
+                name.1.set(String::new());
+                api_tokens.refetch();
+            });
+        }
+    });
+    let delete_action = Action::new(move |id: &ApiTokenId| {
+        let id = *id;
+        async move {
+            CLIENT
+                .delete_api_token(id)
+                .await
+                .error_popup(|_| api_tokens.refetch());
+        }
+    });
+    view! {
+        <h2 class="flex-auto my-6 font-serif text-2xl font-bold grow">Api tokens</h2>
+        <div class="flex flex-row mb-2">
+            <label class="block w-40" for="api_token_name">
+                Name
+            </label>
+            <input
+                type="text"
+                id="api_token_name"
+                class="w-80 input input-secondary input-bordered"
+                bind:value=name
+            />
+        </div>
+        <div class="flex flex-row mb-2">
+            <label class="block w-40" for="api_token_scope">
+                Scope
+            </label>
+            <select id="api_token_scope" class="w-80 select select-secondary" bind:value=scope>
+                {ApiTokenScope::ALL
+                    .map(|s| {
+                        view! { <option value=s.as_str()>{s.as_str()}</option> }
+                    })
+                    .collect_view()}
+            </select>
+        </div>
+        <button
+            class="mb-2 btn btn-primary"
+            prop:disabled=move || name.0.get().trim().is_empty()
+            on:click=move |_| {
+                let params = CreateApiTokenParams {
+                    name: name.0.get(),
+                    scope: scope.0.get().parse().unwrap_or_default(),
+                };
+                create_action.dispatch(params);
+            }
+        >
+            Create
+        </button>
+        {move || {
+            new_token
+                .0
+                .get()
+                .map(|token| {
+                    view! {
+                        <div class="mb-2 alert alert-info">
+                            <span>
+                                "Copy the new token now, it won't be shown again: "
+                                <code>{token}</code>
+                            </span>
+                        </div>
+                    }
+                })
+        }}
+        <SuspenseError result=api_tokens>
+            {move || Suspend::new(async move {
+                api_tokens
+                    .await
+                    .map(|api_tokens| {
+                        view! {
+                            <table class="table">
+                                <thead>
+                                    <tr>
+                                        <th>Name</th>
+                                        <th>Scope</th>
+                                        <th>Last used</th>
+                                        <th></th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {api_tokens
+                                        .into_iter()
+                                        .map(|t| api_token_row(t, delete_action))
+                                        .collect_view()}
+                                </tbody>
+                            </table>
+                        }
+                    })
+            })}
+        </SuspenseError>
+    }
+}
+
+fn api_token_row(api_token: ApiToken, delete_action: Action<ApiTokenId, ()>) -> impl IntoView {
+    let id = api_token.id;
+    view! {
+        <tr>
+            <td>{api_token.name}</td>
+            <td>{api_token.scope.as_str()}</td>
+            <td>{api_token.last_used.map(time_ago).unwrap_or_else(|| "Never".to_string())}</td>
+            <td>
+                <button
+                    class="btn btn-sm btn-outline"
+                    on:click=move |_| {
+                        delete_action.dispatch(id);
+                    }
+                >
+                    Delete
+                </button>
+            </td>
+        </tr>
+    }
+}
+
+/// Download of all data stored about the user, and account deletion.
+#[component]
+fn PersonalData(totp_enabled: bool) -> impl IntoView {
+    let password = signal(String::new());
+    let totp_token = signal(String::new());
+    let confirmed = signal(false);
+    let delete_action = Action::new(move |params: &DeleteAccountParams| {
+        let params = params.clone();
+        async move {
+            CLIENT.delete_account(params).await.error_popup(|_| {
+                site().refetch();
+            });
+        }
+    });
+    view! {
+        <h2 class="flex-auto my-6 font-serif text-2xl font-bold grow">Personal data</h2>
+        <a
+            class="mb-4 btn btn-outline"
+            href="/api/v1/account/personal_data"
+            download="personal_data.json"
+        >
+            Download my data
+        </a>
+        <p class="mb-2">
+            "Deleting your account is permanent. Your edits remain in the article history, but are no longer attributed to you. Your comments are removed."
+        </p>
+        <div class="flex flex-row mb-2">
+            <label class="block w-40" for="delete_password">
+                Password
+            </label>
+            <input
+                type="password"
+                id="delete_password"
+                class="w-80 input input-secondary input-bordered"
+                bind:value=password
+            />
+        </div>
+        <Show when=move || totp_enabled>
+            <div class="flex flex-row mb-2">
+                <label class="block w-40" for="delete_totp_token">
+                    Two-factor code
+                </label>
+                <input
+                    type="text"
+                    id="delete_totp_token"
+                    class="w-80 input input-secondary input-bordered"
+                    autocomplete="one-time-code"
+                    bind:value=totp_token
+                />
+            </div>
+        </Show>
+        <div class="flex flex-row mb-2">
+            <label class="block w-40" for="delete_confirm">
+                I am sure
+            </label>
+            <input type="checkbox" id="delete_confirm" class="checkbox" bind:checked=confirmed />
+        </div>
+        <button
+            class="btn btn-error"
+            prop:disabled=move || !confirmed.0.get()
+            on:click=move |_| {
+                let params = DeleteAccountParams {
+                    password: Some(password.0.get()).filter(|p| !p.is_empty()),
+                    totp_token: Some(totp_token.0.get()).filter(|t| !t.is_empty()),
+                };
+                delete_action.dispatch(params);
+            }
+        >
+            Delete account
+        </button>
+    }
+}
+
+/// Codes which allow others to register, if registration is not open.
+#[component]
+fn InviteCodes() -> impl IntoView {
+    let invite_codes = Resource::new(|| (), |_| async move { CLIENT.list_invite_codes().await });
+    let max_uses = signal("1".to_string());
+    let expires_in_days = signal(String::new());
+    let create_action = Action::new(move |params: &CreateInviteCodeParams| {
+        let params = params.clone();
+        async move {
+            CLIENT
+                .create_invite_code(params)
+                .await
+                .error_popup(|_| invite_codes.refetch());
+        }
+    });
+    let delete_action = Action::new(move |id: &InviteCodeId| {
+        let id = *id;
+        async move {
+            CLIENT
+                .delete_invite_code(id)
+                .await
+                .error_popup(|_| invite_codes.refetch());
+        }
+    });
+    view! {
+        <h2 class="flex-auto my-6 font-serif text-2xl font-bold grow">Invite codes</h2>
+        <div class="flex flex-row mb-2">
+            <label class="block w-40" for="invite_max_uses">
+                Maximum uses
+            </label>
+            <input
+                type="number"
+                min="1"
+                id="invite_max_uses"
+                class="w-80 input input-secondary input-bordered"
+                bind:value=max_uses
+            />
+        </div>
+        <div class="flex flex-row mb-2">
+            <label class="block w-40" for="invite_expires">
+                Expires after days
+            </label>
+            <input
+                type="number"
+                min="1"
+                id="invite_expires"
+                class="w-80 input input-secondary input-bordered"
+                placeholder="Never"
+                bind:value=expires_in_days
+            />
+        </div>
+        <button
+            class="mb-2 btn btn-primary"
+            on:click=move |_| {
+                let params = CreateInviteCodeParams {
+                    max_uses: max_uses.0.get().parse().unwrap_or(1),
+                    expires_in_days: expires_in_days.0.get().parse().ok(),
+                };
+                create_action.dispatch(params);
+            }
+        >
+            Create
+        </button>
+        <SuspenseError result=invite_codes>
+            {move || Suspend::new(async move {
+                invite_codes
+                    .await
+                    .map(|invite_codes| {
+                        view! {
+                            <table class="table">
+                                <thead>
+                                    <tr>
+                                        <th>Link</th>
+                                        <th>Uses</th>
+                                        <th>Expires</th>
+                                        <th></th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {invite_codes
+                                        .into_iter()
+                                        .map(|c| invite_code_row(c, delete_action))
+                                        .collect_view()}
+                                </tbody>
+                            </table>
+                        }
+                    })
+            })}
+        </SuspenseError>
+    }
+}
+
+fn invite_code_row(
+    invite_code: InviteCode,
+    delete_action: Action<InviteCodeId, ()>,
+) -> impl IntoView {
+    let id = invite_code.id;
+    view! {
+        <tr>
+            <td>
+                <code>{format!("/register?invite={}", invite_code.code)}</code>
+            </td>
+            <td>{format!("{}/{}", invite_code.uses, invite_code.max_uses)}</td>
+            <td>
+                {invite_code
+                    .expires
+                    .map(|e| e.format("%Y-%m-%d").to_string())
+                    .unwrap_or_else(|| "Never".to_string())}
+            </td>
+            <td>
+                <button
+                    class="btn btn-sm btn-outline"
+                    on:click=move |_| {
+                        delete_action.dispatch(id);
+                    }
+                >
+                    Delete
+                </button>
+            </td>
+        </tr>
+    }
+}
+
+#[component]
+fn PreferenceSelect(
+    label: &'static str,
+    id: &'static str,
+    value: (ReadSignal<String>, WriteSignal<String>),
+) -> impl IntoView {
+    view! {
+        <div class="flex flex-row mb-2">
+            <label class="block w-40" for=id>
+                {label}
+            </label>
+            <select id=id class="w-80 select select-secondary" bind:value=value>
+                {NotificationPreference::ALL
+                    .map(|p| {
+                        view! { <option value=p.as_str()>{p.as_str()}</option> }
+                    })
+                    .collect_view()}
+            </select>
+        </div>
+    }
+}
+
+/// Subscribe this browser to push notifications, and register the subscription with the server.
+#[cfg(not(feature = "ssr"))]
+async fn enable_web_push() -> FrontendResult<()> {
+    use ibis_api_client::notifications::WebPushSubscribeParams;
+    use leptos::wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{PushSubscription, PushSubscriptionOptionsInit, ServiceWorkerRegistration};
+
+    let js_error = |e: JsValue| FrontendError::new(format!("Push subscription failed: {e:?}"));
+    let container = window().navigator().service_worker();
+    JsFuture::from(container.register("/sw.js"))
+        .await
+        .map_err(js_error)?;
+    let registration: ServiceWorkerRegistration =
+        JsFuture::from(container.ready().map_err(js_error)?)
+            .await
+            .map_err(js_error)?
+            .unchecked_into();
+
+    let key = CLIENT.web_push_key().await?;
+    let options = PushSubscriptionOptionsInit::new();
+    options.set_user_visible_only(true);
+    options.set_application_server_key(&JsValue::from_str(&key.public_key));
+    let push_manager = registration.push_manager().map_err(js_error)?;
+    let subscription: PushSubscription = JsFuture::from(
+        push_manager
+            .subscribe_with_options(&options)
+            .map_err(js_error)?,
+    )
+    .await
+    .map_err(js_error)?
+    .unchecked_into();
+
+    let keys = subscription
+        .to_json()
+        .map_err(js_error)?
+        .get_keys()
+        .ok_or(FrontendError::new("Push subscription has no keys"))?;
+    let params = WebPushSubscribeParams {
+        endpoint: subscription.endpoint(),
+        p256dh: keys.get_p256dh().unwrap_or_default(),
+        auth: keys.get_auth().unwrap_or_default(),
+    };
+    CLIENT.web_push_subscribe(params).await?;
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+async fn enable_web_push() -> FrontendResult<()> {
+    Err(FrontendError::new(
+        "Push notifications can only be enabled in the browser",
+    ))
+}
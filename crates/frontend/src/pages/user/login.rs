@@ -1,29 +1,47 @@
-use ibis_api_client::{CLIENT, errors::FrontendResultExt, user::LoginUserParams};
+use ibis_api_client::{
+    CLIENT,
+    errors::FrontendResultExt,
+    user::{LoginUserParams, TOTP_REQUIRED},
+};
 use ibis_frontend_components::{oauth_login_button::OauthLoginButtons, utils::resources::site};
 use leptos::prelude::*;
 use leptos_meta::Title;
-use leptos_router::components::Redirect;
+use leptos_router::{components::Redirect, hooks::use_query_map};
 
 #[component]
 pub fn Login() -> impl IntoView {
     let password = signal(String::new());
     let username_or_email = signal(String::new());
+    let totp_token = signal(String::new());
+    // shown after the password was accepted, if the user enabled two-factor authentication. Login
+    // with SSO redirects here in the same case.
+    let (totp_required, set_totp_required) = signal(
+        use_query_map()
+            .get_untracked()
+            .get("totp_required")
+            .is_some(),
+    );
     let (login_response, set_login_response) = signal(false);
     let (loading, set_loading) = signal(false);
 
     let login_action = Action::new(move |(): &()| {
         let username_or_email = username_or_email.0.get().to_string();
         let password = password.0.get().to_string();
+        let totp_token = Some(totp_token.0.get()).filter(|t| !t.is_empty());
         let params = LoginUserParams {
             username_or_email,
             password,
+            totp_token,
         };
         async move {
             set_loading.set(true);
-            CLIENT.login(params).await.error_popup(|_| {
-                site().refetch();
-                set_login_response.set(true);
-            });
+            match CLIENT.login(params).await {
+                Err(e) if e.clone().message() == TOTP_REQUIRED => set_totp_required.set(true),
+                res => res.error_popup(|_| {
+                    site().refetch();
+                    set_login_response.set(true);
+                }),
+            }
             set_loading.set(false);
         }
     });
@@ -60,6 +78,17 @@ pub fn Login() -> impl IntoView {
                     prop:disabled=move || loading.get()
                     bind:value=password
                 />
+                <Show when=move || totp_required.get()>
+                    <input
+                        type="text"
+                        class="input input-primary input-bordered my-1"
+                        required
+                        autocomplete="one-time-code"
+                        placeholder="Authentication code or recovery code"
+                        prop:disabled=move || loading.get()
+                        bind:value=totp_token
+                    />
+                </Show>
                 <a href="/account/request_password_reset" class="link text-sm">
                     Reset password
                 </a>
@@ -77,7 +106,7 @@ pub fn Login() -> impl IntoView {
                 </div>
             </form>
 
-            <OauthLoginButtons username=username_or_email.0 />
+            <OauthLoginButtons username=username_or_email.0 totp_token=totp_token.0 />
         </Show>
     }
 }
@@ -46,8 +46,23 @@ pub fn Notifications() -> impl IntoView {
                                             )
                                         }
                                         ArticleCreated => B(article_view(notif, refresh_res)),
-                                        Comment(c) => C(comment_view(notif, c, refresh_res)),
-                                        Edit(e) => D(edit_view(notif, e, refresh_res)),
+                                        Comment(c) => {
+                                            C(comment_view(notif, c, "New comment: ", refresh_res))
+                                        }
+                                        CommentMention(c) => {
+                                            C(
+                                                comment_view(
+                                                    notif,
+                                                    c,
+                                                    "Mentioned in comment: ",
+                                                    refresh_res,
+                                                ),
+                                            )
+                                        }
+                                        Edit(e) => D(edit_view(notif, e, "New edit: ", refresh_res)),
+                                        EditMention(e) => {
+                                            D(edit_view(notif, e, "Mentioned in edit: ", refresh_res))
+                                        }
                                     }
                                 })
                                 .collect::<Vec<_>>()
@@ -101,12 +116,13 @@ fn article_view(notif: &ApiNotification, refresh_res: NotificationsResource) ->
 fn comment_view(
     notif: &ApiNotification,
     comment: &Comment,
+    label: &'static str,
     refresh_res: NotificationsResource,
 ) -> impl IntoView {
     view! {
         <li class="py-2">
             <CardTitle notif=notif.clone() />
-            <div>"New comment: "{comment.content.clone()}</div>
+            <div>{label}{comment.content.clone()}</div>
             <CardActions
                 href=comment_path(comment, &notif.article)
                 notif=notif.clone()
@@ -119,12 +135,13 @@ fn comment_view(
 fn edit_view(
     notif: &ApiNotification,
     edit: &Edit,
+    label: &'static str,
     refresh_res: NotificationsResource,
 ) -> impl IntoView {
     view! {
         <li class="py-2">
             <CardTitle notif=notif.clone() />
-            <div>"New edit: "{edit.summary.clone()}</div>
+            <div>{label}{edit.summary.clone()}</div>
             <CardActions
                 href=edit_path(edit, &notif.article)
                 notif=notif.clone()
@@ -1,9 +1,12 @@
 use ibis_api_client::{
     CLIENT,
     errors::{ErrorPopup, FrontendResultExt},
-    user::AuthenticateWithOauth,
+    user::{AuthenticateWithOauth, TOTP_REQUIRED},
+};
+use ibis_frontend_components::{
+    oauth_login_button::OauthCookie,
+    utils::{resources::config, use_cookie},
 };
-use ibis_frontend_components::{oauth_login_button::OauthCookie, utils::use_cookie};
 use leptos::{prelude::*, task::Executor};
 use leptos_router::hooks::use_query_map;
 
@@ -22,17 +25,39 @@ pub fn OauthCallback() -> impl IntoView {
                 window().location().set_href(&uri).expect("set location");
             }
 
-            let params = AuthenticateWithOauth {
+            let mut params = AuthenticateWithOauth {
                 code: code.expect("code is set"),
                 oauth_issuer: cookie.issuer_url,
                 redirect_uri: cookie.redirect_url,
                 username: cookie.username,
+                pkce_code_verifier: Some(cookie.pkce_code_verifier),
+                totp_token: cookie.totp_token,
+                invite_code: None,
+                application_answer: None,
+                challenge: None,
+                challenge_nonce: None,
             };
+            // the authorization code can only be used once, so solve the challenge beforehand in
+            // case a new account gets registered
+            let registering = params.username.as_ref().is_some_and(|u| !u.is_empty());
             Executor::spawn(async move {
-                CLIENT
-                    .oauth_authenticate(params)
-                    .await
-                    .error_popup(|_| window().location().set_pathname("/").expect("set location"));
+                let res = async {
+                    if registering && config().proof_of_work_difficulty > 0 {
+                        let (challenge, nonce) = CLIENT.solve_challenge().await?;
+                        params.challenge = Some(challenge);
+                        params.challenge_nonce = Some(nonce);
+                    }
+                    CLIENT.oauth_authenticate(params).await
+                };
+                match res.await {
+                    Err(e) if e.clone().message() == TOTP_REQUIRED => window()
+                        .location()
+                        .set_href("/login?totp_required")
+                        .expect("set location"),
+                    res => res.error_popup(|_| {
+                        window().location().set_pathname("/").expect("set location")
+                    }),
+                }
             });
         });
 
@@ -1,9 +1,28 @@
-use ibis_api_client::{CLIENT, user::GetUserParams};
-use ibis_frontend_components::{edit_list::EditList, suspense_error::SuspenseError};
+use chrono::{Datelike, NaiveDate, TimeDelta, Utc};
+use ibis_api_client::{
+    CLIENT,
+    errors::FrontendResultExt,
+    user::{CONTRIBUTIONS_PAGE_SIZE, GetContributionsParams, GetUserParams},
+};
+use ibis_database::common::{
+    comment::CommentViewWithArticle,
+    contributions::{ActivityDay, PersonStats},
+    newtypes::PersonId,
+};
+use ibis_frontend_components::{
+    block_buttons::{BlockButtons, BlockTarget},
+    edit_list::EditList,
+    suspense_error::SuspenseError,
+    utils::{
+        formatting::{article_link, comment_path, edit_time},
+        resources::{is_admin, my_profile},
+    },
+};
 use ibis_markdown::render_article_markdown;
 use leptos::prelude::*;
-use leptos_meta::Title;
+use leptos_meta::{Link, Title};
 use leptos_router::hooks::use_params_map;
+use std::collections::HashMap;
 
 #[component]
 pub fn UserProfile() -> impl IntoView {
@@ -18,43 +37,236 @@ pub fn UserProfile() -> impl IntoView {
         let params = GetUserParams { name, domain };
         CLIENT.get_user(params).await
     });
+    let trust_bot_action = Action::new(move |(person_id, trusted): &(PersonId, bool)| {
+        let (person_id, trusted) = (*person_id, *trusted);
+        async move {
+            CLIENT
+                .trust_bot(person_id, trusted)
+                .await
+                .error_popup(|_| user_profile.refetch());
+        }
+    });
 
     view! {
         <SuspenseError result=user_profile>
             {move || Suspend::new(async move {
-                let edits = Resource::new(
-                    move || user_profile.get(),
-                    move |_| async move { CLIENT.get_person_edits(user_profile.await?.id).await },
-                );
                 user_profile
                     .await
                     .map(|person| {
+                        let (person_id, bot, bot_trusted) = (person.id, person.bot, person.bot_trusted);
+                        let is_me = my_profile().is_some_and(|m| m.person.id == person_id);
                         view! {
                             <Title text=person.title() />
-                            <h1 class="flex-auto my-6 font-serif text-4xl font-bold grow">
-                                {person.title()}
-                            </h1>
+                            <Link
+                                rel="alternate"
+                                type_="application/atom+xml"
+                                title=format!("Contributions of {}", person.title())
+                                href=format!("/feeds/user/{}", name())
+                            />
+                            <div class="flex flex-row items-center">
+                                <h1 class="flex-auto my-6 font-serif text-4xl font-bold grow">
+                                    {person.title()}
+                                </h1>
+                                <Show when=move || !is_me>
+                                    <BlockButtons target=BlockTarget::Person(person_id) />
+                                </Show>
+                            </div>
+                            <Show when=move || bot>
+                                <div class="mb-2">
+                                    <span class="badge badge-outline">Bot</span>
+                                    <Show when=move || bot_trusted>
+                                        <span class="ml-2 badge badge-outline">Trusted</span>
+                                    </Show>
+                                </div>
+                                <Show when=is_admin>
+                                    <button
+                                        class="mb-2 btn btn-sm btn-outline"
+                                        title="Edits of trusted bots are marked as patrolled automatically"
+                                        on:click=move |_| {
+                                            trust_bot_action.dispatch((person_id, !bot_trusted));
+                                        }
+                                    >
+                                        {if bot_trusted { "Untrust bot" } else { "Trust bot" }}
+                                    </button>
+                                </Show>
+                            </Show>
 
                             <div
                                 class="mb-2 max-w-full prose prose-slate"
                                 inner_html=render_article_markdown(&person.bio.unwrap_or_default())
                             ></div>
 
-                            <SuspenseError result=user_profile>
-                                {move || Suspend::new(async move {
-                                    edits
-                                        .await
-                                        .map(|edits| {
-                                            view! {
-                                                <h2 class="font-serif text-xl font-bold">Edits</h2>
-                                                <EditList edits=edits for_article=false />
-                                            }
-                                        })
-                                })}
-                            </SuspenseError>
+                            <PersonStatsSummary person_id=person_id />
+                            <Contributions person_id=person_id />
+                        }
+                    })
+            })}
+        </SuspenseError>
+    }
+}
+
+/// Aggregated contribution counts and a heatmap of the activity during the last year.
+#[component]
+fn PersonStatsSummary(person_id: PersonId) -> impl IntoView {
+    let stats = Resource::new(
+        move || person_id,
+        |person_id| async move { CLIENT.get_person_stats(person_id).await },
+    );
+    view! {
+        <SuspenseError result=stats>
+            {move || Suspend::new(async move {
+                stats
+                    .await
+                    .map(|stats| {
+                        view! {
+                            {stats_table(stats.stats)}
+                            {activity_heatmap(stats.activity)}
                         }
                     })
             })}
         </SuspenseError>
     }
 }
+
+fn stats_table(stats: PersonStats) -> impl IntoView {
+    let activity = |time: Option<_>| time.map(edit_time);
+    view! {
+        <div class="my-4 shadow stats stats-vertical lg:stats-horizontal">
+            <div class="stat">
+                <div class="stat-title">Edits</div>
+                <div class="stat-value">{stats.edits}</div>
+                <div class="stat-desc">{stats.articles_created}" articles created"</div>
+            </div>
+            <div class="stat">
+                <div class="stat-title">Comments</div>
+                <div class="stat-value">{stats.comments}</div>
+            </div>
+            <div class="stat">
+                <div class="stat-title">Bytes changed</div>
+                <div class="stat-value">
+                    <span class="text-success">"+"{stats.bytes_added}</span>
+                    " "
+                    <span class="text-error">"-"{stats.bytes_removed}</span>
+                </div>
+            </div>
+            <div class="stat">
+                <div class="stat-title">Active</div>
+                <div class="text-sm">"First: "{activity(stats.first_activity)}</div>
+                <div class="text-sm">"Last: "{activity(stats.last_activity)}</div>
+            </div>
+        </div>
+    }
+}
+
+/// One column per week and one row per weekday, colored by the number of contributions.
+fn activity_heatmap(activity: Vec<ActivityDay>) -> impl IntoView {
+    let counts: HashMap<NaiveDate, i64> = activity.into_iter().map(|a| (a.day, a.count)).collect();
+    let today = Utc::now().date_naive();
+    let start = today - TimeDelta::days(364);
+    let start = start - TimeDelta::days(start.weekday().num_days_from_monday().into());
+    let weeks = (0..53)
+        .map(|week| {
+            let days = (0..7)
+                .map(|day| {
+                    let date = start + TimeDelta::days(week * 7 + day);
+                    if date > today {
+                        return view! { <div class="w-3 h-3"></div> }.into_any();
+                    }
+                    let count = counts.get(&date).copied().unwrap_or_default();
+                    let color = match count {
+                        0 => "bg-base-300",
+                        1..=2 => "bg-success/40",
+                        3..=5 => "bg-success/70",
+                        _ => "bg-success",
+                    };
+                    view! {
+                        <div
+                            class=format!("w-3 h-3 rounded-sm {color}")
+                            title=format!("{date}: {count} contributions")
+                        ></div>
+                    }
+                    .into_any()
+                })
+                .collect_view();
+            view! { <div class="flex flex-col gap-1">{days}</div> }
+        })
+        .collect_view();
+    view! {
+        <h2 class="font-serif text-xl font-bold">Activity</h2>
+        <div class="flex overflow-x-auto gap-1 my-2">{weeks}</div>
+    }
+}
+
+/// Edits and comments of the person, with pagination.
+#[component]
+fn Contributions(person_id: PersonId) -> impl IntoView {
+    let (page, set_page) = signal(1);
+    let contributions = Resource::new(
+        move || page.get(),
+        move |page| async move {
+            let params = GetContributionsParams {
+                person_id,
+                page: Some(page),
+            };
+            CLIENT.get_person_contributions(params).await
+        },
+    );
+    view! {
+        <SuspenseError result=contributions>
+            {move || Suspend::new(async move {
+                contributions
+                    .await
+                    .map(|contributions| {
+                        let has_next = contributions.edits.len() as i64 == CONTRIBUTIONS_PAGE_SIZE
+                            || contributions.comments.len() as i64 == CONTRIBUTIONS_PAGE_SIZE;
+                        let comments = contributions
+                            .comments
+                            .into_iter()
+                            .map(comment_row)
+                            .collect_view();
+                        view! {
+                            <h2 class="font-serif text-xl font-bold">Edits</h2>
+                            <EditList edits=contributions.edits for_article=false />
+                            <h2 class="font-serif text-xl font-bold">Comments</h2>
+                            <ul class="list-disc list-inside">{comments}</ul>
+                            <div class="my-4 join">
+                                <button
+                                    class="join-item btn btn-sm"
+                                    disabled=move || page.get() <= 1
+                                    on:click=move |_| set_page.update(|p| *p -= 1)
+                                >
+                                    Previous
+                                </button>
+                                <button class="join-item btn btn-sm btn-disabled">
+                                    "Page "{move || page.get()}
+                                </button>
+                                <button
+                                    class="join-item btn btn-sm"
+                                    disabled=move || !has_next
+                                    on:click=move |_| set_page.update(|p| *p += 1)
+                                >
+                                    Next
+                                </button>
+                            </div>
+                        }
+                    })
+            })}
+        </SuspenseError>
+    }
+}
+
+fn comment_row(view: CommentViewWithArticle) -> impl IntoView {
+    let path = comment_path(&view.comment, &view.article);
+    let mut content = view.comment.content;
+    if content.chars().count() > 100 {
+        content = format!("{}…", content.chars().take(100).collect::<String>());
+    }
+    view! {
+        <li>
+            {edit_time(view.comment.published)}" on "{article_link(&view.article)}": "
+            <a class="link" href=path>
+                {content}
+            </a>
+        </li>
+    }
+}
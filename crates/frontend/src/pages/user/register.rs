@@ -3,9 +3,14 @@ use ibis_api_client::{
     errors::FrontendResultExt,
     user::{RegisterUserParams, RegistrationResponse},
 };
-use ibis_frontend_components::{suspense_error::SuspenseError, utils::resources::site};
+use ibis_database::common::instance::RegistrationMode;
+use ibis_frontend_components::{
+    suspense_error::SuspenseError,
+    utils::resources::{config, site},
+};
 use leptos::prelude::*;
 use leptos_meta::Title;
+use leptos_router::hooks::use_query_map;
 use log::info;
 
 #[component]
@@ -14,20 +19,38 @@ pub fn Register() -> impl IntoView {
     let email = signal(String::new());
     let password = signal(String::new());
     let confirm_password = signal(String::new());
+    let invite_code = signal(
+        use_query_map()
+            .get_untracked()
+            .get("invite")
+            .unwrap_or_default(),
+    );
+    let application_answer = signal(String::new());
     let (register_response, set_register_response) = signal(None::<RegistrationResponse>);
     let (loading, set_loading) = signal(false);
 
     let register_action = Action::new(move |(): &()| {
-        let params = RegisterUserParams {
+        let mut params = RegisterUserParams {
             username: username.0.get().to_string(),
             email: Some(email.0.get().to_string()),
             password: password.0.get().to_string(),
             confirm_password: confirm_password.0.get().to_string(),
+            invite_code: Some(invite_code.0.get()),
+            application_answer: Some(application_answer.0.get()),
+            ..Default::default()
         };
         info!("Try to register new account for {}", params.username);
         async move {
             set_loading.set(true);
-            CLIENT.register(params).await.error_popup(|res| {
+            let res = async {
+                if config().proof_of_work_difficulty > 0 {
+                    let (challenge, nonce) = CLIENT.solve_challenge().await?;
+                    params.challenge = Some(challenge);
+                    params.challenge_nonce = Some(nonce);
+                }
+                CLIENT.register(params).await
+            };
+            res.await.error_popup(|res| {
                 site().refetch();
                 set_register_response.set(Some(res));
             });
@@ -43,17 +66,32 @@ pub fn Register() -> impl IntoView {
         <Title text="Register" />
         <SuspenseError result=site>
             {move || Suspend::new(async move {
-                let email_required = site
-                    .await
-                    .map(|s| s.config.email_required)
-                    .unwrap_or_default();
+                let options = site.await.map(|s| s.config).unwrap_or_default();
+                let email_required = options.email_required;
+                let registration_mode = options.registration_mode;
                 let email_placeholder = if email_required { "Email" } else { "Email (optional)" };
+                let invite_placeholder = if registration_mode == RegistrationMode::Invite {
+                    "Invite code"
+                } else {
+                    "Invite code (optional)"
+                };
                 let button_is_disabled = Signal::derive(move || {
                     let disabled = loading.get() || username.0.get().is_empty()
                         || password.0.get().is_empty() || confirm_password.0.get().is_empty();
                     if email_required && email.0.get().is_empty() {
                         return false;
                     }
+                    if registration_mode == RegistrationMode::Invite
+                        && invite_code.0.get().is_empty()
+                    {
+                        return true;
+                    }
+                    if registration_mode == RegistrationMode::Application
+                        && invite_code.0.get().is_empty()
+                        && application_answer.0.get().is_empty()
+                    {
+                        return true;
+                    }
                     disabled
                 });
                 view! {
@@ -76,6 +114,16 @@ pub fn Register() -> impl IntoView {
                                         "Registration successful, now verify the email address to login"
                                     </p>
                                 </Show>
+                                <Show when=move || {
+                                    register_response
+                                        .get()
+                                        .map(|r| r.application_pending)
+                                        .unwrap_or_default()
+                                }>
+                                    <p>
+                                        "Your registration application was submitted. You can login after it is approved by an admin."
+                                    </p>
+                                </Show>
                             }
                         }
                     >
@@ -116,6 +164,24 @@ pub fn Register() -> impl IntoView {
                                 prop:disabled=move || loading.get()
                                 bind:value=confirm_password
                             />
+                            <Show when=move || registration_mode != RegistrationMode::Open>
+                                <input
+                                    type="text"
+                                    class="input input-primary input-bordered my-1"
+                                    placeholder=invite_placeholder
+                                    prop:disabled=move || loading.get()
+                                    bind:value=invite_code
+                                />
+                            </Show>
+                            <Show when=move || registration_mode == RegistrationMode::Application>
+                                <p class="mt-2">{move || config().registration_question}</p>
+                                <textarea
+                                    class="my-1 textarea textarea-primary"
+                                    placeholder="Answer (not needed with invite code)"
+                                    prop:disabled=move || loading.get()
+                                    bind:value=application_answer
+                                ></textarea>
+                            </Show>
 
                             <div>
                                 <button
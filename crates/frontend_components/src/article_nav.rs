@@ -2,6 +2,7 @@ use crate::{
     Pending,
     utils::{
         formatting::article_path,
+        live_events::use_live_events,
         resources::{is_admin, is_logged_in},
     },
 };
@@ -9,9 +10,13 @@ use ibis_api_client::{
     CLIENT,
     errors::{FrontendResult, FrontendResultExt},
 };
-use ibis_database::common::article::{ArticleView, can_edit_article};
+use ibis_database::common::{
+    article::{ArticleView, EditVersion, can_edit_article},
+    events::LiveEvent,
+    newtypes::ArticleId,
+};
 use leptos::prelude::*;
-use leptos_meta::Title;
+use leptos_meta::{Link, Title};
 use leptos_router::components::A;
 use phosphor_leptos::{
     BELL,
@@ -56,6 +61,8 @@ pub fn ArticleNav(
                         let removed = article_.article.removed;
                         let protected = article_.article.protected;
                         let pending = article_.article.pending;
+                        let article_id = article_.article.id;
+                        let latest_version = article_.latest_version.clone();
                         let follow_article_action = Action::new(move |_: &()| async move {
                             CLIENT
                                 .follow_article(article_.article.id, !article_.following)
@@ -69,6 +76,18 @@ pub fn ArticleNav(
                         };
                         view! {
                             <Title text=page_title(active_tab, &title) />
+                            <Link
+                                rel="alternate"
+                                type_="application/atom+xml"
+                                title=format!("{title} - History")
+                                href=format!("/feeds/article/{}", article_id.0)
+                            />
+                            <Link
+                                rel="alternate"
+                                type_="application/atom+xml"
+                                title=format!("{title} - Discussion")
+                                href=format!("/feeds/article/{}/comments", article_id.0)
+                            />
                             <div role="tablist" class="tabs tabs-lifted">
                                 <A href=article_link.clone() {..} class=tab_classes.read>
                                     <Icon icon=BOOK />
@@ -151,6 +170,7 @@ pub fn ArticleNav(
                                     </Show>
                                 </button>
                             </div>
+                            <ArticleUpdated article article_id latest_version />
                         }
                     })
             })}
@@ -159,6 +179,34 @@ pub fn ArticleNav(
     }
 }
 
+/// Warn when someone else changes the article while it is open, so that editors dont overwrite
+/// each other's changes.
+#[component]
+fn ArticleUpdated(
+    article: Resource<FrontendResult<ArticleView>>,
+    article_id: ArticleId,
+    latest_version: EditVersion,
+) -> impl IntoView {
+    let live_event = use_live_events(Some(article_id));
+    let updated = move || {
+        matches!(
+            live_event.get(),
+            Some(LiveEvent::ArticleUpdated { article_id: id, latest_version: v })
+                if id == article_id && v != latest_version
+        )
+    };
+    view! {
+        <Show when=updated>
+            <div role="alert" class="mb-4 alert alert-warning">
+                <span class="grow">"This article was updated in the meantime."</span>
+                <button class="btn btn-sm" on:click=move |_| article.refetch()>
+                    "Reload"
+                </button>
+            </div>
+        </Show>
+    }
+}
+
 struct ActiveTab2Classes {
     read: &'static str,
     discussion: &'static str,
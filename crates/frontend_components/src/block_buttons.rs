@@ -0,0 +1,101 @@
+use crate::{suspense_error::SuspenseError, utils::resources::is_logged_in};
+use ibis_api_client::{
+    CLIENT,
+    errors::{FrontendResult, FrontendResultExt},
+};
+use ibis_database::common::{
+    block::{BlockKind, BlockList},
+    newtypes::{InstanceId, PersonId},
+};
+use leptos::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlockTarget {
+    Person(PersonId),
+    Instance(InstanceId),
+}
+
+impl BlockTarget {
+    fn current(&self, blocks: &BlockList) -> Option<BlockKind> {
+        match self {
+            BlockTarget::Person(id) => blocks
+                .persons
+                .iter()
+                .find(|b| b.person.id == *id)
+                .map(|b| b.block.kind),
+            BlockTarget::Instance(id) => blocks
+                .instances
+                .iter()
+                .find(|b| b.instance.id == *id)
+                .map(|b| b.block.kind),
+        }
+    }
+
+    async fn set(self, kind: Option<BlockKind>) -> FrontendResult<()> {
+        match (self, kind) {
+            (BlockTarget::Person(id), Some(kind)) => {
+                CLIENT.block_person(id, kind).await.map(|_| ())
+            }
+            (BlockTarget::Person(id), None) => CLIENT.unblock_person(id).await.map(|_| ()),
+            (BlockTarget::Instance(id), Some(kind)) => {
+                CLIENT.block_instance(id, kind).await.map(|_| ())
+            }
+            (BlockTarget::Instance(id), None) => CLIENT.unblock_instance(id).await.map(|_| ()),
+        }
+    }
+}
+
+/// Buttons to mute or block a person or instance, only shown when logged in.
+#[component]
+pub fn BlockButtons(target: BlockTarget) -> impl IntoView {
+    let blocks = Resource::new(|| (), |_| async move { CLIENT.list_blocks().await });
+    let block_action = Action::new(move |kind: &Option<BlockKind>| {
+        let kind = *kind;
+        async move {
+            target.set(kind).await.error_popup(|_| blocks.refetch());
+        }
+    });
+    let button = move |text: &'static str, title: &'static str, kind: Option<BlockKind>| {
+        view! {
+            <button
+                class="btn btn-sm btn-outline ml-2"
+                title=title
+                on:click=move |_| {
+                    block_action.dispatch(kind);
+                }
+            >
+                {text}
+            </button>
+        }
+    };
+
+    view! {
+        <Show when=is_logged_in>
+            <SuspenseError result=blocks>
+                {move || Suspend::new(async move {
+                    blocks
+                        .await
+                        .map(|blocks| {
+                            let mute = button(
+                                "Mute",
+                                "Don't receive any notifications caused by them",
+                                Some(BlockKind::Mute),
+                            );
+                            let block = button(
+                                "Block",
+                                "Hide their comments and don't receive notifications",
+                                Some(BlockKind::Block),
+                            );
+                            match target.current(&blocks) {
+                                None => view! { {mute}{block} }.into_any(),
+                                Some(BlockKind::Mute) => {
+                                    view! { {button("Unmute", "", None)}{block} }.into_any()
+                                }
+                                Some(BlockKind::Block) => button("Unblock", "", None).into_any(),
+                            }
+                        })
+                })}
+            </SuspenseError>
+        </Show>
+    }
+}
@@ -1,6 +1,7 @@
 use crate::prevent_navigation;
 use ibis_api_client::{
     CLIENT,
+    challenge::CHALLENGE_REQUIRED,
     comment::{CreateCommentParams, EditCommentParams},
     errors::{FrontendResult, FrontendResultExt},
 };
@@ -52,12 +53,25 @@ pub fn CommentEditorView(
                     edit_params.set_is_editing.set(false);
                 });
             } else {
-                let params = CreateCommentParams {
+                let mut params = CreateCommentParams {
                     content: content.get_untracked(),
                     article_id: article.await.map(|a| a.article.id).unwrap_or_default(),
                     parent_id,
+                    ..Default::default()
                 };
-                CLIENT.create_comment(&params).await.error_popup(|_| {
+                let mut res = CLIENT.create_comment(&params).await;
+                // the first comment of a new user may need a solved challenge
+                if matches!(&res, Err(e) if e.clone().message() == CHALLENGE_REQUIRED) {
+                    res = match CLIENT.solve_challenge().await {
+                        Ok((challenge, nonce)) => {
+                            params.challenge = Some(challenge);
+                            params.challenge_nonce = Some(nonce);
+                            CLIENT.create_comment(&params).await
+                        }
+                        Err(e) => Err(e),
+                    };
+                }
+                res.error_popup(|_| {
                     article.refetch();
                     if let Some(set_show_editor) = set_show_editor {
                         set_show_editor.set(CommentId(-1));
@@ -2,7 +2,7 @@ use crate::{
     Pending,
     utils::formatting::{article_link, edit_path, edit_time, user_link},
 };
-use ibis_database::common::article::EditView;
+use ibis_database::common::article::{Edit, EditView};
 use leptos::{either::Either, prelude::*};
 
 // If `for_article` is true, edit entries link to the respective user account. Otherwise
@@ -18,6 +18,7 @@ pub fn EditList(edits: Vec<EditView>, for_article: bool) -> impl IntoView {
                     .map(|edit: EditView| {
                         let path = edit_path(&edit.edit, &edit.article);
                         let edit_time = edit_time(edit.edit.published);
+                        let badges = view! { <EditBadges edit=edit.edit.clone() /> };
                         let second_line = if for_article {
                             Either::Left(
                                 view! {
@@ -42,6 +43,7 @@ pub fn EditList(edits: Vec<EditView>, for_article: bool) -> impl IntoView {
                                         <a class="text-lg grow link link-primary" href=path>
                                             {edit.edit.summary}
                                         </a>
+                                        {badges}
                                         <Pending pending=edit.edit.pending />
                                     </div>
                                     <p>{second_line}</p>
@@ -54,3 +56,23 @@ pub fn EditList(edits: Vec<EditView>, for_article: bool) -> impl IntoView {
         </div>
     }
 }
+
+/// Shows if the edit is minor, and its tags.
+#[component]
+pub fn EditBadges(edit: Edit) -> impl IntoView {
+    let minor = edit.minor;
+    view! {
+        <div class="flex gap-1 items-center">
+            <Show when=move || minor>
+                <span class="badge badge-ghost" title="Minor edit">
+                    m
+                </span>
+            </Show>
+            {edit
+                .tags
+                .iter()
+                .map(|t| view! { <span class="badge badge-outline">{t.as_str()}</span> })
+                .collect_view()}
+        </div>
+    }
+}
@@ -3,6 +3,7 @@ use leptos_use::{use_event_listener, use_window};
 
 pub mod article_editor;
 pub mod article_nav;
+pub mod block_buttons;
 pub mod comment;
 pub mod comment_editor;
 pub mod edit_list;
@@ -1,14 +1,17 @@
 use crate::utils::{
     dark_mode::DarkMode,
     formatting::instance_title,
+    live_events::use_live_events,
     resources::{config, is_admin, is_logged_in, my_profile, site},
 };
 use ibis_api_client::{CLIENT, errors::FrontendResultExt};
+use ibis_database::common::events::LiveEvent;
 use leptos::{IntoView, component, prelude::*, view, *};
 use leptos_router::hooks::use_navigate;
 use phosphor_leptos::{
     BELL_RINGING,
     CARDS,
+    CLOCK_COUNTER_CLOCKWISE,
     EXCLAMATION_MARK,
     GEAR,
     HOUSE,
@@ -22,11 +25,6 @@ pub fn Nav() -> impl IntoView {
     let logout_action = Action::new(move |_| async move {
         CLIENT.logout().await.error_popup(|_| site().refetch());
     });
-    let notification_count = Resource::new(
-        || (),
-        move |_| async move { CLIENT.notifications_count().await.unwrap_or_default() },
-    );
-
     let (search_query, set_search_query) = signal(String::new());
     let mut dark_mode = expect_context::<DarkMode>();
     view! {
@@ -68,6 +66,12 @@ pub fn Nav() -> impl IntoView {
                                     "Explore"
                                 </a>
                             </li>
+                            <li>
+                                <a href="/recent_changes">
+                                    <Icon icon=CLOCK_COUNTER_CLOCKWISE />
+                                    "Recent changes"
+                                </a>
+                            </li>
                             <li>
                                 <a href="/about">
                                     <Icon icon=EXCLAMATION_MARK />
@@ -85,9 +89,7 @@ pub fn Nav() -> impl IntoView {
                                     <a href="/notifications">
                                         <Icon icon=BELL_RINGING />
                                         "Notifications "
-                                        <span class="indicator-item indicator-end badge badge-neutral">
-                                            {notification_count}
-                                        </span>
+                                        <NotificationCount />
                                     </a>
                                 </li>
                             </Show>
@@ -203,3 +205,21 @@ pub fn Nav() -> impl IntoView {
         </nav>
     }
 }
+
+/// Number of unread notifications, which is updated when a new notification arrives.
+#[component]
+fn NotificationCount() -> impl IntoView {
+    let notification_count = Resource::new(
+        || (),
+        move |_| async move { CLIENT.notifications_count().await.unwrap_or_default() },
+    );
+    let live_event = use_live_events(None);
+    Effect::new(move || {
+        if live_event.get() == Some(LiveEvent::Notification) {
+            notification_count.refetch();
+        }
+    });
+    view! {
+        <span class="indicator-item indicator-end badge badge-neutral">{notification_count}</span>
+    }
+}
@@ -2,9 +2,11 @@ use crate::{
     suspense_error::SuspenseError,
     utils::{resources::site, use_cookie},
 };
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use ibis_database::common::instance::OAuthProviderPublic;
 use leptos::{ev::MouseEvent, prelude::*};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::str::FromStr;
 use url::Url;
 use uuid::Uuid;
@@ -15,6 +17,9 @@ pub struct OauthCookie {
     pub issuer_url: Url,
     pub redirect_url: Url,
     pub username: Option<String>,
+    pub pkce_code_verifier: String,
+    /// Needed to login if the user enabled two-factor authentication
+    pub totp_token: Option<String>,
 }
 
 impl FromStr for OauthCookie {
@@ -32,7 +37,10 @@ impl ToString for OauthCookie {
 }
 
 #[component]
-pub fn OauthLoginButtons(username: ReadSignal<String>) -> impl IntoView {
+pub fn OauthLoginButtons(
+    username: ReadSignal<String>,
+    totp_token: ReadSignal<String>,
+) -> impl IntoView {
     let site = site();
     view! {
         <SuspenseError result=site>
@@ -57,7 +65,7 @@ pub fn OauthLoginButtons(username: ReadSignal<String>) -> impl IntoView {
                                 view! {
                                     <button
                                         class="m-2 btn btn-secondary"
-                                        on:click=on_click(p.clone(), username)
+                                        on:click=on_click(p.clone(), Some(username), Some(totp_token))
                                     >
                                         {p.display_name.clone()}
                                     </button>
@@ -71,7 +79,45 @@ pub fn OauthLoginButtons(username: ReadSignal<String>) -> impl IntoView {
     }
 }
 
-fn on_click(provider: OAuthProviderPublic, username: ReadSignal<String>) -> impl Fn(MouseEvent) {
+/// Link the account of the logged in user with an SSO provider, so it can be used for login.
+#[component]
+pub fn OauthLinkButtons() -> impl IntoView {
+    let site = site();
+    view! {
+        <SuspenseError result=site>
+            {move || Suspend::new(async move {
+                let providers = site.await.map(|s| s.oauth_providers).unwrap_or_default();
+                let has_oauth_providers = !providers.is_empty();
+                view! {
+                    <Show when=move || { has_oauth_providers }>
+                        <h2 class="flex-auto my-6 font-serif text-2xl font-bold grow">
+                            Linked SSO Accounts
+                        </h2>
+                        {providers
+                            .iter()
+                            .map(|p| {
+                                view! {
+                                    <button
+                                        class="m-2 btn btn-secondary"
+                                        on:click=on_click(p.clone(), None, None)
+                                    >
+                                        {format!("Link {}", p.display_name)}
+                                    </button>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </Show>
+                }
+            })}
+        </SuspenseError>
+    }
+}
+
+fn on_click(
+    provider: OAuthProviderPublic,
+    username: Option<ReadSignal<String>>,
+    totp_token: Option<ReadSignal<String>>,
+) -> impl Fn(MouseEvent) {
     let oauth_cookie = use_cookie("oauth_state");
     move |_| {
         let redirect_uri = Url::parse(&format!(
@@ -80,12 +126,16 @@ fn on_click(provider: OAuthProviderPublic, username: ReadSignal<String>) -> impl
         ))
         .expect("format url");
         let state = Uuid::new_v4().to_string();
+        // 244 random bits, and only contains characters which are allowed by RFC 7636
+        let pkce_code_verifier = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
 
         oauth_cookie.1.set(Some(OauthCookie {
             state: state.clone(),
             issuer_url: provider.issuer.clone(),
             redirect_url: redirect_uri.clone(),
-            username: Some(username.get()),
+            username: username.map(|u| u.get()),
+            pkce_code_verifier: pkce_code_verifier.clone(),
+            totp_token: totp_token.map(|t| t.get()).filter(|t| !t.is_empty()),
         }));
 
         let mut oauth_redirect = provider.authorization_endpoint.clone();
@@ -96,6 +146,8 @@ fn on_click(provider: OAuthProviderPublic, username: ReadSignal<String>) -> impl
             .append_pair("scope", &provider.scopes)
             .append_pair("redirect_uri", redirect_uri.as_str())
             .append_pair("state", &state)
+            .append_pair("code_challenge", &pkce_code_challenge(&pkce_code_verifier))
+            .append_pair("code_challenge_method", "S256")
             .finish();
         window()
             .location()
@@ -103,3 +155,22 @@ fn on_click(provider: OAuthProviderPublic, username: ReadSignal<String>) -> impl
             .expect("set location")
     }
 }
+
+/// S256 code challenge for PKCE, see RFC 7636
+fn pkce_code_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pkce_code_challenge() {
+        // unpadded base64url of the sha256 hash
+        assert_eq!(
+            "1sdMAuAo_FM2ZmTmVWVXSyWYWd5AJAmMb4z87Z0Eytc",
+            pkce_code_challenge("dBjftJeZ4CVP-mJ92IZ1hiGgpNwVm2Ylv6u9LJF3VQEA")
+        );
+    }
+}
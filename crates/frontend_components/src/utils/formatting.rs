@@ -32,8 +32,8 @@ pub fn article_link(article: &Article) -> impl IntoView {
     }
 }
 
-pub fn user_link(person: &Person) -> impl IntoView {
-    let creator_path = if person.local {
+pub fn user_path(person: &Person) -> String {
+    if person.local {
         format!("/user/{}", person.username)
     } else {
         format!(
@@ -41,7 +41,11 @@ pub fn user_link(person: &Person) -> impl IntoView {
             person.username,
             extract_domain(person.ap_id.inner())
         )
-    };
+    }
+}
+
+pub fn user_link(person: &Person) -> impl IntoView {
+    let creator_path = user_path(person);
     view! {
         <a class="link" href=creator_path>
             {person.title()}
@@ -0,0 +1,49 @@
+use codee::string::JsonSerdeCodec;
+use ibis_api_client::notifications::{LiveEventsParams, live_events_path};
+use ibis_database::common::{events::LiveEvent, newtypes::ArticleId};
+use leptos::prelude::*;
+use leptos_use::use_event_source;
+
+/// Single connection for events which are pushed by the server, see [LiveEvent]. It is shared by
+/// all components of the page, and reopened when a different article is viewed.
+#[derive(Clone, Copy)]
+pub struct LiveEvents {
+    /// Articles of the currently mounted components, the last one is included in the events
+    articles: RwSignal<Vec<ArticleId>>,
+    event: RwSignal<Option<LiveEvent>>,
+}
+
+impl LiveEvents {
+    pub fn init() {
+        let articles = RwSignal::new(vec![]);
+        let event = RwSignal::new(None);
+        let article_id = Memo::new(move |_| articles.with(|a| a.last().copied()));
+        Effect::new(move || {
+            let params = LiveEventsParams {
+                article_id: article_id.get(),
+            };
+            // the previous connection is closed when this effect reruns
+            let data =
+                use_event_source::<LiveEvent, JsonSerdeCodec>(&live_events_path(&params)).data;
+            Effect::new(move || event.set(data.get()));
+        });
+        provide_context(Self { articles, event });
+    }
+}
+
+/// Receive events which are pushed by the server. If an article is given, changes to it are also
+/// received while the calling component is mounted.
+pub fn use_live_events(article_id: Option<ArticleId>) -> Signal<Option<LiveEvent>> {
+    let live_events = expect_context::<LiveEvents>();
+    if let Some(article_id) = article_id {
+        live_events.articles.update(|a| a.push(article_id));
+        on_cleanup(move || {
+            live_events.articles.update(|a| {
+                if let Some(i) = a.iter().rposition(|id| id == &article_id) {
+                    a.remove(i);
+                }
+            })
+        });
+    }
+    live_events.event.into()
+}
@@ -5,13 +5,16 @@ use markdown_it::{
     plugins::cmark::block::{heading::ATXHeading, lheading::SetextHeader},
 };
 use math_equation::MathEquationScanner;
+pub use mention::{Mention, extract_mentions};
 use std::{borrow::Cow, sync::OnceLock};
 use table_of_contents::{TocMarkerScanner, TocScanner};
 
 mod article_link;
 mod external_link;
 mod math_equation;
+mod mention;
 mod table_of_contents;
+pub mod wikitext;
 
 pub fn render_article_markdown(text: &str) -> String {
     static INSTANCE: OnceLock<MarkdownIt> = OnceLock::new();
@@ -95,6 +98,7 @@ fn common_markdown() -> MarkdownIt {
     }
 
     external_link::add(p);
+    mention::add(p);
 
     parser
 }
@@ -112,3 +116,35 @@ pub fn format_markdown(text: &str) -> Result<String, std::fmt::Error> {
     };
     fmtm::format_with_config(text, CONFIG)
 }
+
+/// Returns true if the texts only differ in markdown formatting, for example different list
+/// markers or heading style, so that they are rendered to the same html.
+pub fn is_formatting_change(old: &str, new: &str) -> Result<bool, std::fmt::Error> {
+    let old = render_article_markdown(&format_markdown(old)?);
+    let new = render_article_markdown(&format_markdown(new)?);
+    Ok(old == new)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_formatting_change() -> Result<(), std::fmt::Error> {
+        assert!(is_formatting_change("* one\n* two\n", "- one\n-   two\n")?);
+        assert!(is_formatting_change("some  text\n", "some\ntext\n")?);
+        assert!(is_formatting_change("Title\n=====\n", "# Title\n")?);
+        assert!(is_formatting_change(
+            "[link](http://example.com)\n",
+            "[link][1]\n\n[1]: http://example.com\n"
+        )?);
+        assert!(!is_formatting_change("some text\n", "some other text\n")?);
+        // changes of the document structure are not only formatting
+        assert!(!is_formatting_change("one\ntwo\n", "one\n\ntwo\n")?);
+        assert!(!is_formatting_change("one\n\ntwo\n", "one\ntwo\n")?);
+        assert!(!is_formatting_change("    code\n", "        code\n")?);
+        assert!(!is_formatting_change("#heading\n", "# heading\n")?);
+        assert!(!is_formatting_change("a\n-b\n", "a\n\n- b\n")?);
+        Ok(())
+    }
+}
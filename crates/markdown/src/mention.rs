@@ -0,0 +1,141 @@
+use markdown_it::{
+    MarkdownIt,
+    Node,
+    NodeValue,
+    Renderer,
+    parser::inline::{InlineRule, InlineState},
+};
+use std::sync::OnceLock;
+
+/// Mention of a user in the form `@name` or `@name@domain`. Without domain the user is on the
+/// same instance as the author of the text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mention {
+    pub name: String,
+    pub domain: Option<String>,
+}
+
+impl NodeValue for Mention {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+
+        let name = match &self.domain {
+            Some(domain) => format!("{}@{domain}", self.name),
+            None => self.name.clone(),
+        };
+        attrs.push(("href", format!("/user/{name}")));
+        attrs.push(("class", "mention".to_string()));
+
+        fmt.open("a", &attrs);
+        fmt.text(&format!("@{name}"));
+        fmt.close("a");
+    }
+}
+
+pub struct MentionScanner;
+
+impl InlineRule for MentionScanner {
+    const MARKER: char = '@';
+
+    /// Find `@name` or `@name@domain`, which must not be preceded by a word character so that
+    /// email addresses are not treated as mentions.
+    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+        let before = state.src[..state.pos].chars().next_back();
+        if before.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '@') {
+            return None;
+        }
+        let input = &state.src[state.pos + 1..state.pos_max];
+        let name_len = input
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(input.len());
+        if name_len == 0 {
+            return None;
+        }
+        let name = &input[..name_len];
+        let mut length = 1 + name_len;
+
+        let mut domain = None;
+        if let Some(rest) = input[name_len..].strip_prefix('@') {
+            let domain_len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == ':'))
+                .unwrap_or(rest.len());
+            // dont include punctuation at the end of a sentence
+            let domain_ = rest[..domain_len].trim_end_matches(['.', ':', '-']);
+            if !domain_.is_empty() {
+                domain = Some(domain_.to_string());
+                length += 1 + domain_.len();
+            }
+        }
+        let node = Node::new(Mention {
+            name: name.to_string(),
+            domain,
+        });
+        Some((node, length))
+    }
+}
+
+pub(crate) fn add(parser: &mut MarkdownIt) {
+    parser.inline.add_rule::<MentionScanner>();
+}
+
+/// List all users mentioned in the markdown text, without duplicates. Mentions inside of code
+/// are ignored.
+pub fn extract_mentions(text: &str) -> Vec<Mention> {
+    static INSTANCE: OnceLock<MarkdownIt> = OnceLock::new();
+    let parser = INSTANCE.get_or_init(|| {
+        let mut parser = MarkdownIt::new();
+        markdown_it::plugins::cmark::add(&mut parser);
+        add(&mut parser);
+        parser
+    });
+    let mut mentions = vec![];
+    parser.parse(text).walk(|node, _| {
+        if let Some(mention) = node.cast::<Mention>() {
+            if !mentions.contains(mention) {
+                mentions.push(mention.clone());
+            }
+        }
+    });
+    mentions
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::render_comment_markdown;
+
+    #[test]
+    fn test_markdown_mention() {
+        assert_eq!(
+            "<p>hi <a href=\"/user/alice\" class=\"mention\">@alice</a></p>\n",
+            render_comment_markdown("hi @alice")
+        );
+        assert_eq!(
+            "<p><a href=\"/user/bob@example.com\" class=\"mention\">@bob@example.com</a>.</p>\n",
+            render_comment_markdown("@bob@example.com.")
+        );
+        // email address is not a mention
+        assert_eq!(
+            "<p>mail@example.com</p>\n",
+            render_comment_markdown("mail@example.com")
+        );
+    }
+
+    #[test]
+    fn test_extract_mentions() {
+        let text = "@alice and @bob@localhost:8080, again @alice\n\n`@code`";
+        assert_eq!(
+            vec![
+                Mention {
+                    name: "alice".to_string(),
+                    domain: None
+                },
+                Mention {
+                    name: "bob".to_string(),
+                    domain: Some("localhost:8080".to_string())
+                },
+            ],
+            extract_mentions(text)
+        );
+    }
+}
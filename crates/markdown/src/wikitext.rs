@@ -0,0 +1,672 @@
+//! Conversion of MediaWiki wikitext into the markdown dialect which is used for articles. This
+//! covers the commonly used syntax, while constructs without equivalent (eg templates) are
+//! removed.
+
+use std::collections::HashMap;
+
+/// Surrounds the index of text which is already converted and must not be changed anymore.
+const PROTECTED_START: char = '\u{E000}';
+const PROTECTED_END: char = '\u{E001}';
+
+/// Convert wikitext to markdown. Links to other wiki pages are converted to article links on
+/// the given domain.
+pub fn wikitext_to_markdown(wikitext: &str, domain: &str) -> String {
+    let mut converter = Converter {
+        domain,
+        protected: vec![],
+        footnotes: vec![],
+        footnote_names: HashMap::new(),
+    };
+    let text = remove_between(wikitext, "<!--", "-->");
+    let text = converter.protect_tags(&text);
+    let text = converter.extract_references(&text);
+    let text = replace_tags(&text, "references", |_, _| String::new());
+    let text = remove_templates(&text);
+    let mut markdown = converter.convert_blocks(&text).trim_end().to_string();
+    markdown.push('\n');
+
+    for (i, footnote) in converter.footnotes.iter().enumerate() {
+        markdown.push_str(&format!("\n[^{}]: {}\n", i + 1, footnote));
+    }
+    let mut markdown = converter.restore(&markdown).trim().to_string();
+    markdown.push('\n');
+    markdown
+}
+
+/// Convert a MediaWiki page title to a valid article title. Underscores and punctuation are
+/// replaced with spaces, and the first letter is capitalized like MediaWiki does. Returns `None`
+/// if the title contains other characters or is too short or long.
+pub fn normalize_title(title: &str) -> Option<String> {
+    let title: String = title
+        .chars()
+        .map(|c| if c.is_ascii_punctuation() { ' ' } else { c })
+        .collect();
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut chars = title.chars();
+    let title: String = chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default();
+    let valid = (3..=100).contains(&title.len())
+        && title.chars().all(|c| c.is_ascii_alphanumeric() || c == ' ');
+    valid.then_some(title)
+}
+
+struct Converter<'a> {
+    domain: &'a str,
+    protected: Vec<String>,
+    footnotes: Vec<String>,
+    footnote_names: HashMap<String, usize>,
+}
+
+impl Converter<'_> {
+    /// Convert tags whose content must be kept verbatim.
+    fn protect_tags(&mut self, text: &str) -> String {
+        let text = replace_tags(text, "nowiki", |_, content| {
+            self.protect(escape_markdown(content.unwrap_or_default()))
+        });
+        let text = replace_tags(&text, "pre", |_, content| {
+            self.protect(code_block(content.unwrap_or_default(), None))
+        });
+        let text = replace_tags(&text, "syntaxhighlight", |attrs, content| {
+            self.protect(code_block(
+                content.unwrap_or_default(),
+                attribute(attrs, "lang"),
+            ))
+        });
+        let text = replace_tags(&text, "source", |attrs, content| {
+            self.protect(code_block(
+                content.unwrap_or_default(),
+                attribute(attrs, "lang"),
+            ))
+        });
+        let text = replace_tags(&text, "math", |_, content| {
+            self.protect(format!("$${}$$", content.unwrap_or_default().trim()))
+        });
+        replace_tags(&text, "code", |_, content| {
+            self.protect(format!("`{}`", content.unwrap_or_default()))
+        })
+    }
+
+    fn protect(&mut self, text: String) -> String {
+        self.protected.push(text);
+        format!(
+            "{PROTECTED_START}{}{PROTECTED_END}",
+            self.protected.len() - 1
+        )
+    }
+
+    fn restore(&self, text: &str) -> String {
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find(PROTECTED_START) {
+            out.push_str(&rest[..start]);
+            rest = &rest[start + PROTECTED_START.len_utf8()..];
+            let end = rest.find(PROTECTED_END).unwrap_or(rest.len());
+            if let Some(protected) = rest[..end]
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| self.protected.get(i))
+            {
+                out.push_str(protected);
+            }
+            rest = rest
+                .get(end + PROTECTED_END.len_utf8()..)
+                .unwrap_or_default();
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Replace `<ref>` tags with footnote markers. Named references which are used multiple
+    /// times get the same footnote.
+    fn extract_references(&mut self, text: &str) -> String {
+        replace_tags(text, "ref", |attrs, content| {
+            let name = attribute(attrs, "name");
+            let existing = name
+                .as_ref()
+                .and_then(|n| self.footnote_names.get(n))
+                .copied();
+            let index = existing.unwrap_or_else(|| {
+                self.footnotes.push(String::new());
+                self.footnotes.len() - 1
+            });
+            if let Some(name) = name {
+                self.footnote_names.insert(name, index);
+            }
+            if let Some(content) = content.filter(|c| !c.trim().is_empty()) {
+                let content = remove_templates(content);
+                self.footnotes[index] = self.convert_inline(content.trim());
+            }
+            format!("[^{}]", index + 1)
+        })
+    }
+
+    fn convert_blocks(&self, text: &str) -> String {
+        let mut out: Vec<String> = vec![];
+        let mut previous = Block::Blank;
+        let mut lines = text.lines();
+        while let Some(line) = lines.next() {
+            let trimmed = line.trim();
+            let (block, converted) = if trimmed.starts_with("{|") {
+                let mut table = vec![];
+                let mut depth = 1;
+                for line in lines.by_ref() {
+                    let trimmed = line.trim();
+                    if trimmed.starts_with("{|") {
+                        depth += 1;
+                    } else if trimmed.starts_with("|}") {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    table.push(trimmed);
+                }
+                (Block::Separate, self.convert_table(&table))
+            } else if trimmed.is_empty() {
+                (Block::Blank, vec![String::new()])
+            } else if let Some((level, title)) = heading(trimmed) {
+                // Markdown headings are rendered one level smaller, and the wikitext top level
+                // heading is reserved for the page title
+                let level = level.saturating_sub(1).max(1);
+                (
+                    Block::Separate,
+                    vec![format!(
+                        "{} {}",
+                        "#".repeat(level),
+                        self.convert_inline(title)
+                    )],
+                )
+            } else if trimmed.len() >= 4 && trimmed.chars().all(|c| c == '-') {
+                (Block::Separate, vec!["---".to_string()])
+            } else if trimmed.starts_with(['*', '#', ':', ';']) {
+                (Block::List, vec![self.convert_list_item(trimmed)])
+            } else {
+                (Block::Text, vec![self.convert_inline(line.trim_end())])
+            };
+
+            let needs_separation =
+                previous != block && previous != Block::Blank && block != Block::Blank
+                    || block == Block::Separate;
+            if needs_separation && out.last().is_some_and(|l| !l.is_empty()) {
+                out.push(String::new());
+            }
+            if block == Block::Blank && out.last().is_none_or(|l| l.is_empty()) {
+                continue;
+            }
+            out.extend(converted);
+            if block == Block::Separate {
+                out.push(String::new());
+            }
+            previous = block;
+        }
+        out.join("\n")
+    }
+
+    fn convert_list_item(&self, line: &str) -> String {
+        let prefix: String = line
+            .chars()
+            .take_while(|c| ['*', '#', ':', ';'].contains(c))
+            .collect();
+        let content = self.convert_inline(line[prefix.len()..].trim());
+        if prefix.chars().all(|c| c == ':') {
+            return format!("{}{content}", "> ".repeat(prefix.len()));
+        }
+        let indent = "    ".repeat(prefix.len() - 1);
+        match prefix.chars().last() {
+            Some('*') => format!("{indent}- {content}"),
+            Some('#') => format!("{indent}1. {content}"),
+            Some(';') => match content.split_once(" : ") {
+                Some((term, definition)) => format!("{indent}**{}**: {definition}", term.trim()),
+                None => format!("{indent}**{content}**"),
+            },
+            // continuation of a list item
+            _ => format!("{indent}{content}"),
+        }
+    }
+
+    /// Tables are converted to markdown tables, the first row is used as header.
+    fn convert_table(&self, lines: &[&str]) -> Vec<String> {
+        let mut caption = None;
+        let mut rows: Vec<Vec<String>> = vec![];
+        let mut row: Vec<String> = vec![];
+        for line in lines {
+            if let Some(text) = line.strip_prefix("|+") {
+                caption = Some(self.convert_inline(cell_content(text).trim()));
+            } else if line.starts_with("|-") {
+                if !row.is_empty() {
+                    rows.push(std::mem::take(&mut row));
+                }
+            } else if let Some(cells) = line.strip_prefix('!') {
+                row.extend(
+                    cells
+                        .split("!!")
+                        .flat_map(|c| c.split("||"))
+                        .map(|c| self.convert_cell(c)),
+                );
+            } else if let Some(cells) = line.strip_prefix('|') {
+                row.extend(cells.split("||").map(|c| self.convert_cell(c)));
+            } else if let Some(cell) = row.last_mut() {
+                // multiline cell content
+                cell.push(' ');
+                cell.push_str(&self.convert_cell(line));
+            }
+        }
+        if !row.is_empty() {
+            rows.push(row);
+        }
+
+        let mut out = vec![];
+        if let Some(caption) = caption {
+            out.push(format!("**{caption}**"));
+            out.push(String::new());
+        }
+        let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+        let format_row = |row: &Vec<String>| {
+            let mut cells = row.clone();
+            cells.resize(columns, String::new());
+            format!("| {} |", cells.join(" | "))
+        };
+        for (i, row) in rows.iter().enumerate() {
+            out.push(format_row(row));
+            if i == 0 {
+                out.push(format!("|{}", " --- |".repeat(columns)));
+            }
+        }
+        out
+    }
+
+    fn convert_cell(&self, cell: &str) -> String {
+        self.convert_inline(cell_content(cell).trim())
+            .replace('|', "\\|")
+    }
+
+    fn convert_inline(&self, text: &str) -> String {
+        let text = self.convert_links(text);
+        let text = text
+            .replace("'''''", "***")
+            .replace("'''", "**")
+            .replace("''", "*");
+        let text = convert_html_tags(&text);
+        convert_magic_words(&text)
+    }
+
+    fn convert_links(&self, text: &str) -> String {
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find('[') {
+            out.push_str(&rest[..start]);
+            let link = &rest[start..];
+            if link.starts_with("[[") {
+                if let Some(end) = find_closing(link) {
+                    // Letters directly after the link are part of the label, eg `[[bus]]es`
+                    let trail_len = link[end..]
+                        .find(|c: char| !c.is_alphabetic())
+                        .unwrap_or(link.len() - end);
+                    out.push_str(
+                        &self.internal_link(&link[2..end - 2], &link[end..end + trail_len]),
+                    );
+                    rest = &link[end + trail_len..];
+                    continue;
+                }
+            } else if let Some(end) = link.find(']').filter(|_| is_external_link(&link[1..])) {
+                let content = &link[1..end];
+                let (url, label) = content.split_once(' ').unwrap_or((content, ""));
+                let url = match url.strip_prefix("//") {
+                    Some(url) => format!("https://{url}"),
+                    None => url.to_string(),
+                };
+                if label.trim().is_empty() {
+                    out.push_str(&format!("<{url}>"));
+                } else {
+                    out.push_str(&format!("[{}]({url})", label.trim()));
+                }
+                rest = &link[end + 1..];
+                continue;
+            }
+            out.push('[');
+            rest = &link[1..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn internal_link(&self, content: &str, trail: &str) -> String {
+        let (target, label) = match content.split_once('|') {
+            Some((target, label)) => (target.trim(), Some(label.trim())),
+            None => (content.trim(), None),
+        };
+        let escaped = target.starts_with(':');
+        let target = target.trim_start_matches(':');
+        let label = match label {
+            Some(label) if !label.is_empty() => format!("{label}{trail}"),
+            _ => format!("{target}{trail}"),
+        };
+
+        if let Some((namespace, _)) = target.split_once(':') {
+            let namespace = namespace.trim().to_lowercase();
+            let is_media = ["file", "image", "media", "category"].contains(&namespace.as_str());
+            if is_media && !escaped {
+                return String::new();
+            }
+            // There are no other namespaces, so only keep the text
+            return label;
+        }
+
+        // Links to pages which can't be imported are replaced by their text
+        let Some(title) = normalize_title(target.split('#').next().unwrap_or_default()) else {
+            return label;
+        };
+        if label == title {
+            format!("[[{title}@{}]]", self.domain)
+        } else {
+            format!("[[{title}@{}|{label}]]", self.domain)
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Block {
+    Blank,
+    Text,
+    List,
+    /// Needs blank lines before and after
+    Separate,
+}
+
+/// Parse a heading like `== Title ==`, returning level and title.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let start = line.chars().take_while(|c| *c == '=').count();
+    let end = line.chars().rev().take_while(|c| *c == '=').count();
+    let level = start.min(end).min(6);
+    if level == 0 || level * 2 >= line.len() {
+        return None;
+    }
+    Some((level, line[level..line.len() - level].trim()))
+}
+
+/// Remove the attributes part of a table cell, eg `style="color:red" | content`.
+fn cell_content(cell: &str) -> &str {
+    let mut depth = 0;
+    let bytes = cell.as_bytes();
+    for (i, c) in bytes.iter().enumerate() {
+        match c {
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' => depth -= 1,
+            b'|' if depth <= 0 => return &cell[i + 1..],
+            _ => {}
+        }
+    }
+    cell
+}
+
+/// Find the end of an internal link which starts at the beginning of the text, considering
+/// nested links. Returns the position after the closing brackets.
+fn find_closing(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = 0;
+    while i < text.len() {
+        if text[i..].starts_with("[[") {
+            depth += 1;
+            i += 2;
+        } else if text[i..].starts_with("]]") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return Some(i);
+            }
+        } else {
+            i += text[i..].chars().next().map(char::len_utf8).unwrap_or(1);
+        }
+    }
+    None
+}
+
+fn is_external_link(text: &str) -> bool {
+    ["http://", "https://", "ftp://", "mailto:", "//"]
+        .iter()
+        .any(|p| text.starts_with(p))
+}
+
+/// Remove all templates, including nested ones.
+fn remove_templates(text: &str) -> String {
+    let mut out = String::new();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < text.len() {
+        if text[i..].starts_with("{{") {
+            depth += 1;
+            i += 2;
+        } else if depth > 0 && text[i..].starts_with("}}") {
+            depth -= 1;
+            i += 2;
+        } else {
+            let len = text[i..].chars().next().map(char::len_utf8).unwrap_or(1);
+            if depth == 0 {
+                out.push_str(&text[i..i + len]);
+            }
+            i += len;
+        }
+    }
+    out
+}
+
+fn remove_between(text: &str, start: &str, end: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(i) = rest.find(start) {
+        out.push_str(&rest[..i]);
+        rest = match rest[i..].find(end) {
+            Some(j) => &rest[i + j + end.len()..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Replace all occurences of the html tag using the given function. It gets called with the tag
+/// attributes, and with the content unless it is a self-closing tag.
+fn replace_tags<F>(text: &str, tag: &str, mut f: F) -> String
+where
+    F: FnMut(&str, Option<&str>) -> String,
+{
+    let open = format!("<{tag}");
+    let close = format!("</{tag}>");
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        let Some(tag_end) = after
+            .starts_with([' ', '>', '/', '\n'])
+            .then(|| after.find('>'))
+            .flatten()
+        else {
+            out.push_str(&rest[..start + open.len()]);
+            rest = after;
+            continue;
+        };
+        out.push_str(&rest[..start]);
+        let attrs = &after[..tag_end];
+        let after = &after[tag_end + 1..];
+        if let Some(attrs) = attrs.strip_suffix('/') {
+            out.push_str(&f(attrs, None));
+            rest = after;
+        } else {
+            let end = after.find(&close).unwrap_or(after.len());
+            out.push_str(&f(attrs, Some(&after[..end])));
+            rest = after.get(end + close.len()..).unwrap_or_default();
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Read a tag attribute like `name="value"`.
+fn attribute(attrs: &str, name: &str) -> Option<String> {
+    let start = attrs.find(&format!("{name}="))? + name.len() + 1;
+    let value = &attrs[start..];
+    let value = match value.chars().next() {
+        Some(quote @ ('"' | '\'')) => value[1..].split(quote).next(),
+        _ => value.split([' ', '/']).next(),
+    }?;
+    Some(value.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// Html tags which are commonly used in wikitext. Others are left unchanged.
+fn convert_html_tags(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let tag = &rest[start + 1..];
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(char::is_ascii_alphabetic)
+            .collect();
+        let replacement = match name.to_lowercase().as_str() {
+            "b" | "strong" => Some("**"),
+            "i" | "em" => Some("*"),
+            "s" | "del" | "strike" => Some("~~"),
+            "sup" => Some("^"),
+            "sub" => Some("~"),
+            "br" => Some(" "),
+            "u" | "small" | "big" | "span" | "div" | "center" | "font" | "abbr" | "cite"
+            | "blockquote" | "p" => Some(""),
+            _ => None,
+        };
+        match (replacement, tag.find('>')) {
+            (Some(replacement), Some(end)) => {
+                out.push_str(replacement);
+                rest = &tag[end + 1..];
+            }
+            _ => {
+                out.push('<');
+                rest = tag;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn convert_magic_words(text: &str) -> String {
+    let mut out = text.replace("__TOC__", "[!toc]");
+    for word in [
+        "__NOTOC__",
+        "__FORCETOC__",
+        "__NOEDITSECTION__",
+        "__NEWSECTIONLINK__",
+        "__NOGALLERY__",
+        "__HIDDENCAT__",
+        "__INDEX__",
+        "__NOINDEX__",
+    ] {
+        out = out.replace(word, "");
+    }
+    out
+}
+
+fn code_block(content: &str, lang: Option<String>) -> String {
+    format!(
+        "\n```{}\n{}\n```\n",
+        lang.unwrap_or_default(),
+        content.trim_matches('\n')
+    )
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        if "\\`*_[]<>#|$~^".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::wikitext_to_markdown;
+    use crate::render_article_markdown;
+
+    fn convert(wikitext: &str) -> String {
+        wikitext_to_markdown(wikitext, "example.com")
+    }
+
+    #[test]
+    fn test_wikitext_headings_and_formatting() {
+        let wikitext = "Intro with '''bold''' and ''italic''.\n== Section ==\nText\n=== Sub ===";
+        assert_eq!(
+            "Intro with **bold** and *italic*.\n\n# Section\n\nText\n\n## Sub\n",
+            convert(wikitext)
+        );
+    }
+
+    #[test]
+    fn test_wikitext_links() {
+        assert_eq!(
+            "See [[Main page@example.com|main page]] and [[Rust language@example.com|Rust]].\n",
+            convert("See [[main page]] and [[Rust (language)|Rust]].")
+        );
+        assert_eq!("C++ and Zürich\n", convert("[[C++]] and [[Zürich]]"));
+        assert_eq!(
+            "[[Bus@example.com|buses]] [[Article@example.com]]\n",
+            convert("[[bus]]es [[Article#Section|Article]]")
+        );
+        assert_eq!(
+            "Text\n",
+            convert("Text [[Category:Things]][[File:a.png|thumb|[[Caption]]]]")
+        );
+        assert_eq!(
+            "[Ibis](https://ibis.wiki) <https://example.org>\n",
+            convert("[https://ibis.wiki Ibis] [//example.org]")
+        );
+    }
+
+    #[test]
+    fn test_wikitext_lists() {
+        let wikitext = "Items:\n* one\n** nested\n# first\n#: continued\n:quote\nEnd";
+        assert_eq!(
+            "Items:\n\n- one\n    - nested\n1. first\n    continued\n> quote\n\nEnd\n",
+            convert(wikitext)
+        );
+    }
+
+    #[test]
+    fn test_wikitext_references() {
+        let wikitext = "Fact<ref name=\"a\">Source {{cite}}</ref> and again<ref name=\"a\"/>\
+            <ref>Other</ref>\n== References ==\n<references />";
+        assert_eq!(
+            "Fact[^1] and again[^1][^2]\n\n# References\n\n[^1]: Source\n\n[^2]: Other\n",
+            convert(wikitext)
+        );
+    }
+
+    #[test]
+    fn test_wikitext_table() {
+        let wikitext = "{| class=\"wikitable\"\n|+ Caption\n! A !! B\n|-\n| style=\"x\" | 1 \
+            || [[Link|label]]\n|-\n| 2\n| 3\n|}";
+        let markdown = convert(wikitext);
+        assert_eq!(
+            "**Caption**\n\n| A | B |\n| --- | --- |\n| 1 | [[Link@example.com\\|label]] |\n| 2 | 3 |\n",
+            markdown
+        );
+        let html = render_article_markdown(&markdown);
+        assert!(html.contains("<th>A</th>"));
+        assert!(html.contains("<a href=\"/article/Link@example.com\">label</a>"));
+    }
+
+    #[test]
+    fn test_wikitext_protected() {
+        let wikitext = "{{Infobox|a={{b}}}}<!-- comment -->x<nowiki>[[a]]</nowiki> \
+            <math>x^2</math> __TOC__\n<pre>\n''code''\n</pre>";
+        assert_eq!(
+            "x\\[\\[a\\]\\] $$x^2$$ [!toc]\n\n```\n''code''\n```\n",
+            convert(wikitext)
+        );
+    }
+}